@@ -1,9 +1,16 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use arrow::array::RecordBatch;
+use arrow::datatypes::DataType;
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
 
-use log_utils::parquet_ops;
+use log_utils::parquet_ops::{self, MergeOptions};
 use log_utils::utils;
 
 #[derive(Parser)]
@@ -15,6 +22,29 @@ struct Cli {
     command: Commands,
 }
 
+/// Output formats supported by the export subcommand
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ExportFormat {
+    /// InfluxDB line protocol (.lp)
+    InfluxdbLp,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum StatsFormat {
+    /// Aligned table for the terminal
+    Table,
+    Csv,
+    /// JSON object keyed by column name
+    Json,
+}
+
+#[cfg(feature = "mcap")]
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    /// ROS2 / MCAP bag with JSON encoded messages (.mcap)
+    Mcap,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Merge multiple parquet files into a single file
@@ -38,6 +68,30 @@ enum Commands {
         /// Force merge even if schemas are incompatible (may cause errors)
         #[arg(short = 'F', long, default_value_t = false)]
         force: bool,
+
+        /// Sort columns alphabetically so files with differently ordered columns can be merged
+        #[arg(long, default_value_t = false)]
+        canonical_order: bool,
+
+        /// Compression codec of the merged file: snappy, zstd, lz4 or uncompressed
+        #[arg(long)]
+        parquet_compression: Option<String>,
+
+        /// Sort the merged rows by this column (ascending), e.g. a timestamp
+        #[arg(long)]
+        sort_column: Option<String>,
+
+        /// The input files are each already sorted by --sort-column: merge them in one
+        /// streaming pass instead of sorting afterwards. Without --parquet-compression the
+        /// codec of the first file is used. The schemas must match, so --force and
+        /// --canonical-order are not supported.
+        #[arg(
+            long,
+            default_value_t = false,
+            requires = "sort_column",
+            conflicts_with_all = ["force", "canonical_order"]
+        )]
+        sorted: bool,
     },
     /// Smart merge by automatically grouping files by schema compatibility
     SmartMerge {
@@ -71,131 +125,1192 @@ enum Commands {
         #[arg(short, long, default_value_t = true)]
         color: bool,
 
-        /// Show only specific columns
-        #[arg(short = 'C', long)]
-        columns: Option<Vec<String>>,
+        /// Show only specific columns
+        #[arg(short = 'C', long)]
+        columns: Option<Vec<String>>,
+
+        /// Filter files by pattern (e.g., "attitude" matches "attitude.parquet" and "attitude_final.parquet")
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Recursively search for parquet files in subdirectories
+        #[arg(short, long, default_value_t = false)]
+        recursive: bool,
+
+        /// Limit the number of rows printed
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+    /// Show row group metadata and column statistics of a parquet file
+    RowGroups {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+    },
+    /// Plot a numeric column of a parquet file as an ASCII chart
+    Plot {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Column to plot
+        #[arg(short = 'C', long)]
+        column: String,
+
+        /// Height of the chart in characters
+        #[arg(long, default_value_t = 20)]
+        height: usize,
+
+        /// Width of the chart in characters
+        #[arg(long, default_value_t = 80)]
+        width: usize,
+
+        /// Use colored output formatting
+        #[arg(short, long, default_value_t = true)]
+        color: bool,
+    },
+    /// Print an ASCII histogram of a numeric column
+    Histogram {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Column to summarize
+        #[arg(short = 'C', long)]
+        column: String,
+
+        /// Number of bins
+        #[arg(short, long, default_value_t = 10)]
+        bins: usize,
+
+        /// Width of the longest bar in characters
+        #[arg(long, default_value_t = 60)]
+        width: usize,
+    },
+    /// Print descriptive statistics (min, max, mean, percentiles, ...) of numeric columns
+    Stats {
+        /// Input parquet files or directories, summarized together
+        #[arg(short, long, num_args = 1.., required = true)]
+        input: Vec<PathBuf>,
+
+        /// Columns to summarize (comma separated or repeated), all numeric columns if omitted
+        #[arg(short = 'C', long, alias = "column", value_delimiter = ',')]
+        columns: Vec<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = StatsFormat::Table)]
+        format: StatsFormat,
+
+        /// Filter files of input directories by pattern
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Recursively search input directories for parquet files
+        #[arg(short, long, default_value_t = false)]
+        recursive: bool,
+
+        /// Use colored output formatting
+        #[arg(short, long, default_value_t = true)]
+        color: bool,
+    },
+    /// Recompress a parquet file with a different codec
+    Compress {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output parquet file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Compression codec: snappy, zstd, gzip, lz4 or uncompressed
+        #[arg(long, default_value = "zstd")]
+        codec: String,
+    },
+    /// Split a parquet file into parts with a maximum number of rows each
+    Split {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output directory for the parts
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Maximum number of rows per part
+        #[arg(short, long, default_value_t = 1_000_000)]
+        rows: usize,
+    },
+    /// Split a parquet file into parts of about a maximum size each
+    SplitBySize {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output directory for the parts
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Maximum size of a part in bytes
+        #[arg(short, long, default_value_t = 100 * 1024 * 1024)]
+        max_bytes: usize,
+    },
+    /// Recover the readable row groups of a damaged parquet file
+    Repair {
+        /// Damaged parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output parquet file for the recovered rows
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Check that a parquet file has the columns and types of an expected schema
+    ValidateSchema {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Expected schema as Arrow schema JSON
+        #[arg(short, long)]
+        schema_file: PathBuf,
+    },
+    /// Export a parquet file to another format
+    Export {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Export format
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::InfluxdbLp)]
+        format: ExportFormat,
+
+        /// Measurement name (defaults to the input file name)
+        #[arg(short, long)]
+        measurement: Option<String>,
+
+        /// Column holding the row timestamps
+        #[arg(short, long, default_value = "timestamp")]
+        timestamp_column: String,
+
+        /// Columns written as tags instead of fields (can be repeated)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Print a random sample of rows from a parquet file
+    Sample {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Number of rows to sample
+        #[arg(short, long, default_value_t = 10)]
+        n: usize,
+
+        /// Seed for a reproducible sample
+        #[arg(short, long)]
+        seed: Option<u64>,
+
+        /// Use colored output formatting
+        #[arg(short, long, default_value_t = true)]
+        color: bool,
+    },
+    /// Print the rows of a parquet file within a time window
+    Query {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Timestamp column to filter on (microsecond or nanosecond)
+        #[arg(short = 'C', long)]
+        column: String,
+
+        /// Start of the window (inclusive), e.g. 2024-01-01T00:00:00Z
+        #[arg(short, long)]
+        start: DateTime<Utc>,
+
+        /// End of the window (exclusive), e.g. 2024-01-01T01:00:00Z
+        #[arg(short, long)]
+        end: DateTime<Utc>,
+
+        /// Write the matching rows to this parquet file instead of printing them
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Use colored output formatting
+        #[arg(short, long, default_value_t = true)]
+        color: bool,
+    },
+    /// Write a per-column summary (min, max, mean, null count) of parquet files
+    Snapshot {
+        /// Input file or directory
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output parquet file for the summary
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Recursively search for parquet files in subdirectories
+        #[arg(short, long, default_value_t = false)]
+        recursive: bool,
+
+        /// Use colored output formatting
+        #[arg(short, long, default_value_t = true)]
+        color: bool,
+    },
+    /// Print the strongest frequencies of a numeric column
+    #[cfg(feature = "fft")]
+    Fft {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Column to analyze
+        #[arg(short = 'C', long)]
+        column: String,
+
+        /// Sample rate of the column in Hz
+        #[arg(short, long)]
+        sample_rate: f64,
+
+        /// Number of peak frequencies to print
+        #[arg(short, long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Convert another log format into one parquet file per topic
+    #[cfg(feature = "mcap")]
+    Import {
+        /// Input file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output directory for the parquet files
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Input format
+        #[arg(short, long, value_enum, default_value_t = ImportFormat::Mcap)]
+        format: ImportFormat,
+
+        /// Only import topics containing this string
+        #[arg(short, long)]
+        topic: Option<String>,
+    },
+    /// Cast columns of a parquet file to new types
+    ConvertSchema {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output parquet file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Column cast as COLUMN=TYPE, e.g. "roll=Float64" (can be repeated)
+        #[arg(long = "cast")]
+        casts: Vec<String>,
+
+        /// Widen numeric columns to the types of this parquet file's schema
+        #[arg(long)]
+        like: Option<PathBuf>,
+    },
+    /// Dump a single column (or an x/y pair) to a text file for plotting
+    Column {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Column to extract
+        #[arg(short, long)]
+        column: String,
+
+        /// Output text file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Second column, writes "column,y" pairs for scatter plots
+        #[arg(short, long)]
+        y: Option<String>,
+
+        /// Write the column name as the first line
+        #[arg(long)]
+        header: bool,
+    },
+    /// Convert parquet files to CSV or JSON, one output file per input file
+    Convert {
+        /// Input parquet file or directory containing parquet files
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output directory
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Output format: csv or json
+        #[arg(long)]
+        format: parquet_ops::ConvertFormat,
+
+        /// Only convert files matching this pattern
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Recursively search for parquet files in subdirectories
+        #[arg(short, long, default_value_t = false)]
+        recursive: bool,
+    },
+    /// Compare the data of two parquet files, fails if they differ
+    Compare {
+        /// Reference parquet file
+        #[arg(short, long)]
+        expected: PathBuf,
+
+        /// Parquet file to check against the reference
+        #[arg(short, long)]
+        actual: PathBuf,
+
+        /// Maximum absolute difference allowed for numeric values
+        #[arg(short, long)]
+        tolerance: Option<f64>,
+
+        /// Column to leave out of the comparison (can be repeated)
+        #[arg(long = "ignore")]
+        ignore_columns: Vec<String>,
+
+        /// Sort both files by all columns before comparing
+        #[arg(long)]
+        ignore_row_order: bool,
+    },
+    /// Show the rows added, removed and changed between two parquet files
+    Diff {
+        /// Original parquet file
+        #[arg(short = 'a', long)]
+        file_a: PathBuf,
+
+        /// Parquet file to compare against the original
+        #[arg(short = 'b', long)]
+        file_b: PathBuf,
+
+        /// Columns identifying a row (comma separated or repeated)
+        #[arg(short, long, value_delimiter = ',', required = true)]
+        key: Vec<String>,
+
+        /// Use colored output formatting
+        #[arg(short, long, default_value_t = true)]
+        color: bool,
+    },
+    /// Run consistency checks on a parquet file
+    Check {
+        /// Input parquet file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Column to check
+        #[arg(short = 'C', long)]
+        column: String,
+
+        /// Check that every value of the column exists in this lookup parquet file
+        #[arg(long)]
+        referential: Option<PathBuf>,
+
+        /// Key column of the lookup file, defaults to the checked column name
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Run interactive TUI mode
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Input directory containing parquet files
+        #[arg(short, long)]
+        input: PathBuf,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Merge {
+            input,
+            output,
+            recursive,
+            filter,
+            force,
+            canonical_order,
+            parquet_compression,
+            sort_column,
+            sorted,
+        } => {
+            println!("Merging parquet files from {:?} to {:?}", input, output);
+            let compression = parquet_compression
+                .as_deref()
+                .map(parquet_ops::parse_compression)
+                .transpose()?;
+            match sort_column {
+                // Presorted inputs are merged in one pass, clap ensures the column is set
+                Some(sort_column) if sorted => {
+                    merge_sorted_parquet_files(
+                        input,
+                        output,
+                        recursive,
+                        filter,
+                        sort_column,
+                        compression,
+                    )?;
+                }
+                sort_column => {
+                    // When sorting, merge into a temporary file first and sort that into the output
+                    let merged = match &sort_column {
+                        Some(_) => output.with_extension("unsorted.parquet"),
+                        None => output.clone(),
+                    };
+                    merge_parquet_files(
+                        input,
+                        merged.clone(),
+                        recursive,
+                        filter,
+                        force,
+                        canonical_order,
+                        compression,
+                    )?;
+                    if let Some(sort_column) = sort_column {
+                        sort_merged_file(merged, output, sort_column)?;
+                    }
+                }
+            }
+        }
+        Commands::SmartMerge {
+            input,
+            output_dir,
+            base_name,
+            recursive,
+            filter,
+        } => {
+            println!(
+                "Smart merging parquet files from {:?} to {:?}",
+                input, output_dir
+            );
+            smart_merge_parquet_files(input, output_dir, base_name, recursive, filter)?;
+        }
+        Commands::Print {
+            input,
+            color,
+            columns,
+            filter,
+            recursive,
+            limit,
+        } => {
+            println!("Printing parquet files from {:?}", input);
+            print_parquet_files(input, color, columns, filter, recursive, limit)?;
+        }
+        Commands::RowGroups { input } => {
+            if !input.is_file() {
+                return Err(anyhow::anyhow!(
+                    "Input path is not a file: {}",
+                    input.display()
+                ));
+            }
+            println!("{}", parquet_ops::print_row_groups(&input)?);
+        }
+        Commands::Plot {
+            input,
+            column,
+            height,
+            width,
+            color,
+        } => {
+            println!("Plotting column '{}' from {:?}", column, input);
+            plot_parquet_column(input, column, height, width, color)?;
+        }
+        Commands::Compress {
+            input,
+            output,
+            codec,
+        } => {
+            println!("Recompressing {:?} to {:?} with {}", input, output, codec);
+            compress_parquet_file(input, output, codec)?;
+        }
+        Commands::Split {
+            input,
+            output,
+            rows,
+        } => {
+            println!(
+                "Splitting {:?} into parts of {} rows in {:?}",
+                input, rows, output
+            );
+            let parts = parquet_ops::split_by_row_count(&input, &output, rows)?;
+            print_split_parts(&parts)?;
+        }
+        Commands::SplitBySize {
+            input,
+            output,
+            max_bytes,
+        } => {
+            println!(
+                "Splitting {:?} into parts of at most {} bytes in {:?}",
+                input, max_bytes, output
+            );
+            let parts = parquet_ops::split_by_size_bytes(&input, &output, max_bytes)?;
+            print_split_parts(&parts)?;
+        }
+        Commands::Repair { input, output } => {
+            println!("Repairing {:?} into {:?}", input, output);
+            let report = parquet_ops::repair_parquet(&input, &output)?;
+            println!(
+                "Recovered {} rows from {} row groups, {} row groups lost",
+                report.total_rows_recovered, report.row_groups_recovered, report.row_groups_lost
+            );
+        }
+        Commands::ValidateSchema { input, schema_file } => {
+            let expected = parquet_ops::read_schema_json(&schema_file)?;
+            let report = parquet_ops::validate_schema(&input, &expected)?;
+            print_schema_validation(&input, &report);
+            if !report.is_compatible {
+                return Err(anyhow::anyhow!(
+                    "{} does not match the schema in {}",
+                    input.display(),
+                    schema_file.display()
+                ));
+            }
+        }
+        Commands::Export {
+            input,
+            output,
+            format,
+            measurement,
+            timestamp_column,
+            tags,
+        } => {
+            println!("Exporting {:?} to {:?} as {:?}", input, output, format);
+            export_parquet_file(input, output, format, measurement, timestamp_column, tags)?;
+        }
+        Commands::Sample {
+            input,
+            n,
+            seed,
+            color,
+        } => {
+            println!("Sampling {} rows from {:?}", n, input);
+            sample_parquet_file(input, n, seed, color)?;
+        }
+        Commands::Stats {
+            input,
+            columns,
+            format,
+            filter,
+            recursive,
+            color,
+        } => {
+            // Keep stdout parseable for csv / json
+            eprintln!("Computing column statistics of {:?}", input);
+            print_column_statistics(input, columns, format, filter, recursive, color)?;
+        }
+        Commands::Query {
+            input,
+            column,
+            start,
+            end,
+            output,
+            color,
+        } => {
+            println!(
+                "Querying {:?} for '{}' between {} and {}",
+                input, column, start, end
+            );
+            query_parquet_file(input, column, start, end, output, color)?;
+        }
+        Commands::Snapshot {
+            input,
+            output,
+            recursive,
+            color,
+        } => {
+            println!("Summarizing parquet files from {:?}", input);
+            snapshot_parquet_files(input, output, recursive, color)?;
+        }
+        Commands::ConvertSchema {
+            input,
+            output,
+            casts,
+            like,
+        } => {
+            println!("Converting schema of {:?} to {:?}", input, output);
+            convert_parquet_schema(input, output, casts, like)?;
+        }
+        Commands::Histogram {
+            input,
+            column,
+            bins,
+            width,
+        } => {
+            println!("Histogram of column '{}' from {:?}", column, input);
+            let histogram = parquet_ops::compute_histogram(&input, &column, bins)?;
+            print!("{}", histogram.to_ascii(width));
+        }
+        Commands::Column {
+            input,
+            column,
+            output,
+            y,
+            header,
+        } => {
+            println!(
+                "Extracting column '{}' from {:?} to {:?}",
+                column, input, output
+            );
+            extract_parquet_column(input, column, output, y, header)?;
+        }
+        Commands::Convert {
+            input,
+            output,
+            format,
+            filter,
+            recursive,
+        } => {
+            println!("Converting {:?} to {:?} as {:?}", input, output, format);
+            convert_parquet_files(input, output, format, filter, recursive)?;
+        }
+        Commands::Compare {
+            expected,
+            actual,
+            tolerance,
+            ignore_columns,
+            ignore_row_order,
+        } => {
+            println!("Comparing {:?} against {:?}", actual, expected);
+            compare_parquet_files(
+                expected,
+                actual,
+                tolerance,
+                ignore_columns,
+                ignore_row_order,
+            )?;
+        }
+        Commands::Diff {
+            file_a,
+            file_b,
+            key,
+            color,
+        } => {
+            println!("Diffing {:?} against {:?}", file_b, file_a);
+            diff_parquet_files(file_a, file_b, key, color)?;
+        }
+        Commands::Check {
+            input,
+            column,
+            referential,
+            key,
+        } => {
+            check_parquet_file(input, column, referential, key)?;
+        }
+        #[cfg(feature = "fft")]
+        Commands::Fft {
+            input,
+            column,
+            sample_rate,
+            top,
+        } => {
+            println!("Computing spectrum of column '{}' from {:?}", column, input);
+            fft_parquet_column(input, column, sample_rate, top)?;
+        }
+        #[cfg(feature = "mcap")]
+        Commands::Import {
+            input,
+            output,
+            format,
+            topic,
+        } => {
+            println!("Importing {:?} as {:?} into {:?}", input, format, output);
+            import_log_file(input, output, format, topic)?;
+        }
+        #[cfg(feature = "tui")]
+        Commands::Tui { input } => {
+            println!("Starting TUI mode with input directory {:?}", input);
+            log_utils::tui::run_tui_app(input)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the parquet files to merge and create the parent directories of the output
+fn find_merge_inputs(
+    input: &Path,
+    output: &Path,
+    recursive: bool,
+    filter: Option<String>,
+) -> Result<Vec<PathBuf>> {
+    // Check if input exists
+    if !input.exists() {
+        return Err(anyhow::anyhow!(
+            "Input path does not exist: {}",
+            input.display()
+        ));
+    }
+
+    // Find all parquet files in the input directory
+    let filter_str = filter.as_deref();
+    let files = if input.is_dir() {
+        parquet_ops::find_parquet_files(input, recursive, filter_str)?
+    } else if input.is_file() {
+        vec![input.to_path_buf()]
+    } else {
+        return Err(anyhow::anyhow!(
+            "Input path is neither a file nor directory: {}",
+            input.display()
+        ));
+    };
+
+    // Make sure we found at least one file
+    if files.is_empty() {
+        return Err(anyhow::anyhow!("No parquet files found matching filter"));
+    }
+
+    println!("Found {} parquet files to merge", files.len());
+
+    // Create parent directories for output if necessary
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    Ok(files)
+}
+
+/// Merge parquet files that are each sorted by `sort_column` into one sorted file
+fn merge_sorted_parquet_files(
+    input: PathBuf,
+    output: PathBuf,
+    recursive: bool,
+    filter: Option<String>,
+    sort_column: String,
+    compression: Option<parquet::basic::Compression>,
+) -> Result<()> {
+    let files = find_merge_inputs(&input, &output, recursive, filter)?;
+    println!("Merging rows in '{}' order", sort_column);
+    parquet_ops::merge_parquet_files_sorted(&files, &output, &sort_column, compression)?;
+
+    println!(
+        "Successfully merged {} files into {}",
+        files.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+fn merge_parquet_files(
+    input: PathBuf,
+    output: PathBuf,
+    recursive: bool,
+    filter: Option<String>,
+    force: bool,
+    canonical_order: bool,
+    compression: Option<parquet::basic::Compression>,
+) -> Result<()> {
+    let files = find_merge_inputs(&input, &output, recursive, filter)?;
+
+    // Merge files and write output
+    let progress_bar = new_merge_progress_bar(files.len());
+    let callback_bar = progress_bar.clone();
+    let mut options = MergeOptions::new()
+        .with_force_merge(force)
+        .with_canonical_order(canonical_order)
+        .with_progress_callback(move |report| {
+            callback_bar.set_position(report.files_processed as u64);
+            callback_bar.set_message(format!("{} rows", report.rows_written));
+        });
+    if let Some(compression) = compression {
+        options = options.with_compression(compression);
+    }
+    parquet_ops::merge_parquet_files_to_output(&files, &output, &options)?;
+    progress_bar.finish_and_clear();
+
+    println!(
+        "Successfully merged {} files into {}",
+        files.len(),
+        output.display()
+    );
+
+    Ok(())
+}
+
+fn sort_merged_file(merged: PathBuf, output: PathBuf, sort_column: String) -> Result<()> {
+    println!("Sorting merged rows by '{}'", sort_column);
+    let sorted = parquet_ops::sort_parquet_by_column(
+        std::slice::from_ref(&merged),
+        &output,
+        &sort_column,
+        true,
+        Some(parquet_ops::DEFAULT_SORT_MEMORY_BYTES),
+    );
+    std::fs::remove_file(&merged)
+        .with_context(|| format!("Failed to remove {}", merged.display()))?;
+    sorted?;
+
+    println!("Wrote sorted rows to {}", output.display());
+    Ok(())
+}
+
+fn compress_parquet_file(input: PathBuf, output: PathBuf, codec: String) -> Result<()> {
+    if !input.is_file() {
+        return Err(anyhow::anyhow!(
+            "Input path is not a file: {}",
+            input.display()
+        ));
+    }
+
+    let compression = parquet_ops::parse_compression(&codec)?;
+
+    // Create parent directories for output if necessary
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let (rows, old_size, new_size) = parquet_ops::recompress_parquet(&input, &output, compression)?;
+    let ratio = if old_size > 0 {
+        new_size as f64 / old_size as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    println!(
+        "Wrote {} rows to {}: {} -> {} bytes ({:.1}%)",
+        rows,
+        output.display(),
+        old_size,
+        new_size,
+        ratio
+    );
+
+    Ok(())
+}
+
+fn print_schema_validation(input: &Path, report: &parquet_ops::SchemaValidationReport) {
+    for column in &report.missing_columns {
+        println!("{} {}", "Missing column:".red(), column);
+    }
+    for (column, expected, actual) in &report.type_mismatches {
+        println!(
+            "{} {} (expected {}, found {})",
+            "Type mismatch:".red(),
+            column,
+            expected,
+            actual
+        );
+    }
+    for column in &report.extra_columns {
+        println!("{} {}", "Extra column:".yellow(), column);
+    }
+    if report.is_compatible {
+        println!("{} is compatible", input.display().to_string().green());
+    }
+}
+
+fn print_split_parts(parts: &[PathBuf]) -> Result<()> {
+    for part in parts {
+        let size = std::fs::metadata(part)
+            .with_context(|| format!("Failed to read metadata of: {}", part.display()))?
+            .len();
+        println!("  {} ({} bytes)", part.display(), size);
+    }
+    println!("Wrote {} parts", parts.len());
+    Ok(())
+}
+
+fn export_parquet_file(
+    input: PathBuf,
+    output: PathBuf,
+    format: ExportFormat,
+    measurement: Option<String>,
+    timestamp_column: String,
+    tags: Vec<String>,
+) -> Result<()> {
+    if !input.is_file() {
+        return Err(anyhow::anyhow!(
+            "Input path is not a file: {}",
+            input.display()
+        ));
+    }
+
+    // Create parent directories for output if necessary
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let lines = match format {
+        ExportFormat::InfluxdbLp => {
+            let measurement = measurement.unwrap_or_else(|| {
+                input
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "measurement".to_string())
+            });
+            parquet_ops::export_to_influxdb_line_protocol(
+                &input,
+                &output,
+                &measurement,
+                &timestamp_column,
+                &tags,
+            )?
+        }
+    };
+
+    println!("Exported {} lines to {}", lines, output.display());
+    Ok(())
+}
+
+fn extract_parquet_column(
+    input: PathBuf,
+    column: String,
+    output: PathBuf,
+    y: Option<String>,
+    header: bool,
+) -> Result<()> {
+    if !input.is_file() {
+        return Err(anyhow::anyhow!(
+            "Input path is not a file: {}",
+            input.display()
+        ));
+    }
+
+    // Create parent directories for output if necessary
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let rows = match y {
+        Some(y) => parquet_ops::extract_two_columns_as_csv(&input, &column, &y, &output)?,
+        None => parquet_ops::extract_column_as_csv(&input, &column, &output, header)?,
+    };
+
+    println!("Wrote {} rows to {}", rows, output.display());
+    Ok(())
+}
+
+fn convert_parquet_files(
+    input: PathBuf,
+    output: PathBuf,
+    format: parquet_ops::ConvertFormat,
+    filter: Option<String>,
+    recursive: bool,
+) -> Result<()> {
+    // Output files keep their path relative to the input directory
+    let (files, base_dir) = if input.is_file() {
+        let base_dir = input.parent().map(PathBuf::from).unwrap_or_default();
+        (vec![input], base_dir)
+    } else {
+        let files = parquet_ops::find_parquet_files(&input, recursive, filter.as_deref())?;
+        (files, input)
+    };
+
+    if files.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No parquet files found in {}",
+            base_dir.display()
+        ));
+    }
 
-        /// Filter files by pattern (e.g., "attitude" matches "attitude.parquet" and "attitude_final.parquet")
-        #[arg(short, long)]
-        filter: Option<String>,
+    for file in &files {
+        let relative = file.strip_prefix(&base_dir).unwrap_or(file);
+        let target = output.join(relative).with_extension(format.extension());
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
 
-        /// Recursively search for parquet files in subdirectories
-        #[arg(short, long, default_value_t = false)]
-        recursive: bool,
+        parquet_ops::convert_to_format(file, &target, format)?;
+        println!("Converted {} to {}", file.display(), target.display());
+    }
 
-        /// Limit the number of rows printed
-        #[arg(short, long)]
-        limit: Option<usize>,
-    },
-    /// Run interactive TUI mode
-    #[cfg(feature = "tui")]
-    Tui {
-        /// Input directory containing parquet files
-        #[arg(short, long)]
-        input: PathBuf,
-    },
+    println!("Converted {} files", files.len());
+    Ok(())
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn compare_parquet_files(
+    expected: PathBuf,
+    actual: PathBuf,
+    tolerance: Option<f64>,
+    ignore_columns: Vec<String>,
+    ignore_row_order: bool,
+) -> Result<()> {
+    let mut options = parquet_ops::CompareOptions::new()
+        .with_ignore_columns(ignore_columns)
+        .with_ignore_row_order(ignore_row_order);
+    if let Some(tolerance) = tolerance {
+        options = options.with_tolerance(tolerance);
+    }
 
-    match cli.command {
-        Commands::Merge {
-            input,
-            output,
-            recursive,
-            filter,
-            force,
-        } => {
-            println!("Merging parquet files from {:?} to {:?}", input, output);
-            merge_parquet_files(input, output, recursive, filter, force)?;
-        }
-        Commands::SmartMerge {
-            input,
-            output_dir,
-            base_name,
-            recursive,
-            filter,
-        } => {
-            println!(
-                "Smart merging parquet files from {:?} to {:?}",
-                input, output_dir
-            );
-            smart_merge_parquet_files(input, output_dir, base_name, recursive, filter)?;
+    let report = parquet_ops::compare_parquet_data(&expected, &actual, options)?;
+    if report.equal {
+        println!("Files are equal");
+        return Ok(());
+    }
+
+    if report.row_count_diff != 0 {
+        println!("Row count differs by {:+}", report.row_count_diff);
+    }
+    for mismatch in &report.mismatched_columns {
+        match mismatch.first_mismatch_row {
+            Some(row) => println!(
+                "Column '{}': {} rows differ, first at row {} ({})",
+                mismatch.column, mismatch.mismatched_rows, row, mismatch.detail
+            ),
+            None => println!("Column '{}': {}", mismatch.column, mismatch.detail),
         }
-        Commands::Print {
-            input,
-            color,
-            columns,
-            filter,
-            recursive,
-            limit,
-        } => {
-            println!("Printing parquet files from {:?}", input);
-            print_parquet_files(input, color, columns, filter, recursive, limit)?;
+    }
+    Err(anyhow::anyhow!("Files differ"))
+}
+
+fn diff_parquet_files(
+    file_a: PathBuf,
+    file_b: PathBuf,
+    key: Vec<String>,
+    color: bool,
+) -> Result<()> {
+    let key_columns: Vec<&str> = key.iter().map(String::as_str).collect();
+    let result = parquet_ops::diff(&file_a, &file_b, &key_columns)?;
+    let sign = |sign: &str| {
+        if !color {
+            sign.normal()
+        } else if sign == "+" {
+            sign.green().bold()
+        } else if sign == "-" {
+            sign.red().bold()
+        } else {
+            sign.yellow().bold()
         }
-        #[cfg(feature = "tui")]
-        Commands::Tui { input } => {
-            println!("Starting TUI mode with input directory {:?}", input);
-            log_utils::tui::run_tui_app(input)?;
+    };
+
+    for batch in &result.removed {
+        println!("{} {}", sign("-"), format_diff_row(batch, None, color)?);
+    }
+    for batch in &result.added {
+        println!("{} {}", sign("+"), format_diff_row(batch, None, color)?);
+    }
+    for (old, new) in &result.changed {
+        println!("{} {}", sign("~"), format_diff_row(old, Some(&key), color)?);
+        let old_values = utils::get_row_values(old, 0, None)?;
+        let new_values = utils::get_row_values(new, 0, None)?;
+        for ((name, old_value, data_type), (_, new_value, _)) in old_values.iter().zip(&new_values)
+        {
+            if old_value == new_value {
+                continue;
+            }
+            let (old_value, new_value) = if color {
+                (
+                    utils::colorize_value(old_value, data_type),
+                    utils::colorize_value(new_value, data_type),
+                )
+            } else {
+                (old_value.normal(), new_value.normal())
+            };
+            println!("    {} {}: {}", sign("-"), name, old_value);
+            println!("    {} {}: {}", sign("+"), name, new_value);
         }
     }
 
+    println!(
+        "{} added, {} removed, {} changed",
+        result.added.len(),
+        result.removed.len(),
+        result.changed.len()
+    );
     Ok(())
 }
 
-fn merge_parquet_files(
+/// Formats the first row of a batch as `name=value` pairs, limited to `columns` if given
+fn format_diff_row(batch: &RecordBatch, columns: Option<&[String]>, color: bool) -> Result<String> {
+    let values = utils::get_row_values(batch, 0, columns)?;
+    let pairs: Vec<String> = values
+        .iter()
+        .map(|(name, value, data_type)| {
+            if color {
+                format!(
+                    "{}={}",
+                    name.bright_blue(),
+                    utils::colorize_value(value, data_type)
+                )
+            } else {
+                format!("{}={}", name, value)
+            }
+        })
+        .collect();
+    Ok(pairs.join(" "))
+}
+
+fn check_parquet_file(
     input: PathBuf,
-    output: PathBuf,
-    recursive: bool,
-    filter: Option<String>,
-    force: bool,
+    column: String,
+    referential: Option<PathBuf>,
+    key: Option<String>,
 ) -> Result<()> {
-    // Check if input exists
-    if !input.exists() {
+    let Some(dimension_file) = referential else {
         return Err(anyhow::anyhow!(
-            "Input path does not exist: {}",
-            input.display()
+            "No check selected, use --referential <LOOKUP_FILE>"
+        ));
+    };
+    let key = key.unwrap_or_else(|| column.clone());
+    println!(
+        "Checking '{}' of {:?} against '{}' of {:?}",
+        column, input, key, dimension_file
+    );
+
+    let report = parquet_ops::verify_referential_integrity(&input, &column, &dimension_file, &key)?;
+    println!(
+        "{} of {} rows reference existing keys",
+        report.valid_rows, report.total_rows
+    );
+    if report.orphan_rows() > 0 {
+        println!("Missing keys: {}", report.orphan_values.join(", "));
+        return Err(anyhow::anyhow!(
+            "{} rows reference missing keys",
+            report.orphan_rows()
         ));
     }
 
-    // Find all parquet files in the input directory
-    let filter_str = filter.as_deref();
-    let files = if input.is_dir() {
-        parquet_ops::find_parquet_files(&input, recursive, filter_str)?
-    } else if input.is_file() {
-        vec![input.clone()]
-    } else {
+    Ok(())
+}
+
+#[cfg(feature = "mcap")]
+fn import_log_file(
+    input: PathBuf,
+    output: PathBuf,
+    format: ImportFormat,
+    topic: Option<String>,
+) -> Result<()> {
+    if !input.is_file() {
         return Err(anyhow::anyhow!(
-            "Input path is neither a file nor directory: {}",
+            "Input path is not a file: {}",
             input.display()
         ));
-    };
-
-    // Make sure we found at least one file
-    if files.is_empty() {
-        return Err(anyhow::anyhow!("No parquet files found matching filter"));
     }
 
-    // Merge the files
-    println!("Found {} parquet files to merge", files.len());
+    let files = match format {
+        ImportFormat::Mcap => parquet_ops::import_from_mcap(&input, &output, topic.as_deref())?,
+    };
 
-    // Create parent directories for output if necessary
-    if let Some(parent) = output.parent() {
-        std::fs::create_dir_all(parent)
-            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    for file in &files {
+        println!("  {}", file.display());
     }
+    println!("Imported {} topics to {}", files.len(), output.display());
+    Ok(())
+}
 
-    // Merge files and write output
-    parquet_ops::merge_parquet_files_to_output(&files, &output, force)?;
-
-    println!(
-        "Successfully merged {} files into {}",
-        files.len(),
-        output.display()
+fn new_merge_progress_bar(total_files: usize) -> ProgressBar {
+    let progress_bar = ProgressBar::new(total_files as u64);
+    progress_bar.set_style(
+        ProgressStyle::with_template("[{elapsed_precise}] {bar:40} {pos}/{len} files ({msg})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar()),
     );
-
-    Ok(())
+    progress_bar
 }
 
 fn smart_merge_parquet_files(
@@ -238,8 +1353,24 @@ fn smart_merge_parquet_files(
         .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
 
     // Smart merge files by grouping according to schema
-    let output_files =
-        parquet_ops::merge_parquet_files_by_schema_groups(&files, &output_dir, &base_name)?;
+    // Each schema group restarts the bar with its own file count
+    let progress_bar = new_merge_progress_bar(files.len());
+    let callback_bar = progress_bar.clone();
+    let options = MergeOptions::new().with_progress_callback(move |report| {
+        if report.files_processed == 1 {
+            callback_bar.reset();
+            callback_bar.set_length(report.total_files as u64);
+        }
+        callback_bar.set_position(report.files_processed as u64);
+        callback_bar.set_message(format!("{} rows", report.rows_written));
+    });
+    let output_files = parquet_ops::merge_parquet_files_by_schema_groups(
+        &files,
+        &output_dir,
+        &base_name,
+        &options,
+    )?;
+    progress_bar.finish_and_clear();
 
     println!("Successfully created {} merged files", output_files.len());
 
@@ -317,3 +1448,332 @@ fn print_parquet_files(
 
     Ok(())
 }
+
+fn sample_parquet_file(input: PathBuf, n: usize, seed: Option<u64>, color: bool) -> Result<()> {
+    if !input.is_file() {
+        return Err(anyhow::anyhow!(
+            "Input path is not a file: {}",
+            input.display()
+        ));
+    }
+
+    let sample = parquet_ops::sample_parquet(&input, n, seed)?;
+    println!("{}", utils::pretty_print_batch(&sample, color, None, None)?);
+
+    Ok(())
+}
+
+fn print_column_statistics(
+    input: Vec<PathBuf>,
+    columns: Vec<String>,
+    format: StatsFormat,
+    filter: Option<String>,
+    recursive: bool,
+    color: bool,
+) -> Result<()> {
+    let mut files = Vec::new();
+    for path in input {
+        if path.is_dir() {
+            files.extend(parquet_ops::find_parquet_files(
+                &path,
+                recursive,
+                filter.as_deref(),
+            )?);
+        } else {
+            files.push(path);
+        }
+    }
+    if files.is_empty() {
+        return Err(anyhow::anyhow!("No parquet files found"));
+    }
+
+    let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+    let stats = parquet_ops::compute_files_column_statistics(&files, &columns)?;
+
+    let mut names: Vec<&String> = stats.keys().collect();
+    names.sort();
+    let headers = [
+        "column", "rows", "nulls", "min", "max", "mean", "p25", "p50", "p75", "p95", "std_dev",
+    ];
+    let values = |s: &parquet_ops::ColumnStats| {
+        [s.min, s.max, s.mean, s.p25, s.p50, s.p75, s.p95, s.std_dev]
+    };
+
+    match format {
+        StatsFormat::Table => {
+            let header = format!(
+                "{:<24} {:>8} {:>8} {}",
+                headers[0],
+                headers[1],
+                headers[2],
+                headers[3..]
+                    .iter()
+                    .map(|h| format!("{:>12}", h))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+            println!(
+                "{}",
+                if color {
+                    header.bold()
+                } else {
+                    header.normal()
+                }
+            );
+            for name in names {
+                let s = &stats[name];
+                let column = format!("{:<24}", name);
+                let nulls = format!("{:>8}", s.null_count);
+                println!(
+                    "{} {:>8} {} {}",
+                    if color {
+                        column.cyan()
+                    } else {
+                        column.normal()
+                    },
+                    s.row_count,
+                    if color && s.null_count > 0 {
+                        nulls.yellow()
+                    } else {
+                        nulls.normal()
+                    },
+                    values(s)
+                        .iter()
+                        .map(|v| format!("{:>12.4}", v))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+            }
+        }
+        StatsFormat::Csv => {
+            println!("{}", headers.join(","));
+            for name in names {
+                let s = &stats[name];
+                let values: Vec<String> = values(s).iter().map(f64::to_string).collect();
+                println!(
+                    "{},{},{},{}",
+                    name,
+                    s.row_count,
+                    s.null_count,
+                    values.join(",")
+                );
+            }
+        }
+        StatsFormat::Json => {
+            // NaN is not valid JSON, serde_json turns it into null
+            let entries: serde_json::Map<String, serde_json::Value> = names
+                .iter()
+                .map(|name| {
+                    let s = &stats[*name];
+                    let mut entry = serde_json::json!({
+                        "rows": s.row_count,
+                        "nulls": s.null_count,
+                    });
+                    for (header, value) in headers[3..].iter().zip(values(s)) {
+                        entry[*header] = serde_json::json!(value);
+                    }
+                    (name.to_string(), entry)
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&entries)?);
+        }
+    }
+    Ok(())
+}
+
+fn query_parquet_file(
+    input: PathBuf,
+    column: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    output: Option<PathBuf>,
+    color: bool,
+) -> Result<()> {
+    let batches = parquet_ops::query_by_time_range(&input, &column, start, end)?;
+    let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    if rows == 0 {
+        println!("No rows in the time range");
+        return Ok(());
+    }
+
+    let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches)?;
+    match output {
+        Some(output) => {
+            let file = std::fs::File::create(&output)
+                .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+            let mut writer = parquet::arrow::ArrowWriter::try_new(file, batch.schema(), None)?;
+            writer.write(&batch)?;
+            writer.close()?;
+            println!("Wrote {} rows to {}", rows, output.display());
+        }
+        None => println!("{}", utils::pretty_print_batch(&batch, color, None, None)?),
+    }
+    Ok(())
+}
+
+fn snapshot_parquet_files(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    recursive: bool,
+    color: bool,
+) -> Result<()> {
+    if input.is_file() {
+        if let Some(output) = output {
+            parquet_ops::snapshot_parquet(&input, &output)?;
+            println!(
+                "Wrote summary of {} to {}",
+                input.display(),
+                output.display()
+            );
+        } else {
+            let summary = parquet_ops::snapshot_record_batch(&input)?;
+            println!(
+                "{}",
+                utils::pretty_print_batch(&summary, color, None, None)?
+            );
+        }
+        return Ok(());
+    }
+
+    if !input.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Input path is neither a file nor directory: {}",
+            input.display()
+        ));
+    }
+
+    let files = parquet_ops::find_parquet_files(&input, recursive, None)?;
+    if files.is_empty() {
+        return Err(anyhow::anyhow!("No parquet files found"));
+    }
+
+    let summary = parquet_ops::snapshot_multiple_parquet(&files)?;
+    match output {
+        Some(output) => {
+            let file = std::fs::File::create(&output)
+                .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+            let mut writer =
+                parquet::arrow::arrow_writer::ArrowWriter::try_new(file, summary.schema(), None)?;
+            writer.write(&summary)?;
+            writer.close()?;
+            println!(
+                "Wrote summary of {} files to {}",
+                files.len(),
+                output.display()
+            );
+        }
+        None => println!(
+            "{}",
+            utils::pretty_print_batch(&summary, color, None, None)?
+        ),
+    }
+
+    Ok(())
+}
+
+fn convert_parquet_schema(
+    input: PathBuf,
+    output: PathBuf,
+    casts: Vec<String>,
+    like: Option<PathBuf>,
+) -> Result<()> {
+    if !input.is_file() {
+        return Err(anyhow::anyhow!(
+            "Input path is not a file: {}",
+            input.display()
+        ));
+    }
+
+    match (like, casts.is_empty()) {
+        (Some(like), true) => {
+            let target_schema = parquet_ops::get_schema(&like)?;
+            parquet_ops::auto_widen_schema(&input, &target_schema, &output)?;
+        }
+        (None, false) => {
+            let mut type_map = HashMap::new();
+            for cast in &casts {
+                let (column, data_type) = cast.split_once('=').ok_or_else(|| {
+                    anyhow::anyhow!("Invalid cast '{}', expected COLUMN=TYPE", cast)
+                })?;
+                let data_type = DataType::from_str(data_type.trim())
+                    .map_err(|e| anyhow::anyhow!("Invalid type in cast '{}': {}", cast, e))?;
+                type_map.insert(column.trim().to_string(), data_type);
+            }
+            parquet_ops::convert_schema_types(&input, &output, &type_map)?;
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Specify either --cast or --like, but not both"
+            ))
+        }
+    }
+
+    println!("Wrote converted file to {}", output.display());
+    Ok(())
+}
+
+fn plot_parquet_column(
+    input: PathBuf,
+    column: String,
+    height: usize,
+    width: usize,
+    color: bool,
+) -> Result<()> {
+    if !input.is_file() {
+        return Err(anyhow::anyhow!(
+            "Input path is not a file: {}",
+            input.display()
+        ));
+    }
+
+    let batches = parquet_ops::collect_record_batches(&input)?;
+    if batches.is_empty() {
+        return Err(anyhow::anyhow!("No data in file: {}", input.display()));
+    }
+
+    // Plot all rows of the file as one series
+    let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches)?;
+    let output = utils::plot_ascii_timeseries(&batch, &column, height, width, color)?;
+    println!("{}", output);
+
+    Ok(())
+}
+
+#[cfg(feature = "fft")]
+fn fft_parquet_column(input: PathBuf, column: String, sample_rate: f64, top: usize) -> Result<()> {
+    if !input.is_file() {
+        return Err(anyhow::anyhow!(
+            "Input path is not a file: {}",
+            input.display()
+        ));
+    }
+
+    let batches = parquet_ops::collect_record_batches(&input)?;
+    if batches.is_empty() {
+        return Err(anyhow::anyhow!("No data in file: {}", input.display()));
+    }
+
+    // Analyze all rows of the file as one series, skipping nulls
+    let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches)?;
+    let samples: Vec<f64> = utils::get_numeric_column_values(&batch, &column)?
+        .into_iter()
+        .flatten()
+        .collect();
+    let (frequencies, magnitudes) = utils::compute_fft(&samples, sample_rate)?;
+
+    let mut bins: Vec<usize> = (0..magnitudes.len()).collect();
+    bins.sort_by(|&a, &b| magnitudes[b].total_cmp(&magnitudes[a]));
+
+    println!(
+        "{} samples at {} Hz, resolution {:.4} Hz",
+        samples.len(),
+        sample_rate,
+        sample_rate / samples.len() as f64
+    );
+    println!("{:>14}  {:>14}", "frequency (Hz)", "magnitude");
+    for bin in bins.into_iter().take(top) {
+        println!("{:>14.4}  {:>14.6}", frequencies[bin], magnitudes[bin]);
+    }
+
+    Ok(())
+}
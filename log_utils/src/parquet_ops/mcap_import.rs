@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use arrow::array::{ArrayRef, RecordBatch, TimestampNanosecondArray};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::json::reader::{infer_json_schema_from_iterator, ReaderBuilder};
+use parquet::arrow::arrow_writer::ArrowWriter;
+
+/// Column added to every imported topic with the MCAP log time of each message
+pub const MCAP_LOG_TIME_COLUMN: &str = "log_time";
+
+/// Messages of a single MCAP topic, decoded as JSON
+#[derive(Default)]
+struct TopicMessages {
+    log_times: Vec<i64>,
+    values: Vec<serde_json::Value>,
+}
+
+/// Converts the JSON encoded topics of an MCAP bag into one `{topic_name}.parquet` file each.
+/// Slashes in topic names become underscores, and only topics containing `topic_filter` are
+/// imported if it is given. Channels with a non-JSON message encoding are skipped.
+pub fn import_from_mcap(
+    mcap_path: &Path,
+    output_dir: &Path,
+    topic_filter: Option<&str>,
+) -> Result<Vec<PathBuf>> {
+    let bytes = std::fs::read(mcap_path)
+        .with_context(|| format!("Failed to read MCAP file: {}", mcap_path.display()))?;
+
+    // BTreeMap keeps the output files in topic order
+    let mut topics: BTreeMap<String, TopicMessages> = BTreeMap::new();
+    let mut skipped_topics: BTreeMap<String, String> = BTreeMap::new();
+    for message in mcap::MessageStream::new(&bytes)? {
+        let message = message?;
+        let topic = &message.channel.topic;
+        if let Some(filter) = topic_filter {
+            if !topic.contains(filter) {
+                continue;
+            }
+        }
+        if message.channel.message_encoding != "json" {
+            skipped_topics
+                .entry(topic.clone())
+                .or_insert_with(|| message.channel.message_encoding.clone());
+            continue;
+        }
+
+        let value: serde_json::Value = serde_json::from_slice(&message.data)
+            .with_context(|| format!("Invalid JSON message on topic {}", topic))?;
+        let messages = topics.entry(topic.clone()).or_default();
+        messages.log_times.push(message.log_time as i64);
+        messages.values.push(value);
+    }
+
+    for (topic, encoding) in &skipped_topics {
+        eprintln!(
+            "Warning: Skipping topic {} with unsupported message encoding '{}'",
+            topic, encoding
+        );
+    }
+
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
+
+    let mut written = Vec::with_capacity(topics.len());
+    for (topic, messages) in topics {
+        let output = output_dir.join(format!("{}.parquet", mcap_topic_file_stem(&topic)));
+        let batch = topic_record_batch(&messages)
+            .with_context(|| format!("Failed to convert topic {}", topic))?;
+
+        let file = File::create(&output)
+            .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        written.push(output);
+    }
+
+    Ok(written)
+}
+
+/// `/imu/data` becomes `imu_data`
+fn mcap_topic_file_stem(topic: &str) -> String {
+    topic.trim_start_matches('/').replace('/', "_")
+}
+
+/// Builds a batch from the JSON messages of a topic with the log time as the first column
+fn topic_record_batch(messages: &TopicMessages) -> Result<RecordBatch> {
+    let json_schema = infer_json_schema_from_iterator(messages.values.iter().map(Ok))?;
+    let mut decoder = ReaderBuilder::new(Arc::new(json_schema))
+        .with_batch_size(messages.values.len().max(1))
+        .build_decoder()?;
+    decoder.serialize(&messages.values)?;
+    let json_batch = decoder
+        .flush()?
+        .ok_or_else(|| anyhow::anyhow!("No messages decoded"))?;
+
+    // Keep the message's own field if it already has one with the same name
+    if json_batch
+        .schema()
+        .column_with_name(MCAP_LOG_TIME_COLUMN)
+        .is_some()
+    {
+        return Ok(json_batch);
+    }
+
+    let mut fields = vec![Arc::new(Field::new(
+        MCAP_LOG_TIME_COLUMN,
+        DataType::Timestamp(TimeUnit::Nanosecond, None),
+        false,
+    ))];
+    fields.extend(json_batch.schema().fields().iter().cloned());
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(TimestampNanosecondArray::from(
+        messages.log_times.clone(),
+    ))];
+    columns.extend(json_batch.columns().iter().cloned());
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parquet_ops::read_parquet_file;
+    use arrow::record_batch::RecordBatchReader;
+
+    fn write_test_mcap(path: &Path) {
+        let mut writer =
+            mcap::Writer::new(std::io::BufWriter::new(File::create(path).unwrap())).unwrap();
+
+        // The writer assigns channel ids
+        let mut channel = |topic: &str, encoding: &str| {
+            writer
+                .add_channel(&mcap::Channel {
+                    topic: topic.to_string(),
+                    schema: None,
+                    message_encoding: encoding.to_string(),
+                    metadata: BTreeMap::new(),
+                })
+                .unwrap()
+        };
+        let imu = channel("/imu/data", "json");
+        let gps = channel("/gps/fix", "json");
+        let raw = channel("/camera/raw", "cdr");
+
+        let header = |channel_id: u16, sequence: u32, log_time: u64| mcap::records::MessageHeader {
+            channel_id,
+            sequence,
+            log_time,
+            publish_time: log_time,
+        };
+        for i in 0..3u32 {
+            let data = format!(r#"{{"roll": {}.5, "pitch": {}}}"#, i, i * 2);
+            writer
+                .write_to_known_channel(&header(imu, i, 1_000 + i as u64), data.as_bytes())
+                .unwrap();
+        }
+        writer
+            .write_to_known_channel(
+                &header(gps, 0, 2_000),
+                br#"{"lat": 47.6, "lon": -122.3, "fix": "3d"}"#,
+            )
+            .unwrap();
+        writer
+            .write_to_known_channel(&header(raw, 0, 3_000), &[0, 1, 0, 0])
+            .unwrap();
+        writer.finish().unwrap();
+    }
+
+    fn row_count(path: &Path) -> usize {
+        read_parquet_file(path)
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum()
+    }
+
+    #[test]
+    fn test_import_from_mcap() {
+        let dir = std::env::temp_dir().join(format!("log_utils_mcap_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bag = dir.join("bag.mcap");
+        write_test_mcap(&bag);
+
+        let output_dir = dir.join("parquet");
+        let files = import_from_mcap(&bag, &output_dir, None).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                output_dir.join("gps_fix.parquet"),
+                output_dir.join("imu_data.parquet")
+            ]
+        );
+        assert_eq!(row_count(&files[0]), 1);
+        assert_eq!(row_count(&files[1]), 3);
+
+        let schema = read_parquet_file(&files[1]).unwrap().schema();
+        assert_eq!(schema.field(0).name(), MCAP_LOG_TIME_COLUMN);
+        assert!(schema.column_with_name("roll").is_some());
+
+        let filtered = import_from_mcap(&bag, &dir.join("filtered"), Some("imu")).unwrap();
+        assert_eq!(
+            filtered,
+            vec![dir.join("filtered").join("imu_data.parquet")]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,16 +1,33 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
 use std::fs::File;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use arrow::array::RecordBatch;
-use arrow::datatypes::Schema;
+use arrow::array::{Array, ArrayRef, AsArray, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Float64Type, Int64Type, Schema, TimeUnit, UInt64Type};
 use arrow::record_batch::RecordBatchReader;
+use arrow::row::{RowConverter, Rows, SortField};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use chrono::{DateTime, Utc};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReader;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions};
 use parquet::arrow::arrow_writer::ArrowWriter;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
 use parquet::file::properties::WriterProperties;
 use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::statistics::Statistics;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::utils;
+
+#[cfg(feature = "mcap")]
+mod mcap_import;
+#[cfg(feature = "mcap")]
+pub use mcap_import::{import_from_mcap, MCAP_LOG_TIME_COLUMN};
 
 /// Reads a single parquet file and returns an iterator of record batches
 pub fn read_parquet_file(path: &Path) -> Result<ParquetRecordBatchReader> {
@@ -63,12 +80,69 @@ pub fn find_parquet_files(
     Ok(result)
 }
 
+/// Progress information passed to a merge progress callback after each source file
+#[derive(Debug, Clone)]
+pub struct ProgressReport {
+    pub files_processed: usize,
+    pub total_files: usize,
+    pub rows_written: u64,
+    pub current_file: PathBuf,
+}
+
+/// Options controlling how parquet files are merged
+#[derive(Default)]
+pub struct MergeOptions {
+    pub force_merge: bool,
+    pub canonical_order: bool,
+    /// Codec of the merged file, uncompressed when None
+    pub compression: Option<Compression>,
+    pub progress_callback: Option<Box<dyn Fn(ProgressReport)>>,
+}
+
+impl MergeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignore schema differences and skip unreadable files / batches
+    pub fn with_force_merge(mut self, force_merge: bool) -> Self {
+        self.force_merge = force_merge;
+        self
+    }
+
+    /// Sort the columns of every file alphabetically before merging
+    pub fn with_canonical_order(mut self, canonical_order: bool) -> Self {
+        self.canonical_order = canonical_order;
+        self
+    }
+
+    /// Compress the merged file with `codec`
+    pub fn with_compression(mut self, codec: Compression) -> Self {
+        self.compression = Some(codec);
+        self
+    }
+
+    /// Callback invoked after each source file has been written
+    pub fn with_progress_callback(mut self, callback: impl Fn(ProgressReport) + 'static) -> Self {
+        self.progress_callback = Some(Box::new(callback));
+        self
+    }
+
+    fn report_progress(&self, report: ProgressReport) {
+        if let Some(callback) = &self.progress_callback {
+            callback(report);
+        }
+    }
+}
+
 /// Merges multiple parquet files into a single output file
 pub fn merge_parquet_files_to_output(
     input_files: &[PathBuf],
     output_path: &Path,
-    force_merge: bool,
+    options: &MergeOptions,
 ) -> Result<()> {
+    let force_merge = options.force_merge;
+
     if input_files.is_empty() {
         return Err(anyhow::anyhow!("No input files found to merge"));
     }
@@ -76,13 +150,19 @@ pub fn merge_parquet_files_to_output(
     // Read the schema from the first file to ensure all files are compatible
     let first_file = &input_files[0];
     let first_reader = read_parquet_file(first_file)?;
-    let schema = first_reader.schema();
+    let mut schema = first_reader.schema();
+    if options.canonical_order {
+        schema = canonical_schema(&schema)?;
+    }
 
     // Check schema compatibility if not force merging
     if !force_merge && input_files.len() > 1 {
         for file_path in input_files.iter().skip(1) {
             let reader = read_parquet_file(file_path)?;
-            let file_schema = reader.schema();
+            let mut file_schema = reader.schema();
+            if options.canonical_order {
+                file_schema = canonical_schema(&file_schema)?;
+            }
 
             // Compare schemas for compatibility
             if !schemas_compatible(&schema, &file_schema) {
@@ -101,14 +181,26 @@ pub fn merge_parquet_files_to_output(
         .with_context(|| format!("Failed to create output file: {}", output_path.display()))?;
 
     // Create Arrow writer with the schema
-    let props = WriterProperties::builder().build();
+    let mut props = WriterProperties::builder();
+    if let Some(compression) = options.compression {
+        props = props.set_compression(compression);
+    }
+    let props = props.build();
     let mut writer = ArrowWriter::try_new(output_file, schema.clone(), Some(props))?;
 
     // Read and write all batches from all files
-    for file_path in input_files {
+    let mut rows_written: u64 = 0;
+    for (i, file_path) in input_files.iter().enumerate() {
         match read_parquet_file(file_path) {
             Ok(reader) => {
                 for batch_result in reader {
+                    let batch_result = match batch_result {
+                        Ok(batch) if options.canonical_order => {
+                            let order = canonical_column_order(&batch.schema());
+                            reorder_record_batch(&batch, &order)
+                        }
+                        result => result.map_err(anyhow::Error::from),
+                    };
                     match batch_result {
                         Ok(batch) => {
                             if let Err(e) = writer.write(&batch) {
@@ -120,6 +212,8 @@ pub fn merge_parquet_files_to_output(
                                 if !force_merge {
                                     return Err(anyhow::anyhow!("Failed to write batch: {}", e));
                                 }
+                            } else {
+                                rows_written += batch.num_rows() as u64;
                             }
                         }
                         Err(e) => {
@@ -144,6 +238,13 @@ pub fn merge_parquet_files_to_output(
             }
             Err(e) => return Err(e),
         }
+
+        options.report_progress(ProgressReport {
+            files_processed: i + 1,
+            total_files: input_files.len(),
+            rows_written,
+            current_file: file_path.clone(),
+        });
     }
 
     // Finish writing and close the file
@@ -152,6 +253,562 @@ pub fn merge_parquet_files_to_output(
     Ok(())
 }
 
+/// Memory budget used by the merge CLI when sorting, see `sort_parquet_by_column`
+pub const DEFAULT_SORT_MEMORY_BYTES: usize = 512 * 1024 * 1024;
+
+/// Rows per batch written by the external merge of `sort_parquet_by_column`
+const SORT_OUTPUT_BATCH_ROWS: usize = 8192;
+
+/// Writes the rows of all input files to one file, sorted by `sort_column`.
+///
+/// Nulls are placed last and rows with equal keys keep their input order. If the uncompressed
+/// size of the inputs exceeds `max_memory_bytes`, the rows are sorted in chunks of that size
+/// spilled to temporary files, then k-way merged so only one batch per chunk is held in memory.
+/// The output uses the compression codec of the first input file.
+pub fn sort_parquet_by_column(
+    input_files: &[PathBuf],
+    output_path: &Path,
+    sort_column: &str,
+    ascending: bool,
+    max_memory_bytes: Option<usize>,
+) -> Result<()> {
+    let first_file = input_files
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("No input files found to sort"))?;
+    let schema = read_parquet_file(first_file)?.schema();
+    if schema.column_with_name(sort_column).is_none() {
+        return Err(anyhow::anyhow!("Column '{}' not found", sort_column));
+    }
+    let props = WriterProperties::builder()
+        .set_compression(file_compression(first_file)?)
+        .build();
+
+    let mut total_bytes = 0;
+    for file in input_files {
+        total_bytes += uncompressed_size(file)?;
+    }
+
+    match max_memory_bytes {
+        Some(max_memory_bytes) if total_bytes > max_memory_bytes => external_sort(
+            input_files,
+            output_path,
+            sort_column,
+            ascending,
+            max_memory_bytes,
+            schema,
+            props,
+        ),
+        _ => {
+            let mut batches = Vec::new();
+            for file in input_files {
+                batches.extend(collect_record_batches(file)?);
+            }
+            let batch = arrow::compute::concat_batches(&schema, &batches)?;
+            let sorted = sort_batch(&batch, sort_column, ascending)?;
+
+            let output_file = File::create(output_path).with_context(|| {
+                format!("Failed to create output file: {}", output_path.display())
+            })?;
+            let mut writer = ArrowWriter::try_new(output_file, schema, Some(props))?;
+            writer.write(&sorted)?;
+            writer.close()?;
+            Ok(())
+        }
+    }
+}
+
+fn sort_options(ascending: bool) -> arrow::compute::SortOptions {
+    arrow::compute::SortOptions {
+        descending: !ascending,
+        nulls_first: false,
+    }
+}
+
+fn sort_batch(batch: &RecordBatch, sort_column: &str, ascending: bool) -> Result<RecordBatch> {
+    let column = batch_column(batch, sort_column)?;
+    let indices = arrow::compute::sort_to_indices(column, Some(sort_options(ascending)), None)?;
+    Ok(arrow::compute::take_record_batch(batch, &indices)?)
+}
+
+/// Codec of the first column chunk of a file, uncompressed for an empty file
+fn file_compression(path: &Path) -> Result<Compression> {
+    let reader = SerializedFileReader::new(File::open(path)?)?;
+    let metadata = reader.metadata();
+    Ok(metadata
+        .row_groups()
+        .first()
+        .and_then(|row_group| row_group.columns().first())
+        .map(|column| column.compression())
+        .unwrap_or(Compression::UNCOMPRESSED))
+}
+
+/// Uncompressed size of all row groups of a file, as recorded in its metadata
+fn uncompressed_size(path: &Path) -> Result<usize> {
+    let reader = SerializedFileReader::new(File::open(path)?)?;
+    Ok(reader
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.total_byte_size().max(0) as usize)
+        .sum())
+}
+
+/// A sorted run being read back during the k-way merge of `external_sort`
+struct SortedRun {
+    reader: ParquetRecordBatchReader,
+    batch: RecordBatch,
+    /// Sort keys of `batch`
+    keys: Rows,
+    row: usize,
+    done: bool,
+}
+
+impl SortedRun {
+    fn open(
+        path: &Path,
+        schema: &Arc<Schema>,
+        sort_column: &str,
+        converter: &RowConverter,
+    ) -> Result<Self> {
+        let mut run = Self {
+            reader: read_parquet_file(path)?,
+            batch: RecordBatch::new_empty(schema.clone()),
+            keys: converter.empty_rows(0, 0),
+            row: 0,
+            done: false,
+        };
+        run.load_next_batch(sort_column, converter)?;
+        Ok(run)
+    }
+
+    /// Whether the current row is the last of the current batch
+    fn at_batch_end(&self) -> bool {
+        self.row + 1 >= self.batch.num_rows()
+    }
+
+    fn advance(&mut self, sort_column: &str, converter: &RowConverter) -> Result<()> {
+        self.row += 1;
+        if self.row >= self.batch.num_rows() {
+            self.load_next_batch(sort_column, converter)?;
+        }
+        Ok(())
+    }
+
+    fn load_next_batch(&mut self, sort_column: &str, converter: &RowConverter) -> Result<()> {
+        loop {
+            match self.reader.next() {
+                Some(batch) => {
+                    let batch = batch?;
+                    if batch.num_rows() == 0 {
+                        continue;
+                    }
+                    self.keys =
+                        converter.convert_columns(&[batch_column(&batch, sort_column)?.clone()])?;
+                    self.batch = batch;
+                    self.row = 0;
+                }
+                None => self.done = true,
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Sorts chunks of at most `max_memory_bytes` into temporary run files, then merges the runs
+fn external_sort(
+    input_files: &[PathBuf],
+    output_path: &Path,
+    sort_column: &str,
+    ascending: bool,
+    max_memory_bytes: usize,
+    schema: Arc<Schema>,
+    props: WriterProperties,
+) -> Result<()> {
+    let run_dir = std::env::temp_dir().join(format!(
+        "log_utils_sort_{}_{}",
+        std::process::id(),
+        output_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    ));
+    std::fs::create_dir_all(&run_dir)
+        .with_context(|| format!("Failed to create directory: {}", run_dir.display()))?;
+
+    let result = write_sorted_runs(
+        input_files,
+        &run_dir,
+        sort_column,
+        ascending,
+        max_memory_bytes,
+        &schema,
+    )
+    .and_then(|runs| merge_sorted_runs(&runs, output_path, sort_column, ascending, schema, props));
+    if let Err(e) = std::fs::remove_dir_all(&run_dir) {
+        eprintln!(
+            "Warning: Failed to remove temporary directory {}: {}",
+            run_dir.display(),
+            e
+        );
+    }
+    result
+}
+
+fn write_sorted_runs(
+    input_files: &[PathBuf],
+    run_dir: &Path,
+    sort_column: &str,
+    ascending: bool,
+    max_memory_bytes: usize,
+    schema: &Arc<Schema>,
+) -> Result<Vec<PathBuf>> {
+    let mut runs = Vec::new();
+    let mut pending = Vec::new();
+    let mut pending_bytes = 0;
+
+    let mut write_run = |pending: &mut Vec<RecordBatch>| -> Result<()> {
+        let batch = arrow::compute::concat_batches(schema, pending.iter())?;
+        pending.clear();
+        let path = run_dir.join(format!("run_{}.parquet", runs.len()));
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(SORT_OUTPUT_BATCH_ROWS)
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(&path)?, schema.clone(), Some(props))?;
+        writer.write(&sort_batch(&batch, sort_column, ascending)?)?;
+        writer.close()?;
+        runs.push(path);
+        Ok(())
+    };
+
+    for file in input_files {
+        for batch in read_parquet_file(file)? {
+            let batch = batch?;
+            pending_bytes += batch.get_array_memory_size();
+            pending.push(batch);
+            if pending_bytes >= max_memory_bytes {
+                write_run(&mut pending)?;
+                pending_bytes = 0;
+            }
+        }
+    }
+    if !pending.is_empty() {
+        write_run(&mut pending)?;
+    }
+
+    Ok(runs)
+}
+
+fn merge_sorted_runs(
+    runs: &[PathBuf],
+    output_path: &Path,
+    sort_column: &str,
+    ascending: bool,
+    schema: Arc<Schema>,
+    props: WriterProperties,
+) -> Result<()> {
+    let sort_type = schema.field_with_name(sort_column)?.data_type().clone();
+    let converter = RowConverter::new(vec![SortField::new_with_options(
+        sort_type,
+        sort_options(ascending),
+    )])?;
+    let mut runs = runs
+        .iter()
+        .map(|path| SortedRun::open(path, &schema, sort_column, &converter))
+        .collect::<Result<Vec<_>>>()?;
+
+    let output_file = File::create(output_path)
+        .with_context(|| format!("Failed to create output file: {}", output_path.display()))?;
+    let mut writer = ArrowWriter::try_new(output_file, schema, Some(props))?;
+
+    // (run, row) of the rows picked from the current batch of each run
+    let mut pending: Vec<(usize, usize)> = Vec::new();
+
+    // The first of several equal keys wins, keeping the input order of equal rows
+    while let Some(next) = (0..runs.len()).filter(|i| !runs[*i].done).min_by(|a, b| {
+        let (a, b) = (&runs[*a], &runs[*b]);
+        a.keys.row(a.row).cmp(&b.keys.row(b.row))
+    }) {
+        pending.push((next, runs[next].row));
+        // A run's batch is about to be replaced, write out the rows still pointing into it
+        if runs[next].at_batch_end() || pending.len() >= SORT_OUTPUT_BATCH_ROWS {
+            write_pending_rows(&runs, &mut pending, &mut writer)?;
+        }
+        runs[next].advance(sort_column, &converter)?;
+    }
+    write_pending_rows(&runs, &mut pending, &mut writer)?;
+
+    writer.close()?;
+    Ok(())
+}
+
+/// Write the `(run, row)` rows picked so far from the current batches of `runs`
+fn write_pending_rows(
+    runs: &[SortedRun],
+    pending: &mut Vec<(usize, usize)>,
+    writer: &mut ArrowWriter<File>,
+) -> Result<()> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let batches: Vec<&RecordBatch> = runs.iter().map(|run| &run.batch).collect();
+    writer.write(&arrow::compute::interleave_record_batch(&batches, pending)?)?;
+    pending.clear();
+    Ok(())
+}
+
+/// Merges parquet files that are each sorted ascending by `sort_column` into one sorted file.
+///
+/// An external k-way merge: one reader per input file and a min-heap of their current rows,
+/// so only one batch per file is held in memory. Rows with equal keys are taken in input
+/// file order. Fails if an input file is not sorted, see `sort_parquet_by_column` for those.
+/// The output uses `compression`, or the codec of the first input file when None.
+pub fn merge_parquet_files_sorted(
+    input_files: &[PathBuf],
+    output_path: &Path,
+    sort_column: &str,
+    compression: Option<Compression>,
+) -> Result<()> {
+    let first_file = input_files
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("No input files found to merge"))?;
+    let schema = read_parquet_file(first_file)?.schema();
+    let sort_type = schema
+        .column_with_name(sort_column)
+        .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", sort_column))?
+        .1
+        .data_type()
+        .clone();
+    for file in input_files.iter().skip(1) {
+        let file_schema = read_parquet_file(file)?.schema();
+        if !schemas_compatible(&schema, &file_schema) {
+            return Err(anyhow::anyhow!(
+                "Incompatible schemas between {} and {}",
+                first_file.display(),
+                file.display()
+            ));
+        }
+    }
+    let props = WriterProperties::builder()
+        .set_compression(match compression {
+            Some(compression) => compression,
+            None => file_compression(first_file)?,
+        })
+        .build();
+
+    let converter = RowConverter::new(vec![SortField::new_with_options(
+        sort_type,
+        sort_options(true),
+    )])?;
+    let mut runs = input_files
+        .iter()
+        .map(|path| SortedRun::open(path, &schema, sort_column, &converter))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Keyed on (current row, file index) so equal keys pop in input file order
+    let mut heap = BinaryHeap::new();
+    for (i, run) in runs.iter().enumerate() {
+        if !run.done {
+            heap.push(Reverse((run.keys.row(run.row).owned(), i)));
+        }
+    }
+
+    let output_file = File::create(output_path)
+        .with_context(|| format!("Failed to create output file: {}", output_path.display()))?;
+    let mut writer = ArrowWriter::try_new(output_file, schema, Some(props))?;
+
+    let mut pending: Vec<(usize, usize)> = Vec::new();
+    while let Some(Reverse((key, next))) = heap.pop() {
+        pending.push((next, runs[next].row));
+        // A run's batch is about to be replaced, write out the rows still pointing into it
+        if runs[next].at_batch_end() || pending.len() >= SORT_OUTPUT_BATCH_ROWS {
+            write_pending_rows(&runs, &mut pending, &mut writer)?;
+        }
+
+        let run = &mut runs[next];
+        run.advance(sort_column, &converter)?;
+        if run.done {
+            continue;
+        }
+        let next_key = run.keys.row(run.row);
+        if next_key < key.row() {
+            return Err(anyhow::anyhow!(
+                "{} is not sorted by '{}'",
+                input_files[next].display(),
+                sort_column
+            ));
+        }
+        heap.push(Reverse((next_key.owned(), next)));
+    }
+    write_pending_rows(&runs, &mut pending, &mut writer)?;
+
+    writer.close()?;
+    Ok(())
+}
+
+/// Column names of a schema sorted alphabetically
+fn canonical_column_order(schema: &Schema) -> Vec<String> {
+    let mut names: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
+    names.sort();
+    names
+}
+
+/// Schema with its columns sorted alphabetically
+fn canonical_schema(schema: &Arc<Schema>) -> Result<Arc<Schema>> {
+    let order = canonical_column_order(schema);
+    Ok(reorder_record_batch(&RecordBatch::new_empty(schema.clone()), &order)?.schema())
+}
+
+/// Rearranges the columns of a batch to `column_order`.
+/// Columns missing from the batch are added as null columns, columns not listed are dropped.
+pub fn reorder_record_batch(batch: &RecordBatch, column_order: &[String]) -> Result<RecordBatch> {
+    let schema = batch.schema();
+    let indices: Vec<Option<usize>> = column_order
+        .iter()
+        .map(|name| schema.index_of(name).ok())
+        .collect();
+
+    if indices.iter().all(Option::is_some) {
+        let indices: Vec<usize> = indices.into_iter().flatten().collect();
+        return Ok(batch.project(&indices)?);
+    }
+
+    let mut fields = Vec::with_capacity(column_order.len());
+    let mut columns = Vec::with_capacity(column_order.len());
+    for (name, index) in column_order.iter().zip(indices) {
+        match index {
+            Some(index) => {
+                fields.push(schema.field(index).clone());
+                columns.push(batch.column(index).clone());
+            }
+            None => {
+                fields.push(Field::new(name, DataType::Null, true));
+                columns.push(arrow::array::new_null_array(
+                    &DataType::Null,
+                    batch.num_rows(),
+                ));
+            }
+        }
+    }
+
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    let options = arrow::array::RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+    Ok(RecordBatch::try_new_with_options(
+        Arc::new(schema),
+        columns,
+        &options,
+    )?)
+}
+
+/// Rewrites a parquet file with its columns in `column_order`, padding missing columns with nulls
+pub fn reorder_columns(input: &Path, output: &Path, column_order: &[String]) -> Result<()> {
+    let reader = read_parquet_file(input)?;
+    let schema =
+        reorder_record_batch(&RecordBatch::new_empty(reader.schema()), column_order)?.schema();
+
+    let output_file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    let mut writer = ArrowWriter::try_new(output_file, schema, None)?;
+    for batch in reader {
+        writer.write(&reorder_record_batch(&batch?, column_order)?)?;
+    }
+    writer.close()?;
+
+    Ok(())
+}
+
+/// Casts the columns named in `type_map` to their new types and writes the result to `output`
+pub fn convert_schema_types(
+    input: &Path,
+    output: &Path,
+    type_map: &HashMap<String, DataType>,
+) -> Result<()> {
+    let reader = read_parquet_file(input)?;
+    let schema = reader.schema();
+
+    for name in type_map.keys() {
+        if schema.column_with_name(name).is_none() {
+            return Err(anyhow::anyhow!(
+                "Column {} not found in {}",
+                name,
+                input.display()
+            ));
+        }
+    }
+
+    let target_schema = Arc::new(Schema::new_with_metadata(
+        schema
+            .fields()
+            .iter()
+            .map(|field| match type_map.get(field.name()) {
+                Some(data_type) => field.as_ref().clone().with_data_type(data_type.clone()),
+                None => field.as_ref().clone(),
+            })
+            .collect::<Vec<_>>(),
+        schema.metadata().clone(),
+    ));
+
+    let output_file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    let mut writer = ArrowWriter::try_new(output_file, target_schema.clone(), None)?;
+    for batch in reader {
+        let batch = batch?;
+        let columns = batch
+            .columns()
+            .iter()
+            .zip(target_schema.fields())
+            .map(|(column, field)| arrow::compute::cast(column, field.data_type()))
+            .collect::<Result<Vec<_>, _>>()?;
+        writer.write(&RecordBatch::try_new(target_schema.clone(), columns)?)?;
+    }
+    writer.close()?;
+
+    Ok(())
+}
+
+/// True if every value of `from` can be represented exactly as `to`
+fn is_widening_cast(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+
+    match (from, to) {
+        _ if from == to => true,
+        (Int8, Int16 | Int32 | Int64) | (Int16, Int32 | Int64) | (Int32, Int64) => true,
+        (UInt8, UInt16 | UInt32 | UInt64 | Int16 | Int32 | Int64) => true,
+        (UInt16, UInt32 | UInt64 | Int32 | Int64) | (UInt32, UInt64 | Int64) => true,
+        (Int8 | Int16 | UInt8 | UInt16, Float32 | Float64) => true,
+        (Int32 | UInt32 | Float16, Float64) | (Float16, Float32) | (Float32, Float64) => true,
+        _ => false,
+    }
+}
+
+/// Widens the numeric columns of a file to the types they have in `target_schema`.
+/// Only lossless widening casts are allowed (e.g. Int32 to Int64); narrowing is an error.
+/// Columns that are not numeric or not part of `target_schema` are left unchanged.
+pub fn auto_widen_schema(input: &Path, target_schema: &Schema, output: &Path) -> Result<()> {
+    let schema = get_schema(input)?;
+
+    let mut type_map = HashMap::new();
+    for field in schema.fields() {
+        let Ok(target) = target_schema.field_with_name(field.name()) else {
+            continue;
+        };
+        let (from, to) = (field.data_type(), target.data_type());
+        if from == to || !from.is_numeric() || !to.is_numeric() {
+            continue;
+        }
+        if !is_widening_cast(from, to) {
+            return Err(anyhow::anyhow!(
+                "Refusing to narrow column {} from {} to {}",
+                field.name(),
+                from,
+                to
+            ));
+        }
+        type_map.insert(field.name().clone(), to.clone());
+    }
+
+    convert_schema_types(input, output, &type_map)
+}
+
 /// Helper function to check if two schemas are compatible for merging
 fn schemas_compatible(
     schema1: &arrow::datatypes::Schema,
@@ -217,71 +874,2186 @@ pub fn get_parquet_metadata(path: &Path) -> Result<String> {
     Ok(output)
 }
 
-/// Extracts the schema from a parquet file
-pub fn get_schema(path: &Path) -> Result<Schema> {
-    let reader = read_parquet_file(path)?;
-    Ok(Schema::from(reader.schema().as_ref().clone()))
-}
-
-/// Merges parquet files by first grouping them by schema compatibility
-pub fn merge_parquet_files_by_schema_groups(
-    input_files: &[PathBuf],
-    output_dir: &Path,
-    base_filename: &str,
-) -> Result<Vec<PathBuf>> {
-    if input_files.is_empty() {
-        return Err(anyhow::anyhow!("No input files found to merge"));
+/// Formats the min/max of column chunk statistics, with the arrow type used for coloring
+fn format_statistics(stats: &Statistics) -> Option<(String, String, DataType)> {
+    fn min_max<T: ToString>(min: Option<&T>, max: Option<&T>) -> Option<(String, String)> {
+        Some((min?.to_string(), max?.to_string()))
+    }
+    fn bytes_to_string(bytes: &parquet::data_type::ByteArray) -> String {
+        bytes
+            .as_utf8()
+            .map(|s| format!("\"{}\"", s))
+            .unwrap_or_else(|_| "<binary>".to_string())
     }
 
-    // Create schema groups - maps schema hash to list of files
-    let mut schema_groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let (min, max, data_type) = match stats {
+        Statistics::Boolean(s) => {
+            let (min, max) = min_max(s.min_opt(), s.max_opt())?;
+            (min, max, DataType::Boolean)
+        }
+        Statistics::Int32(s) => {
+            let (min, max) = min_max(s.min_opt(), s.max_opt())?;
+            (min, max, DataType::Int32)
+        }
+        Statistics::Int64(s) => {
+            let (min, max) = min_max(s.min_opt(), s.max_opt())?;
+            (min, max, DataType::Int64)
+        }
+        Statistics::Int96(s) => {
+            let (min, max) = (s.min_opt()?, s.max_opt()?);
+            (format!("{:?}", min), format!("{:?}", max), DataType::Binary)
+        }
+        Statistics::Float(s) => {
+            let (min, max) = min_max(s.min_opt(), s.max_opt())?;
+            (min, max, DataType::Float32)
+        }
+        Statistics::Double(s) => {
+            let (min, max) = min_max(s.min_opt(), s.max_opt())?;
+            (min, max, DataType::Float64)
+        }
+        Statistics::ByteArray(s) => {
+            let (min, max) = (s.min_opt()?, s.max_opt()?);
+            (bytes_to_string(min), bytes_to_string(max), DataType::Utf8)
+        }
+        Statistics::FixedLenByteArray(s) => {
+            let (min, max) = (s.min_opt()?, s.max_opt()?);
+            (bytes_to_string(min), bytes_to_string(max), DataType::Binary)
+        }
+    };
 
-    // Sort files into schema groups
-    for file_path in input_files {
-        // Skip files we can't read
-        let reader = match read_parquet_file(file_path) {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("Warning: Unable to read {}: {}", file_path.display(), e);
-                continue;
-            }
-        };
+    Some((min, max, data_type))
+}
 
-        // Get a schema representation for grouping
-        let schema = reader.schema();
-        let _schema_key = get_schema_key(&schema);
+/// Prints the row groups of a parquet file with their sizes and per column min/max statistics
+pub fn print_row_groups(path: &Path) -> Result<String> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open parquet file: {}", path.display()))?;
 
-        schema_groups
-            .entry(_schema_key)
-            .or_default()
-            .push(file_path.clone());
-    }
+    let reader = SerializedFileReader::new(file)?;
+    let metadata = reader.metadata();
 
-    // Make sure output directory exists
-    std::fs::create_dir_all(output_dir).with_context(|| {
-        format!(
-            "Failed to create output directory: {}",
-            output_dir.display()
-        )
-    })?;
+    let mut output = String::new();
+    output.push_str(&format!("File: {}\n", path.display()));
+    output.push_str(&format!("Num row groups: {}\n", metadata.num_row_groups()));
 
-    // Now merge each group separately
-    let mut output_files = Vec::new();
-    let group_count = schema_groups.len();
+    for (i, row_group) in metadata.row_groups().iter().enumerate() {
+        output.push_str(&format!(
+            "\nRow group {}: {} rows, {} bytes compressed\n",
+            i,
+            row_group.num_rows(),
+            row_group.compressed_size()
+        ));
 
-    for (i, (_schema_key, files)) in schema_groups.into_iter().enumerate() {
-        let output_filename = if group_count > 1 {
-            format!("{}_{}.parquet", base_filename, i + 1)
-        } else {
-            format!("{}.parquet", base_filename)
-        };
+        for column in row_group.columns() {
+            let stats = column.statistics().and_then(format_statistics);
+            match stats {
+                Some((min, max, data_type)) => output.push_str(&format!(
+                    "  {}: min {} max {}\n",
+                    column.column_path().string(),
+                    utils::colorize_value(&min, &data_type),
+                    utils::colorize_value(&max, &data_type)
+                )),
+                None => output.push_str(&format!(
+                    "  {}: no statistics\n",
+                    column.column_path().string()
+                )),
+            }
+        }
+    }
 
-        let output_path = output_dir.join(output_filename);
+    Ok(output)
+}
 
-        println!("Merging schema group {} with {} files", i + 1, files.len());
+/// Reads the rows of a parquet file whose timestamp `column` lies in `[start, end)`.
+/// Row groups whose min/max statistics fall outside the range are skipped without decoding.
+/// The column must be a microsecond or nanosecond timestamp.
+pub fn query_by_time_range(
+    path: &Path,
+    column: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<RecordBatch>> {
+    let file = File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+
+    let field = builder
+        .schema()
+        .field_with_name(column)
+        .with_context(|| format!("Column '{}' not found in {:?}", column, path))?
+        .clone();
+    let (start, end) = match field.data_type() {
+        DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            (start.timestamp_micros(), end.timestamp_micros())
+        }
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => (
+            start
+                .timestamp_nanos_opt()
+                .context("Start time out of range for nanoseconds")?,
+            end.timestamp_nanos_opt()
+                .context("End time out of range for nanoseconds")?,
+        ),
+        other => {
+            return Err(anyhow::anyhow!(
+                "Column '{}' must be a microsecond or nanosecond timestamp, found {}",
+                column,
+                other
+            ))
+        }
+    };
+
+    // Keep row groups that may overlap the range, or that have no usable statistics
+    let metadata = builder.metadata().clone();
+    let leaf_index = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .position(|leaf| leaf.path().string() == column);
+    let row_groups: Vec<usize> = metadata
+        .row_groups()
+        .iter()
+        .enumerate()
+        .filter(|(_, row_group)| {
+            let stats = leaf_index.and_then(|index| row_group.column(index).statistics());
+            match stats {
+                Some(Statistics::Int64(stats)) => match (stats.min_opt(), stats.max_opt()) {
+                    (Some(min), Some(max)) => *max >= start && *min < end,
+                    _ => true,
+                },
+                _ => true,
+            }
+        })
+        .map(|(index, _)| index)
+        .collect();
+    let bound = |value: i64| -> Result<arrow::array::Scalar<ArrayRef>> {
+        let array: ArrayRef = Arc::new(Int64Array::from(vec![value]));
+        Ok(arrow::array::Scalar::new(arrow::compute::cast(
+            &array,
+            field.data_type(),
+        )?))
+    };
+    let (start, end) = (bound(start)?, bound(end)?);
+
+    let mut batches = Vec::new();
+    for batch in builder.with_row_groups(row_groups).build()? {
+        let batch = batch?;
+        let timestamps = batch_column(&batch, column)?;
+        let mask = arrow::compute::and(
+            &arrow::compute::kernels::cmp::gt_eq(timestamps, &start)?,
+            &arrow::compute::kernels::cmp::lt(timestamps, &end)?,
+        )?;
+        let filtered = arrow::compute::filter_record_batch(&batch, &mask)?;
+        if filtered.num_rows() > 0 {
+            batches.push(filtered);
+        }
+    }
+    Ok(batches)
+}
+
+/// Randomly samples `n` rows from a parquet file using reservoir sampling.
+/// The file is streamed batch by batch, so only the sampled rows are kept in memory.
+/// Passing a `seed` makes the sample reproducible. Rows are returned in file order.
+pub fn sample_parquet(path: &Path, n: usize, seed: Option<u64>) -> Result<RecordBatch> {
+    let reader = read_parquet_file(path)?;
+    let schema = reader.schema();
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_os_rng(),
+    };
+
+    // Each reservoir entry is (row index in file, single row copied out of its batch)
+    let mut reservoir: Vec<(usize, RecordBatch)> = Vec::with_capacity(n);
+    let mut rows_seen = 0;
+    for batch in reader {
+        let batch = batch?;
+        for row in 0..batch.num_rows() {
+            let slot = if reservoir.len() < n {
+                Some(reservoir.len())
+            } else {
+                let candidate = rng.random_range(0..=rows_seen);
+                (candidate < n).then_some(candidate)
+            };
+
+            if let Some(slot) = slot {
+                // take copies the row so the rest of the batch can be dropped
+                let indices = arrow::array::UInt32Array::from(vec![row as u32]);
+                let columns = batch
+                    .columns()
+                    .iter()
+                    .map(|column| arrow::compute::take(column, &indices, None))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let sampled = RecordBatch::try_new(schema.clone(), columns)?;
+
+                if slot == reservoir.len() {
+                    reservoir.push((rows_seen, sampled));
+                } else {
+                    reservoir[slot] = (rows_seen, sampled);
+                }
+            }
+            rows_seen += 1;
+        }
+    }
+
+    reservoir.sort_by_key(|(row, _)| *row);
+    let rows: Vec<RecordBatch> = reservoir.into_iter().map(|(_, batch)| batch).collect();
+    Ok(arrow::compute::concat_batches(&schema, &rows)?)
+}
+
+/// Running statistics for one column while streaming a parquet file
+#[derive(Default)]
+struct ColumnSummary {
+    null_count: i64,
+    min: Option<String>,
+    max: Option<String>,
+    numeric_min: Option<f64>,
+    numeric_max: Option<f64>,
+    sum: f64,
+    numeric_count: usize,
+}
+
+impl ColumnSummary {
+    fn update(&mut self, column: &ArrayRef) -> Result<()> {
+        self.null_count += column.null_count() as i64;
+
+        let data_type = column.data_type();
+        if data_type.is_numeric() {
+            let values = arrow::compute::cast(column, &DataType::Float64)?;
+            for value in values.as_primitive::<Float64Type>().iter().flatten() {
+                self.numeric_min = Some(self.numeric_min.map_or(value, |min| min.min(value)));
+                self.numeric_max = Some(self.numeric_max.map_or(value, |max| max.max(value)));
+                self.sum += value;
+                self.numeric_count += 1;
+            }
+        } else if matches!(data_type, DataType::Utf8 | DataType::LargeUtf8) {
+            let values = arrow::compute::cast(column, &DataType::Utf8)?;
+            for value in values.as_string::<i32>().iter().flatten() {
+                if self.min.as_deref().is_none_or(|min| value < min) {
+                    self.min = Some(value.to_string());
+                }
+                if self.max.as_deref().is_none_or(|max| value > max) {
+                    self.max = Some(value.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn min_value(&self) -> Option<String> {
+        self.numeric_min
+            .map(|min| min.to_string())
+            .or(self.min.clone())
+    }
+
+    fn max_value(&self) -> Option<String> {
+        self.numeric_max
+            .map(|max| max.to_string())
+            .or(self.max.clone())
+    }
+
+    fn mean_value(&self) -> Option<f64> {
+        (self.numeric_count > 0).then(|| self.sum / self.numeric_count as f64)
+    }
+}
+
+/// Descriptive statistics of a numeric column, see `compute_column_statistics`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnStats {
+    /// NaN when the column has no values
+    pub min: f64,
+    /// NaN when the column has no values
+    pub max: f64,
+    /// NaN when the column has no values
+    pub mean: f64,
+    /// Population standard deviation
+    pub std_dev: f64,
+    /// Percentiles interpolated between the closest values, NaN when the column has no values.
+    /// Columns with more than `EXACT_PERCENTILE_VALUES` values get P² estimates instead.
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+    pub null_count: usize,
+    pub row_count: usize,
+}
+
+/// Running Welford mean / variance of a column, merged one batch at a time
+struct RunningStats {
+    min: f64,
+    max: f64,
+    count: usize,
+    mean: f64,
+    m2: f64,
+    null_count: usize,
+    row_count: usize,
+    percentiles: RunningPercentiles,
+}
+
+impl RunningStats {
+    fn new() -> Self {
+        Self {
+            min: f64::NAN,
+            max: f64::NAN,
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            null_count: 0,
+            row_count: 0,
+            percentiles: RunningPercentiles::new(),
+        }
+    }
+
+    fn update(&mut self, column: &ArrayRef) -> Result<()> {
+        self.row_count += column.len();
+        self.null_count += column.null_count();
+
+        let values = arrow::compute::cast(column, &DataType::Float64)?;
+        let values = values.as_primitive::<Float64Type>();
+        let count = values.len() - values.null_count();
+        if count == 0 {
+            return Ok(());
+        }
+
+        // fmin / fmax ignore NaN when the other side is a number
+        if let Some(min) = arrow::compute::min(values) {
+            self.min = self.min.min(min);
+        }
+        if let Some(max) = arrow::compute::max(values) {
+            self.max = self.max.max(max);
+        }
+        let batch_mean = arrow::compute::sum(values).unwrap_or(0.0) / count as f64;
+        let batch_m2: f64 = values
+            .iter()
+            .flatten()
+            .map(|value| (value - batch_mean).powi(2))
+            .sum();
+        for value in values.iter().flatten().filter(|value| !value.is_nan()) {
+            self.percentiles.push(value);
+        }
+
+        // Combine with the running totals (Chan et al. parallel variance)
+        let total = self.count + count;
+        let delta = batch_mean - self.mean;
+        self.mean += delta * count as f64 / total as f64;
+        self.m2 += batch_m2 + delta * delta * (self.count * count) as f64 / total as f64;
+        self.count = total;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ColumnStats {
+        let (mean, std_dev) = if self.count == 0 {
+            (f64::NAN, f64::NAN)
+        } else {
+            (self.mean, (self.m2 / self.count as f64).sqrt())
+        };
+        let [p25, p50, p75, p95] = self.percentiles.finish();
+        ColumnStats {
+            min: self.min,
+            max: self.max,
+            mean,
+            std_dev,
+            p25,
+            p50,
+            p75,
+            p95,
+            null_count: self.null_count,
+            row_count: self.row_count,
+        }
+    }
+}
+
+/// Value at quantile `q` of sorted `values`, linearly interpolated between the closest ranks
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let rank = q * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Values of a column kept for exact percentiles, about 512 KiB. Longer columns switch to
+/// P² estimates so memory stays bounded however large the files are.
+pub const EXACT_PERCENTILE_VALUES: usize = 1 << 16;
+
+/// Quantiles reported in `ColumnStats`
+const STATS_QUANTILES: [f64; 4] = [0.25, 0.50, 0.75, 0.95];
+
+/// Percentiles of a column in bounded memory, exact until `EXACT_PERCENTILE_VALUES` values
+struct RunningPercentiles {
+    values: Vec<f64>,
+    /// One estimator per `STATS_QUANTILES` entry once `values` overflowed
+    estimators: Vec<P2Quantile>,
+}
+
+impl RunningPercentiles {
+    fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            estimators: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if !self.estimators.is_empty() {
+            for estimator in &mut self.estimators {
+                estimator.push(value);
+            }
+            return;
+        }
+
+        self.values.push(value);
+        if self.values.len() >= EXACT_PERCENTILE_VALUES {
+            self.values.sort_by(f64::total_cmp);
+            self.estimators = STATS_QUANTILES
+                .iter()
+                .map(|&q| P2Quantile::from_sorted(&self.values, q))
+                .collect();
+            self.values = Vec::new();
+        }
+    }
+
+    fn finish(&mut self) -> [f64; 4] {
+        if self.estimators.is_empty() {
+            self.values.sort_by(f64::total_cmp);
+            STATS_QUANTILES.map(|q| percentile(&self.values, q))
+        } else {
+            let mut estimates = [f64::NAN; 4];
+            for (estimate, estimator) in estimates.iter_mut().zip(&self.estimators) {
+                *estimate = estimator.estimate();
+            }
+            estimates
+        }
+    }
+}
+
+/// P² estimate of one quantile (Jain & Chlamtac, 1985). Five markers track the minimum,
+/// the maximum, the quantile and the two midpoints, their heights are adjusted with a
+/// piecewise parabolic fit as values arrive.
+struct P2Quantile {
+    /// Marker heights
+    heights: [f64; 5],
+    /// Actual marker positions, 1-based ranks
+    positions: [f64; 5],
+    /// Desired marker positions
+    desired: [f64; 5],
+    /// Desired position increments per value
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    /// Start from the markers of at least 5 sorted values
+    fn from_sorted(sorted: &[f64], q: f64) -> Self {
+        let last = (sorted.len() - 1) as f64;
+        let increments = [0.0, q / 2.0, q, (1.0 + q) / 2.0, 1.0];
+        let desired = increments.map(|increment| 1.0 + last * increment);
+        let mut positions = desired.map(f64::round);
+        // Markers need distinct positions, only matters for quantiles very close to 0 or 1
+        for i in 1..5 {
+            positions[i] = positions[i].max(positions[i - 1] + 1.0);
+        }
+        for i in (0..4).rev() {
+            positions[i] = positions[i].min(positions[i + 1] - 1.0);
+        }
+        Self {
+            heights: positions.map(|position| sorted[position as usize - 1]),
+            positions,
+            desired,
+            increments,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        let h = &mut self.heights;
+        let cell = if value < h[0] {
+            h[0] = value;
+            0
+        } else if value >= h[4] {
+            h[4] = value;
+            3
+        } else {
+            (0..4).rfind(|&i| h[i] <= value).unwrap_or(0)
+        };
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+            let n = &self.positions;
+            if (offset >= 1.0 && n[i + 1] - n[i] > 1.0)
+                || (offset <= -1.0 && n[i - 1] - n[i] < -1.0)
+            {
+                let step = offset.signum();
+                let parabolic = self.parabolic(i, step);
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, step)
+                    };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (h, n) = (&self.heights, &self.positions);
+        h[i] + step / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + step) * (h[i + 1] - h[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - step) * (h[i] - h[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let (h, n) = (&self.heights, &self.positions);
+        let j = if step > 0.0 { i + 1 } else { i - 1 };
+        h[i] + step * (h[j] - h[i]) / (n[j] - n[i])
+    }
+
+    fn estimate(&self) -> f64 {
+        self.heights[2]
+    }
+}
+
+/// Computes descriptive statistics of numeric columns, streaming the file one batch at a
+/// time. Passing no columns uses every numeric top level column.
+/// Non-numeric columns are skipped with a warning.
+/// Memory is bounded per column: percentiles are exact up to `EXACT_PERCENTILE_VALUES`
+/// values and estimated with P² beyond that.
+pub fn compute_column_statistics(
+    path: &Path,
+    columns: &[&str],
+) -> Result<HashMap<String, ColumnStats>> {
+    compute_files_column_statistics(&[path.to_path_buf()], columns)
+}
+
+/// Same as `compute_column_statistics` over the rows of several files, e.g. the logs of a
+/// topic split over multiple files. Columns are picked from the first file and must be
+/// present in all of them.
+pub fn compute_files_column_statistics(
+    paths: &[PathBuf],
+    columns: &[&str],
+) -> Result<HashMap<String, ColumnStats>> {
+    let Some(first) = paths.first() else {
+        return Ok(HashMap::new());
+    };
+    let file = File::open(first).with_context(|| format!("Failed to open file: {:?}", first))?;
+    let schema = ParquetRecordBatchReaderBuilder::try_new(file)?
+        .schema()
+        .clone();
+
+    let names: Vec<String> = if columns.is_empty() {
+        schema
+            .fields()
+            .iter()
+            .filter(|f| f.data_type().is_numeric())
+            .map(|f| f.name().clone())
+            .collect()
+    } else {
+        let mut names = Vec::new();
+        for column in columns {
+            let field = schema
+                .field_with_name(column)
+                .with_context(|| format!("Column '{}' not found in {:?}", column, first))?;
+            if field.data_type().is_numeric() {
+                names.push(column.to_string());
+            } else {
+                eprintln!(
+                    "Warning: skipping non-numeric column '{}' ({})",
+                    column,
+                    field.data_type()
+                );
+            }
+        }
+        names
+    };
+
+    let mut stats: Vec<RunningStats> = names.iter().map(|_| RunningStats::new()).collect();
+    for path in paths {
+        let file = File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+
+        // Only decode the columns we need
+        let indices = names
+            .iter()
+            .map(|name| builder.schema().index_of(name))
+            .collect::<Result<Vec<usize>, _>>()
+            .with_context(|| format!("Missing a summarized column in {:?}", path))?;
+        let mask = parquet::arrow::ProjectionMask::roots(builder.parquet_schema(), indices);
+        let reader = builder.with_projection(mask).build()?;
+
+        for batch in reader {
+            let batch = batch?;
+            for (name, running) in names.iter().zip(stats.iter_mut()) {
+                running.update(batch_column(&batch, name)?)?;
+            }
+        }
+    }
+
+    Ok(names
+        .into_iter()
+        .zip(stats.iter_mut().map(RunningStats::finish))
+        .collect())
+}
+
+/// Schema of the summary produced by `snapshot_parquet`
+fn snapshot_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("column_name", DataType::Utf8, false),
+        Field::new("data_type", DataType::Utf8, false),
+        Field::new("row_count", DataType::Int64, false),
+        Field::new("null_count", DataType::Int64, false),
+        Field::new("min_value", DataType::Utf8, true),
+        Field::new("max_value", DataType::Utf8, true),
+        Field::new("mean_value", DataType::Float64, true),
+    ])
+}
+
+/// Computes the per-column summary of a parquet file, one row per top level column
+pub fn snapshot_record_batch(input: &Path) -> Result<RecordBatch> {
+    let reader = read_parquet_file(input)?;
+    let schema = reader.schema();
+
+    let mut summaries: Vec<ColumnSummary> = schema
+        .fields()
+        .iter()
+        .map(|_| ColumnSummary::default())
+        .collect();
+    let mut row_count = 0;
+    for batch in reader {
+        let batch = batch?;
+        row_count += batch.num_rows() as i64;
+        for (summary, column) in summaries.iter_mut().zip(batch.columns()) {
+            summary.update(column)?;
+        }
+    }
+
+    let fields = schema.fields();
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            fields.iter().map(|f| f.name().clone()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            fields.iter().map(|f| f.data_type().to_string()),
+        )),
+        Arc::new(Int64Array::from(vec![row_count; fields.len()])),
+        Arc::new(Int64Array::from_iter_values(
+            summaries.iter().map(|s| s.null_count),
+        )),
+        Arc::new(StringArray::from_iter(
+            summaries.iter().map(|s| s.min_value()),
+        )),
+        Arc::new(StringArray::from_iter(
+            summaries.iter().map(|s| s.max_value()),
+        )),
+        Arc::new(Float64Array::from_iter(
+            summaries.iter().map(|s| s.mean_value()),
+        )),
+    ];
+
+    Ok(RecordBatch::try_new(Arc::new(snapshot_schema()), columns)?)
+}
+
+/// Writes a summary parquet file with the row count, null count, min, max and mean of every column
+pub fn snapshot_parquet(input: &Path, output: &Path) -> Result<()> {
+    let summary = snapshot_record_batch(input)?;
+    let output_file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    let mut writer = ArrowWriter::try_new(output_file, summary.schema(), None)?;
+    writer.write(&summary)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Stacks the summaries of several parquet files, with a leading `file_path` column
+pub fn snapshot_multiple_parquet(files: &[PathBuf]) -> Result<RecordBatch> {
+    let mut fields = vec![Field::new("file_path", DataType::Utf8, false)];
+    fields.extend(
+        snapshot_schema()
+            .fields()
+            .iter()
+            .map(|f| f.as_ref().clone()),
+    );
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut batches = Vec::with_capacity(files.len());
+    for file in files {
+        let summary = snapshot_record_batch(file)?;
+        let file_path = file.display().to_string();
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(StringArray::from(vec![
+            file_path;
+            summary.num_rows()
+        ]))];
+        columns.extend(summary.columns().iter().cloned());
+        batches.push(RecordBatch::try_new(schema.clone(), columns)?);
+    }
+
+    Ok(arrow::compute::concat_batches(&schema, &batches)?)
+}
+
+/// Parses a codec name (snappy, zstd, gzip, lz4, uncompressed) into a parquet compression
+pub fn parse_compression(codec: &str) -> Result<Compression> {
+    match codec.to_lowercase().as_str() {
+        "snappy" => Ok(Compression::SNAPPY),
+        "zstd" => Ok(Compression::ZSTD(ZstdLevel::default())),
+        "gzip" => Ok(Compression::GZIP(GzipLevel::default())),
+        // LZ4_RAW is the non-deprecated LZ4 codec in the parquet format
+        "lz4" => Ok(Compression::LZ4_RAW),
+        "uncompressed" | "none" => Ok(Compression::UNCOMPRESSED),
+        other => Err(anyhow::anyhow!("Unsupported compression codec: {}", other)),
+    }
+}
+
+/// Rewrites a parquet file with a different compression codec, keeping the data unchanged.
+/// Returns the number of rows written and the input and output file sizes in bytes.
+pub fn recompress_parquet(
+    input: &Path,
+    output: &Path,
+    compression: Compression,
+) -> Result<(usize, u64, u64)> {
+    let old_size = std::fs::metadata(input)
+        .with_context(|| format!("Failed to read metadata of: {}", input.display()))?
+        .len();
+
+    let reader = read_parquet_file(input)?;
+    let schema = reader.schema();
+
+    let output_file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    let props = WriterProperties::builder()
+        .set_compression(compression)
+        .build();
+    let mut writer = ArrowWriter::try_new(output_file, schema, Some(props))?;
+
+    let mut row_count = 0;
+    for batch in reader {
+        let batch = batch?;
+        row_count += batch.num_rows();
+        writer.write(&batch)?;
+    }
+    writer.close()?;
+
+    let new_size = std::fs::metadata(output)
+        .with_context(|| format!("Failed to read metadata of: {}", output.display()))?
+        .len();
+
+    Ok((row_count, old_size, new_size))
+}
+
+/// Appends `new_batches` to a parquet file as one new row group.
+///
+/// The existing row groups are copied into a temporary file without being decoded, then the
+/// temporary file replaces the original with `std::fs::rename`, so readers never see a partial
+/// file. The new rows use the codec of the existing file. A missing file is created.
+pub fn append_to_parquet(existing_path: &Path, new_batches: &[RecordBatch]) -> Result<()> {
+    let Some(first_batch) = new_batches.first() else {
+        return Ok(());
+    };
+    let tmp_path = existing_path.with_extension("parquet.tmp");
+
+    if !existing_path.exists() {
+        let file = File::create(&tmp_path)
+            .with_context(|| format!("Failed to create output file: {}", tmp_path.display()))?;
+        let mut writer = ArrowWriter::try_new(file, first_batch.schema(), None)?;
+        for batch in new_batches {
+            writer.write(batch)?;
+        }
+        writer.close()?;
+    } else {
+        let compression = file_compression(existing_path)?;
+        if let Err(e) = write_appended_file(existing_path, &tmp_path, new_batches, compression) {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    }
+
+    std::fs::rename(&tmp_path, existing_path)
+        .with_context(|| format!("Failed to replace {}", existing_path.display()))?;
+    Ok(())
+}
+
+/// Writes the row groups of `existing_path` followed by `new_batches` to `output`
+fn write_appended_file(
+    existing_path: &Path,
+    output: &Path,
+    new_batches: &[RecordBatch],
+    compression: Compression,
+) -> Result<()> {
+    use parquet::arrow::arrow_writer::{compute_leaves, get_column_writers};
+    use parquet::column::writer::ColumnCloseResult;
+    use parquet::file::writer::SerializedFileWriter;
+
+    let existing = File::open(existing_path)
+        .with_context(|| format!("Failed to open parquet file: {}", existing_path.display()))?;
+    let metadata = SerializedFileReader::new(existing.try_clone()?)?
+        .metadata()
+        .clone();
+    let file_metadata = metadata.file_metadata();
+    let schema = Arc::new(parquet::arrow::parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )?);
+
+    for batch in new_batches {
+        if batch.schema().fields() != schema.fields() {
+            return Err(anyhow::anyhow!(
+                "Cannot append to {}, schemas differ. File has schema: \n{:?}\n\nNew rows have schema: \n{:?}",
+                existing_path.display(),
+                schema,
+                batch.schema()
+            ));
+        }
+    }
+
+    // Keep the key-value metadata, it holds the arrow schema readers restore types from
+    let props = Arc::new(
+        WriterProperties::builder()
+            .set_compression(compression)
+            .set_key_value_metadata(file_metadata.key_value_metadata().cloned())
+            .build(),
+    );
+    let output_file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    let mut writer = SerializedFileWriter::new(
+        output_file,
+        file_metadata.schema_descr().root_schema_ptr(),
+        props.clone(),
+    )?;
+
+    for row_group in metadata.row_groups() {
+        let mut row_group_writer = writer.next_row_group()?;
+        for column in row_group.columns() {
+            row_group_writer.append_column(
+                &existing,
+                ColumnCloseResult {
+                    bytes_written: column.compressed_size() as u64,
+                    rows_written: row_group.num_rows() as u64,
+                    metadata: column.clone(),
+                    bloom_filter: None,
+                    column_index: None,
+                    offset_index: None,
+                },
+            )?;
+        }
+        row_group_writer.close()?;
+    }
+
+    let mut column_writers = get_column_writers(file_metadata.schema_descr(), &props, &schema)?;
+    for batch in new_batches {
+        let mut leaf_writers = column_writers.iter_mut();
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            for leaf in compute_leaves(field, column)? {
+                leaf_writers
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("More leaf columns than column writers"))?
+                    .write(&leaf)?;
+            }
+        }
+    }
+    let mut row_group_writer = writer.next_row_group()?;
+    for column_writer in column_writers {
+        column_writer
+            .close()?
+            .append_to_row_group(&mut row_group_writer)?;
+    }
+    row_group_writer.close()?;
+    writer.close()?;
+
+    Ok(())
+}
+
+/// Rows written at a time by `split_by_size_bytes`, the size of a part is checked in between
+const SPLIT_SIZE_CHUNK_ROWS: usize = 1024;
+
+/// Splits a parquet file into parts of at most `rows_per_file` rows, written to `output_dir`
+/// as `{stem}_part_{n:04}.parquet`. Returns the paths of the parts in row order.
+///
+/// The schema metadata, e.g. topic and flags, is kept in every part and the parts use the
+/// compression codec of the input. An empty input produces no parts.
+pub fn split_by_row_count(
+    input: &Path,
+    output_dir: &Path,
+    rows_per_file: usize,
+) -> Result<Vec<PathBuf>> {
+    if rows_per_file == 0 {
+        return Err(anyhow::anyhow!("Rows per file must be greater than 0"));
+    }
+    split_parquet_file(input, output_dir, |_, rows_in_part| {
+        rows_per_file - rows_in_part
+    })
+}
+
+/// Splits a parquet file into parts of about `max_bytes` each, see `split_by_row_count`.
+///
+/// A new part is started once the encoded size of the current one reaches `max_bytes`. The size
+/// is checked every `SPLIT_SIZE_CHUNK_ROWS` rows, so a part can exceed `max_bytes` by up to that
+/// many rows.
+pub fn split_by_size_bytes(
+    input: &Path,
+    output_dir: &Path,
+    max_bytes: usize,
+) -> Result<Vec<PathBuf>> {
+    if max_bytes == 0 {
+        return Err(anyhow::anyhow!("Maximum part size must be greater than 0"));
+    }
+    split_parquet_file(input, output_dir, |writer, rows_in_part| {
+        let part_bytes = writer.bytes_written() + writer.in_progress_size();
+        if rows_in_part > 0 && part_bytes >= max_bytes {
+            0
+        } else {
+            SPLIT_SIZE_CHUNK_ROWS
+        }
+    })
+}
+
+/// Streams the rows of `input` into numbered parts. `part_capacity` returns how many more rows
+/// the current part takes given its writer and row count, 0 starts a new part.
+fn split_parquet_file(
+    input: &Path,
+    output_dir: &Path,
+    mut part_capacity: impl FnMut(&ArrowWriter<File>, usize) -> usize,
+) -> Result<Vec<PathBuf>> {
+    let stem = input
+        .file_stem()
+        .ok_or_else(|| anyhow::anyhow!("Input path has no file name: {}", input.display()))?
+        .to_string_lossy()
+        .to_string();
+    let file = File::open(input)
+        .with_context(|| format!("Failed to open parquet file: {}", input.display()))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    // Batches read back lose the schema metadata, so take it from the file schema
+    let schema = builder.schema().clone();
+    let reader = builder.build()?;
+    let props = WriterProperties::builder()
+        .set_compression(file_compression(input)?)
+        .build();
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
+
+    let mut parts = Vec::new();
+    let mut writer: Option<ArrowWriter<File>> = None;
+    let mut rows_in_part = 0;
+    for batch in reader {
+        let batch = batch?;
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let capacity = writer
+                .as_ref()
+                .map_or(0, |writer| part_capacity(writer, rows_in_part));
+            if capacity == 0 {
+                if let Some(full) = writer.take() {
+                    full.close()?;
+                }
+                let path = output_dir.join(format!("{}_part_{:04}.parquet", stem, parts.len()));
+                let file = File::create(&path)
+                    .with_context(|| format!("Failed to create output file: {}", path.display()))?;
+                writer = Some(ArrowWriter::try_new(
+                    file,
+                    schema.clone(),
+                    Some(props.clone()),
+                )?);
+                parts.push(path);
+                rows_in_part = 0;
+                continue;
+            }
+
+            if let Some(writer) = writer.as_mut() {
+                let rows = capacity.min(batch.num_rows() - offset);
+                writer.write(&batch.slice(offset, rows))?;
+                rows_in_part += rows;
+                offset += rows;
+            }
+        }
+    }
+    if let Some(writer) = writer {
+        writer.close()?;
+    }
+
+    Ok(parts)
+}
+
+/// Outcome of `repair_parquet`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    pub row_groups_recovered: usize,
+    pub row_groups_lost: usize,
+    pub total_rows_recovered: usize,
+}
+
+/// Copies the row groups of `input` that can still be decoded to `output`, e.g. after a logger
+/// was killed mid-write. Each row group is decoded on its own, one that fails to decode, or
+/// panics the decoder, is skipped instead of failing the whole file.
+///
+/// The footer holds the schema and row group offsets, so it has to be intact. Fails without
+/// writing `output` if it can't be read, e.g. when the file was cut before it was written.
+pub fn repair_parquet(input: &Path, output: &Path) -> Result<RepairReport> {
+    let file = File::open(input)
+        .with_context(|| format!("Failed to open parquet file: {}", input.display()))?;
+    let row_group_count = SerializedFileReader::new(file.try_clone()?)
+        .with_context(|| {
+            format!(
+                "Failed to read the footer of {}, no row groups can be recovered",
+                input.display()
+            )
+        })?
+        .metadata()
+        .num_row_groups();
+    let metadata = ArrowReaderMetadata::load(&file, ArrowReaderOptions::default())?;
+    // Batches read back lose the schema metadata, so take it from the file schema
+    let schema = metadata.schema().clone();
+
+    let props = WriterProperties::builder()
+        .set_compression(file_compression(input)?)
+        .build();
+    let output_file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    let mut writer = ArrowWriter::try_new(output_file, schema, Some(props))?;
+
+    let mut report = RepairReport::default();
+    for row_group in 0..row_group_count {
+        // Decode the whole row group before writing, so a broken one leaves nothing behind
+        let decoded = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            ParquetRecordBatchReaderBuilder::new_with_metadata(file.try_clone()?, metadata.clone())
+                .with_row_groups(vec![row_group])
+                .build()?
+                .collect::<Result<Vec<RecordBatch>, _>>()
+                .map_err(anyhow::Error::from)
+        }))
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("Decoder panicked")));
+
+        match decoded {
+            Ok(batches) => {
+                for batch in &batches {
+                    writer.write(batch)?;
+                    report.total_rows_recovered += batch.num_rows();
+                }
+                report.row_groups_recovered += 1;
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: Skipping row group {} of {}: {}",
+                    row_group,
+                    input.display(),
+                    e
+                );
+                report.row_groups_lost += 1;
+            }
+        }
+    }
+    writer.close()?;
+
+    Ok(report)
+}
+
+/// Outcome of `validate_schema`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaValidationReport {
+    /// Expected columns the file doesn't have
+    pub missing_columns: Vec<String>,
+    /// Columns of the file that are not expected
+    pub extra_columns: Vec<String>,
+    /// Column, expected type and type in the file
+    pub type_mismatches: Vec<(String, DataType, DataType)>,
+    /// Every expected column is present with the expected type, extra columns are allowed
+    pub is_compatible: bool,
+}
+
+/// Compares the schema of a parquet file with `expected`, e.g. to catch logs of a firmware
+/// version that renamed or retyped a column. Columns are matched by name, nullability is ignored.
+pub fn validate_schema(path: &Path, expected: &Schema) -> Result<SchemaValidationReport> {
+    let schema = get_schema(path)?;
+
+    let mut report = SchemaValidationReport::default();
+    for field in expected.fields() {
+        match schema.field_with_name(field.name()) {
+            Ok(actual) if actual.data_type() != field.data_type() => {
+                report.type_mismatches.push((
+                    field.name().clone(),
+                    field.data_type().clone(),
+                    actual.data_type().clone(),
+                ));
+            }
+            Ok(_) => {}
+            Err(_) => report.missing_columns.push(field.name().clone()),
+        }
+    }
+    report.extra_columns = schema
+        .fields()
+        .iter()
+        .filter(|field| expected.field_with_name(field.name()).is_err())
+        .map(|field| field.name().clone())
+        .collect();
+    report.is_compatible = report.missing_columns.is_empty() && report.type_mismatches.is_empty();
+
+    Ok(report)
+}
+
+/// Reads an Arrow schema from its JSON representation, as written by serializing a `Schema`
+pub fn read_schema_json(path: &Path) -> Result<Schema> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read schema file: {}", path.display()))?;
+    serde_json::from_str(&json)
+        .with_context(|| format!("Invalid Arrow schema JSON in {}", path.display()))
+}
+
+/// Exports a parquet file as InfluxDB line protocol, one line per row.
+///
+/// `timestamp_column` is written as a nanosecond timestamp: Arrow timestamp columns are
+/// converted from their unit, plain integer columns are assumed to already be nanoseconds.
+/// Tag columns become tags, every other column becomes a field. Returns the number of lines written.
+pub fn export_to_influxdb_line_protocol(
+    path: &Path,
+    output: &Path,
+    measurement: &str,
+    timestamp_column: &str,
+    tag_columns: &[String],
+) -> Result<usize> {
+    use std::io::Write;
+
+    let reader = read_parquet_file(path)?;
+    let mut writer = std::io::BufWriter::new(
+        File::create(output)
+            .with_context(|| format!("Failed to create output file: {}", output.display()))?,
+    );
+
+    let measurement = escape_line_protocol(measurement, &[',', ' ']);
+    let mut lines_written = 0;
+    for batch in reader {
+        let batch = batch?;
+        let timestamps = influxdb_timestamps(&batch, timestamp_column)?;
+
+        for row in 0..batch.num_rows() {
+            if let Some(line) =
+                format_line_protocol_row(&batch, row, &measurement, timestamp_column, tag_columns)?
+            {
+                match timestamps.as_ref().and_then(|ts| ts[row]) {
+                    Some(ts) => writeln!(writer, "{} {}", line, ts)?,
+                    None => writeln!(writer, "{}", line)?,
+                }
+                lines_written += 1;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(lines_written)
+}
+
+/// Reads the timestamp column of a batch as nanoseconds, if the batch has one
+fn influxdb_timestamps(
+    batch: &RecordBatch,
+    timestamp_column: &str,
+) -> Result<Option<Vec<Option<i64>>>> {
+    let Some(column) = batch.column_by_name(timestamp_column) else {
+        return Ok(None);
+    };
+
+    let column = match column.data_type() {
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => column.clone(),
+        DataType::Timestamp(_, tz) => arrow::compute::cast(
+            column,
+            &DataType::Timestamp(TimeUnit::Nanosecond, tz.clone()),
+        )?,
+        _ => column.clone(),
+    };
+    let values = arrow::compute::cast(&column, &DataType::Int64)?;
+    Ok(Some(values.as_primitive::<Int64Type>().iter().collect()))
+}
+
+/// Formats the measurement, tags and fields of a single row, without the timestamp.
+/// Returns `None` if the row has no non-null fields, which line protocol does not allow.
+/// NaN and infinite floats can't be written in line protocol and are skipped like nulls.
+fn format_line_protocol_row(
+    batch: &RecordBatch,
+    row: usize,
+    measurement: &str,
+    timestamp_column: &str,
+    tag_columns: &[String],
+) -> Result<Option<String>> {
+    let schema = batch.schema();
+    let mut tags = Vec::new();
+    let mut fields = Vec::new();
+
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let name = field.name();
+        if name == timestamp_column || column.is_null(row) {
+            continue;
+        }
+
+        if tag_columns.contains(name) {
+            let value = line_protocol_text(column, row);
+            tags.push(format!(
+                "{}={}",
+                escape_line_protocol(name, &[',', '=', ' ']),
+                escape_line_protocol(&value, &[',', '=', ' '])
+            ));
+        } else if let Some(value) = format_line_protocol_field(column, row)? {
+            fields.push(format!(
+                "{}={}",
+                escape_line_protocol(name, &[',', '=', ' ']),
+                value
+            ));
+        }
+    }
+
+    if fields.is_empty() {
+        return Ok(None);
+    }
+
+    let mut line = measurement.to_string();
+    for tag in tags {
+        line.push(',');
+        line.push_str(&tag);
+    }
+    line.push(' ');
+    line.push_str(&fields.join(","));
+    Ok(Some(line))
+}
+
+/// Formats a single field value: signed integers get an `i` suffix, unsigned ones a `u`
+/// suffix, strings and other types are quoted. Returns `None` for NaN and infinite floats.
+fn format_line_protocol_field(column: &ArrayRef, row: usize) -> Result<Option<String>> {
+    let data_type = column.data_type();
+    let value = if data_type == &DataType::Boolean {
+        column.as_boolean().value(row).to_string()
+    } else if data_type.is_unsigned_integer() {
+        let values = arrow::compute::cast(&column.slice(row, 1), &DataType::UInt64)?;
+        format!("{}u", values.as_primitive::<UInt64Type>().value(0))
+    } else if data_type.is_integer() {
+        let values = arrow::compute::cast(&column.slice(row, 1), &DataType::Int64)?;
+        format!("{}i", values.as_primitive::<Int64Type>().value(0))
+    } else if data_type.is_floating() {
+        let values = arrow::compute::cast(&column.slice(row, 1), &DataType::Float64)?;
+        let value = values.as_primitive::<Float64Type>().value(0);
+        if !value.is_finite() {
+            return Ok(None);
+        }
+        value.to_string()
+    } else {
+        let value = line_protocol_text(column, row);
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    };
+    Ok(Some(value))
+}
+
+/// Gets the unquoted text of a value for use in tags and string fields
+fn line_protocol_text(column: &ArrayRef, row: usize) -> String {
+    match column.data_type() {
+        DataType::Utf8 => column.as_string::<i32>().value(row).to_string(),
+        DataType::LargeUtf8 => column.as_string::<i64>().value(row).to_string(),
+        _ => utils::format_array_value(column, row),
+    }
+}
+
+/// Escapes the given special characters with a backslash
+fn escape_line_protocol(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Writes a single column to a text file, one value per line.
+/// Timestamps are written as ISO 8601 and nulls as empty lines. Returns the number of values written.
+pub fn extract_column_as_csv(
+    path: &Path,
+    column: &str,
+    output: &Path,
+    with_header: bool,
+) -> Result<usize> {
+    use std::io::Write;
+
+    let reader = read_parquet_file(path)?;
+    let mut writer = std::io::BufWriter::new(
+        File::create(output)
+            .with_context(|| format!("Failed to create output file: {}", output.display()))?,
+    );
+    if with_header {
+        writeln!(writer, "{}", column)?;
+    }
+
+    let options = plot_format_options();
+    let mut rows_written = 0;
+    for batch in reader {
+        let batch = batch?;
+        let values = batch_column(&batch, column)?;
+        let formatter = ArrayFormatter::try_new(values.as_ref(), &options)?;
+        for row in 0..batch.num_rows() {
+            writeln!(writer, "{}", formatter.value(row))?;
+            rows_written += 1;
+        }
+    }
+    writer.flush()?;
+
+    Ok(rows_written)
+}
+
+/// Writes two columns as `x,y` pairs with a header line, e.g. for scatter plots.
+/// Returns the number of rows written.
+pub fn extract_two_columns_as_csv(
+    path: &Path,
+    col_x: &str,
+    col_y: &str,
+    output: &Path,
+) -> Result<usize> {
+    use std::io::Write;
+
+    let reader = read_parquet_file(path)?;
+    let mut writer = std::io::BufWriter::new(
+        File::create(output)
+            .with_context(|| format!("Failed to create output file: {}", output.display()))?,
+    );
+    writeln!(writer, "{},{}", col_x, col_y)?;
+
+    let options = plot_format_options();
+    let mut rows_written = 0;
+    for batch in reader {
+        let batch = batch?;
+        let x_values = batch_column(&batch, col_x)?;
+        let y_values = batch_column(&batch, col_y)?;
+        let x_formatter = ArrayFormatter::try_new(x_values.as_ref(), &options)?;
+        let y_formatter = ArrayFormatter::try_new(y_values.as_ref(), &options)?;
+        for row in 0..batch.num_rows() {
+            writeln!(
+                writer,
+                "{},{}",
+                x_formatter.value(row),
+                y_formatter.value(row)
+            )?;
+            rows_written += 1;
+        }
+    }
+    writer.flush()?;
+
+    Ok(rows_written)
+}
+
+/// Formatting used for plot data: nulls are empty and timestamps are ISO 8601
+fn plot_format_options() -> FormatOptions<'static> {
+    FormatOptions::default().with_null("")
+}
+
+fn batch_column<'a>(batch: &'a RecordBatch, column: &str) -> Result<&'a ArrayRef> {
+    batch
+        .column_by_name(column)
+        .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", column))
+}
+
+/// File formats `convert_to_format` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertFormat {
+    /// Comma separated values with a header line, nested columns are flattened
+    Csv,
+    /// A single JSON array with one object per row
+    Json,
+}
+
+impl ConvertFormat {
+    /// File extension of the format, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConvertFormat::Csv => "csv",
+            ConvertFormat::Json => "json",
+        }
+    }
+}
+
+impl std::str::FromStr for ConvertFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(ConvertFormat::Csv),
+            "json" => Ok(ConvertFormat::Json),
+            other => Err(anyhow::anyhow!(
+                "Unknown format '{}', expected csv or json",
+                other
+            )),
+        }
+    }
+}
+
+/// Writes every row of a parquet file to a single CSV or JSON file
+pub fn convert_to_format(input: &Path, output: &Path, format: ConvertFormat) -> Result<()> {
+    let reader = read_parquet_file(input)?;
+    let file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+
+    match format {
+        ConvertFormat::Csv => {
+            let mut writer = arrow::csv::Writer::new(file);
+            for batch in reader {
+                writer.write(&flatten_record_batch(&batch?)?)?;
+            }
+        }
+        ConvertFormat::Json => {
+            let mut writer = arrow::json::ArrayWriter::new(file);
+            for batch in reader {
+                writer.write(&batch?)?;
+            }
+            writer.finish()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Expands struct columns into one column per field, named `parent.child` like the CSV logs
+/// written by pubsub. List and map columns, which CSV cannot hold, become their display strings.
+pub fn flatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch> {
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        flatten_column(field.name(), field, column, &mut fields, &mut columns)?;
+    }
+
+    let schema = Schema::new_with_metadata(fields, batch.schema().metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn flatten_column(
+    name: &str,
+    field: &Field,
+    column: &ArrayRef,
+    fields: &mut Vec<Field>,
+    columns: &mut Vec<ArrayRef>,
+) -> Result<()> {
+    match field.data_type() {
+        DataType::Struct(children) => {
+            let struct_array = column.as_struct();
+            for (child, child_column) in children.iter().zip(struct_array.columns()) {
+                // A null struct makes all of its fields null
+                let child_column = match struct_array.nulls() {
+                    Some(parent_nulls) => {
+                        let nulls = arrow::buffer::NullBuffer::union(
+                            Some(parent_nulls),
+                            child_column.nulls(),
+                        );
+                        arrow::array::make_array(
+                            child_column.to_data().into_builder().nulls(nulls).build()?,
+                        )
+                    }
+                    None => child_column.clone(),
+                };
+                let child = child
+                    .as_ref()
+                    .clone()
+                    .with_nullable(child.is_nullable() || struct_array.nulls().is_some());
+                flatten_column(
+                    &format!("{}.{}", name, child.name()),
+                    &child,
+                    &child_column,
+                    fields,
+                    columns,
+                )?;
+            }
+        }
+        DataType::List(_)
+        | DataType::LargeList(_)
+        | DataType::FixedSizeList(_, _)
+        | DataType::Map(_, _) => {
+            let formatter = ArrayFormatter::try_new(column.as_ref(), &plot_format_options())?;
+            let values: StringArray = (0..column.len())
+                .map(|row| {
+                    column
+                        .is_valid(row)
+                        .then(|| formatter.value(row).to_string())
+                })
+                .collect();
+            fields.push(Field::new(name, DataType::Utf8, true));
+            columns.push(Arc::new(values));
+        }
+        _ => {
+            fields.push(field.clone().with_name(name));
+            columns.push(column.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Maximum number of distinct orphan values listed in an `IntegrityReport`
+const MAX_ORPHAN_VALUES: usize = 20;
+
+/// Result of `verify_referential_integrity`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IntegrityReport {
+    pub total_rows: usize,
+    /// Rows whose key exists in the dimension file, or is null
+    pub valid_rows: usize,
+    /// First distinct keys missing from the dimension file, at most 20
+    pub orphan_values: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn orphan_rows(&self) -> usize {
+        self.total_rows - self.valid_rows
+    }
+}
+
+/// Checks that every value of `fact_column` exists in `dimension_column` of `dimension_file`.
+/// Keys are compared by their display value, so e.g. Int32 and Int64 ids match.
+pub fn verify_referential_integrity(
+    fact_file: &Path,
+    fact_column: &str,
+    dimension_file: &Path,
+    dimension_column: &str,
+) -> Result<IntegrityReport> {
+    let options = FormatOptions::default();
+
+    let mut keys = std::collections::HashSet::new();
+    for batch in read_parquet_file(dimension_file)? {
+        let batch = batch?;
+        let values = batch_column(&batch, dimension_column)?;
+        let formatter = ArrayFormatter::try_new(values.as_ref(), &options)?;
+        for row in 0..batch.num_rows() {
+            if values.is_valid(row) {
+                keys.insert(formatter.value(row).to_string());
+            }
+        }
+    }
+
+    let mut report = IntegrityReport::default();
+    for batch in read_parquet_file(fact_file)? {
+        let batch = batch?;
+        let values = batch_column(&batch, fact_column)?;
+        let formatter = ArrayFormatter::try_new(values.as_ref(), &options)?;
+        for row in 0..batch.num_rows() {
+            report.total_rows += 1;
+            if values.is_null(row) {
+                report.valid_rows += 1;
+                continue;
+            }
+
+            let value = formatter.value(row).to_string();
+            if keys.contains(&value) {
+                report.valid_rows += 1;
+            } else if report.orphan_values.len() < MAX_ORPHAN_VALUES
+                && !report.orphan_values.contains(&value)
+            {
+                report.orphan_values.push(value);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Value distribution of a numeric column, see `compute_histogram`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Lower edge of each bin
+    pub bins: Vec<f64>,
+    /// Number of values in each bin
+    pub counts: Vec<u64>,
+    pub bin_width: f64,
+}
+
+impl Histogram {
+    /// Renders the histogram as horizontal bars, the fullest bin is `width` characters long
+    pub fn to_ascii(&self, width: usize) -> String {
+        let max_count = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let labels: Vec<String> = self
+            .bins
+            .iter()
+            .map(|lower| format!("[{:.3}, {:.3})", lower, lower + self.bin_width))
+            .collect();
+        let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+
+        let mut output = String::new();
+        for (label, count) in labels.iter().zip(&self.counts) {
+            let bar_len = (*count as f64 / max_count as f64 * width as f64).round() as usize;
+            output.push_str(&format!(
+                "{:<label_width$} | {} {}\n",
+                label,
+                "#".repeat(bar_len),
+                count
+            ));
+        }
+        output
+    }
+}
+
+/// Counts the values of a numeric column in `bins` equal width bins spanning its range.
+/// Nulls and NaNs are skipped, the maximum value is counted in the last bin.
+pub fn compute_histogram(path: &Path, column: &str, bins: usize) -> Result<Histogram> {
+    if bins == 0 {
+        return Err(anyhow::anyhow!("Histogram needs at least one bin"));
+    }
+
+    // First pass finds the range, the second one fills the bins
+    let mut range: Option<(f64, f64)> = None;
+    for batch in read_parquet_file(path)? {
+        let values = histogram_values(&batch?, column)?;
+        if let (Some(min), Some(max)) = (arrow::compute::min(&values), arrow::compute::max(&values))
+        {
+            range = Some(match range {
+                Some((lo, hi)) => (lo.min(min), hi.max(max)),
+                None => (min, max),
+            });
+        }
+    }
+    let (min, max) =
+        range.ok_or_else(|| anyhow::anyhow!("Column '{}' has no numeric values", column))?;
+
+    // A constant column still gets bins of width 1 so the labels are readable
+    let bin_width = if max > min {
+        (max - min) / bins as f64
+    } else {
+        1.0
+    };
+    let mut counts = vec![0u64; bins];
+    for batch in read_parquet_file(path)? {
+        let values = histogram_values(&batch?, column)?;
+        for value in values.iter().flatten() {
+            let bin = ((value - min) / bin_width) as usize;
+            counts[bin.min(bins - 1)] += 1;
+        }
+    }
+
+    Ok(Histogram {
+        bins: (0..bins).map(|i| min + i as f64 * bin_width).collect(),
+        counts,
+        bin_width,
+    })
+}
+
+/// Column as Float64 with NaNs turned into nulls
+fn histogram_values(batch: &RecordBatch, column: &str) -> Result<Float64Array> {
+    let array = batch_column(batch, column)?;
+    if !array.data_type().is_numeric() {
+        return Err(anyhow::anyhow!(
+            "Column '{}' is not numeric ({})",
+            column,
+            array.data_type()
+        ));
+    }
+    let values = arrow::compute::cast(array, &DataType::Float64)?;
+    Ok(values
+        .as_primitive::<Float64Type>()
+        .iter()
+        .map(|v| v.filter(|v| !v.is_nan()))
+        .collect())
+}
+
+/// Upserts the rows of `updates` into `base` using `key_column` as the primary key.
+/// Base rows whose key appears in `updates` are replaced, the other update rows are appended.
+/// If `updates` repeats a key, its last row wins. The output is sorted by key.
+/// Returns `(rows_unchanged, rows_updated, rows_inserted)`.
+pub fn merge_by_primary_key(
+    base: &Path,
+    updates: &Path,
+    key_column: &str,
+    output: &Path,
+) -> Result<(usize, usize, usize)> {
+    let base_batch = read_whole_file(base)?;
+    let base_schema = base_batch.schema();
+    let column_order: Vec<String> = base_schema
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect();
+
+    let updates_batch = read_whole_file(updates)?;
+    if canonical_column_order(&base_schema) != canonical_column_order(&updates_batch.schema()) {
+        return Err(anyhow::anyhow!(
+            "Columns of {} do not match {}",
+            updates.display(),
+            base.display()
+        ));
+    }
+    let updates_batch = reorder_record_batch(&updates_batch, &column_order)?;
+
+    let base_keys = batch_column(&base_batch, key_column)?;
+    let updates_keys = batch_column(&updates_batch, key_column)?;
+    if base_keys.data_type() != updates_keys.data_type() {
+        return Err(anyhow::anyhow!(
+            "Key column '{}' is {} in base but {} in updates",
+            key_column,
+            base_keys.data_type(),
+            updates_keys.data_type()
+        ));
+    }
+
+    // Row format keys compare the same way across both files
+    let converter = RowConverter::new(vec![SortField::new(base_keys.data_type().clone())])?;
+    let base_rows = converter.convert_columns(std::slice::from_ref(base_keys))?;
+    let updates_rows = converter.convert_columns(std::slice::from_ref(updates_keys))?;
+
+    let base_order = sorted_row_indices(&base_rows);
+    let mut updates_order = sorted_row_indices(&updates_rows);
+    // The sort is stable, so the last of a run of equal keys is the latest update
+    updates_order.reverse();
+    updates_order.dedup_by(|a, b| updates_rows.row(*a) == updates_rows.row(*b));
+    updates_order.reverse();
+
+    // Merge-join the sorted keys, recording (source batch, row) for each output row
+    let mut selection = Vec::with_capacity(base_order.len() + updates_order.len());
+    let (mut unchanged, mut updated, mut inserted) = (0, 0, 0);
+    let (mut i, mut j) = (0, 0);
+    while i < base_order.len() || j < updates_order.len() {
+        let ordering = match (base_order.get(i), updates_order.get(j)) {
+            (Some(&b), Some(&u)) => base_rows.row(b).cmp(&updates_rows.row(u)),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            _ => std::cmp::Ordering::Greater,
+        };
+        match ordering {
+            std::cmp::Ordering::Less => {
+                selection.push((0, base_order[i]));
+                unchanged += 1;
+                i += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                let u = updates_order[j];
+                selection.push((1, u));
+                updated += 1;
+                // Every base row with this key is replaced by the one update
+                while i < base_order.len() && base_rows.row(base_order[i]) == updates_rows.row(u) {
+                    i += 1;
+                }
+                j += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                selection.push((1, updates_order[j]));
+                inserted += 1;
+                j += 1;
+            }
+        }
+    }
+
+    let columns = base_batch
+        .columns()
+        .iter()
+        .zip(updates_batch.columns())
+        .map(|(base_column, updates_column)| {
+            arrow::compute::interleave(&[base_column.as_ref(), updates_column.as_ref()], &selection)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let merged = RecordBatch::try_new(base_schema.clone(), columns)?;
+
+    let output_file = File::create(output)
+        .with_context(|| format!("Failed to create output file: {}", output.display()))?;
+    let mut writer = ArrowWriter::try_new(output_file, base_schema, None)?;
+    writer.write(&merged)?;
+    writer.close()?;
+
+    Ok((unchanged, updated, inserted))
+}
+
+/// Reads every batch of a parquet file into a single batch
+fn read_whole_file(path: &Path) -> Result<RecordBatch> {
+    let reader = read_parquet_file(path)?;
+    let schema = reader.schema();
+    let batches: Vec<RecordBatch> = reader.collect::<Result<_, _>>()?;
+    Ok(arrow::compute::concat_batches(&schema, &batches)?)
+}
+
+/// Row indices ordered by key, equal keys keep their original order
+fn sorted_row_indices(rows: &Rows) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..rows.num_rows()).collect();
+    indices.sort_by(|a, b| rows.row(*a).cmp(&rows.row(*b)));
+    indices
+}
+
+/// Settings for `compare_parquet_data`
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    /// Maximum absolute difference for numeric values, exact when None
+    pub tolerance: Option<f64>,
+    pub ignore_columns: Vec<String>,
+    /// Sort both files by all compared columns first
+    pub ignore_row_order: bool,
+}
+
+impl CompareOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    pub fn with_ignore_columns(mut self, columns: Vec<String>) -> Self {
+        self.ignore_columns = columns;
+        self
+    }
+
+    pub fn with_ignore_row_order(mut self, ignore_row_order: bool) -> Self {
+        self.ignore_row_order = ignore_row_order;
+        self
+    }
+}
+
+/// A column that differs between the compared files
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMismatch {
+    pub column: String,
+    /// Number of differing rows among the rows present in both files
+    pub mismatched_rows: usize,
+    pub first_mismatch_row: Option<usize>,
+    /// Human readable description of the first difference
+    pub detail: String,
+}
+
+/// Result of `compare_parquet_data`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareReport {
+    pub equal: bool,
+    /// Rows in `actual` minus rows in `expected`
+    pub row_count_diff: i64,
+    pub mismatched_columns: Vec<ColumnMismatch>,
+}
+
+/// Compares the data of two parquet files column by column, e.g. for regression tests.
+/// Numeric columns are compared as Float64 within `tolerance`, other columns by exact value.
+/// Columns missing from either file are reported as mismatches.
+pub fn compare_parquet_data(
+    expected: &Path,
+    actual: &Path,
+    options: CompareOptions,
+) -> Result<CompareReport> {
+    let expected_batch = read_whole_file(expected)?;
+    let actual_batch = read_whole_file(actual)?;
+    let row_count_diff = actual_batch.num_rows() as i64 - expected_batch.num_rows() as i64;
+
+    let mut mismatched_columns = Vec::new();
+    let mut columns = Vec::new();
+    for field in expected_batch.schema().fields() {
+        if options.ignore_columns.contains(field.name()) {
+            continue;
+        }
+        if actual_batch.column_by_name(field.name()).is_some() {
+            columns.push(field.name().clone());
+        } else {
+            mismatched_columns.push(missing_column_mismatch(field.name(), "actual"));
+        }
+    }
+    for field in actual_batch.schema().fields() {
+        if !options.ignore_columns.contains(field.name())
+            && expected_batch.column_by_name(field.name()).is_none()
+        {
+            mismatched_columns.push(missing_column_mismatch(field.name(), "expected"));
+        }
+    }
+
+    let mut expected_batch = reorder_record_batch(&expected_batch, &columns)?;
+    let mut actual_batch = reorder_record_batch(&actual_batch, &columns)?;
+    if options.ignore_row_order && !columns.is_empty() {
+        expected_batch = sort_by_all_columns(&expected_batch)?;
+        actual_batch = sort_by_all_columns(&actual_batch)?;
+    }
+
+    let rows = expected_batch.num_rows().min(actual_batch.num_rows());
+    for (index, column) in columns.iter().enumerate() {
+        let expected_column = expected_batch.column(index).slice(0, rows);
+        let actual_column = actual_batch.column(index).slice(0, rows);
+        if let Some(mismatch) =
+            compare_columns(column, &expected_column, &actual_column, options.tolerance)?
+        {
+            mismatched_columns.push(mismatch);
+        }
+    }
+
+    Ok(CompareReport {
+        equal: row_count_diff == 0 && mismatched_columns.is_empty(),
+        row_count_diff,
+        mismatched_columns,
+    })
+}
+
+fn missing_column_mismatch(column: &str, missing_from: &str) -> ColumnMismatch {
+    ColumnMismatch {
+        column: column.to_string(),
+        mismatched_rows: 0,
+        first_mismatch_row: None,
+        detail: format!("missing from {}", missing_from),
+    }
+}
+
+/// Sorts the rows of a batch by every column, left to right
+fn sort_by_all_columns(batch: &RecordBatch) -> Result<RecordBatch> {
+    let fields = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| SortField::new(f.data_type().clone()))
+        .collect();
+    let converter = RowConverter::new(fields)?;
+    let rows = converter.convert_columns(batch.columns())?;
+    let indices = arrow::array::UInt32Array::from_iter_values(
+        sorted_row_indices(&rows).into_iter().map(|i| i as u32),
+    );
+    Ok(arrow::compute::take_record_batch(batch, &indices)?)
+}
+
+/// Compares two equally long columns, returning None if they match
+fn compare_columns(
+    column: &str,
+    expected: &ArrayRef,
+    actual: &ArrayRef,
+    tolerance: Option<f64>,
+) -> Result<Option<ColumnMismatch>> {
+    let mut mismatched_rows = 0;
+    let mut first_mismatch: Option<(usize, String)> = None;
+
+    if expected.data_type().is_numeric() && actual.data_type().is_numeric() {
+        let tolerance = tolerance.unwrap_or(0.0);
+        let expected_values = arrow::compute::cast(expected, &DataType::Float64)?;
+        let actual_values = arrow::compute::cast(actual, &DataType::Float64)?;
+        let pairs = expected_values
+            .as_primitive::<Float64Type>()
+            .iter()
+            .zip(actual_values.as_primitive::<Float64Type>().iter());
+        for (row, (e, a)) in pairs.enumerate() {
+            let equal = match (e, a) {
+                (Some(e), Some(a)) => (e.is_nan() && a.is_nan()) || (e - a).abs() <= tolerance,
+                (None, None) => true,
+                _ => false,
+            };
+            if !equal {
+                mismatched_rows += 1;
+                first_mismatch
+                    .get_or_insert_with(|| (row, format!("expected {:?}, got {:?}", e, a)));
+            }
+        }
+    } else if expected.data_type() != actual.data_type() {
+        return Ok(Some(ColumnMismatch {
+            column: column.to_string(),
+            mismatched_rows: expected.len(),
+            first_mismatch_row: None,
+            detail: format!(
+                "type {} does not match {}",
+                actual.data_type(),
+                expected.data_type()
+            ),
+        }));
+    } else {
+        let options = FormatOptions::default().with_null("null");
+        let expected_values = ArrayFormatter::try_new(expected.as_ref(), &options)?;
+        let actual_values = ArrayFormatter::try_new(actual.as_ref(), &options)?;
+        for row in 0..expected.len() {
+            let e = expected_values.value(row).to_string();
+            let a = actual_values.value(row).to_string();
+            if e != a || expected.is_null(row) != actual.is_null(row) {
+                mismatched_rows += 1;
+                first_mismatch
+                    .get_or_insert_with(|| (row, format!("expected '{}', got '{}'", e, a)));
+            }
+        }
+    }
+
+    Ok(first_mismatch.map(|(row, detail)| ColumnMismatch {
+        column: column.to_string(),
+        mismatched_rows,
+        first_mismatch_row: Some(row),
+        detail,
+    }))
+}
+
+/// Result of `diff`, one single-row batch per row in key order
+#[derive(Debug, Clone, Default)]
+pub struct DiffResult {
+    /// Rows whose key is only in `file_b`
+    pub added: Vec<RecordBatch>,
+    /// Rows whose key is only in `file_a`
+    pub removed: Vec<RecordBatch>,
+    /// Old and new version of rows whose key is in both files but whose values differ
+    pub changed: Vec<(RecordBatch, RecordBatch)>,
+}
+
+impl DiffResult {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Diffs two parquet files with the same columns, matching rows by `key_columns`.
+/// If a key repeats, its rows are paired in file order and the extra rows of
+/// either file are reported as added or removed.
+pub fn diff(file_a: &Path, file_b: &Path, key_columns: &[&str]) -> Result<DiffResult> {
+    if key_columns.is_empty() {
+        return Err(anyhow::anyhow!("At least one key column is required"));
+    }
+
+    let batch_a = read_whole_file(file_a)?;
+    let schema = batch_a.schema();
+    let batch_b = read_whole_file(file_b)?;
+    if canonical_column_order(&schema) != canonical_column_order(&batch_b.schema()) {
+        return Err(anyhow::anyhow!(
+            "Columns of {} do not match {}",
+            file_b.display(),
+            file_a.display()
+        ));
+    }
+    let column_order: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
+    let batch_b = reorder_record_batch(&batch_b, &column_order)?;
+    for (field_a, field_b) in schema.fields().iter().zip(batch_b.schema().fields()) {
+        if field_a.data_type() != field_b.data_type() {
+            return Err(anyhow::anyhow!(
+                "Column '{}' is {} in {} but {} in {}",
+                field_a.name(),
+                field_a.data_type(),
+                file_a.display(),
+                field_b.data_type(),
+                file_b.display()
+            ));
+        }
+    }
+
+    let key_fields = key_columns
+        .iter()
+        .map(|column| {
+            Ok(SortField::new(
+                batch_column(&batch_a, column)?.data_type().clone(),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let key_converter = RowConverter::new(key_fields)?;
+    let key_rows = |batch: &RecordBatch| -> Result<Rows> {
+        let columns = key_columns
+            .iter()
+            .map(|column| batch_column(batch, column).cloned())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(key_converter.convert_columns(&columns)?)
+    };
+    let keys_a = key_rows(&batch_a)?;
+    let keys_b = key_rows(&batch_b)?;
+
+    // Whole rows in row format tell changed rows from unchanged ones
+    let value_converter = RowConverter::new(
+        schema
+            .fields()
+            .iter()
+            .map(|f| SortField::new(f.data_type().clone()))
+            .collect(),
+    )?;
+    let values_a = value_converter.convert_columns(batch_a.columns())?;
+    let values_b = value_converter.convert_columns(batch_b.columns())?;
+
+    let order_a = sorted_row_indices(&keys_a);
+    let order_b = sorted_row_indices(&keys_b);
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    let (mut changed_a, mut changed_b) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < order_a.len() || j < order_b.len() {
+        let ordering = match (order_a.get(i), order_b.get(j)) {
+            (Some(&a), Some(&b)) => keys_a.row(a).cmp(&keys_b.row(b)),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            _ => std::cmp::Ordering::Greater,
+        };
+        match ordering {
+            std::cmp::Ordering::Less => {
+                removed.push(order_a[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                if values_a.row(order_a[i]) != values_b.row(order_b[j]) {
+                    changed_a.push(order_a[i]);
+                    changed_b.push(order_b[j]);
+                }
+                i += 1;
+                j += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                added.push(order_b[j]);
+                j += 1;
+            }
+        }
+    }
+
+    let changed_a = take_rows(&batch_a, &changed_a)?;
+    let changed_b = take_rows(&batch_b, &changed_b)?;
+    Ok(DiffResult {
+        added: take_rows(&batch_b, &added)?,
+        removed: take_rows(&batch_a, &removed)?,
+        changed: changed_a.into_iter().zip(changed_b).collect(),
+    })
+}
+
+/// Takes the given rows of a batch as single-row batches
+fn take_rows(batch: &RecordBatch, rows: &[usize]) -> Result<Vec<RecordBatch>> {
+    let indices = arrow::array::UInt32Array::from_iter_values(rows.iter().map(|&i| i as u32));
+    let taken = arrow::compute::take_record_batch(batch, &indices)?;
+    Ok((0..taken.num_rows())
+        .map(|row| taken.slice(row, 1))
+        .collect())
+}
+
+/// Extracts the schema from a parquet file
+pub fn get_schema(path: &Path) -> Result<Schema> {
+    let reader = read_parquet_file(path)?;
+    Ok(Schema::from(reader.schema().as_ref().clone()))
+}
+
+/// Merges parquet files by first grouping them by schema compatibility.
+/// Progress is reported per schema group, `total_files` being the size of the current group.
+pub fn merge_parquet_files_by_schema_groups(
+    input_files: &[PathBuf],
+    output_dir: &Path,
+    base_filename: &str,
+    options: &MergeOptions,
+) -> Result<Vec<PathBuf>> {
+    if input_files.is_empty() {
+        return Err(anyhow::anyhow!("No input files found to merge"));
+    }
+
+    // Create schema groups - maps schema hash to list of files
+    let mut schema_groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+    // Sort files into schema groups
+    for file_path in input_files {
+        // Skip files we can't read
+        let reader = match read_parquet_file(file_path) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Warning: Unable to read {}: {}", file_path.display(), e);
+                continue;
+            }
+        };
+
+        // Get a schema representation for grouping
+        let schema = reader.schema();
+        let _schema_key = get_schema_key(&schema);
+
+        schema_groups
+            .entry(_schema_key)
+            .or_default()
+            .push(file_path.clone());
+    }
+
+    // Make sure output directory exists
+    std::fs::create_dir_all(output_dir).with_context(|| {
+        format!(
+            "Failed to create output directory: {}",
+            output_dir.display()
+        )
+    })?;
+
+    // Now merge each group separately
+    let mut output_files = Vec::new();
+    let group_count = schema_groups.len();
+
+    for (i, (_schema_key, files)) in schema_groups.into_iter().enumerate() {
+        let output_filename = if group_count > 1 {
+            format!("{}_{}.parquet", base_filename, i + 1)
+        } else {
+            format!("{}.parquet", base_filename)
+        };
+
+        let output_path = output_dir.join(output_filename);
+
+        println!("Merging schema group {} with {} files", i + 1, files.len());
 
         // Merge this group
-        match merge_parquet_files_to_output(&files, &output_path, false) {
+        match merge_parquet_files_to_output(&files, &output_path, options) {
             Ok(_) => {
                 println!("Successfully created {}", output_path.display());
                 output_files.push(output_path);
@@ -319,3 +3091,1274 @@ fn get_schema_key(schema: &arrow::datatypes::Schema) -> String {
 
     key
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::Field;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    fn write_test_file(path: &Path, values: Vec<i32>) {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Int32,
+            false,
+        )]));
+        let batch =
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))]).unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_merge_progress_callback() {
+        let dir =
+            std::env::temp_dir().join(format!("log_utils_merge_progress_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let files: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = dir.join(format!("part_{}.parquet", i));
+                write_test_file(&path, vec![i; 2]);
+                path
+            })
+            .collect();
+
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let reports_clone = reports.clone();
+        let options = MergeOptions::new()
+            .with_progress_callback(move |report| reports_clone.borrow_mut().push(report));
+
+        merge_parquet_files_to_output(&files, &dir.join("merged.parquet"), &options).unwrap();
+
+        let reports = reports.borrow();
+        assert_eq!(reports.len(), files.len());
+        for (i, report) in reports.iter().enumerate() {
+            assert_eq!(report.files_processed, i + 1);
+            assert_eq!(report.total_files, files.len());
+            assert_eq!(report.rows_written, (i as u64 + 1) * 2);
+            assert_eq!(report.current_file, files[i]);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_with_compression() {
+        let dir = std::env::temp_dir().join(format!("log_utils_merge_zstd_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.parquet");
+        let values: Vec<i32> = (0..10_000).map(|i| i % 8).collect();
+        write_test_file(&input, values.clone());
+
+        let uncompressed = dir.join("uncompressed.parquet");
+        let zstd = dir.join("zstd.parquet");
+        merge_parquet_files_to_output(
+            std::slice::from_ref(&input),
+            &uncompressed,
+            &MergeOptions::new().with_compression(Compression::UNCOMPRESSED),
+        )
+        .unwrap();
+        merge_parquet_files_to_output(
+            std::slice::from_ref(&input),
+            &zstd,
+            &MergeOptions::new().with_compression(parse_compression("zstd").unwrap()),
+        )
+        .unwrap();
+
+        let size = |path: &Path| std::fs::metadata(path).unwrap().len();
+        assert!(size(&zstd) < size(&uncompressed));
+
+        let batches = collect_record_batches(&zstd).unwrap();
+        let read: Vec<i32> = batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(0)
+                    .as_primitive::<arrow::datatypes::Int32Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(read, values);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_print_row_groups() {
+        let dir = std::env::temp_dir().join(format!("log_utils_row_groups_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("row_groups.parquet");
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Int32,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from((0..10).collect::<Vec<i32>>()))],
+        )
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(5)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), schema, Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        colored::control::set_override(false);
+        let output = print_row_groups(&path).unwrap();
+
+        assert!(output.contains("Num row groups: 2"));
+        assert!(output.contains("Row group 0: 5 rows"));
+        assert!(output.contains("Row group 1: 5 rows"));
+        assert!(output.contains("value: min 0 max 4"));
+        assert!(output.contains("value: min 5 max 9"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recompress_parquet() {
+        let dir = std::env::temp_dir().join(format!("log_utils_recompress_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("uncompressed.parquet");
+        let output = dir.join("snappy.parquet");
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Int32,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(
+                (0..1000).map(|i| i % 10).collect::<Vec<i32>>(),
+            ))],
+        )
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_compression(Compression::UNCOMPRESSED)
+            .set_dictionary_enabled(false)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&input).unwrap(), schema, Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let (rows, old_size, new_size) =
+            recompress_parquet(&input, &output, parse_compression("snappy").unwrap()).unwrap();
+        assert_eq!(rows, 1000);
+        assert!(new_size < old_size);
+
+        let batches = collect_record_batches(&output).unwrap();
+        let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(total_rows, 1000);
+
+        assert!(parse_compression("brotli-9000").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_to_influxdb_line_protocol() {
+        use arrow::array::{BooleanArray, UInt16Array};
+
+        let dir = std::env::temp_dir().join(format!("log_utils_influxdb_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("attitude.parquet");
+        let output = dir.join("attitude.lp");
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("timestamp", DataType::Int64, false),
+            Field::new("vehicle", DataType::Utf8, false),
+            Field::new("roll", DataType::Float64, false),
+            Field::new("satellites", DataType::Int64, true),
+            Field::new("armed", DataType::Boolean, false),
+            Field::new("heading", DataType::UInt16, false),
+            Field::new("pitch", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1_000_000_000, 2_000_000_000])),
+                Arc::new(StringArray::from(vec!["quad 1", "quad 1"])),
+                Arc::new(Float64Array::from(vec![0.5, -1.25])),
+                Arc::new(Int64Array::from(vec![Some(12), None])),
+                Arc::new(BooleanArray::from(vec![true, false])),
+                Arc::new(UInt16Array::from(vec![90, 180])),
+                // Not representable in line protocol, skipped
+                Arc::new(Float64Array::from(vec![f64::NAN, f64::INFINITY])),
+            ],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(&input).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let lines_written = export_to_influxdb_line_protocol(
+            &input,
+            &output,
+            "attitude",
+            "timestamp",
+            &["vehicle".to_string()],
+        )
+        .unwrap();
+        assert_eq!(lines_written, 2);
+
+        let contents = std::fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(
+            lines[0],
+            "attitude,vehicle=quad\\ 1 roll=0.5,satellites=12i,armed=true,heading=90u 1000000000"
+        );
+        assert_eq!(
+            lines[1],
+            "attitude,vehicle=quad\\ 1 roll=-1.25,armed=false,heading=180u 2000000000"
+        );
+
+        for line in lines {
+            let (head, timestamp) = line.rsplit_once(' ').unwrap();
+            assert!(head.starts_with("attitude,"));
+            assert!(timestamp.parse::<i64>().is_ok());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sample_parquet_is_reproducible() {
+        let dir = std::env::temp_dir().join(format!("log_utils_sample_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sample.parquet");
+        write_test_file(&path, (0..1000).collect());
+
+        let first = sample_parquet(&path, 10, Some(42)).unwrap();
+        let second = sample_parquet(&path, 10, Some(42)).unwrap();
+        assert_eq!(first.num_rows(), 10);
+        assert_eq!(first, second);
+
+        let values = first
+            .column(0)
+            .as_primitive::<arrow::datatypes::Int32Type>();
+        assert!(values.values().windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Asking for more rows than the file has returns all of them
+        assert_eq!(sample_parquet(&path, 2000, None).unwrap().num_rows(), 1000);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_parquet() {
+        let dir = std::env::temp_dir().join(format!("log_utils_snapshot_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("attitude.parquet");
+        let output = dir.join("attitude_snapshot.parquet");
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("roll", DataType::Float64, true),
+            Field::new("mode", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Float64Array::from(vec![
+                    Some(1.0),
+                    None,
+                    Some(-2.0),
+                    Some(4.0),
+                ])),
+                Arc::new(StringArray::from(vec!["guided", "auto", "land", "guided"])),
+            ],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(&input).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        snapshot_parquet(&input, &output).unwrap();
+        let summary = &collect_record_batches(&output).unwrap()[0];
+        assert_eq!(summary.num_rows(), 2);
+
+        let column = |name: &str| summary.column_by_name(name).unwrap().clone();
+        assert_eq!(column("column_name").as_string::<i32>().value(0), "roll");
+        assert_eq!(column("data_type").as_string::<i32>().value(0), "Float64");
+        assert_eq!(column("row_count").as_primitive::<Int64Type>().value(0), 4);
+        assert_eq!(column("null_count").as_primitive::<Int64Type>().value(0), 1);
+        assert_eq!(column("min_value").as_string::<i32>().value(0), "-2");
+        assert_eq!(column("max_value").as_string::<i32>().value(0), "4");
+        assert_eq!(
+            column("mean_value").as_primitive::<Float64Type>().value(0),
+            1.0
+        );
+
+        // Strings get a lexicographic min/max but no mean
+        assert_eq!(column("min_value").as_string::<i32>().value(1), "auto");
+        assert_eq!(column("max_value").as_string::<i32>().value(1), "land");
+        assert!(column("mean_value").is_null(1));
+
+        let stacked = snapshot_multiple_parquet(&[input.clone(), input.clone()]).unwrap();
+        assert_eq!(stacked.num_rows(), 4);
+        assert_eq!(stacked.schema().field(0).name(), "file_path");
+        assert_eq!(
+            stacked.column(0).as_string::<i32>().value(3),
+            input.display().to_string()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reorder_columns() {
+        let dir = std::env::temp_dir().join(format!("log_utils_reorder_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.parquet");
+        let output = dir.join("reordered.parquet");
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+            Field::new("c", DataType::Int32, false),
+        ]));
+        let columns: Vec<ArrayRef> = (0..3)
+            .map(|i| Arc::new(Int32Array::from(vec![i; 4])) as ArrayRef)
+            .collect();
+        let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(&input).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let order: Vec<String> = ["c", "a", "b"].iter().map(|s| s.to_string()).collect();
+        reorder_columns(&input, &output, &order).unwrap();
+
+        let reordered = &collect_record_batches(&output).unwrap()[0];
+        let names: Vec<&String> = reordered
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|f| f.name())
+            .collect();
+        assert_eq!(names, vec!["c", "a", "b"]);
+        assert_eq!(
+            reordered
+                .column(0)
+                .as_primitive::<arrow::datatypes::Int32Type>()
+                .value(0),
+            2
+        );
+
+        // Missing columns are padded with nulls
+        let order: Vec<String> = ["b", "missing"].iter().map(|s| s.to_string()).collect();
+        let padded = reorder_record_batch(&batch, &order).unwrap();
+        assert_eq!(padded.num_columns(), 2);
+        assert_eq!(padded.column(1).logical_null_count(), 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_auto_widen_schema() {
+        let dir = std::env::temp_dir().join(format!("log_utils_widen_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("narrow.parquet");
+        let output = dir.join("wide.parquet");
+        write_test_file(&input, vec![i32::MIN, -1, 0, 42, i32::MAX]);
+
+        let target = Schema::new(vec![Field::new("value", DataType::Int64, false)]);
+        auto_widen_schema(&input, &target, &output).unwrap();
+
+        let batch = &collect_record_batches(&output).unwrap()[0];
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Int64);
+        let values = batch.column(0).as_primitive::<Int64Type>();
+        assert_eq!(
+            values.values().to_vec(),
+            vec![i32::MIN as i64, -1, 0, 42, i32::MAX as i64]
+        );
+
+        // Narrowing back is refused
+        let narrow = Schema::new(vec![Field::new("value", DataType::Int16, false)]);
+        assert!(auto_widen_schema(&output, &narrow, &dir.join("narrowed.parquet")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extract_column_as_csv() {
+        use arrow::array::TimestampMillisecondArray;
+
+        let dir = std::env::temp_dir().join(format!("log_utils_column_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("attitude.parquet");
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("roll", DataType::Float64, true),
+        ]));
+        let rolls: Vec<Option<f64>> = (0..100)
+            .map(|i| if i == 5 { None } else { Some(i as f64 * 0.5) })
+            .collect();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampMillisecondArray::from_iter_values(0..100)),
+                Arc::new(Float64Array::from(rolls)),
+            ],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(&input).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let output = dir.join("roll.csv");
+        assert_eq!(
+            extract_column_as_csv(&input, "roll", &output, false).unwrap(),
+            100
+        );
+        let contents = std::fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 100);
+        assert_eq!(lines[1], "0.5");
+        assert_eq!(lines[5], "");
+
+        extract_column_as_csv(&input, "roll", &output, true).unwrap();
+        let contents = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(contents.lines().count(), 101);
+        assert_eq!(contents.lines().next(), Some("roll"));
+
+        let scatter = dir.join("scatter.csv");
+        assert_eq!(
+            extract_two_columns_as_csv(&input, "timestamp", "roll", &scatter).unwrap(),
+            100
+        );
+        let contents = std::fs::read_to_string(&scatter).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], "timestamp,roll");
+        assert_eq!(lines[2], "1970-01-01T00:00:00.001,0.5");
+
+        assert!(extract_column_as_csv(&input, "missing", &output, false).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_convert_to_format() {
+        use arrow::array::{Float32Array, StructArray};
+
+        let dir = std::env::temp_dir().join(format!("log_utils_convert_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("local_position.parquet");
+
+        let pose_fields = vec![
+            Field::new("x", DataType::Float32, false),
+            Field::new("y", DataType::Float32, false),
+        ];
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("pose", DataType::Struct(pose_fields.clone().into()), false),
+        ]));
+        let make_batch = |ids: Vec<i64>| {
+            let xs: Vec<f32> = ids.iter().map(|id| *id as f32).collect();
+            let ys: Vec<f32> = ids.iter().map(|id| *id as f32 * 2.0).collect();
+            let pose = StructArray::new(
+                pose_fields.clone().into(),
+                vec![
+                    Arc::new(Float32Array::from(xs)),
+                    Arc::new(Float32Array::from(ys)),
+                ],
+                None,
+            );
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int64Array::from(ids)), Arc::new(pose)],
+            )
+            .unwrap()
+        };
+        let mut writer =
+            ArrowWriter::try_new(File::create(&input).unwrap(), schema.clone(), None).unwrap();
+        writer.write(&make_batch(vec![1, 2])).unwrap();
+        writer.flush().unwrap();
+        writer.write(&make_batch(vec![3])).unwrap();
+        writer.close().unwrap();
+
+        let csv = dir.join("local_position.csv");
+        convert_to_format(&input, &csv, ConvertFormat::Csv).unwrap();
+        let contents = std::fs::read_to_string(&csv).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(
+            lines,
+            vec!["id,pose.x,pose.y", "1,1.0,2.0", "2,2.0,4.0", "3,3.0,6.0"]
+        );
+
+        let json = dir.join("local_position.json");
+        convert_to_format(&input, &json, ConvertFormat::Json).unwrap();
+        let contents = std::fs::read_to_string(&json).unwrap();
+        assert!(contents.starts_with('[') && contents.trim_end().ends_with(']'));
+        assert_eq!(contents.matches("\"id\":").count(), 3);
+        assert!(contents.contains("\"pose\":{\"x\":3.0,\"y\":6.0}"));
+
+        assert_eq!(
+            "JSON".parse::<ConvertFormat>().unwrap(),
+            ConvertFormat::Json
+        );
+        assert!("xml".parse::<ConvertFormat>().is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_repair_parquet() {
+        let dir = std::env::temp_dir().join(format!("log_utils_repair_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("attitude.parquet");
+
+        let metadata = HashMap::from([("topic".to_string(), "mavlink/attitude".to_string())]);
+        let schema = Arc::new(
+            Schema::new(vec![Field::new("value", DataType::Int32, false)])
+                .with_metadata(metadata.clone()),
+        );
+        let mut writer =
+            ArrowWriter::try_new(File::create(&input).unwrap(), schema.clone(), None).unwrap();
+        // One row group per flush
+        for chunk in (0..3_000).collect::<Vec<i32>>().chunks(1_000) {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int32Array::from(chunk.to_vec()))],
+            )
+            .unwrap();
+            writer.write(&batch).unwrap();
+            writer.flush().unwrap();
+        }
+        writer.close().unwrap();
+
+        // Overwrite the data of the middle row group
+        let reader = SerializedFileReader::new(File::open(&input).unwrap()).unwrap();
+        let column = reader.metadata().row_group(1).column(0);
+        let (start, len) = column.byte_range();
+        let mut bytes = std::fs::read(&input).unwrap();
+        bytes[start as usize..(start + len) as usize].fill(0xFF);
+        std::fs::write(&input, &bytes).unwrap();
+        assert!(collect_record_batches(&input).is_err());
+
+        let output = dir.join("attitude_repaired.parquet");
+        let report = repair_parquet(&input, &output).unwrap();
+        assert_eq!(
+            report,
+            RepairReport {
+                row_groups_recovered: 2,
+                row_groups_lost: 1,
+                total_rows_recovered: 2_000,
+            }
+        );
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&output).unwrap()).unwrap();
+        assert_eq!(builder.schema().metadata(), &metadata);
+        let values: Vec<i32> = collect_record_batches(&output)
+            .unwrap()
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(0)
+                    .as_primitive::<arrow::datatypes::Int32Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(values, (0..1_000).chain(2_000..3_000).collect::<Vec<i32>>());
+
+        // Without a footer there is nothing to recover
+        std::fs::write(&input, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(repair_parquet(&input, &dir.join("truncated_repaired.parquet")).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_parquet_file() {
+        let dir = std::env::temp_dir().join(format!("log_utils_split_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("attitude.parquet");
+
+        let metadata = HashMap::from([("topic".to_string(), "mavlink/attitude".to_string())]);
+        let schema = Arc::new(
+            Schema::new(vec![Field::new("value", DataType::Int32, false)])
+                .with_metadata(metadata.clone()),
+        );
+        let values: Vec<i32> = (0..10_000).collect();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&input).unwrap(), schema.clone(), None).unwrap();
+        // Several input batches, so parts are cut across batch boundaries
+        for chunk in values.chunks(3_000) {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int32Array::from(chunk.to_vec()))],
+            )
+            .unwrap();
+            writer.write(&batch).unwrap();
+            writer.flush().unwrap();
+        }
+        writer.close().unwrap();
+
+        let read_parts = |parts: &[PathBuf]| -> Vec<i32> {
+            parts
+                .iter()
+                .flat_map(|part| {
+                    let builder =
+                        ParquetRecordBatchReaderBuilder::try_new(File::open(part).unwrap())
+                            .unwrap();
+                    assert_eq!(builder.schema().metadata(), &metadata);
+                    collect_record_batches(part)
+                        .unwrap()
+                        .iter()
+                        .flat_map(|batch| {
+                            batch
+                                .column(0)
+                                .as_primitive::<arrow::datatypes::Int32Type>()
+                                .values()
+                                .to_vec()
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        };
+
+        let parts = split_by_row_count(&input, &dir.join("by_rows"), 4_000).unwrap();
+        assert_eq!(parts.len(), 3);
+        assert_eq!(
+            parts[2].file_name().unwrap().to_string_lossy(),
+            "attitude_part_0002.parquet"
+        );
+        let rows: Vec<usize> = parts
+            .iter()
+            .map(|part| {
+                collect_record_batches(part)
+                    .unwrap()
+                    .iter()
+                    .map(|batch| batch.num_rows())
+                    .sum()
+            })
+            .collect();
+        assert_eq!(rows, vec![4_000, 4_000, 2_000]);
+        assert_eq!(read_parts(&parts), values);
+
+        let parts = split_by_size_bytes(&input, &dir.join("by_size"), 4_096).unwrap();
+        assert!(parts.len() > 1);
+        assert_eq!(read_parts(&parts), values);
+
+        assert!(split_by_row_count(&input, &dir, 0).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_parquet_files_sorted() {
+        let dir =
+            std::env::temp_dir().join(format!("log_utils_merge_sorted_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Each session is sorted on its own, the second one written in two row groups
+        let first = dir.join("session_1.parquet");
+        let second = dir.join("session_2.parquet");
+        write_param_file(&first, vec![10, 30, 30, 70], vec![1.0, 3.0, 3.1, 7.0]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("value", DataType::Float64, false),
+        ]));
+        let mut writer =
+            ArrowWriter::try_new(File::create(&second).unwrap(), schema.clone(), None).unwrap();
+        for (ids, values) in [
+            (vec![0, 30], vec![0.0, 3.2]),
+            (vec![40, 90], vec![4.0, 9.0]),
+        ] {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(ids)),
+                    Arc::new(Float64Array::from(values)),
+                ],
+            )
+            .unwrap();
+            writer.write(&batch).unwrap();
+            writer.flush().unwrap();
+        }
+        writer.close().unwrap();
+
+        let output = dir.join("merged.parquet");
+        merge_parquet_files_sorted(&[first.clone(), second.clone()], &output, "id", None).unwrap();
+        assert_eq!(
+            file_compression(&output).unwrap(),
+            file_compression(&first).unwrap()
+        );
+
+        let batches = collect_record_batches(&output).unwrap();
+        let ids: Vec<i64> = batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column_by_name("id")
+                    .unwrap()
+                    .as_primitive::<Int64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(ids.len(), 8);
+        assert!(ids.windows(2).all(|pair| pair[0] <= pair[1]));
+        // Equal keys keep the input file order
+        let values: Vec<f64> = batches
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column_by_name("value")
+                    .unwrap()
+                    .as_primitive::<Float64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        assert_eq!(&values[2..5], &[3.0, 3.1, 3.2]);
+
+        let unsorted = dir.join("unsorted.parquet");
+        write_param_file(&unsorted, vec![20, 5], vec![2.0, 0.5]);
+        assert!(
+            merge_parquet_files_sorted(&[first.clone(), unsorted], &output, "id", None).is_err()
+        );
+
+        let zstd = Compression::ZSTD(Default::default());
+        merge_parquet_files_sorted(&[first, second], &output, "id", Some(zstd)).unwrap();
+        assert_eq!(file_compression(&output).unwrap(), zstd);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sort_parquet_by_column() {
+        let dir = std::env::temp_dir().join(format!("log_utils_sort_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Two sessions with interleaved timestamps, the second one written in two row groups
+        let first = dir.join("session_1.parquet");
+        let second = dir.join("session_2.parquet");
+        write_param_file(&first, vec![30, 10, 50], vec![3.0, 1.0, 5.0]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("value", DataType::Float64, false),
+        ]));
+        let mut writer =
+            ArrowWriter::try_new(File::create(&second).unwrap(), schema.clone(), None).unwrap();
+        for (ids, values) in [
+            (vec![40, 20], vec![4.0, 2.0]),
+            (vec![60, 0], vec![6.0, 0.0]),
+        ] {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(ids)),
+                    Arc::new(Float64Array::from(values)),
+                ],
+            )
+            .unwrap();
+            writer.write(&batch).unwrap();
+            writer.flush().unwrap();
+        }
+        writer.close().unwrap();
+        let inputs = vec![first, second];
+
+        let read_ids = |path: &Path| -> Vec<i64> {
+            collect_record_batches(path)
+                .unwrap()
+                .iter()
+                .flat_map(|batch| {
+                    batch
+                        .column_by_name("id")
+                        .unwrap()
+                        .as_primitive::<Int64Type>()
+                        .values()
+                        .to_vec()
+                })
+                .collect()
+        };
+
+        let in_memory = dir.join("in_memory.parquet");
+        sort_parquet_by_column(&inputs, &in_memory, "id", true, None).unwrap();
+        assert_eq!(read_ids(&in_memory), vec![0, 10, 20, 30, 40, 50, 60]);
+
+        // A tiny budget spills every batch to its own run before merging
+        let external = dir.join("external.parquet");
+        sort_parquet_by_column(&inputs, &external, "id", true, Some(1)).unwrap();
+        assert_eq!(read_ids(&external), vec![0, 10, 20, 30, 40, 50, 60]);
+
+        let descending = dir.join("descending.parquet");
+        sort_parquet_by_column(&inputs, &descending, "id", false, Some(1)).unwrap();
+        assert_eq!(read_ids(&descending), vec![60, 50, 40, 30, 20, 10, 0]);
+
+        assert!(sort_parquet_by_column(&inputs, &in_memory, "missing", true, None).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_append_to_parquet() {
+        let dir = std::env::temp_dir().join(format!("log_utils_append_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("params.parquet");
+
+        let batch = |ids: Vec<i64>| {
+            let values = ids.iter().map(|id| *id as f64 * 0.5).collect::<Vec<_>>();
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("value", DataType::Float64, false),
+            ]));
+            RecordBatch::try_new(
+                schema,
+                vec![
+                    Arc::new(Int64Array::from(ids)),
+                    Arc::new(Float64Array::from(values)),
+                ],
+            )
+            .unwrap()
+        };
+
+        // Creates the file, then adds one row group per append
+        append_to_parquet(&path, &[batch(vec![1, 2])]).unwrap();
+        append_to_parquet(&path, &[batch(vec![3]), batch(vec![4, 5])]).unwrap();
+        append_to_parquet(&path, &[]).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let batches = collect_record_batches(&path).unwrap();
+        let merged = arrow::compute::concat_batches(&batches[0].schema(), &batches).unwrap();
+        assert_eq!(merged, batch(vec![1, 2, 3, 4, 5]));
+        assert!(!dir.join("params.parquet.tmp").exists());
+
+        let other_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        let mismatched = RecordBatch::try_new(
+            other_schema,
+            vec![Arc::new(arrow::array::Int32Array::from(vec![6]))],
+        )
+        .unwrap();
+        assert!(append_to_parquet(&path, &[mismatched]).is_err());
+        assert_eq!(collect_record_batches(&path).unwrap().len(), batches.len());
+        assert!(!dir.join("params.parquet.tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn write_param_file(path: &Path, ids: Vec<i64>, values: Vec<f64>) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("value", DataType::Float64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(ids)),
+                Arc::new(Float64Array::from(values)),
+            ],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_merge_by_primary_key() {
+        let dir = std::env::temp_dir().join(format!("log_utils_upsert_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let base = dir.join("base.parquet");
+        write_param_file(&base, vec![5, 1, 3, 2, 4], vec![5.0, 1.0, 3.0, 2.0, 4.0]);
+        let updates = dir.join("updates.parquet");
+        write_param_file(&updates, vec![3, 6, 1], vec![30.0, 60.0, 10.0]);
+
+        let output = dir.join("merged.parquet");
+        let counts = merge_by_primary_key(&base, &updates, "id", &output).unwrap();
+        assert_eq!(counts, (3, 2, 1));
+
+        let merged = read_whole_file(&output).unwrap();
+        let ids = merged.column(0).as_primitive::<Int64Type>();
+        let values = merged.column(1).as_primitive::<Float64Type>();
+        assert_eq!(ids.values().to_vec(), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            values.values().to_vec(),
+            vec![10.0, 2.0, 30.0, 4.0, 5.0, 60.0]
+        );
+
+        assert!(merge_by_primary_key(&base, &updates, "missing", &output).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_diff() {
+        let dir = std::env::temp_dir().join(format!("log_utils_diff_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let file_a = dir.join("a.parquet");
+        write_param_file(&file_a, vec![4, 1, 2, 3], vec![4.0, 1.0, 2.0, 3.0]);
+        let file_b = dir.join("b.parquet");
+        write_param_file(&file_b, vec![5, 3, 2, 1], vec![5.0, 30.0, 2.0, 1.5]);
+
+        let result = diff(&file_a, &file_b, &["id"]).unwrap();
+        let ids = |batches: &[RecordBatch]| -> Vec<i64> {
+            batches
+                .iter()
+                .map(|b| b.column(0).as_primitive::<Int64Type>().value(0))
+                .collect()
+        };
+        assert_eq!(ids(&result.added), vec![5]);
+        assert_eq!(ids(&result.removed), vec![4]);
+        assert_eq!(result.changed.len(), 2);
+        let (old, new) = &result.changed[1];
+        assert_eq!(old.column(0).as_primitive::<Int64Type>().value(0), 3);
+        assert_eq!(old.column(1).as_primitive::<Float64Type>().value(0), 3.0);
+        assert_eq!(new.column(1).as_primitive::<Float64Type>().value(0), 30.0);
+
+        assert!(diff(&file_a, &file_a, &["id"]).unwrap().is_empty());
+        assert!(diff(&file_a, &file_b, &["missing"]).is_err());
+        assert!(diff(&file_a, &file_b, &[]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compute_histogram_uniform() {
+        let dir = std::env::temp_dir().join(format!("log_utils_histogram_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("uniform.parquet");
+        let ids: Vec<i64> = (0..1000).collect();
+        let values: Vec<f64> = (0..1000).map(|i| i as f64 / 10.0).collect();
+        write_param_file(&input, ids, values);
+
+        let histogram = compute_histogram(&input, "value", 10).unwrap();
+        assert_eq!(histogram.counts.len(), 10);
+        assert!((histogram.bin_width - 9.99).abs() < 1e-9);
+        assert_eq!(histogram.bins[0], 0.0);
+        for count in &histogram.counts {
+            assert!((99..=101).contains(count), "got {}", count);
+        }
+        assert_eq!(histogram.counts.iter().sum::<u64>(), 1000);
+
+        let ascii = histogram.to_ascii(20);
+        assert_eq!(ascii.lines().count(), 10);
+        assert!(ascii.lines().next().unwrap().starts_with("[0.000, 9.990) "));
+
+        assert!(compute_histogram(&input, "value", 0).is_err());
+        assert!(compute_histogram(&input, "missing", 10).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_referential_integrity() {
+        let dir = std::env::temp_dir().join(format!("log_utils_integrity_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let waypoints = dir.join("waypoints.parquet");
+        write_param_file(&waypoints, vec![1, 2, 3], vec![10.0, 20.0, 30.0]);
+        let navigation = dir.join("navigation.parquet");
+        write_param_file(&navigation, vec![1, 1, 2, 7, 3], vec![0.0; 5]);
+
+        let report = verify_referential_integrity(&navigation, "id", &waypoints, "id").unwrap();
+        assert_eq!(
+            report,
+            IntegrityReport {
+                total_rows: 5,
+                valid_rows: 4,
+                orphan_values: vec!["7".to_string()],
+            }
+        );
+        assert_eq!(report.orphan_rows(), 1);
+
+        assert!(verify_referential_integrity(&navigation, "waypoint", &waypoints, "id").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compare_parquet_data() {
+        let dir = std::env::temp_dir().join(format!("log_utils_compare_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let expected = dir.join("expected.parquet");
+        write_param_file(&expected, vec![1, 2, 3], vec![1.0, 2.0, 3.0]);
+        let report = compare_parquet_data(&expected, &expected, CompareOptions::new()).unwrap();
+        assert!(report.equal);
+        assert!(report.mismatched_columns.is_empty());
+
+        let actual = dir.join("actual.parquet");
+        write_param_file(&actual, vec![1, 2, 3], vec![1.0, 2.5, 3.001]);
+        let report = compare_parquet_data(&expected, &actual, CompareOptions::new()).unwrap();
+        assert!(!report.equal);
+        assert_eq!(report.row_count_diff, 0);
+        assert_eq!(
+            report.mismatched_columns,
+            vec![ColumnMismatch {
+                column: "value".to_string(),
+                mismatched_rows: 2,
+                first_mismatch_row: Some(1),
+                detail: "expected Some(2.0), got Some(2.5)".to_string(),
+            }]
+        );
+
+        let report = compare_parquet_data(
+            &expected,
+            &actual,
+            CompareOptions::new().with_tolerance(0.01),
+        )
+        .unwrap();
+        assert_eq!(report.mismatched_columns[0].mismatched_rows, 1);
+
+        let ignored = CompareOptions::new().with_ignore_columns(vec!["value".to_string()]);
+        assert!(
+            compare_parquet_data(&expected, &actual, ignored)
+                .unwrap()
+                .equal
+        );
+
+        let shuffled = dir.join("shuffled.parquet");
+        write_param_file(&shuffled, vec![3, 1, 2, 4], vec![3.0, 1.0, 2.0, 4.0]);
+        let report = compare_parquet_data(
+            &expected,
+            &shuffled,
+            CompareOptions::new().with_ignore_row_order(true),
+        )
+        .unwrap();
+        assert!(!report.equal);
+        assert_eq!(report.row_count_diff, 1);
+        assert!(report.mismatched_columns.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_query_by_time_range() {
+        use arrow::array::{TimestampMicrosecondArray, TimestampNanosecondArray};
+        use chrono::TimeZone;
+
+        let dir = std::env::temp_dir().join(format!("log_utils_time_range_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("time_range.parquet");
+
+        // One row per second, five rows per row group
+        let base = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let micros: Vec<i64> = (0..15)
+            .map(|s| base.timestamp_micros() + s * 1_000_000)
+            .collect();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "ts",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                false,
+            ),
+            Field::new(
+                "ts_ns",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("id", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(TimestampMicrosecondArray::from(micros.clone()).with_timezone("UTC")),
+                Arc::new(TimestampNanosecondArray::from(
+                    micros.iter().map(|us| us * 1000).collect::<Vec<_>>(),
+                )),
+                Arc::new(Int64Array::from_iter_values(0..15)),
+            ],
+        )
+        .unwrap();
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(5)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), schema, Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let ids = |batches: Vec<RecordBatch>| -> Vec<i64> {
+            batches
+                .iter()
+                .flat_map(|b| {
+                    batch_column(b, "id")
+                        .unwrap()
+                        .as_primitive::<Int64Type>()
+                        .values()
+                        .to_vec()
+                })
+                .collect()
+        };
+        let start = base + chrono::Duration::seconds(3);
+        let end = base + chrono::Duration::seconds(7);
+        assert_eq!(
+            ids(query_by_time_range(&path, "ts", start, end).unwrap()),
+            vec![3, 4, 5, 6]
+        );
+        assert_eq!(
+            ids(query_by_time_range(&path, "ts_ns", start, end).unwrap()),
+            vec![3, 4, 5, 6]
+        );
+
+        let after = base + chrono::Duration::hours(1);
+        assert!(
+            query_by_time_range(&path, "ts", after, after + chrono::Duration::seconds(1))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(query_by_time_range(&path, "id", start, end).is_err());
+        assert!(query_by_time_range(&path, "missing", start, end).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compute_column_statistics() {
+        let dir = std::env::temp_dir().join(format!("log_utils_stats_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stats.parquet");
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("altitude", DataType::Float64, true),
+            Field::new("mode", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Float64Array::from(vec![
+                    Some(2.0),
+                    Some(4.0),
+                    None,
+                    Some(4.0),
+                    Some(4.0),
+                    Some(5.0),
+                    Some(5.0),
+                    Some(7.0),
+                    Some(9.0),
+                ])),
+                Arc::new(StringArray::from(vec!["GUIDED"; 9])),
+            ],
+        )
+        .unwrap();
+        // Small row groups so the statistics are merged across batches
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(4)
+            .build();
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), schema, Some(props)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let stats = compute_column_statistics(&path, &["altitude", "mode"]).unwrap();
+        assert_eq!(stats.len(), 1);
+        let altitude = stats["altitude"];
+        assert_eq!(altitude.min, 2.0);
+        assert_eq!(altitude.max, 9.0);
+        assert!((altitude.mean - 5.0).abs() < 1e-12);
+        assert!((altitude.std_dev - 2.0).abs() < 1e-12);
+        assert_eq!(altitude.p25, 4.0);
+        assert_eq!(altitude.p50, 4.5);
+        assert!((altitude.p75 - 5.5).abs() < 1e-12);
+        assert!((altitude.p95 - 8.3).abs() < 1e-12);
+        assert_eq!(altitude.null_count, 1);
+        assert_eq!(altitude.row_count, 9);
+
+        // Several files are summarized as one
+        let both = compute_files_column_statistics(&[path.clone(), path.clone()], &[]).unwrap();
+        assert_eq!(both["altitude"].row_count, 18);
+        assert_eq!(both["altitude"].null_count, 2);
+        assert_eq!(both["altitude"].p50, 4.5);
+        assert!((both["altitude"].std_dev - 2.0).abs() < 1e-12);
+
+        // No columns means every numeric column
+        let all = compute_column_statistics(&path, &[]).unwrap();
+        assert_eq!(all.keys().collect::<Vec<_>>(), vec!["altitude"]);
+        assert!(compute_column_statistics(&path, &["missing"]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_column_percentiles_stay_bounded() {
+        // Every value of 0..n once, in a scrambled order
+        let n = 4 * EXACT_PERCENTILE_VALUES;
+        let mut percentiles = RunningPercentiles::new();
+        for i in 0..n {
+            percentiles.push(((i * 7919) % n) as f64);
+        }
+        assert!(percentiles.values.is_empty());
+
+        let estimates = percentiles.finish();
+        for (estimate, q) in estimates.iter().zip(STATS_QUANTILES) {
+            let exact = q * (n - 1) as f64;
+            assert!(
+                (estimate - exact).abs() < 0.005 * n as f64,
+                "p{} estimated {} instead of {}",
+                q * 100.0,
+                estimate,
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_schema() {
+        let dir = std::env::temp_dir().join(format!("log_utils_validate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("values.parquet");
+        write_test_file(&input, vec![1, 2, 3]);
+
+        let report = validate_schema(
+            &input,
+            &Schema::new(vec![Field::new("value", DataType::Int32, true)]),
+        )
+        .unwrap();
+        assert!(report.is_compatible);
+        assert_eq!(
+            report,
+            SchemaValidationReport {
+                is_compatible: true,
+                ..Default::default()
+            }
+        );
+
+        // Extra columns in the file are allowed
+        let report = validate_schema(&input, &Schema::empty()).unwrap();
+        assert!(report.is_compatible);
+        assert_eq!(report.extra_columns, vec!["value".to_string()]);
+
+        let expected = Schema::new(vec![
+            Field::new("value", DataType::Int64, false),
+            Field::new("altitude", DataType::Float64, false),
+        ]);
+        let report = validate_schema(&input, &expected).unwrap();
+        assert!(!report.is_compatible);
+        assert_eq!(report.missing_columns, vec!["altitude".to_string()]);
+        assert_eq!(
+            report.type_mismatches,
+            vec![("value".to_string(), DataType::Int64, DataType::Int32)]
+        );
+        assert!(report.extra_columns.is_empty());
+
+        // The expected schema round trips through its JSON representation
+        let schema_file = dir.join("schema.json");
+        std::fs::write(&schema_file, serde_json::to_string(&expected).unwrap()).unwrap();
+        assert_eq!(read_schema_json(&schema_file).unwrap(), expected);
+        std::fs::write(&schema_file, "{\"fields\": 3}").unwrap();
+        assert!(read_schema_json(&schema_file).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
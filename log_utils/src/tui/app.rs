@@ -1,7 +1,8 @@
 #[cfg(feature = "tui")]
 use std::io;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use arrow::array::RecordBatch;
@@ -15,17 +16,37 @@ use ratatui::{
     layout::{Margin, Rect},
     prelude::*,
     style::{Color, Modifier, Style, Stylize},
+    symbols::Marker,
     text::{Line, Span},
     widgets::{
-        Block, Borders, Cell, List, ListItem, Paragraph, Row, Scrollbar, ScrollbarOrientation,
-        ScrollbarState, Table, Tabs,
+        canvas::{Canvas, Line as CanvasLine},
+        BarChart, Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, Tabs,
     },
     Frame, Terminal,
 };
 
-use crate::parquet_ops;
+use super::filter::FilterExpression;
+use super::stats::{ColumnStatsView, ColumnSummary};
+use crate::parquet_ops::{self, MergeOptions};
 use crate::utils;
 
+/// How long a status message stays on screen
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(2);
+
+/// What key presses are currently interpreted as
+enum InputMode {
+    Normal,
+    /// Typing the output path for exporting the current file
+    ExportMode {
+        file_path_input: String,
+    },
+    /// Typing a `column op value` filter for the record view
+    SearchMode {
+        filter_input: String,
+    },
+}
+
 struct App {
     input_dir: PathBuf,
     parquet_files: Vec<PathBuf>,
@@ -36,6 +57,16 @@ struct App {
     scroll_offset: usize,
     file_browser_scroll: usize,
     max_rows_per_page: usize,
+    plot_column_index: usize,
+    input_mode: InputMode,
+    export_result: Option<mpsc::Receiver<Result<String, String>>>,
+    status_message: Option<(String, Instant)>,
+    filter: Option<FilterExpression>,
+    // Rows of the current batch matching `filter`, `current_row` and `scroll_offset` index into these
+    filtered_rows: Option<Vec<usize>>,
+    stats_column_index: usize,
+    // Stats of `stats_column_index`, computed when the Column Stats tab is entered
+    column_stats: Option<ColumnStatsView>,
 }
 
 impl App {
@@ -52,6 +83,14 @@ impl App {
             scroll_offset: 0,
             file_browser_scroll: 0,
             max_rows_per_page: 20,
+            plot_column_index: 0,
+            input_mode: InputMode::Normal,
+            export_result: None,
+            status_message: None,
+            filter: None,
+            filtered_rows: None,
+            stats_column_index: 0,
+            column_stats: None,
         })
     }
 
@@ -67,13 +106,34 @@ impl App {
             self.current_batch = Some(batches[0].clone());
             self.current_row = 0;
             self.scroll_offset = 0;
+            self.plot_column_index = 0;
         } else {
             self.current_batch = None;
         }
+        self.stats_column_index = 0;
+        self.column_stats = None;
+        self.refresh_filter();
 
         Ok(())
     }
 
+    // Number of rows shown in the record view, after filtering
+    fn row_count(&self) -> usize {
+        match (&self.filtered_rows, &self.current_batch) {
+            (Some(rows), _) => rows.len(),
+            (None, Some(batch)) => batch.num_rows(),
+            (None, None) => 0,
+        }
+    }
+
+    // Index in the current batch of the `position`-th shown row
+    fn batch_row(&self, position: usize) -> usize {
+        match &self.filtered_rows {
+            Some(rows) => rows[position],
+            None => position,
+        }
+    }
+
     fn next_file(&mut self) -> Result<()> {
         if self.parquet_files.is_empty() {
             return Ok(());
@@ -108,14 +168,12 @@ impl App {
     }
 
     fn next_row(&mut self) {
-        if let Some(batch) = &self.current_batch {
-            if self.current_row < batch.num_rows() - 1 {
-                self.current_row += 1;
+        if self.current_row + 1 < self.row_count() {
+            self.current_row += 1;
 
-                // Adjust scroll if needed
-                if self.current_row >= self.scroll_offset + self.max_rows_per_page {
-                    self.scroll_offset = self.current_row - self.max_rows_per_page + 1;
-                }
+            // Adjust scroll if needed
+            if self.current_row >= self.scroll_offset + self.max_rows_per_page {
+                self.scroll_offset = self.current_row - self.max_rows_per_page + 1;
             }
         }
     }
@@ -132,17 +190,19 @@ impl App {
     }
 
     fn next_tab(&mut self) {
-        self.selected_tab = (self.selected_tab + 1) % 3; // We have 3 tabs
+        self.selected_tab = (self.selected_tab + 1) % 5; // We have 5 tabs
 
         // When switching to file browser, ensure selection is visible
         if self.selected_tab == 0 {
             self.ensure_selected_file_visible();
+        } else if self.selected_tab == 4 {
+            self.ensure_column_stats();
         }
     }
 
     fn prev_tab(&mut self) {
         self.selected_tab = if self.selected_tab == 0 {
-            2 // We have 3 tabs
+            4 // We have 5 tabs
         } else {
             self.selected_tab - 1
         };
@@ -150,6 +210,213 @@ impl App {
         // When switching to file browser, ensure selection is visible
         if self.selected_tab == 0 {
             self.ensure_selected_file_visible();
+        } else if self.selected_tab == 4 {
+            self.ensure_column_stats();
+        }
+    }
+
+    // Names of the numeric columns in the current batch, which can be plotted
+    fn numeric_columns(&self) -> Vec<String> {
+        match &self.current_batch {
+            Some(batch) => batch
+                .schema()
+                .fields()
+                .iter()
+                .filter(|f| f.data_type().is_numeric())
+                .map(|f| f.name().to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn next_plot_column(&mut self) {
+        let count = self.numeric_columns().len();
+        if count > 0 {
+            self.plot_column_index = (self.plot_column_index + 1) % count;
+        }
+    }
+
+    fn prev_plot_column(&mut self) {
+        let count = self.numeric_columns().len();
+        if count > 0 {
+            self.plot_column_index = (self.plot_column_index + count - 1) % count;
+        }
+    }
+
+    fn compute_column_stats(&self, column_idx: usize) -> ColumnStatsView {
+        self.current_batch
+            .as_ref()
+            .and_then(|batch| ColumnStatsView::from_batch(batch, column_idx).ok())
+            .unwrap_or_default()
+    }
+
+    // Compute the stats of the selected column unless they are already cached
+    fn ensure_column_stats(&mut self) {
+        if self.column_stats.is_none() {
+            self.column_stats = Some(self.compute_column_stats(self.stats_column_index));
+        }
+    }
+
+    fn next_stats_column(&mut self) {
+        let count = self.current_batch.as_ref().map_or(0, |b| b.num_columns());
+        if count > 0 {
+            self.stats_column_index = (self.stats_column_index + 1) % count;
+            self.column_stats = None;
+            self.ensure_column_stats();
+        }
+    }
+
+    fn prev_stats_column(&mut self) {
+        let count = self.current_batch.as_ref().map_or(0, |b| b.num_columns());
+        if count > 0 {
+            self.stats_column_index = (self.stats_column_index + count - 1) % count;
+            self.column_stats = None;
+            self.ensure_column_stats();
+        }
+    }
+
+    // Default export path: `{original_filename}_export.parquet` next to the original file
+    fn default_export_path(&self) -> Option<PathBuf> {
+        let selected_file = self.parquet_files.get(self.selected_file_index)?;
+        let stem = selected_file.file_stem()?.to_string_lossy();
+        Some(selected_file.with_file_name(format!("{}_export.parquet", stem)))
+    }
+
+    fn start_export_input(&mut self) {
+        if self.export_result.is_some() {
+            self.set_status("An export is already running");
+            return;
+        }
+        if let Some(path) = self.default_export_path() {
+            self.input_mode = InputMode::ExportMode {
+                file_path_input: path.display().to_string(),
+            };
+        }
+    }
+
+    fn handle_export_input(&mut self, code: KeyCode) {
+        let InputMode::ExportMode { file_path_input } = &mut self.input_mode else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => file_path_input.push(c),
+            KeyCode::Backspace => {
+                file_path_input.pop();
+            }
+            KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Enter => {
+                let output = PathBuf::from(file_path_input.trim());
+                self.input_mode = InputMode::Normal;
+                self.start_export(output);
+            }
+            _ => {}
+        }
+    }
+
+    // Export on a background thread so the UI keeps drawing while it runs
+    fn start_export(&mut self, output: PathBuf) {
+        let Some(source) = self.parquet_files.get(self.selected_file_index).cloned() else {
+            return;
+        };
+        let batch = self.current_batch.clone();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = export_current_view(&source, batch.as_ref(), &output)
+                .map_err(|e| format!("Export failed: {}", e));
+            let _ = tx.send(result);
+        });
+        self.export_result = Some(rx);
+        self.set_status("Exporting...");
+    }
+
+    fn poll_export(&mut self) {
+        let result = match &self.export_result {
+            Some(rx) => match rx.try_recv() {
+                Ok(result) => result,
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => Err("Export thread stopped".to_string()),
+            },
+            None => return,
+        };
+        self.export_result = None;
+        match result {
+            Ok(message) | Err(message) => self.set_status(message),
+        }
+    }
+
+    fn start_search_input(&mut self) {
+        let filter_input = self
+            .filter
+            .as_ref()
+            .map(|filter| filter.to_string())
+            .unwrap_or_default();
+        self.input_mode = InputMode::SearchMode { filter_input };
+    }
+
+    fn handle_search_input(&mut self, code: KeyCode) {
+        let InputMode::SearchMode { filter_input } = &mut self.input_mode else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => filter_input.push(c),
+            KeyCode::Backspace => {
+                filter_input.pop();
+            }
+            KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Enter => {
+                let input = filter_input.trim().to_string();
+                self.input_mode = InputMode::Normal;
+                if input.is_empty() {
+                    self.filter = None;
+                    self.refresh_filter();
+                    self.set_status("Filter cleared");
+                    return;
+                }
+                match input.parse::<FilterExpression>() {
+                    Ok(filter) => {
+                        self.filter = Some(filter);
+                        self.refresh_filter();
+                    }
+                    Err(err) => self.set_status(format!("Invalid filter: {}", err)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Re-evaluate the filter on the current batch and go back to the first matching row.
+    // A filter that does not apply to the batch, e.g. a missing column, is cleared.
+    fn refresh_filter(&mut self) {
+        self.current_row = 0;
+        self.scroll_offset = 0;
+        self.filtered_rows = None;
+
+        let (Some(filter), Some(batch)) = (&self.filter, &self.current_batch) else {
+            return;
+        };
+        match filter.matching_rows(batch) {
+            Ok(rows) => {
+                let message = format!("{} of {} rows match", rows.len(), batch.num_rows());
+                self.filtered_rows = Some(rows);
+                self.set_status(message);
+            }
+            Err(err) => {
+                self.filter = None;
+                self.set_status(format!("Filter cleared: {}", err));
+            }
+        }
+    }
+
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.status_message = Some((message.into(), Instant::now()));
+    }
+
+    fn visible_status(&self) -> Option<&str> {
+        match &self.status_message {
+            Some((message, shown_at)) if shown_at.elapsed() < STATUS_MESSAGE_DURATION => {
+                Some(message)
+            }
+            _ => None,
         }
     }
 
@@ -241,6 +508,34 @@ impl App {
     }
 }
 
+/// Copies `source` to `output` as parquet and writes the displayed batch next to it as CSV
+fn export_current_view(
+    source: &Path,
+    batch: Option<&RecordBatch>,
+    output: &Path,
+) -> Result<String> {
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    parquet_ops::merge_parquet_files_to_output(
+        &[source.to_path_buf()],
+        output,
+        &MergeOptions::new(),
+    )?;
+
+    let csv_output = output.with_extension("csv");
+    if let Some(batch) = batch {
+        std::fs::write(&csv_output, utils::format_record_as_table(batch, ',')?)?;
+        Ok(format!(
+            "Exported to {} and {}",
+            output.display(),
+            csv_output.display()
+        ))
+    } else {
+        Ok(format!("Exported to {}", output.display()))
+    }
+}
+
 pub fn run_tui_app(input_dir: PathBuf) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
@@ -281,20 +576,39 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
     }
 
     loop {
+        app.poll_export();
         terminal.draw(|f| ui(f, app))?;
 
         if crossterm::event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                    match app.input_mode {
+                        InputMode::ExportMode { .. } => {
+                            app.handle_export_input(key.code);
+                            continue;
+                        }
+                        InputMode::SearchMode { .. } => {
+                            app.handle_search_input(key.code);
+                            continue;
+                        }
+                        InputMode::Normal => {}
+                    }
+
                     match key.code {
                         KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Char('e') if app.selected_tab == 1 => app.start_export_input(),
+                        KeyCode::Char('/') if app.selected_tab == 1 => app.start_search_input(),
                         KeyCode::Tab => app.next_tab(),
                         KeyCode::BackTab => app.prev_tab(),
+                        KeyCode::Right if app.selected_tab == 4 => app.next_stats_column(),
+                        KeyCode::Left if app.selected_tab == 4 => app.prev_stats_column(),
                         KeyCode::Right => app.next_file()?,
                         KeyCode::Left => app.prev_file()?,
                         KeyCode::Down => {
                             if app.selected_tab == 0 {
                                 app.scroll_file_browser_down();
+                            } else if app.selected_tab == 3 {
+                                app.next_plot_column();
                             } else {
                                 app.next_row();
                             }
@@ -302,6 +616,8 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
                         KeyCode::Up => {
                             if app.selected_tab == 0 {
                                 app.scroll_file_browser_up();
+                            } else if app.selected_tab == 3 {
+                                app.prev_plot_column();
                             } else {
                                 app.prev_row();
                             }
@@ -342,12 +658,10 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
                                 // Approximate scroll to end
                                 app.file_browser_scroll =
                                     app.parquet_files.len().saturating_sub(10);
-                            } else if let Some(batch) = &app.current_batch {
-                                if batch.num_rows() > 0 {
-                                    app.current_row = batch.num_rows() - 1;
-                                    app.scroll_offset =
-                                        app.current_row.saturating_sub(app.max_rows_per_page) + 1;
-                                }
+                            } else if app.row_count() > 0 {
+                                app.current_row = app.row_count() - 1;
+                                app.scroll_offset =
+                                    app.current_row.saturating_sub(app.max_rows_per_page) + 1;
                             }
                         }
                         _ => {}
@@ -366,10 +680,16 @@ fn ui(f: &mut Frame, app: &App) {
         .split(f.area());
 
     // Tabs
-    let titles: Vec<_> = ["File Browser", "Record View", "Help"]
-        .iter()
-        .map(|t| Line::from(*t))
-        .collect();
+    let titles: Vec<_> = [
+        "File Browser",
+        "Record View",
+        "Help",
+        "Plot",
+        "Column Stats",
+    ]
+    .iter()
+    .map(|t| Line::from(*t))
+    .collect();
 
     let tabs = Tabs::new(titles)
         .block(Block::default().borders(Borders::ALL).title("Tabs"))
@@ -383,8 +703,71 @@ fn ui(f: &mut Frame, app: &App) {
         0 => render_file_browser(f, app, chunks[1]),
         1 => render_record_view(f, app, chunks[1]),
         2 => render_help(f, app, chunks[1]),
+        3 => render_plot(f, app, chunks[1]),
+        4 => render_column_stats(f, app, chunks[1]),
         _ => {}
     }
+
+    match &app.input_mode {
+        InputMode::ExportMode { file_path_input } => render_export_input(f, file_path_input),
+        InputMode::SearchMode { filter_input } => render_search_input(f, filter_input),
+        InputMode::Normal => {
+            if let Some(message) = app.visible_status() {
+                render_status_message(f, message);
+            }
+        }
+    }
+}
+
+// Centered popup of the given size, clamped to the frame
+fn popup_area(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn render_export_input(f: &mut Frame, file_path_input: &str) {
+    let area = popup_area(f.area(), 80, 3);
+    let input = Paragraph::new(format!("{}_", file_path_input)).block(
+        Block::default()
+            .title("Export to (Enter to save, Esc to cancel)")
+            .borders(Borders::ALL),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+}
+
+fn render_search_input(f: &mut Frame, filter_input: &str) {
+    let area = popup_area(f.area(), 80, 3);
+    let input = Paragraph::new(format!("/{}_", filter_input)).block(
+        Block::default()
+            .title("Filter, e.g. battery_remaining < 20 (Enter to apply, empty to clear)")
+            .borders(Borders::ALL),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+}
+
+fn render_status_message(f: &mut Frame, message: &str) {
+    let area = f.area();
+    let width = (message.chars().count() as u16 + 4).min(area.width);
+    let area = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(4),
+        width,
+        3,
+    )
+    .intersection(f.area());
+    let status = Paragraph::new(message)
+        .block(Block::default().borders(Borders::ALL))
+        .green();
+    f.render_widget(Clear, area);
+    f.render_widget(status, area);
 }
 
 fn render_file_browser(f: &mut Frame, app: &App, area: Rect) {
@@ -544,6 +927,9 @@ fn render_record_view(f: &mut Frame, app: &App, area: Rect) {
             if let Some(topic) = metadata.get("topic") {
                 header_text.push(format!("Topic: {}", topic));
             }
+            if let Some(filter) = &app.filter {
+                header_text.push(format!("Filter: {} ({} rows)", filter, app.row_count()));
+            }
 
             let header = Paragraph::new(header_text.join(" | "))
                 .block(Block::default().title("Metadata").borders(Borders::ALL))
@@ -564,12 +950,18 @@ fn render_record_view(f: &mut Frame, app: &App, area: Rect) {
 
             let visible_rows = std::cmp::min(
                 app.max_rows_per_page,
-                batch.num_rows().saturating_sub(app.scroll_offset),
+                app.row_count().saturating_sub(app.scroll_offset),
             );
+            // Rows not matching the filter are skipped, the filtered column is highlighted
+            let filter_column = app
+                .filter
+                .as_ref()
+                .and_then(|filter| schema.index_of(&filter.column).ok());
 
             let rows = (0..visible_rows).map(|i| {
-                let row_idx = i + app.scroll_offset;
-                let row_style = if row_idx == app.current_row {
+                let position = i + app.scroll_offset;
+                let row_idx = app.batch_row(position);
+                let row_style = if position == app.current_row {
                     Style::default().bg(Color::DarkGray)
                 } else {
                     Style::default()
@@ -578,7 +970,11 @@ fn render_record_view(f: &mut Frame, app: &App, area: Rect) {
                 let cells = schema.fields().iter().enumerate().map(|(col_idx, _)| {
                     let col = batch.column(col_idx);
                     let value = utils::format_array_value(col, row_idx);
-                    Cell::from(value)
+                    if Some(col_idx) == filter_column {
+                        Cell::from(value).yellow()
+                    } else {
+                        Cell::from(value)
+                    }
                 });
 
                 Row::new(cells).style(row_style)
@@ -620,11 +1016,13 @@ fn render_help(f: &mut Frame, _app: &App, area: Rect) {
         "q          - Quit",
         "Tab        - Next tab",
         "Shift+Tab  - Previous tab",
-        "←/→        - Previous/Next file",
-        "↑/↓        - Navigate rows/files",
+        "←/→        - Previous/Next file (column in Column Stats tab)",
+        "↑/↓        - Navigate rows/files (plot column in Plot tab)",
         "Page Up/Dn - Scroll 10 items at a time",
         "Home       - Go to beginning",
         "End        - Go to end",
+        "e          - Export the current file to parquet and CSV (Record View)",
+        "/          - Filter rows by `column op value`, e.g. battery_remaining < 20 (Record View)",
     ];
 
     let paragraph = Paragraph::new(help_text.join("\n"))
@@ -633,3 +1031,162 @@ fn render_help(f: &mut Frame, _app: &App, area: Rect) {
 
     f.render_widget(paragraph, area);
 }
+
+fn render_plot(f: &mut Frame, app: &App, area: Rect) {
+    let columns = app.numeric_columns();
+    let (batch, column) = match (&app.current_batch, columns.get(app.plot_column_index)) {
+        (Some(batch), Some(column)) => (batch, column),
+        _ => {
+            let paragraph = Paragraph::new("No numeric columns to plot")
+                .block(Block::default().title("Plot").borders(Borders::ALL))
+                .red();
+            f.render_widget(paragraph, area);
+            return;
+        }
+    };
+
+    let points: Vec<(f64, f64)> = match utils::get_numeric_column_values(batch, column) {
+        Ok(values) => values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.filter(|v| v.is_finite()).map(|v| (i as f64, v)))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let min = points.iter().map(|(_, y)| *y).fold(f64::INFINITY, f64::min);
+    let max = points
+        .iter()
+        .map(|(_, y)| *y)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let (min, max) = if points.is_empty() {
+        (0.0, 1.0)
+    } else if min == max {
+        (min - 0.5, max + 0.5)
+    } else {
+        (min, max)
+    };
+    let x_max = batch.num_rows().saturating_sub(1).max(1) as f64;
+
+    let title = format!(
+        "Plot: {} ({}/{}) | min: {:.3} max: {:.3} rows: {}",
+        column,
+        app.plot_column_index + 1,
+        columns.len(),
+        min,
+        max,
+        batch.num_rows()
+    );
+
+    let canvas = Canvas::default()
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .marker(Marker::Braille)
+        .x_bounds([0.0, x_max])
+        .y_bounds([min, max])
+        .paint(|ctx| {
+            for pair in points.windows(2) {
+                ctx.draw(&CanvasLine {
+                    x1: pair[0].0,
+                    y1: pair[0].1,
+                    x2: pair[1].0,
+                    y2: pair[1].1,
+                    color: Color::Yellow,
+                });
+            }
+        });
+
+    f.render_widget(canvas, area);
+}
+
+fn render_column_stats(f: &mut Frame, app: &App, area: Rect) {
+    let stats = match &app.column_stats {
+        Some(stats) if !stats.column.is_empty() => stats,
+        _ => {
+            let paragraph = Paragraph::new("No column to show")
+                .block(Block::default().title("Column Stats").borders(Borders::ALL))
+                .red();
+            f.render_widget(paragraph, area);
+            return;
+        }
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(0)])
+        .split(area);
+
+    let column_count = app.current_batch.as_ref().map_or(0, |b| b.num_columns());
+    let data_type = stats
+        .data_type
+        .as_ref()
+        .map_or("unknown".to_string(), |t| t.to_string());
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(&stats.column, Style::default().fg(Color::Yellow)),
+            Span::raw(format!(
+                " ({}/{}) | {}",
+                app.stats_column_index + 1,
+                column_count,
+                data_type
+            )),
+        ]),
+        Line::from(format!(
+            "rows: {} nulls: {}",
+            stats.row_count, stats.null_count
+        )),
+    ];
+    if let ColumnSummary::Numeric { min, max, mean, .. } = &stats.summary {
+        lines.push(Line::from(format!(
+            "min: {:.3} max: {:.3} mean: {:.3}",
+            min, max, mean
+        )));
+    }
+    let summary =
+        Paragraph::new(lines).block(Block::default().title("Column Stats").borders(Borders::ALL));
+    f.render_widget(summary, chunks[0]);
+
+    match &stats.summary {
+        ColumnSummary::Numeric { histogram, .. } => {
+            let labels: Vec<String> = stats
+                .bucket_starts()
+                .iter()
+                .map(|start| format!("{:.2}", start))
+                .collect();
+            let data: Vec<(&str, u64)> = labels
+                .iter()
+                .map(|label| label.as_str())
+                .zip(histogram.iter().copied())
+                .collect();
+            // Spread the buckets over the width, keeping a 1 column gap
+            let bar_width = (chunks[1].width.saturating_sub(2) / histogram.len().max(1) as u16)
+                .saturating_sub(1)
+                .max(1);
+
+            let chart = BarChart::default()
+                .block(Block::default().title("Histogram").borders(Borders::ALL))
+                .data(&data)
+                .bar_width(bar_width)
+                .bar_gap(1)
+                .bar_style(Style::default().fg(Color::Yellow))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+            f.render_widget(chart, chunks[1]);
+        }
+        ColumnSummary::Strings { value_counts } => {
+            let items: Vec<ListItem> = value_counts
+                .iter()
+                .map(|(value, count)| ListItem::new(format!("{:>8}  {}", count, value)))
+                .collect();
+            let list = List::new(items).block(
+                Block::default()
+                    .title(format!("Unique values ({})", value_counts.len()))
+                    .borders(Borders::ALL),
+            );
+            f.render_widget(list, chunks[1]);
+        }
+        ColumnSummary::Unsupported => {
+            let paragraph = Paragraph::new(format!("No summary for {} columns", data_type))
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(paragraph, chunks[1]);
+        }
+    }
+}
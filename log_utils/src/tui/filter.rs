@@ -0,0 +1,261 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Result;
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::datatypes::DataType;
+
+use crate::utils;
+
+/// Comparison of a `FilterExpression`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Contains,
+}
+
+impl FilterOp {
+    // Longer operators first so `<=` is not parsed as `<`
+    const ALL: [FilterOp; 7] = [
+        FilterOp::Eq,
+        FilterOp::Ne,
+        FilterOp::Le,
+        FilterOp::Ge,
+        FilterOp::Lt,
+        FilterOp::Gt,
+        FilterOp::Contains,
+    ];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            FilterOp::Eq => "==",
+            FilterOp::Ne => "!=",
+            FilterOp::Lt => "<",
+            FilterOp::Gt => ">",
+            FilterOp::Le => "<=",
+            FilterOp::Ge => ">=",
+            FilterOp::Contains => "contains",
+        }
+    }
+
+    fn compare_numbers(&self, value: f64, target: f64) -> bool {
+        match self {
+            FilterOp::Eq => value == target,
+            FilterOp::Ne => value != target,
+            FilterOp::Lt => value < target,
+            FilterOp::Gt => value > target,
+            FilterOp::Le => value <= target,
+            FilterOp::Ge => value >= target,
+            FilterOp::Contains => false,
+        }
+    }
+
+    fn compare_strings(&self, value: &str, target: &str) -> bool {
+        match self {
+            FilterOp::Eq => value == target,
+            FilterOp::Ne => value != target,
+            FilterOp::Contains => value.contains(target),
+            _ => false,
+        }
+    }
+}
+
+/// A `column op value` filter on the rows of a record batch, e.g. `battery_remaining < 20`.
+/// Numeric columns support `==`, `!=`, `<`, `>`, `<=` and `>=`,
+/// string columns support `==`, `!=` and `contains`. Null values never match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterExpression {
+    pub column: String,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+impl FromStr for FilterExpression {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let column_end = input
+            .find(|c: char| c.is_whitespace() || "=!<>".contains(c))
+            .unwrap_or(input.len());
+        let (column, rest) = input.split_at(column_end);
+        if column.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Expected `column op value`, got '{}'",
+                input
+            ));
+        }
+
+        let rest = rest.trim_start();
+        let op = FilterOp::ALL
+            .into_iter()
+            .find(|op| rest.starts_with(op.symbol()))
+            .ok_or_else(|| anyhow::anyhow!("Unknown operator in '{}'", input))?;
+
+        let value = rest[op.symbol().len()..].trim();
+        // Strings are displayed quoted, so accept them quoted too
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        if value.is_empty() {
+            return Err(anyhow::anyhow!("Missing value in '{}'", input));
+        }
+        if !matches!(op, FilterOp::Eq | FilterOp::Ne | FilterOp::Contains)
+            && value.parse::<f64>().is_err()
+        {
+            return Err(anyhow::anyhow!(
+                "'{}' needs a numeric value, got '{}'",
+                op.symbol(),
+                value
+            ));
+        }
+
+        Ok(Self {
+            column: column.to_string(),
+            op,
+            value: value.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for FilterExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.column, self.op.symbol(), self.value)
+    }
+}
+
+impl FilterExpression {
+    /// Indices of the rows of `batch` matching the expression
+    pub fn matching_rows(&self, batch: &RecordBatch) -> Result<Vec<usize>> {
+        let array = batch
+            .column_by_name(&self.column)
+            .ok_or_else(|| anyhow::anyhow!("Column not found: {}", self.column))?;
+
+        match array.data_type() {
+            data_type if data_type.is_numeric() => {
+                if self.op == FilterOp::Contains {
+                    return Err(anyhow::anyhow!(
+                        "'contains' only works on string columns, {} is {}",
+                        self.column,
+                        data_type
+                    ));
+                }
+                let target: f64 = self.value.parse().map_err(|_| {
+                    anyhow::anyhow!("Column {} is numeric, got '{}'", self.column, self.value)
+                })?;
+                let values = utils::get_numeric_column_values(batch, &self.column)?;
+                Ok(values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| v.is_some_and(|v| self.op.compare_numbers(v, target)))
+                    .map(|(i, _)| i)
+                    .collect())
+            }
+            DataType::Utf8 | DataType::LargeUtf8 => {
+                if !matches!(self.op, FilterOp::Eq | FilterOp::Ne | FilterOp::Contains) {
+                    return Err(anyhow::anyhow!(
+                        "'{}' only works on numeric columns, {} is a string",
+                        self.op.symbol(),
+                        self.column
+                    ));
+                }
+                let strings = arrow::compute::cast(array, &DataType::Utf8)?;
+                Ok(strings
+                    .as_string::<i32>()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, v)| v.is_some_and(|v| self.op.compare_strings(v, &self.value)))
+                    .map(|(i, _)| i)
+                    .collect())
+            }
+            data_type => Err(anyhow::anyhow!(
+                "Column {} is {}, only numeric and string columns can be filtered",
+                self.column,
+                data_type
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, StringArray};
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    fn test_batch() -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("battery_remaining", DataType::Float64, true),
+            Field::new("mode", DataType::Utf8, true),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Float64Array::from(vec![
+                    Some(80.0),
+                    Some(15.0),
+                    None,
+                    Some(20.0),
+                ])),
+                Arc::new(StringArray::from(vec![
+                    Some("GUIDED"),
+                    Some("RTL"),
+                    Some("LAND"),
+                    None,
+                ])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_filter_expression() {
+        let filter: FilterExpression = "battery_remaining < 20".parse().unwrap();
+        assert_eq!(filter.column, "battery_remaining");
+        assert_eq!(filter.op, FilterOp::Lt);
+        assert_eq!(filter.value, "20");
+
+        let filter: FilterExpression = "battery_remaining<=20".parse().unwrap();
+        assert_eq!(filter.op, FilterOp::Le);
+        assert_eq!(filter.to_string(), "battery_remaining <= 20");
+
+        let filter: FilterExpression = "mode contains \"GUID\"".parse().unwrap();
+        assert_eq!(filter.op, FilterOp::Contains);
+        assert_eq!(filter.value, "GUID");
+
+        assert!("battery_remaining".parse::<FilterExpression>().is_err());
+        assert!("battery_remaining ~ 20"
+            .parse::<FilterExpression>()
+            .is_err());
+        assert!("battery_remaining >".parse::<FilterExpression>().is_err());
+        assert!("mode > RTL".parse::<FilterExpression>().is_err());
+    }
+
+    #[test]
+    fn test_matching_rows() {
+        let batch = test_batch();
+        let rows = |expr: &str| {
+            expr.parse::<FilterExpression>()
+                .unwrap()
+                .matching_rows(&batch)
+        };
+
+        assert_eq!(rows("battery_remaining < 20").unwrap(), vec![1]);
+        assert_eq!(rows("battery_remaining >= 20").unwrap(), vec![0, 3]);
+        assert_eq!(rows("battery_remaining != 80").unwrap(), vec![1, 3]);
+        assert_eq!(rows("mode == RTL").unwrap(), vec![1]);
+        assert_eq!(rows("mode != RTL").unwrap(), vec![0, 2]);
+        assert_eq!(rows("mode contains D").unwrap(), vec![0, 2]);
+
+        assert!(rows("battery_remaining contains 2").is_err());
+        assert!(rows("battery_remaining == high").is_err());
+        assert!(rows("altitude > 10").is_err());
+    }
+}
@@ -1,5 +1,9 @@
 #[cfg(feature = "tui")]
 mod app;
+#[cfg(feature = "tui")]
+mod filter;
+#[cfg(feature = "tui")]
+mod stats;
 
 #[cfg(feature = "tui")]
 pub use app::run_tui_app;
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use arrow::array::{Array, AsArray, RecordBatch};
+use arrow::datatypes::DataType;
+
+use crate::utils;
+
+/// Number of buckets of the histogram of a numeric column
+pub const HISTOGRAM_BUCKETS: usize = 10;
+
+/// Summary of the values of a column, depending on its type
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ColumnSummary {
+    /// Non-finite values (NaN, ±inf) are left out of min, max, mean and the histogram
+    Numeric {
+        min: f64,
+        max: f64,
+        mean: f64,
+        /// `HISTOGRAM_BUCKETS` equal width buckets from `min` to `max`
+        histogram: Vec<u64>,
+    },
+    /// Occurrences of each distinct value, most frequent first
+    Strings { value_counts: Vec<(String, usize)> },
+    /// Column type without a summary, or no column selected
+    #[default]
+    Unsupported,
+}
+
+/// Statistics of one column of a record batch, shown in the Column Stats tab
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColumnStatsView {
+    pub column: String,
+    pub data_type: Option<DataType>,
+    pub row_count: usize,
+    pub null_count: usize,
+    pub summary: ColumnSummary,
+}
+
+impl ColumnStatsView {
+    /// Compute the statistics of column `column_idx` of `batch`
+    pub fn from_batch(batch: &RecordBatch, column_idx: usize) -> Result<Self> {
+        let schema = batch.schema();
+        let field = schema
+            .fields()
+            .get(column_idx)
+            .ok_or_else(|| anyhow::anyhow!("No column at index {}", column_idx))?;
+        let array = batch.column(column_idx);
+
+        let summary = match array.data_type() {
+            data_type if data_type.is_numeric() => {
+                let values = utils::get_numeric_column_values(batch, field.name())?;
+                numeric_summary(values.into_iter().flatten().filter(|v| v.is_finite()))
+            }
+            DataType::Utf8 | DataType::LargeUtf8 => {
+                let strings = arrow::compute::cast(array, &DataType::Utf8)?;
+                let mut counts: HashMap<&str, usize> = HashMap::new();
+                for value in strings.as_string::<i32>().iter().flatten() {
+                    *counts.entry(value).or_default() += 1;
+                }
+                let mut value_counts: Vec<(String, usize)> = counts
+                    .into_iter()
+                    .map(|(value, count)| (value.to_string(), count))
+                    .collect();
+                value_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                ColumnSummary::Strings { value_counts }
+            }
+            _ => ColumnSummary::Unsupported,
+        };
+
+        Ok(Self {
+            column: field.name().to_string(),
+            data_type: Some(field.data_type().clone()),
+            row_count: array.len(),
+            null_count: array.null_count(),
+            summary,
+        })
+    }
+
+    /// Lower bound of each histogram bucket, empty for non-numeric columns
+    pub fn bucket_starts(&self) -> Vec<f64> {
+        match &self.summary {
+            ColumnSummary::Numeric { min, max, .. } => {
+                let width = (max - min) / HISTOGRAM_BUCKETS as f64;
+                (0..HISTOGRAM_BUCKETS)
+                    .map(|i| min + width * i as f64)
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn numeric_summary(values: impl Iterator<Item = f64>) -> ColumnSummary {
+    let values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return ColumnSummary::Numeric {
+            min: 0.0,
+            max: 0.0,
+            mean: 0.0,
+            histogram: vec![0; HISTOGRAM_BUCKETS],
+        };
+    }
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+    let mut histogram = vec![0; HISTOGRAM_BUCKETS];
+    let width = (max - min) / HISTOGRAM_BUCKETS as f64;
+    for value in &values {
+        // A constant column lands in the first bucket, the maximum in the last one
+        let bucket = if width > 0.0 {
+            (((value - min) / width) as usize).min(HISTOGRAM_BUCKETS - 1)
+        } else {
+            0
+        };
+        histogram[bucket] += 1;
+    }
+
+    ColumnSummary::Numeric {
+        min,
+        max,
+        mean,
+        histogram,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{BooleanArray, Float64Array, StringArray};
+    use arrow::datatypes::{Field, Schema};
+    use std::sync::Arc;
+
+    fn test_batch() -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("altitude", DataType::Float64, true),
+            Field::new("mode", DataType::Utf8, true),
+            Field::new("armed", DataType::Boolean, true),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Float64Array::from(vec![
+                    Some(0.0),
+                    Some(5.0),
+                    None,
+                    Some(10.0),
+                    Some(f64::NAN),
+                    Some(1.0),
+                ])),
+                Arc::new(StringArray::from(vec![
+                    Some("GUIDED"),
+                    Some("RTL"),
+                    Some("GUIDED"),
+                    None,
+                    Some("LAND"),
+                    Some("RTL"),
+                ])),
+                Arc::new(BooleanArray::from(vec![true; 6])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_numeric_column_stats() {
+        let stats = ColumnStatsView::from_batch(&test_batch(), 0).unwrap();
+        assert_eq!(stats.column, "altitude");
+        assert_eq!(stats.row_count, 6);
+        assert_eq!(stats.null_count, 1);
+
+        let ColumnSummary::Numeric {
+            min,
+            max,
+            mean,
+            histogram,
+        } = &stats.summary
+        else {
+            panic!("Expected a numeric summary, got {:?}", stats.summary);
+        };
+        assert_eq!((*min, *max, *mean), (0.0, 10.0, 4.0));
+        assert_eq!(histogram, &vec![1, 1, 0, 0, 0, 1, 0, 0, 0, 1]);
+        assert_eq!(stats.bucket_starts()[5], 5.0);
+    }
+
+    #[test]
+    fn test_string_column_stats() {
+        let batch = test_batch();
+        let stats = ColumnStatsView::from_batch(&batch, 1).unwrap();
+        assert_eq!(stats.null_count, 1);
+        assert_eq!(
+            stats.summary,
+            ColumnSummary::Strings {
+                value_counts: vec![
+                    ("GUIDED".to_string(), 2),
+                    ("RTL".to_string(), 2),
+                    ("LAND".to_string(), 1),
+                ]
+            }
+        );
+        assert!(stats.bucket_starts().is_empty());
+
+        let stats = ColumnStatsView::from_batch(&batch, 2).unwrap();
+        assert_eq!(stats.summary, ColumnSummary::Unsupported);
+        assert!(ColumnStatsView::from_batch(&batch, 3).is_err());
+    }
+}
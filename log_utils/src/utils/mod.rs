@@ -1,6 +1,7 @@
 use anyhow::Result;
-use arrow::array::{ArrayRef, AsArray, RecordBatch};
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch};
 use arrow::datatypes::{DataType, Schema};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
 use colored::{ColoredString, Colorize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -105,6 +106,26 @@ pub fn format_array_value(array: &ArrayRef, row_index: usize) -> String {
 
             format!("{{{}}}", result)
         }
+        DataType::Map(_, _) => {
+            // Each map row is a slice of key/value entries
+            let map_array = array.as_map();
+            let entries = map_array.value(row_index);
+            let keys = entries.column(0);
+            let values = entries.column(1);
+
+            let result = (0..entries.len())
+                .map(|i| {
+                    format!(
+                        "{}: {}",
+                        format_array_value(keys, i),
+                        format_array_value(values, i)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            format!("{{{}}}", result)
+        }
         DataType::Timestamp(time_unit, _) => match time_unit {
             arrow::datatypes::TimeUnit::Second => {
                 let arr = array.as_primitive::<arrow::datatypes::TimestampSecondType>();
@@ -305,6 +326,45 @@ pub fn pretty_print_batch(
     Ok(result)
 }
 
+/// Formats a record batch as delimited text with a header row, e.g. CSV with `','`.
+/// Null cells are empty and cells containing the separator, quotes or newlines are quoted.
+pub fn format_record_as_table(batch: &RecordBatch, separator: char) -> Result<String> {
+    let quote = |value: &str| {
+        if value.contains(separator) || value.contains('"') || value.contains('\n') {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    };
+    let separator = separator.to_string();
+
+    let mut result = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| quote(field.name()))
+        .collect::<Vec<_>>()
+        .join(&separator);
+    result.push('\n');
+
+    let options = FormatOptions::default().with_null("");
+    let formatters = batch
+        .columns()
+        .iter()
+        .map(|column| ArrayFormatter::try_new(column.as_ref(), &options))
+        .collect::<Result<Vec<_>, _>>()?;
+    for row in 0..batch.num_rows() {
+        let cells: Vec<String> = formatters
+            .iter()
+            .map(|formatter| quote(&formatter.value(row).to_string()))
+            .collect();
+        result.push_str(&cells.join(&separator));
+        result.push('\n');
+    }
+
+    Ok(result)
+}
+
 /// Gets the topic from record batch metadata
 pub fn get_topic(batch: &RecordBatch) -> Option<String> {
     batch.schema().metadata().get("topic").cloned()
@@ -323,3 +383,273 @@ pub fn format_topic(topic: &str, use_color: bool) -> String {
         format!("[{}]", topic)
     }
 }
+
+/// Reads a numeric column as f64 values, keeping nulls so row indices are preserved
+pub fn get_numeric_column_values(batch: &RecordBatch, column: &str) -> Result<Vec<Option<f64>>> {
+    let array = batch
+        .column_by_name(column)
+        .ok_or_else(|| anyhow::anyhow!("Column not found: {}", column))?;
+
+    if !array.data_type().is_numeric() {
+        return Err(anyhow::anyhow!(
+            "Column {} is not numeric ({})",
+            column,
+            array.data_type()
+        ));
+    }
+
+    let casted = arrow::compute::cast(array, &DataType::Float64)?;
+    let values = casted.as_primitive::<arrow::datatypes::Float64Type>();
+    Ok(values.iter().collect())
+}
+
+/// Computes the one-sided amplitude spectrum of evenly spaced samples.
+/// A Hann window is applied first. Returns `(frequencies_hz, magnitudes)` up to the Nyquist frequency.
+#[cfg(feature = "fft")]
+pub fn compute_fft(samples: &[f64], sample_rate_hz: f64) -> Result<(Vec<f64>, Vec<f64>)> {
+    use rustfft::{num_complex::Complex, FftPlanner};
+
+    let n = samples.len();
+    if n < 2 {
+        return Err(anyhow::anyhow!(
+            "At least two samples are needed for an FFT, got {}",
+            n
+        ));
+    }
+
+    let window: Vec<f64> = (0..n)
+        .map(|i| 0.5 * (1.0 - (2.0 * std::f64::consts::PI * i as f64 / (n - 1) as f64).cos()))
+        .collect();
+    let window_sum: f64 = window.iter().sum();
+    let mut buffer: Vec<Complex<f64>> = samples
+        .iter()
+        .zip(&window)
+        .map(|(sample, w)| Complex::new(sample * w, 0.0))
+        .collect();
+
+    FftPlanner::new().plan_fft_forward(n).process(&mut buffer);
+
+    let bins = n / 2 + 1;
+    let frequencies = (0..bins)
+        .map(|k| k as f64 * sample_rate_hz / n as f64)
+        .collect();
+    let magnitudes = buffer[..bins]
+        .iter()
+        .enumerate()
+        .map(|(k, value)| {
+            // DC and Nyquist bins have no mirrored counterpart
+            let one_sided = if k == 0 || (n.is_multiple_of(2) && k == n / 2) {
+                1.0
+            } else {
+                2.0
+            };
+            value.norm() * one_sided / window_sum
+        })
+        .collect();
+
+    Ok((frequencies, magnitudes))
+}
+
+/// Renders an ASCII line chart of a numeric column over row index.
+/// The output is `height` chart lines followed by an x-axis and its labels,
+/// each chart line being a y-axis label, a `|` separator and `width` plot characters.
+pub fn plot_ascii_timeseries(
+    batch: &RecordBatch,
+    column: &str,
+    height: usize,
+    width: usize,
+    use_color: bool,
+) -> Result<String> {
+    if height < 2 || width < 2 {
+        return Err(anyhow::anyhow!("Plot must be at least 2x2 characters"));
+    }
+
+    let values = get_numeric_column_values(batch, column)?;
+    let row_count = values.len();
+    let points: Vec<(usize, f64)> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| v.filter(|v| v.is_finite()).map(|v| (i, v)))
+        .collect();
+
+    let min = points.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+    let max = points
+        .iter()
+        .map(|(_, v)| *v)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let (min, max) = if points.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (min, max)
+    };
+
+    // Map each point onto the character grid (row 0 is the top of the chart)
+    let to_cell = |(index, value): (usize, f64)| -> (usize, usize) {
+        let x = if row_count > 1 {
+            index * (width - 1) / (row_count - 1)
+        } else {
+            0
+        };
+        let y_norm = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        };
+        let y = (height - 1) - (y_norm * (height - 1) as f64).round() as usize;
+        (x, y)
+    };
+
+    let mut grid = vec![vec![' '; width]; height];
+    let cells: Vec<(usize, usize)> = points.iter().map(|p| to_cell(*p)).collect();
+
+    // Connect consecutive points, stepping along the longer axis
+    for pair in cells.windows(2) {
+        let (x0, y0) = (pair[0].0 as i64, pair[0].1 as i64);
+        let (x1, y1) = (pair[1].0 as i64, pair[1].1 as i64);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let steps = dx.abs().max(dy.abs());
+        let connector = if dy.abs() > dx.abs() { '|' } else { '-' };
+
+        for step in 1..steps {
+            let x = x0 + dx * step / steps;
+            let y = y0 + dy * step / steps;
+            grid[y as usize][x as usize] = connector;
+        }
+    }
+
+    for (x, y) in &cells {
+        grid[*y][*x] = '*';
+    }
+
+    // Y-axis labels: max on the top line, min on the bottom line
+    let max_label = format!("{:.3}", max);
+    let min_label = format!("{:.3}", min);
+    let label_width = max_label.len().max(min_label.len());
+
+    let mut result = String::new();
+    for (row_idx, row) in grid.iter().enumerate() {
+        let label = if row_idx == 0 {
+            max_label.clone()
+        } else if row_idx == height - 1 {
+            min_label.clone()
+        } else {
+            String::new()
+        };
+        let label = format!("{:>width$}", label, width = label_width);
+
+        let line: String = row.iter().collect();
+        if use_color {
+            result.push_str(&format!(
+                "{}|{}\n",
+                label.bright_blue(),
+                line.replace('*', &"*".yellow().to_string())
+            ));
+        } else {
+            result.push_str(&format!("{}|{}\n", label, line));
+        }
+    }
+
+    // X-axis with row count
+    result.push_str(&format!(
+        "{}+{}\n",
+        " ".repeat(label_width),
+        "-".repeat(width)
+    ));
+    let end_label = format!("{} rows", row_count);
+    let padding = width.saturating_sub(1 + end_label.len());
+    result.push_str(&format!(
+        "{}0{}{}\n",
+        " ".repeat(label_width + 1),
+        " ".repeat(padding),
+        end_label
+    ));
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Float64Array;
+    use arrow::datatypes::Field;
+
+    #[test]
+    fn test_format_record_as_table() {
+        use arrow::array::StringArray;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("roll", DataType::Float64, true),
+            Field::new("label", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Float64Array::from(vec![Some(0.5), None])),
+                Arc::new(StringArray::from(vec!["level", "bank, \"left\""])),
+            ],
+        )
+        .unwrap();
+
+        let table = format_record_as_table(&batch, ',').unwrap();
+        assert_eq!(table, "roll,label\n0.5,level\n,\"bank, \"\"left\"\"\"\n");
+    }
+
+    #[test]
+    fn test_plot_ascii_timeseries_dimensions() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "value",
+            DataType::Float64,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Float64Array::from(vec![0.0, 1.0, 0.0, -1.0]))],
+        )
+        .unwrap();
+
+        let plot = plot_ascii_timeseries(&batch, "value", 5, 20, false).unwrap();
+        let lines: Vec<&str> = plot.lines().collect();
+
+        // Chart lines plus the x-axis and its labels
+        assert_eq!(lines.len(), 5 + 2);
+        for line in &lines[..5] {
+            let (_, plot_area) = line.split_once('|').unwrap();
+            assert_eq!(plot_area.chars().count(), 20);
+        }
+        assert!(lines[0].trim_start().starts_with("1.000"));
+        assert!(lines[4].trim_start().starts_with("-1.000"));
+        assert_eq!(plot.matches('*').count(), 4);
+    }
+
+    #[test]
+    fn test_format_map_value() {
+        use arrow::array::{Int32Builder, MapBuilder, StringBuilder};
+
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Int32Builder::new());
+        builder.keys().append_value("a");
+        builder.values().append_value(1);
+        builder.keys().append_value("b");
+        builder.values().append_value(2);
+        builder.append(true).unwrap();
+
+        let array: ArrayRef = Arc::new(builder.finish());
+        assert_eq!(format_array_value(&array, 0), "{\"a\": 1, \"b\": 2}");
+    }
+
+    #[cfg(feature = "fft")]
+    #[test]
+    fn test_compute_fft_peak() {
+        let sample_rate = 100.0;
+        let samples: Vec<f64> = (0..200)
+            .map(|i| (2.0 * std::f64::consts::PI * 10.0 * i as f64 / sample_rate).sin())
+            .collect();
+
+        let (frequencies, magnitudes) = compute_fft(&samples, sample_rate).unwrap();
+        assert_eq!(frequencies.len(), 101);
+
+        let peak_bin = (0..magnitudes.len())
+            .max_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b]))
+            .unwrap();
+        assert!((frequencies[peak_bin] - 10.0).abs() <= 0.5);
+    }
+}
@@ -0,0 +1,329 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaResult, Ticket,
+};
+use futures::stream::{self, BoxStream};
+use futures::{Stream, StreamExt, TryStreamExt};
+use tokio::sync::oneshot;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::tasks::state::RunnerState;
+
+type FlightStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
+
+/// Serves the topics of a `RunnerState` over Arrow Flight.
+/// Each topic is one flight, fetched with a ticket holding the topic name.
+pub struct FlightServer {
+    state: Arc<Mutex<RunnerState>>,
+}
+
+impl FlightServer {
+    pub fn new(state: Arc<Mutex<RunnerState>>) -> Self {
+        Self { state }
+    }
+
+    fn topic_flight_info(&self, topic: &str) -> Result<FlightInfo, Status> {
+        let state = self.state.lock().unwrap();
+        let record = state
+            .get_topic_record(topic)
+            .ok_or_else(|| Status::not_found(format!("Topic not found: {}", topic)))?;
+        let batch = record.to_record_batch();
+
+        let info = FlightInfo::new()
+            .try_with_schema(batch.schema().as_ref())
+            .map_err(|e| Status::internal(e.to_string()))?
+            .with_descriptor(FlightDescriptor::new_path(vec![topic.to_string()]))
+            .with_endpoint(FlightEndpoint::new().with_ticket(Ticket::new(topic.to_string())))
+            .with_total_records(batch.num_rows() as i64);
+        Ok(info)
+    }
+}
+
+/// Topic named by a path descriptor, e.g. from `Record::to_arrow_flight_descriptor`
+fn descriptor_topic(descriptor: &FlightDescriptor) -> Result<String, Status> {
+    if descriptor.path.is_empty() {
+        return Err(Status::invalid_argument("Expected a path descriptor"));
+    }
+    Ok(descriptor.path.join("/"))
+}
+
+#[tonic::async_trait]
+impl FlightService for FlightServer {
+    type HandshakeStream = FlightStream<HandshakeResponse>;
+    type ListFlightsStream = FlightStream<FlightInfo>;
+    type DoGetStream = FlightStream<FlightData>;
+    type DoPutStream = FlightStream<PutResult>;
+    type DoActionStream = FlightStream<arrow_flight::Result>;
+    type ListActionsStream = FlightStream<ActionType>;
+    type DoExchangeStream = FlightStream<FlightData>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("handshake is not supported"))
+    }
+
+    /// One flight per topic, in sorted order
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        let mut topics = self.state.lock().unwrap().get_topics();
+        topics.sort();
+
+        let infos = topics
+            .iter()
+            .map(|topic| self.topic_flight_info(topic))
+            .collect::<Result<Vec<_>, Status>>()?;
+        let stream: BoxStream<'static, Result<FlightInfo, Status>> =
+            stream::iter(infos.into_iter().map(Ok)).boxed();
+        Ok(Response::new(stream))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let topic = descriptor_topic(request.get_ref())?;
+        Ok(Response::new(self.topic_flight_info(&topic)?))
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<PollInfo>, Status> {
+        Err(Status::unimplemented("poll_flight_info is not supported"))
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        let topic = descriptor_topic(request.get_ref())?;
+        let info = self.topic_flight_info(&topic)?;
+        Ok(Response::new(SchemaResult {
+            schema: info.schema,
+        }))
+    }
+
+    /// Stream all rows of the topic named in the ticket
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let topic = String::from_utf8(request.into_inner().ticket.to_vec())
+            .map_err(|_| Status::invalid_argument("Ticket is not a valid topic name"))?;
+        let batch = {
+            let state = self.state.lock().unwrap();
+            state
+                .get_topic_record(&topic)
+                .ok_or_else(|| Status::not_found(format!("Topic not found: {}", topic)))?
+                .to_record_batch_cloned()
+        };
+
+        let stream = FlightDataEncoderBuilder::new()
+            .build(stream::iter(vec![Ok(batch)]))
+            .map_err(Status::from)
+            .boxed();
+        Ok(Response::new(stream))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("do_put is not supported"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("do_action is not supported"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Ok(Response::new(stream::empty().boxed()))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("do_exchange is not supported"))
+    }
+}
+
+struct FlightServerInner {
+    addr: SocketAddr,
+    shutdown_tx: Mutex<Option<oneshot::Sender<()>>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FlightServerInner {
+    fn shutdown(&self) {
+        if let Some(tx) = self.shutdown_tx.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+        if let Some(thread) = self.thread.lock().unwrap().take() {
+            if thread.join().is_err() {
+                log::error!("Flight server thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for FlightServerInner {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Handle to a running `FlightServer`. The server stops when `shutdown` is called
+/// or when the last clone of the handle is dropped.
+#[derive(Clone)]
+pub struct FlightServerHandle {
+    inner: Arc<FlightServerInner>,
+}
+
+impl FlightServerHandle {
+    /// Address the server is listening on, useful when binding to port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.inner.addr
+    }
+
+    /// Stop the server and wait for its runtime to exit
+    pub fn shutdown(&self) {
+        self.inner.shutdown();
+    }
+}
+
+/// Start serving `state` on `addr` from a background Tokio runtime
+pub fn spawn_flight_server(
+    state: Arc<Mutex<RunnerState>>,
+    addr: SocketAddr,
+) -> Result<FlightServerHandle, anyhow::Error> {
+    // Bind here so address errors are returned to the caller
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+    let local_addr = listener.local_addr()?;
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let thread = std::thread::Builder::new()
+        .name("flight-server".to_string())
+        .spawn(move || {
+            runtime.block_on(async move {
+                let listener = match tokio::net::TcpListener::from_std(listener) {
+                    Ok(listener) => listener,
+                    Err(err) => {
+                        log::error!("Failed to start flight server: {}", err);
+                        return;
+                    }
+                };
+                let result = tonic::transport::Server::builder()
+                    .add_service(FlightServiceServer::new(FlightServer::new(state)))
+                    .serve_with_incoming_shutdown(
+                        tokio_stream::wrappers::TcpListenerStream::new(listener),
+                        async {
+                            let _ = shutdown_rx.await;
+                        },
+                    )
+                    .await;
+                if let Err(err) = result {
+                    log::error!("Flight server stopped with error: {}", err);
+                }
+            });
+        })?;
+
+    log::info!("Serving flight data on {}", local_addr);
+    Ok(FlightServerHandle {
+        inner: Arc::new(FlightServerInner {
+            addr: local_addr,
+            shutdown_tx: Mutex::new(Some(shutdown_tx)),
+            thread: Mutex::new(Some(thread)),
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publish;
+    use arrow_flight::FlightClient;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestAttitude {
+        roll: f64,
+    }
+
+    #[test]
+    fn test_list_flights_and_do_get() {
+        let mut state = RunnerState::new();
+        for roll in [1.0, 2.0] {
+            state
+                .apply_record(&publish!("mavlink/attitude", &TestAttitude { roll }))
+                .unwrap();
+        }
+        state
+            .apply_record(&publish!("mavlink/gps", &TestAttitude { roll: 0.0 }))
+            .unwrap();
+
+        let handle =
+            spawn_flight_server(Arc::new(Mutex::new(state)), "127.0.0.1:0".parse().unwrap())
+                .unwrap();
+        let url = format!("http://{}", handle.local_addr());
+
+        let client_runtime = tokio::runtime::Runtime::new().unwrap();
+        client_runtime.block_on(async {
+            let channel = tonic::transport::Channel::from_shared(url)
+                .unwrap()
+                .connect()
+                .await
+                .unwrap();
+            let mut client = FlightClient::new(channel);
+
+            let infos: Vec<_> = client
+                .list_flights("")
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            let topics: Vec<String> = infos
+                .iter()
+                .map(|info| info.flight_descriptor.as_ref().unwrap().path.join("/"))
+                .collect();
+            assert_eq!(topics, vec!["mavlink/attitude", "mavlink/gps"]);
+            assert_eq!(infos[0].total_records, 2);
+
+            let batches: Vec<_> = client
+                .do_get(Ticket::new("mavlink/attitude"))
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+            assert_eq!(rows, 2);
+        });
+
+        handle.shutdown();
+    }
+}
@@ -0,0 +1,429 @@
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use anyhow::Context;
+use arrow::ipc::reader::StreamReader;
+use arrow::ipc::writer::StreamWriter;
+use log::{debug, info, warn};
+
+use crate::message::record::{Record, RecordFlag};
+use crate::subscribe;
+use crate::tasks::info::TaskInfo;
+use crate::tasks::task::{MetaTaskChannel, Task, TaskChannel};
+
+/// Schema metadata key set on records received from a peer, so they are not forwarded back
+pub const IPC_SOURCE_METADATA: &str = "ipc_source";
+
+/// Largest frame accepted from a peer
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+
+/// Settings for `IpcBridge`
+#[derive(Debug, Clone)]
+pub struct IpcBridgeConfig {
+    pub socket_path: PathBuf,
+    /// Local topics (prefix or `*` wildcard) sent to every connected peer
+    pub forward_patterns: Vec<String>,
+    /// Topics accepted from peers and published locally
+    pub receive_patterns: Vec<String>,
+}
+
+/// Which end of the socket a bridge is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IpcRole {
+    Listen,
+    Connect,
+}
+
+/// Routes topics between `Runner`s in different processes over a Unix domain socket.
+/// Each record is sent as a length-prefixed Arrow IPC stream carrying its topic in the
+/// schema metadata. Connections are read on background threads and the received records
+/// are published on the next `run`.
+pub struct IpcBridge {
+    info: TaskInfo,
+    config: IpcBridgeConfig,
+    role: IpcRole,
+    peers: Arc<Mutex<Vec<UnixStream>>>,
+    received_tx: mpsc::Sender<Record>,
+    received_rx: mpsc::Receiver<Record>,
+    shutdown: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl IpcBridge {
+    /// Bridge that listens on `config.socket_path` for peers
+    pub fn new(config: IpcBridgeConfig) -> Self {
+        Self::with_role(config, IpcRole::Listen)
+    }
+
+    /// Bridge that connects to a peer listening on `config.socket_path`
+    pub fn connect(config: IpcBridgeConfig) -> Self {
+        Self::with_role(config, IpcRole::Connect)
+    }
+
+    fn with_role(config: IpcBridgeConfig, role: IpcRole) -> Self {
+        let (received_tx, received_rx) = mpsc::channel();
+        Self {
+            info: TaskInfo::new("IpcBridge").with_insta_spawn(),
+            config,
+            role,
+            peers: Arc::new(Mutex::new(Vec::new())),
+            received_tx,
+            received_rx,
+            shutdown: Arc::new(AtomicBool::new(false)),
+            threads: Vec::new(),
+        }
+    }
+
+    /// Number of currently connected peers
+    pub fn peer_count(&self) -> usize {
+        self.peers.lock().unwrap().len()
+    }
+
+    fn topic_matches(pattern: &str, topic: &str) -> bool {
+        if pattern.contains('*') {
+            let pattern = format!("^{}$", regex::escape(pattern).replace("\\*", ".*"));
+            regex::Regex::new(&pattern).is_ok_and(|regex| regex.is_match(topic))
+        } else {
+            topic.starts_with(pattern)
+        }
+    }
+
+    fn matches_any(patterns: &[String], topic: &str) -> bool {
+        patterns.iter().any(|p| Self::topic_matches(p, topic))
+    }
+
+    /// Start reading a peer connection and keep a handle to write to it
+    fn add_peer(&mut self, stream: UnixStream) -> Result<(), anyhow::Error> {
+        let reader = stream.try_clone()?;
+        self.peers.lock().unwrap().push(stream);
+        self.threads.push(spawn_reader(
+            reader,
+            self.config.clone(),
+            self.received_tx.clone(),
+            self.shutdown.clone(),
+        ));
+        Ok(())
+    }
+
+    fn forward(&self, record: &Record) -> Result<(), anyhow::Error> {
+        let frame = encode_frame(record)?;
+        let mut peers = self.peers.lock().unwrap();
+        peers.retain_mut(|peer| match peer.write_all(&frame) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Dropping IPC peer after write error: {}", e);
+                false
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Encode a record as a `u32` little endian length followed by an Arrow IPC stream
+fn encode_frame(record: &Record) -> Result<Vec<u8>, anyhow::Error> {
+    let batch = record.to_record_batch();
+    let mut payload = Vec::new();
+    {
+        let mut writer = StreamWriter::try_new(&mut payload, &batch.schema())?;
+        writer.write(batch)?;
+        writer.finish()?;
+    }
+    let mut frame = Vec::with_capacity(4 + payload.len());
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Read one frame written by `encode_frame`, None once the peer closed the connection
+fn read_frame(stream: &mut UnixStream) -> Result<Option<Vec<Record>>, anyhow::Error> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(anyhow::anyhow!("IPC frame of {} bytes is too large", len));
+    }
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload)?;
+
+    let reader = StreamReader::try_new(payload.as_slice(), None)?;
+    let records = reader
+        .map(|batch| batch.map(Record::from_record_batch))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(records))
+}
+
+fn spawn_reader(
+    mut stream: UnixStream,
+    config: IpcBridgeConfig,
+    received_tx: mpsc::Sender<Record>,
+    shutdown: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let source = config.socket_path.display().to_string();
+        while !shutdown.load(Ordering::Relaxed) {
+            let records = match read_frame(&mut stream) {
+                Ok(Some(records)) => records,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Closing IPC peer after read error: {}", e);
+                    break;
+                }
+            };
+            for mut record in records {
+                let Ok(topic) = record.try_get_topic() else {
+                    warn!("Ignoring IPC record without a topic");
+                    continue;
+                };
+                if !IpcBridge::matches_any(&config.receive_patterns, &topic) {
+                    debug!("Ignoring IPC record on unrouted topic '{}'", topic);
+                    continue;
+                }
+                let marked = record
+                    .set_metadata(IPC_SOURCE_METADATA, source.clone())
+                    .and_then(|_| record.set_flag(RecordFlag::PublishPacket));
+                if marked.is_err() || received_tx.send(record).is_err() {
+                    return;
+                }
+            }
+        }
+    })
+}
+
+impl Task for IpcBridge {
+    fn init(&mut self, tx: TaskChannel, _meta_tx: MetaTaskChannel) -> Result<(), anyhow::Error> {
+        let socket_path = self.config.socket_path.clone();
+        match self.role {
+            IpcRole::Listen => {
+                // A socket file left behind by a previous run would make bind fail
+                if socket_path.exists() {
+                    std::fs::remove_file(&socket_path)?;
+                }
+                let listener = UnixListener::bind(&socket_path)
+                    .with_context(|| format!("Failed to bind IPC socket: {:?}", socket_path))?;
+                info!("IpcBridge listening on {:?}", socket_path);
+
+                let peers = self.peers.clone();
+                let config = self.config.clone();
+                let received_tx = self.received_tx.clone();
+                let shutdown = self.shutdown.clone();
+                self.threads.push(std::thread::spawn(move || {
+                    for stream in listener.incoming() {
+                        if shutdown.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let stream = match stream {
+                            Ok(stream) => stream,
+                            Err(e) => {
+                                warn!("Failed to accept IPC peer: {}", e);
+                                continue;
+                            }
+                        };
+                        let Ok(reader) = stream.try_clone() else {
+                            continue;
+                        };
+                        info!("IPC peer connected");
+                        peers.lock().unwrap().push(stream);
+                        // Reader threads end when their peer disconnects
+                        spawn_reader(
+                            reader,
+                            config.clone(),
+                            received_tx.clone(),
+                            shutdown.clone(),
+                        );
+                    }
+                }));
+            }
+            IpcRole::Connect => {
+                let stream = UnixStream::connect(&socket_path).with_context(|| {
+                    format!("Failed to connect to IPC socket: {:?}", socket_path)
+                })?;
+                info!("IpcBridge connected to {:?}", socket_path);
+                self.add_peer(stream)?;
+            }
+        }
+
+        for pattern in &self.config.forward_patterns {
+            tx.send(subscribe!(pattern))?;
+        }
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<Record>,
+        tx: TaskChannel,
+        _meta_tx: MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in self.received_rx.try_iter() {
+            tx.send(record)?;
+        }
+
+        for record in &inputs {
+            // Records that came from a peer are never sent back out
+            if record.get_metadata(IPC_SOURCE_METADATA).is_some() {
+                continue;
+            }
+            let Ok(topic) = record.try_get_topic() else {
+                continue;
+            };
+            if Self::matches_any(&self.config.forward_patterns, &topic) {
+                self.forward(record)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        self.shutdown.store(true, Ordering::Relaxed);
+        for peer in self.peers.lock().unwrap().drain(..) {
+            let _ = peer.shutdown(std::net::Shutdown::Both);
+        }
+        if self.role == IpcRole::Listen {
+            // Wake the accept loop so it sees the shutdown flag
+            let _ = UnixStream::connect(&self.config.socket_path);
+            let _ = std::fs::remove_file(&self.config.socket_path);
+        }
+        for thread in self.threads.drain(..) {
+            if thread.join().is_err() {
+                log::error!("IpcBridge thread panicked");
+            }
+        }
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publish;
+    use crate::tasks::runner::Runner;
+    use serde::{Deserialize, Serialize};
+    use std::time::{Duration, Instant};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct TestAttitude {
+        roll: f64,
+    }
+
+    struct TestStreamPublisher {
+        info: TaskInfo,
+        count: usize,
+    }
+
+    impl Task for TestStreamPublisher {
+        fn init(&mut self, _tx: TaskChannel, _meta_tx: MetaTaskChannel) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> anyhow::Result<()> {
+            let roll = self.count as f64;
+            self.count += 1;
+            tx.send(publish!("mavlink/attitude", &TestAttitude { roll }))?;
+            tx.send(publish!("exec/stage", &TestAttitude { roll }))?;
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    struct TestSubscriber {
+        info: TaskInfo,
+        received: Arc<Mutex<Vec<Record>>>,
+    }
+
+    impl Task for TestSubscriber {
+        fn init(&mut self, tx: TaskChannel, _meta_tx: MetaTaskChannel) -> anyhow::Result<()> {
+            tx.send(subscribe!("mavlink/*"))?;
+            tx.send(subscribe!("exec/*"))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> anyhow::Result<()> {
+            self.received.lock().unwrap().extend(inputs);
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_topics_cross_runners() {
+        let socket_path =
+            std::env::temp_dir().join(format!("pubsub_ipc_{}.sock", uuid::Uuid::new_v4()));
+        let config = IpcBridgeConfig {
+            socket_path: socket_path.clone(),
+            forward_patterns: vec!["mavlink/*".to_string(), "exec/*".to_string()],
+            receive_patterns: vec!["mavlink/*".to_string()],
+        };
+
+        let mut quad = Runner::new().with_tick_rate(0.0).disable_logging();
+        quad.add_task(Arc::new(Mutex::new(TestStreamPublisher {
+            info: TaskInfo::new("TestStreamPublisher").with_insta_spawn(),
+            count: 0,
+        })));
+        let quad_bridge = Arc::new(Mutex::new(IpcBridge::new(config.clone())));
+        quad.add_task(quad_bridge.clone());
+        quad.init().unwrap();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut ground = Runner::new().with_tick_rate(0.0).disable_logging();
+        ground.add_task(Arc::new(Mutex::new(TestSubscriber {
+            info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+            received: received.clone(),
+        })));
+        ground.add_task(Arc::new(Mutex::new(IpcBridge::connect(config))));
+        ground.init().unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while received.lock().unwrap().is_empty() && Instant::now() < deadline {
+            quad.run().unwrap();
+            ground.run().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(quad_bridge.lock().unwrap().peer_count(), 1);
+
+        // Only topics matching the receive patterns of the ground bridge arrive
+        let received = received.lock().unwrap().clone();
+        assert!(!received.is_empty());
+        for record in &received {
+            assert_eq!(record.try_get_topic().unwrap(), "mavlink/attitude");
+            assert_eq!(
+                record.get_metadata(IPC_SOURCE_METADATA),
+                Some(socket_path.display().to_string().as_str())
+            );
+        }
+        let attitude: Vec<TestAttitude> = received[0].to_serde().unwrap();
+        assert_eq!(attitude.len(), 1);
+
+        ground.cleanup().unwrap();
+        quad.cleanup().unwrap();
+        assert!(!socket_path.exists());
+    }
+}
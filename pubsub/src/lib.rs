@@ -1,2 +1,7 @@
+#[cfg(feature = "arrow-flight")]
+pub mod flight;
+#[cfg(unix)]
+pub mod ipc;
 pub mod message;
+pub mod replay;
 pub mod tasks;
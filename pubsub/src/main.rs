@@ -9,6 +9,7 @@ use tasks::task::Task;
 use log::info;
 
 mod message;
+mod replay;
 mod tasks;
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Pose {
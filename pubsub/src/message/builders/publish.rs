@@ -1,5 +1,6 @@
 use crate::message::record::{Record, RecordFlag};
 use serde::Serialize;
+use std::collections::HashMap;
 
 use super::RecordBuilder;
 
@@ -8,6 +9,8 @@ pub struct PublishBuilder {
     task_id: u32,
     task_name: String,
     content: Option<Record>,
+    flag: RecordFlag,
+    metadata: HashMap<String, String>,
 }
 
 impl PublishBuilder {
@@ -17,6 +20,8 @@ impl PublishBuilder {
             task_id: 0,
             task_name: "unset".to_string(),
             content: None,
+            flag: RecordFlag::PublishPacket,
+            metadata: HashMap::new(),
         }
     }
 
@@ -41,6 +46,24 @@ impl PublishBuilder {
         self.content = Some(record);
         Ok(self)
     }
+
+    pub fn with_record_content(mut self, content: Record) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    /// Publish with another flag than `RecordFlag::PublishPacket`, e.g. `RecordFlag::ErrorPacket`
+    pub fn with_flag(mut self, flag: RecordFlag) -> Self {
+        self.flag = flag;
+        self
+    }
+
+    /// Attach an extra schema metadata entry, e.g. `session_id` or `vehicle_id`.
+    /// `topic` and `flag` are always set from the builder and can't be overridden here.
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
 }
 
 impl RecordBuilder for PublishBuilder {
@@ -52,7 +75,10 @@ impl RecordBuilder for PublishBuilder {
             Record::from_serde(&()).unwrap()
         };
 
-        record.set_flag(RecordFlag::PublishPacket).unwrap();
+        for (key, value) in self.metadata {
+            record.set_metadata(&key, value).unwrap();
+        }
+        record.set_flag(self.flag).unwrap();
         record.set_topic(self.topic).unwrap();
         record
     }
@@ -138,6 +164,43 @@ macro_rules! publish_with_info {
         builder
     }};
 }
+
+/// A macro to publish an `ErrorRecord` on `errors/<task_name>` with the `ErrorPacket` flag.
+///
+/// # Examples
+///
+/// ```
+/// use pubsub::message::error_record::{ErrorRecord, ErrorSeverity};
+/// use pubsub::publish_error;
+///
+/// let record = publish_error!("ExecTaskHealthWatchdog", "AwaitingHealthy", "EKF lost", ErrorSeverity::Critical);
+///
+/// let error_record = ErrorRecord::new("ExecTaskHealthWatchdog", "AwaitingHealthy", "EKF lost", ErrorSeverity::Critical);
+/// let record = publish_error!(&error_record);
+/// ```
+#[macro_export]
+macro_rules! publish_error {
+    ($error:expr) => {{
+        use $crate::message::builders::publish::PublishBuilder;
+        use $crate::message::builders::RecordBuilder;
+        use $crate::message::record::RecordFlag;
+
+        let error: &$crate::message::error_record::ErrorRecord = $error;
+        PublishBuilder::new(error.topic())
+            .with_task_name(error.task_name.clone())
+            .with_serde_content(error)
+            .unwrap()
+            .with_flag(RecordFlag::ErrorPacket)
+            .build()
+    }};
+    ($task_name:expr, $stage:expr, $message:expr, $severity:expr) => {{
+        let error = $crate::message::error_record::ErrorRecord::new(
+            $task_name, $stage, $message, $severity,
+        );
+        $crate::publish_error!(&error)
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -174,4 +237,49 @@ mod tests {
         assert_eq!(record.try_get_topic().unwrap(), "test_topic");
         assert_eq!(record.get_flag().unwrap(), RecordFlag::PublishPacket);
     }
+
+    #[test]
+    fn test_publish_with_metadata() {
+        let record = PublishBuilder::new("test_topic".to_string())
+            .with_metadata("session_id", "flight-42")
+            .with_metadata("vehicle_id", "quad-1")
+            .with_metadata("topic", "ignored")
+            .with_serde_content(&TestStruct::default())
+            .unwrap()
+            .build();
+
+        assert_eq!(record.try_get_topic().unwrap(), "test_topic");
+        assert_eq!(record.get_metadata("session_id"), Some("flight-42"));
+        assert_eq!(record.get_metadata("vehicle_id"), Some("quad-1"));
+        assert_eq!(record.get_metadata("software_version"), None);
+        assert_eq!(record.metadata().len(), 4);
+    }
+
+    #[test]
+    fn test_publish_error() {
+        use crate::message::error_record::{ErrorRecord, ErrorSeverity};
+
+        let record = publish_error!(
+            "ExecTaskHealthWatchdog",
+            "AwaitingHealthy",
+            "EKF lost attitude",
+            ErrorSeverity::Critical
+        );
+        assert_eq!(
+            record.try_get_topic().unwrap(),
+            "errors/ExecTaskHealthWatchdog"
+        );
+        assert_eq!(record.get_flag().unwrap(), RecordFlag::ErrorPacket);
+
+        let errors: Vec<ErrorRecord> = record.to_serde().unwrap();
+        assert_eq!(
+            errors,
+            vec![ErrorRecord::new(
+                "ExecTaskHealthWatchdog",
+                "AwaitingHealthy",
+                "EKF lost attitude",
+                ErrorSeverity::Critical
+            )]
+        );
+    }
 }
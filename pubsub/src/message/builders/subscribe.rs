@@ -5,6 +5,7 @@ use super::RecordBuilder;
 
 pub struct SubscribeBuilder {
     packet: SubscribePacket,
+    flag: RecordFlag,
 }
 #[derive(Serialize)]
 pub struct SubscribePacket {
@@ -20,6 +21,7 @@ impl SubscribeBuilder {
                 task_id: 0,
                 task_name: "unset".to_string(),
             },
+            flag: RecordFlag::SubscribePacket,
         }
     }
 
@@ -32,12 +34,18 @@ impl SubscribeBuilder {
         self.packet.task_name = task_name;
         self
     }
+
+    /// Send with another flag than `RecordFlag::SubscribePacket`, e.g. `RecordFlag::UnsubscribePacket`
+    pub fn with_flag(mut self, flag: RecordFlag) -> Self {
+        self.flag = flag;
+        self
+    }
 }
 
 impl RecordBuilder for SubscribeBuilder {
     fn build(self) -> Record {
         let mut record = Record::from_serde(&self.packet).unwrap();
-        record.set_flag(RecordFlag::SubscribePacket).unwrap();
+        record.set_flag(self.flag).unwrap();
         record.set_topic(self.packet.topic.clone()).unwrap();
         record
     }
@@ -78,6 +86,29 @@ macro_rules! subscribe {
     }};
 }
 
+/// A macro to create an unsubscribe packet, removing the sending task's subscriptions
+/// to the topic. A wildcard pattern also removes the subscriptions it matches.
+///
+/// # Examples
+///
+/// ```
+/// use pubsub::unsubscribe;
+///
+/// let record = unsubscribe!("mavlink/reproc/*");
+/// ```
+#[macro_export]
+macro_rules! unsubscribe {
+    ($topic:expr) => {{
+        use $crate::message::builders::subscribe::SubscribeBuilder;
+        use $crate::message::builders::RecordBuilder;
+        use $crate::message::record::RecordFlag;
+
+        SubscribeBuilder::new($topic.to_string())
+            .with_flag(RecordFlag::UnsubscribePacket)
+            .build()
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,4 +138,11 @@ mod tests {
         let record = subscribe!("test_topic", 42, "my_task");
         assert_eq!(record.try_get_topic().unwrap(), "test_topic");
     }
+
+    #[test]
+    fn test_unsubscribe_macro() {
+        let record = unsubscribe!("mavlink/reproc/*");
+        assert_eq!(record.try_get_topic().unwrap(), "mavlink/reproc/*");
+        assert_eq!(record.get_flag().unwrap(), RecordFlag::UnsubscribePacket);
+    }
 }
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Topic prefix error records are published under, one topic per reporting task
+pub const ERROR_TOPIC_PREFIX: &str = "errors";
+
+/// How bad a reported error is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ErrorSeverity {
+    Info,
+    Warning,
+    Error,
+    /// The vehicle can not continue, e.g. the exec runner moves to its fatal stage
+    Critical,
+}
+
+/// Structured error sent over the bus with `RecordFlag::ErrorPacket`, see `publish_error!`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorRecord {
+    pub task_name: String,
+    /// Stage or step the task was in when the error happened
+    pub stage: String,
+    pub message: String,
+    pub severity: ErrorSeverity,
+}
+
+impl ErrorRecord {
+    pub fn new(
+        task_name: impl Into<String>,
+        stage: impl Into<String>,
+        message: impl Into<String>,
+        severity: ErrorSeverity,
+    ) -> Self {
+        Self {
+            task_name: task_name.into(),
+            stage: stage.into(),
+            message: message.into(),
+            severity,
+        }
+    }
+
+    /// Topic the error is published on, `errors/<task_name>`
+    pub fn topic(&self) -> String {
+        format!("{}/{}", ERROR_TOPIC_PREFIX, self.task_name)
+    }
+}
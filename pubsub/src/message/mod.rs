@@ -1,2 +1,3 @@
 pub mod builders;
+pub mod error_record;
 pub mod record;
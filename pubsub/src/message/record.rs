@@ -1,17 +1,247 @@
-use arrow::array::{ArrayRef, RecordBatch, StructArray};
-use arrow::datatypes::{DataType, Field, Fields, Schema};
+use arrow::array::{
+    Array, ArrayRef, AsArray, BinaryArray, BooleanArray, Float64Array, Float64Builder, Int64Array,
+    MapArray, RecordBatch, StringArray, StructArray, TimestampNanosecondArray, UInt32Array,
+    UInt64Array,
+};
+use arrow::datatypes::{DataType, Field, Fields, Float64Type, Schema};
 use arrow::json::reader::infer_json_schema_from_iterator;
 use arrow::json::reader::ReaderBuilder;
+use arrow::row::{Row, RowConverter, SortField};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
 use serde::de::DeserializeOwned;
 use serde_json::to_value;
+use std::any::Any;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Path separator for flattened field names
 const PATH_SEPARATOR: &str = ".";
 
+/// Schema metadata key holding the source column name of a run-length encoded Record
+pub const RUN_LENGTH_COLUMN_METADATA: &str = "run_length_column";
+
+/// Field metadata key listing the known keys of a map column (comma separated)
+pub const MAP_KEYS_METADATA: &str = "map_keys";
+
+/// Field metadata key explaining why a column was left as-is by `flatten_record_batch`
+pub const FLATTEN_NOTE_METADATA: &str = "flatten_note";
+
+/// Settings for `flatten_record_batch_with_config`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlattenConfig {
+    /// Expand list columns into numbered sibling columns (`pose.0`, `pose.1`, ...)
+    /// instead of keeping them as a single list column
+    pub expand_lists: bool,
+    /// Maximum number of element columns per expanded list, longer lists are truncated
+    pub max_list_len: usize,
+}
+
+impl Default for FlattenConfig {
+    fn default() -> Self {
+        Self {
+            expand_lists: false,
+            max_list_len: 16,
+        }
+    }
+}
+
+/// Parse a single optional string as a one row column of the field's type
+fn parse_string_value(
+    value: Option<String>,
+    field: &Field,
+    label: &str,
+) -> Result<ArrayRef, anyhow::Error> {
+    let cast_options = arrow::compute::CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    let raw = StringArray::from(vec![value]);
+    arrow::compute::cast_with_options(&raw, field.data_type(), &cast_options)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {} as {}: {}", label, field.data_type(), e))
+}
+
+/// Escape text for use inside HTML content and attribute values
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Flattens a map column into a list of fields and arrays.
+///
+/// If the field has a `map_keys` metadata hint, each known key is expanded into its own
+/// typed column `{prefix}.{key}`, null for rows missing that key. Maps keyed at runtime
+/// can't be expanded from the schema, so they become a single JSON string column.
+fn flatten_map_column(
+    prefix: &str,
+    field: &Field,
+    map_array: &MapArray,
+) -> Result<Vec<(Field, ArrayRef)>, anyhow::Error> {
+    let keys = arrow::compute::cast(map_array.keys(), &DataType::Utf8)?;
+    let keys = keys.as_string::<i32>();
+    let values = map_array.values();
+    let offsets = map_array.value_offsets();
+
+    let known_keys = match field.metadata().get(MAP_KEYS_METADATA) {
+        Some(known_keys) => known_keys,
+        None => {
+            let json_column = map_array_to_json_strings(map_array, keys)?;
+            let json_field = Field::new(prefix, DataType::Utf8, true);
+            return Ok(vec![(json_field, Arc::new(json_column) as ArrayRef)]);
+        }
+    };
+
+    let mut flattened_columns = Vec::new();
+    for key in known_keys
+        .split(',')
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+    {
+        // For each row, the index of the entry holding this key (if any)
+        let indices: UInt32Array = (0..map_array.len())
+            .map(|row| {
+                if map_array.is_null(row) {
+                    return None;
+                }
+                let (start, end) = (offsets[row] as usize, offsets[row + 1] as usize);
+                (start..end)
+                    .find(|&i| keys.is_valid(i) && keys.value(i) == key)
+                    .map(|i| i as u32)
+            })
+            .collect();
+
+        let column = arrow::compute::take(values.as_ref(), &indices, None)?;
+        let col_name = format!("{}{}{}", prefix, PATH_SEPARATOR, key);
+        flattened_columns.push((
+            Field::new(col_name, values.data_type().clone(), true),
+            column,
+        ));
+    }
+    Ok(flattened_columns)
+}
+
+/// Converts each row of a map column into a JSON object string
+fn map_array_to_json_strings(
+    map_array: &MapArray,
+    keys: &StringArray,
+) -> Result<StringArray, anyhow::Error> {
+    // Let the arrow JSON writer convert the values, so nested types are handled too
+    let values_batch = RecordBatch::try_from_iter([("value", map_array.values().clone())])?;
+    let mut writer = arrow::json::writer::ArrayWriter::new(Vec::new());
+    writer.write(&values_batch)?;
+    writer.finish()?;
+    let json_data = writer.into_inner();
+    let json_values: Vec<serde_json::Map<String, serde_json::Value>> = if json_data.is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_slice(&json_data)?
+    };
+
+    let offsets = map_array.value_offsets();
+    let rows = (0..map_array.len())
+        .map(|row| {
+            if map_array.is_null(row) {
+                return Ok(None);
+            }
+            let mut object = serde_json::Map::new();
+            for i in offsets[row] as usize..offsets[row + 1] as usize {
+                let value = json_values
+                    .get(i)
+                    .and_then(|v| v.get("value").cloned())
+                    .unwrap_or(serde_json::Value::Null);
+                object.insert(keys.value(i).to_string(), value);
+            }
+            Ok(Some(serde_json::to_string(&object)?))
+        })
+        .collect::<Result<Vec<Option<String>>, anyhow::Error>>()?;
+
+    Ok(StringArray::from(rows))
+}
+
+/// Expands a list column into numbered element columns `{prefix}.0`, `{prefix}.1`, ...
+///
+/// The number of columns is the length of the longest list, capped at `max_list_len`.
+/// Rows with shorter (or null) lists are null in the missing element columns.
+/// Struct elements are flattened further.
+fn flatten_list_column(
+    prefix: &str,
+    column: &ArrayRef,
+    max_list_len: usize,
+) -> Result<Vec<(Field, ArrayRef)>, anyhow::Error> {
+    let (values, offsets): (&ArrayRef, Vec<usize>) = match column.data_type() {
+        DataType::List(_) => {
+            let list = column.as_list::<i32>();
+            let offsets = list.value_offsets().iter().map(|o| *o as usize).collect();
+            (list.values(), offsets)
+        }
+        DataType::LargeList(_) => {
+            let list = column.as_list::<i64>();
+            let offsets = list.value_offsets().iter().map(|o| *o as usize).collect();
+            (list.values(), offsets)
+        }
+        other => return Err(anyhow::anyhow!("Expected a list column, found {}", other)),
+    };
+    let lengths: Vec<usize> = (0..column.len())
+        .map(|row| {
+            if column.is_null(row) {
+                0
+            } else {
+                offsets[row + 1] - offsets[row]
+            }
+        })
+        .collect();
+    let width = lengths.iter().copied().max().unwrap_or(0).min(max_list_len);
+
+    let mut flattened_columns = Vec::new();
+    for element in 0..width {
+        let indices: UInt32Array = (0..column.len())
+            .map(|row| (element < lengths[row]).then(|| (offsets[row] + element) as u32))
+            .collect();
+        let element_column = arrow::compute::take(values.as_ref(), &indices, None)?;
+        let col_name = format!("{}{}{}", prefix, PATH_SEPARATOR, element);
+        match element_column.data_type() {
+            DataType::Struct(_) => {
+                let config = FlattenConfig {
+                    expand_lists: true,
+                    max_list_len,
+                };
+                flattened_columns.extend(flatten_struct_column(
+                    &col_name,
+                    element_column.as_struct(),
+                    &config,
+                )?);
+            }
+            data_type => flattened_columns.push((
+                Field::new(col_name, data_type.clone(), true),
+                element_column,
+            )),
+        }
+    }
+    Ok(flattened_columns)
+}
+
+/// A list field left as-is, with a `flatten_note` saying so
+fn kept_list_field(field: &Field, name: &str) -> Field {
+    let mut metadata = field.metadata().clone();
+    metadata.insert(
+        FLATTEN_NOTE_METADATA.to_string(),
+        "list column kept as-is, set FlattenConfig::expand_lists to expand it".to_string(),
+    );
+    Field::new(name, field.data_type().clone(), field.is_nullable()).with_metadata(metadata)
+}
+
 /// Flattens a struct column into a list of fields and arrays.
 ///
 /// This function recursively processes a struct column, expanding nested structs
@@ -19,6 +249,7 @@ const PATH_SEPARATOR: &str = ".";
 fn flatten_struct_column(
     prefix: &str,
     struct_array: &StructArray,
+    config: &FlattenConfig,
 ) -> Result<Vec<(Field, ArrayRef)>, anyhow::Error> {
     let mut flattened_columns = Vec::new();
     for (i, field) in struct_array.fields().iter().enumerate() {
@@ -35,9 +266,27 @@ fn flatten_struct_column(
                     .as_any()
                     .downcast_ref::<StructArray>()
                     .ok_or_else(|| anyhow::anyhow!("Failed to downcast to StructArray"))?;
-                let sub_flattened = flatten_struct_column(&col_name, sub_struct_array)?;
+                let sub_flattened = flatten_struct_column(&col_name, sub_struct_array, config)?;
                 flattened_columns.extend(sub_flattened);
             }
+            DataType::Map(_, _) => {
+                let map_array = column
+                    .as_any()
+                    .downcast_ref::<MapArray>()
+                    .ok_or_else(|| anyhow::anyhow!("Failed to downcast to MapArray"))?;
+                flattened_columns.extend(flatten_map_column(&col_name, field, map_array)?);
+            }
+            DataType::List(_) | DataType::LargeList(_) => {
+                if config.expand_lists {
+                    flattened_columns.extend(flatten_list_column(
+                        &col_name,
+                        column,
+                        config.max_list_len,
+                    )?);
+                } else {
+                    flattened_columns.push((kept_list_field(field, &col_name), column.clone()));
+                }
+            }
             _ => {
                 let new_field =
                     Field::new(&col_name, field.data_type().clone(), field.is_nullable());
@@ -48,11 +297,76 @@ fn flatten_struct_column(
     Ok(flattened_columns)
 }
 
+/// Single element array holding a value passed to `Record::filter_column_eq`
+fn any_to_array(value: &dyn Any) -> Result<ArrayRef, anyhow::Error> {
+    macro_rules! downcast_to_array {
+        ($($ty:ty => $array:ty),* $(,)?) => {
+            $(
+                if let Some(value) = value.downcast_ref::<$ty>() {
+                    return Ok(Arc::new(<$array>::from(vec![value.clone()])));
+                }
+            )*
+        };
+    }
+
+    downcast_to_array!(
+        bool => BooleanArray,
+        i8 => arrow::array::Int8Array,
+        i16 => arrow::array::Int16Array,
+        i32 => arrow::array::Int32Array,
+        i64 => Int64Array,
+        u8 => arrow::array::UInt8Array,
+        u16 => arrow::array::UInt16Array,
+        u32 => UInt32Array,
+        u64 => arrow::array::UInt64Array,
+        f32 => arrow::array::Float32Array,
+        f64 => Float64Array,
+        String => StringArray,
+        &str => StringArray,
+    );
+    Err(anyhow::anyhow!("Unsupported filter value type"))
+}
+
+/// Mean of the non-null values of each bucket of rows, cast back to the type of `column`.
+/// Integer means are rounded, a bucket without values is null.
+fn bucket_means(column: &ArrayRef, buckets: &[(i64, Vec<u32>)]) -> Result<ArrayRef, anyhow::Error> {
+    let values = arrow::compute::cast(column, &DataType::Float64)?;
+    let values = values.as_primitive::<Float64Type>();
+    let round = column.data_type().is_integer();
+
+    let mut builder = Float64Builder::with_capacity(buckets.len());
+    for (_, rows) in buckets {
+        let (sum, count) = rows
+            .iter()
+            .filter(|&&row| values.is_valid(row as usize))
+            .fold((0.0, 0), |(sum, count), &row| {
+                (sum + values.value(row as usize), count + 1)
+            });
+        if count == 0 {
+            builder.append_null();
+        } else {
+            let mean = sum / count as f64;
+            builder.append_value(if round { mean.round() } else { mean });
+        }
+    }
+    Ok(arrow::compute::cast(&builder.finish(), column.data_type())?)
+}
+
 /// Flattens a RecordBatch, expanding struct columns into separate columns.
 ///
 /// This process is similar to how Serde's `#[serde(flatten)]` attribute works,
 /// bringing nested fields up to the top level with their paths joined.
+/// Map columns are expanded by their known keys (see `flatten_map_column`).
+/// List columns are kept as-is, see `flatten_record_batch_with_config` to expand them.
 pub fn flatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch, anyhow::Error> {
+    flatten_record_batch_with_config(batch, &FlattenConfig::default())
+}
+
+/// Same as `flatten_record_batch`, with control over how list columns are flattened
+pub fn flatten_record_batch_with_config(
+    batch: &RecordBatch,
+    config: &FlattenConfig,
+) -> Result<RecordBatch, anyhow::Error> {
     let mut flattened_fields = Vec::new();
     let mut flattened_columns = Vec::new();
 
@@ -64,12 +378,33 @@ pub fn flatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch, anyhow::
                     .as_any()
                     .downcast_ref::<StructArray>()
                     .ok_or_else(|| anyhow::anyhow!("Failed to downcast to StructArray"))?;
-                let struct_flattened = flatten_struct_column(field.name(), struct_array)?;
+                let struct_flattened = flatten_struct_column(field.name(), struct_array, config)?;
                 for (f, c) in struct_flattened {
                     flattened_fields.push(Arc::new(f));
                     flattened_columns.push(c);
                 }
             }
+            DataType::Map(_, _) => {
+                let map_array = column
+                    .as_any()
+                    .downcast_ref::<MapArray>()
+                    .ok_or_else(|| anyhow::anyhow!("Failed to downcast to MapArray"))?;
+                for (f, c) in flatten_map_column(field.name(), field, map_array)? {
+                    flattened_fields.push(Arc::new(f));
+                    flattened_columns.push(c);
+                }
+            }
+            DataType::List(_) | DataType::LargeList(_) => {
+                if config.expand_lists {
+                    for (f, c) in flatten_list_column(field.name(), column, config.max_list_len)? {
+                        flattened_fields.push(Arc::new(f));
+                        flattened_columns.push(c);
+                    }
+                } else {
+                    flattened_fields.push(Arc::new(kept_list_field(field, field.name())));
+                    flattened_columns.push(column.clone());
+                }
+            }
             _ => {
                 flattened_fields.push(field.clone());
                 flattened_columns.push(column.clone());
@@ -85,10 +420,57 @@ pub fn flatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch, anyhow::
         .map_err(|e| anyhow::anyhow!("Failed to create flattened RecordBatch: {}", e))
 }
 
+/// Rebuilds a list column from the numbered element columns written by `flatten_list_column`.
+///
+/// Returns None unless the field names are exactly `0..n` with a shared type.
+/// Each row's list ends at its first null element, as shorter lists are padded with nulls.
+fn list_from_element_columns(
+    name: &str,
+    fields: &[(String, Arc<Field>, ArrayRef)],
+    num_rows: usize,
+) -> Result<Option<(Field, ArrayRef)>, anyhow::Error> {
+    let mut elements: Vec<Option<&ArrayRef>> = vec![None; fields.len()];
+    for (local_name, _, array) in fields {
+        match local_name.parse::<usize>() {
+            Ok(index) if index < elements.len() && elements[index].is_none() => {
+                elements[index] = Some(array)
+            }
+            _ => return Ok(None),
+        }
+    }
+    let elements: Vec<&dyn Array> = elements.into_iter().flatten().map(|a| a.as_ref()).collect();
+    let data_type = elements[0].data_type().clone();
+    if elements.iter().any(|e| e.data_type() != &data_type) {
+        return Ok(None);
+    }
+
+    let mut indices = Vec::new();
+    let mut offsets = vec![0i32];
+    for row in 0..num_rows {
+        let len = elements.iter().take_while(|e| e.is_valid(row)).count();
+        indices.extend((0..len).map(|element| (element, row)));
+        offsets.push(offsets[offsets.len() - 1] + len as i32);
+    }
+    let values = arrow::compute::interleave(&elements, &indices)?;
+
+    let item = Arc::new(Field::new("item", data_type, true));
+    let list = arrow::array::ListArray::try_new(
+        item.clone(),
+        arrow::buffer::OffsetBuffer::new(offsets.into()),
+        values,
+        None,
+    )?;
+    Ok(Some((
+        Field::new(name, DataType::List(item), true),
+        Arc::new(list),
+    )))
+}
+
 /// Unflattens a previously flattened RecordBatch, reconstructing the original structure.
 ///
 /// This is the inverse operation of `flatten_record_batch`. It takes a flattened RecordBatch
 /// and reconstructs the nested structure based on the field path separators.
+/// Groups of numbered columns (`pose.0`, `pose.1`, ...) become list columns again.
 pub fn unflatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch, anyhow::Error> {
     // If there are no fields with path separators, the batch is already unflattened
     if !batch
@@ -176,6 +558,14 @@ pub fn unflatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch, anyhow
 
         // Process nested struct fields recursively
         for (struct_name, struct_fields) in field_groups {
+            if let Some((list_field, list_array)) =
+                list_from_element_columns(&struct_name, &struct_fields, num_rows)?
+            {
+                direct_fields.push(Arc::new(list_field));
+                direct_arrays.push(list_array);
+                continue;
+            }
+
             let (nested_fields, nested_arrays) = build_nested_struct(&struct_fields, num_rows)?;
 
             // Create a nested struct field
@@ -205,6 +595,14 @@ pub fn unflatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch, anyhow
     let mut unflattened_data = top_level_data;
 
     for (struct_name, fields) in field_groups {
+        if let Some((list_field, list_array)) =
+            list_from_element_columns(&struct_name, &fields, num_rows)?
+        {
+            unflattened_fields.push(Arc::new(list_field));
+            unflattened_data.push(list_array);
+            continue;
+        }
+
         let (struct_fields, struct_arrays) = build_nested_struct(&fields, num_rows)?;
 
         // Create the struct field at top level
@@ -247,6 +645,186 @@ pub fn unflatten_record_batch(batch: &RecordBatch) -> Result<RecordBatch, anyhow
         .map_err(|e| anyhow::anyhow!("Failed to create unflattened RecordBatch: {}", e))
 }
 
+/// Single-column transformations that can be applied with `Record::apply_compute_kernel`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComputeKernel {
+    Abs,
+    Negate,
+    Sin,
+    Cos,
+    Sqrt,
+    /// Natural logarithm
+    Log,
+    Ceil,
+    Floor,
+    /// Round to the given number of decimal places
+    Round(i32),
+}
+
+impl ComputeKernel {
+    /// Apply the kernel to a numeric array.
+    /// Negate keeps the input type, every other kernel produces Float64.
+    pub fn apply(&self, input: &ArrayRef) -> Result<ArrayRef, anyhow::Error> {
+        if !input.data_type().is_numeric() {
+            return Err(anyhow::anyhow!(
+                "Compute kernel {:?} requires a numeric column, got {}",
+                self,
+                input.data_type()
+            ));
+        }
+
+        if let ComputeKernel::Negate = self {
+            return Ok(arrow::compute::kernels::numeric::neg(input)?);
+        }
+
+        let values = arrow::compute::cast(input, &DataType::Float64)?;
+        let values = values.as_primitive::<Float64Type>();
+        let result: Float64Array = match *self {
+            ComputeKernel::Abs => arrow::compute::unary(values, f64::abs),
+            ComputeKernel::Sin => arrow::compute::unary(values, f64::sin),
+            ComputeKernel::Cos => arrow::compute::unary(values, f64::cos),
+            ComputeKernel::Sqrt => arrow::compute::unary(values, f64::sqrt),
+            ComputeKernel::Log => arrow::compute::unary(values, f64::ln),
+            ComputeKernel::Ceil => arrow::compute::unary(values, f64::ceil),
+            ComputeKernel::Floor => arrow::compute::unary(values, f64::floor),
+            ComputeKernel::Round(decimals) => {
+                let scale = 10f64.powi(decimals);
+                arrow::compute::unary(values, |v| (v * scale).round() / scale)
+            }
+            ComputeKernel::Negate => unreachable!(),
+        };
+        Ok(Arc::new(result))
+    }
+}
+
+/// Replacement for null values used by `Record::mask_nulls`.
+/// The value is cast to the type of the column it fills.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FillValue {
+    F64(f64),
+    I64(i64),
+    Bool(bool),
+    Str(String),
+    Zero,
+    One,
+}
+
+impl FillValue {
+    /// Single element array holding the fill value as `data_type`
+    fn to_array(&self, data_type: &DataType) -> Result<ArrayRef, anyhow::Error> {
+        let value: ArrayRef = match self {
+            FillValue::F64(v) => Arc::new(Float64Array::from(vec![*v])),
+            FillValue::I64(v) => Arc::new(Int64Array::from(vec![*v])),
+            FillValue::Bool(v) => Arc::new(BooleanArray::from(vec![*v])),
+            FillValue::Str(v) => Arc::new(StringArray::from(vec![v.as_str()])),
+            FillValue::Zero => Arc::new(Int64Array::from(vec![0])),
+            FillValue::One => Arc::new(Int64Array::from(vec![1])),
+        };
+        // Unsafe casts fail instead of silently producing another null
+        let options = arrow::compute::CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+        arrow::compute::cast_with_options(&value, data_type, &options)
+            .map_err(|e| anyhow::anyhow!("Cannot fill {} with {:?}: {}", data_type, self, e))
+    }
+}
+
+/// Reduction applied by `Record::resample` to the rows of each time bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleMethod {
+    /// Keep the first sample of each bucket
+    Downsample,
+    /// Average the numeric columns of each bucket, other columns keep their last value
+    Mean,
+    /// Keep the most recent sample of each bucket
+    Last,
+}
+
+/// One-sided frequency spectrum returned by `Record::compute_fft`
+#[cfg(feature = "fft")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FftResult {
+    /// Frequency of each bin in Hz, from 0 up to the Nyquist frequency
+    pub frequencies: Vec<f64>,
+    /// Amplitude of each bin
+    pub magnitudes: Vec<f64>,
+}
+
+/// How `Record::join_on_row_index` handles a column name present in both Records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnCollisionPolicy {
+    /// Rename the left column to `left_{name}`
+    PrefixLeft,
+    /// Rename the right column to `right_{name}`
+    PrefixRight,
+    /// Fail the join
+    Error,
+}
+
+/// Describes how the schema of a Record changed relative to a previous one.
+/// Only top-level fields are compared, schema metadata (topic, flag, ...) is ignored.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SchemaDrift {
+    pub added_fields: Vec<String>,
+    pub removed_fields: Vec<String>,
+    pub type_changes: Vec<(String, DataType, DataType)>,
+}
+
+impl SchemaDrift {
+    /// Compare two schemas, returning `None` if their fields are identical
+    pub fn between(previous: &Schema, current: &Schema) -> Option<Self> {
+        let mut drift = SchemaDrift::default();
+
+        for field in current.fields() {
+            match previous.field_with_name(field.name()) {
+                Ok(prev_field) => {
+                    if prev_field.data_type() != field.data_type() {
+                        drift.type_changes.push((
+                            field.name().clone(),
+                            prev_field.data_type().clone(),
+                            field.data_type().clone(),
+                        ));
+                    }
+                }
+                Err(_) => drift.added_fields.push(field.name().clone()),
+            }
+        }
+
+        for field in previous.fields() {
+            if current.field_with_name(field.name()).is_err() {
+                drift.removed_fields.push(field.name().clone());
+            }
+        }
+
+        if drift.is_empty() {
+            None
+        } else {
+            Some(drift)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_fields.is_empty()
+            && self.removed_fields.is_empty()
+            && self.type_changes.is_empty()
+    }
+}
+
+impl std::fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "added: {:?}, removed: {:?}",
+            self.added_fields, self.removed_fields
+        )?;
+        for (name, from, to) in &self.type_changes {
+            write!(f, ", {}: {} -> {}", name, from, to)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct Record {
     record_batch: RecordBatch,
@@ -256,6 +834,10 @@ pub struct Record {
 pub enum RecordFlag {
     PublishPacket,
     SubscribePacket,
+    /// A published `ErrorRecord`, routed like a publish and logged by the runner
+    ErrorPacket,
+    /// Removes the sending task's subscriptions matching the topic, see `unsubscribe!`
+    UnsubscribePacket,
 }
 
 impl std::fmt::Display for RecordFlag {
@@ -271,6 +853,8 @@ impl FromStr for RecordFlag {
         Ok(match s {
             "PublishPacket" => RecordFlag::PublishPacket,
             "SubscribePacket" => RecordFlag::SubscribePacket,
+            "ErrorPacket" => RecordFlag::ErrorPacket,
+            "UnsubscribePacket" => RecordFlag::UnsubscribePacket,
             _ => return Err(anyhow::anyhow!("Invalid record flag: {}", s)),
         })
     }
@@ -286,6 +870,12 @@ pub enum RecordError {
 
     #[error("Flag metadata not set")]
     FlagMetadataNotSet,
+
+    #[error("Column '{0}' not found")]
+    ColumnNotFound(String),
+
+    #[error("Column '{column}' of type {data_type} cannot be sorted, expected a numeric or temporal column")]
+    UnsupportedSortColumn { column: String, data_type: DataType },
 }
 
 impl Record {
@@ -323,6 +913,62 @@ impl Record {
         Ok(Self { record_batch })
     }
 
+    /// Creates a multi-row Record from a slice of serializable items.
+    ///
+    /// The schema is inferred from the first item and all items are encoded
+    /// into a single batch, which is cheaper than concatenating single-row Records.
+    pub fn from_serde_batch<T: serde::Serialize>(items: &[T]) -> Result<Self, anyhow::Error> {
+        let first = items
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("Cannot create a record from an empty batch"))?;
+
+        let inferred_schema =
+            infer_json_schema_from_iterator(std::iter::once(Ok(to_value(first)?)))?;
+        let mut decoder = ReaderBuilder::new(Arc::new(inferred_schema)).build_decoder()?;
+        decoder.serialize(items)?;
+
+        let record_batch = decoder
+            .flush()?
+            .ok_or_else(|| anyhow::anyhow!("Failed to create record batch"))?;
+
+        Ok(Self { record_batch })
+    }
+
+    /// Creates a multi-row Record from an iterator of serializable items.
+    ///
+    /// Items are streamed through the decoder one at a time and flushed in chunks,
+    /// so the whole input never has to be collected first. The schema is inferred from the first item.
+    pub fn from_serde_iter<T: serde::Serialize, I: IntoIterator<Item = T>>(
+        iter: I,
+    ) -> Result<Self, anyhow::Error> {
+        const FLUSH_ROWS: usize = 1024;
+
+        let mut iter = iter.into_iter();
+        let first = iter
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Cannot create a record from an empty iterator"))?;
+
+        let inferred_schema = Arc::new(infer_json_schema_from_iterator(std::iter::once(Ok(
+            to_value(&first)?,
+        )))?);
+        let mut decoder = ReaderBuilder::new(inferred_schema.clone())
+            .with_batch_size(FLUSH_ROWS)
+            .build_decoder()?;
+
+        let mut batches = Vec::new();
+        decoder.serialize(std::slice::from_ref(&first))?;
+        for item in iter {
+            decoder.serialize(std::slice::from_ref(&item))?;
+            if decoder.len() >= FLUSH_ROWS {
+                batches.extend(decoder.flush()?);
+            }
+        }
+        batches.extend(decoder.flush()?);
+
+        let record_batch = arrow::compute::concat_batches(&inferred_schema, &batches)?;
+        Ok(Self { record_batch })
+    }
+
     /// Creates a Record from a JSON string
     ///
     /// This method parses a JSON string and creates a Record from it.
@@ -331,16 +977,14 @@ impl Record {
         // Parse the JSON string to a serde_json::Value
         let json_value: serde_json::Value = serde_json::from_str(json_str)?;
 
-        // If this is an array with a single item, unwrap it to avoid serialization issues
-        let single_item = match &json_value {
-            serde_json::Value::Array(items) if items.len() == 1 => items[0].clone(),
-            _ => json_value.clone(),
+        // Infer schema from the JSON value, or from every item of an array
+        let inferred_schema = match &json_value {
+            serde_json::Value::Array(items) => {
+                infer_json_schema_from_iterator(items.iter().cloned().map(Ok))?
+            }
+            _ => infer_json_schema_from_iterator(std::iter::once(Ok(json_value.clone())))?,
         };
 
-        // Infer schema from the JSON value
-        let inferred_schema =
-            infer_json_schema_from_iterator(std::iter::once(Ok(single_item.clone())))?;
-
         // Create a decoder with the inferred schema
         let mut decoder = ReaderBuilder::new(Arc::new(inferred_schema)).build_decoder()?;
 
@@ -361,6 +1005,129 @@ impl Record {
         Ok(Self { record_batch })
     }
 
+    /// Creates a single-row Record from environment variables.
+    ///
+    /// Each field is read from `{PREFIX}_{FIELD_NAME}` (upper-cased, with `.` replaced by `_`)
+    /// and parsed as the field's declared type. Missing variables become null for nullable fields.
+    pub fn from_environment_variables(
+        prefix: &str,
+        schema: &Schema,
+    ) -> Result<Self, anyhow::Error> {
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+        for field in schema.fields() {
+            let var_name = format!("{}_{}", prefix, field.name())
+                .to_uppercase()
+                .replace(PATH_SEPARATOR, "_");
+
+            let value = match std::env::var(&var_name) {
+                Ok(value) => Some(value),
+                Err(std::env::VarError::NotPresent) if field.is_nullable() => None,
+                Err(e) => {
+                    return Err(anyhow::anyhow!(
+                        "Failed to read environment variable {}: {}",
+                        var_name,
+                        e
+                    ))
+                }
+            };
+
+            columns.push(parse_string_value(value, field, &var_name)?);
+        }
+
+        let record_batch = RecordBatch::try_new(Arc::new(schema.clone()), columns)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Render one row as an HTML form for editing its values.
+    ///
+    /// Numeric fields become `number` inputs, booleans `checkbox` inputs and everything else
+    /// `text` inputs. Each input is named after its field, null values have a `null` placeholder.
+    pub fn to_html_form(&self, row_index: usize, form_id: &str) -> Result<String, anyhow::Error> {
+        if row_index >= self.record_batch.num_rows() {
+            return Err(anyhow::anyhow!(
+                "Row {} out of range for record with {} rows",
+                row_index,
+                self.record_batch.num_rows()
+            ));
+        }
+
+        let mut html = format!("<form id=\"{}\">\n", escape_html(form_id));
+        for (field, column) in self
+            .record_batch
+            .schema()
+            .fields()
+            .iter()
+            .zip(self.record_batch.columns())
+        {
+            let name = escape_html(field.name());
+            let input = if field.data_type() == &DataType::Boolean {
+                let checked = !column.is_null(row_index) && column.as_boolean().value(row_index);
+                format!(
+                    "<input type=\"checkbox\" name=\"{}\" value=\"true\"{}>",
+                    name,
+                    if checked { " checked" } else { "" }
+                )
+            } else {
+                let input_type = if field.data_type().is_numeric() {
+                    "number\" step=\"any"
+                } else {
+                    "text"
+                };
+                if column.is_null(row_index) {
+                    format!(
+                        "<input type=\"{}\" name=\"{}\" placeholder=\"null\">",
+                        input_type, name
+                    )
+                } else {
+                    let value =
+                        ArrayFormatter::try_new(column.as_ref(), &FormatOptions::default())?
+                            .value(row_index)
+                            .to_string();
+                    format!(
+                        "<input type=\"{}\" name=\"{}\" value=\"{}\">",
+                        input_type,
+                        name,
+                        escape_html(&value)
+                    )
+                }
+            };
+            html.push_str(&format!("  <label>{} {}</label>\n", name, input));
+        }
+        html.push_str("</form>\n");
+        Ok(html)
+    }
+
+    /// Build a single row Record from submitted form data, the inverse of `to_html_form`.
+    ///
+    /// Values are parsed as the schema's field types. Booleans follow checkbox semantics: a
+    /// missing value is false. Missing values, and empty values of non-string fields, become null.
+    pub fn from_html_form_data(
+        form_data: &HashMap<String, String>,
+        schema: &Schema,
+    ) -> Result<Self, anyhow::Error> {
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+        for field in schema.fields() {
+            let value = form_data.get(field.name());
+            let value = match field.data_type() {
+                DataType::Boolean => {
+                    Some(matches!(value.map(String::as_str), Some("true" | "on" | "1")).to_string())
+                }
+                DataType::Utf8 | DataType::LargeUtf8 => value.cloned(),
+                _ => value.filter(|v| !v.is_empty()).cloned(),
+            };
+            if value.is_none() && !field.is_nullable() {
+                return Err(anyhow::anyhow!(
+                    "Missing value for non-nullable field '{}'",
+                    field.name()
+                ));
+            }
+            columns.push(parse_string_value(value, field, field.name())?);
+        }
+
+        let record_batch = RecordBatch::try_new(Arc::new(schema.clone()), columns)?;
+        Ok(Self { record_batch })
+    }
+
     pub fn from_record_batch(record_batch: RecordBatch) -> Self {
         Self { record_batch }
     }
@@ -426,6 +1193,28 @@ impl Record {
             .ok_or(RecordError::TopicMetadataNotSet)
     }
 
+    /// Set a schema metadata entry, replacing any previous value
+    pub fn set_metadata(&mut self, key: &str, value: String) -> Result<(), anyhow::Error> {
+        let mut metadata = self.record_batch.schema().metadata().clone();
+        metadata.insert(key.to_string(), value);
+        let new_schema = arrow::datatypes::Schema::new_with_metadata(
+            self.record_batch.schema().fields().clone(),
+            metadata,
+        );
+        let columns = self.record_batch.columns().to_vec();
+        self.record_batch = RecordBatch::try_new(std::sync::Arc::new(new_schema), columns)?;
+        Ok(())
+    }
+
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata().get(key).map(|v| v.as_str())
+    }
+
+    /// All schema metadata entries, including `topic` and `flag`
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        self.record_batch.schema_ref().metadata()
+    }
+
     pub fn set_flag(&mut self, flag: RecordFlag) -> Result<(), anyhow::Error> {
         let mut metadata = self.record_batch.schema().metadata().clone();
         metadata.insert("flag".to_string(), flag.to_string());
@@ -458,88 +1247,1843 @@ impl Record {
         Ok(Self::from_record_batch(record_batch))
     }
 
-    pub fn to_serde<T: DeserializeOwned>(&self) -> Result<Vec<T>, anyhow::Error> {
-        let record_batch = self.to_record_batch_cloned();
-
-        // Use arrow_json::ArrayWriter to convert record batch to JSON
-        let buf = Vec::new();
-        let mut writer = arrow::json::writer::ArrayWriter::new(buf);
-        writer.write_batches(&[&record_batch])?;
-        writer.finish()?;
-        let json_data = writer.into_inner();
-
-        // Parse the JSON string as an array of objects
-        let json_values: Vec<serde_json::Value> = serde_json::from_slice(&json_data)?;
+    /// Reorder all rows by the values of a numeric or timestamp column, nulls last.
+    /// Schema metadata such as the topic and flag is preserved.
+    pub fn sort_by_column(&self, column: &str, ascending: bool) -> Result<Self, RecordError> {
+        let array = self.sortable_column(column)?;
+        let options = arrow::compute::SortOptions {
+            descending: !ascending,
+            nulls_first: false,
+        };
+        let indices = arrow::compute::sort_to_indices(array, Some(options), None)
+            .map_err(anyhow::Error::from)?;
+        let columns = self
+            .record_batch
+            .columns()
+            .iter()
+            .map(|c| arrow::compute::take(c, &indices, None))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(anyhow::Error::from)?;
+        let record_batch = RecordBatch::try_new(self.record_batch.schema(), columns)
+            .map_err(anyhow::Error::from)?;
+        Ok(Self { record_batch })
+    }
 
-        if json_values.is_empty() {
-            return Err(anyhow::anyhow!("No data in record batch"));
+    /// Cheap check that a numeric or timestamp column is in ascending order, comparing only
+    /// its first and last value. A null at either end counts as unsorted.
+    pub fn is_sorted_by_column(&self, column: &str) -> Result<bool, RecordError> {
+        let array = self.sortable_column(column)?;
+        if array.len() < 2 {
+            return Ok(true);
         }
+        let first = array.slice(0, 1);
+        let last = array.slice(array.len() - 1, 1);
+        let in_order =
+            arrow::compute::kernels::cmp::lt_eq(&first, &last).map_err(anyhow::Error::from)?;
+        Ok(in_order.is_valid(0) && in_order.value(0))
+    }
 
-        // Deserialize the first row
-        let result: Vec<T> = serde_json::from_slice(&json_data)?;
+    fn sortable_column(&self, column: &str) -> Result<&ArrayRef, RecordError> {
+        let array = self
+            .record_batch
+            .column_by_name(column)
+            .ok_or_else(|| RecordError::ColumnNotFound(column.to_string()))?;
+        let temporal = matches!(
+            array.data_type(),
+            DataType::Timestamp(_, _)
+                | DataType::Date32
+                | DataType::Date64
+                | DataType::Time32(_)
+                | DataType::Time64(_)
+                | DataType::Duration(_)
+        );
+        if array.data_type().is_numeric() || temporal {
+            Ok(array)
+        } else {
+            Err(RecordError::UnsupportedSortColumn {
+                column: column.to_string(),
+                data_type: array.data_type().clone(),
+            })
+        }
+    }
+
+    /// Combine two Records with the same row count side by side.
+    /// Colliding column names are resolved by `collision_policy`; the result keeps the metadata of `left`.
+    pub fn join_on_row_index(
+        left: &Record,
+        right: &Record,
+        collision_policy: ColumnCollisionPolicy,
+    ) -> Result<Record, anyhow::Error> {
+        let (left_batch, right_batch) = (&left.record_batch, &right.record_batch);
+        if left_batch.num_rows() != right_batch.num_rows() {
+            return Err(anyhow::anyhow!(
+                "Cannot join records with different row counts ({} vs {})",
+                left_batch.num_rows(),
+                right_batch.num_rows()
+            ));
+        }
+
+        let left_schema = left_batch.schema();
+        let right_schema = right_batch.schema();
+        let collides = |name: &str, other: &Schema| other.column_with_name(name).is_some();
+
+        let mut fields: Vec<Field> = Vec::new();
+        for field in left_schema.fields() {
+            let name = if collides(field.name(), &right_schema) {
+                match collision_policy {
+                    ColumnCollisionPolicy::PrefixLeft => format!("left_{}", field.name()),
+                    ColumnCollisionPolicy::PrefixRight => field.name().clone(),
+                    ColumnCollisionPolicy::Error => {
+                        return Err(anyhow::anyhow!(
+                            "Column '{}' exists in both records",
+                            field.name()
+                        ))
+                    }
+                }
+            } else {
+                field.name().clone()
+            };
+            fields.push(field.as_ref().clone().with_name(name));
+        }
+        for field in right_schema.fields() {
+            let name = match collision_policy {
+                ColumnCollisionPolicy::PrefixRight if collides(field.name(), &left_schema) => {
+                    format!("right_{}", field.name())
+                }
+                _ => field.name().clone(),
+            };
+            fields.push(field.as_ref().clone().with_name(name));
+        }
+
+        let mut columns = left_batch.columns().to_vec();
+        columns.extend(right_batch.columns().iter().cloned());
+
+        let schema = Schema::new_with_metadata(fields, left_schema.metadata().clone());
+        let record_batch = RecordBatch::try_new(Arc::new(schema), columns)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Rows of `self` and `other` whose `left_key` and `right_key` values are equal, side by side.
+    /// A left row matching several right rows is repeated, null keys never match.
+    /// Column names present in both records are prefixed `left_` and `right_`,
+    /// the result keeps the metadata of `self`.
+    pub fn inner_join(
+        &self,
+        other: &Record,
+        left_key: &str,
+        right_key: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let mut left_rows = Vec::new();
+        let mut right_rows = Vec::new();
+        for (left_row, matches) in self
+            .key_matches(other, left_key, right_key)?
+            .iter()
+            .enumerate()
+        {
+            for right_row in matches {
+                left_rows.push(left_row as u32);
+                right_rows.push(Some(*right_row));
+            }
+        }
+        self.take_joined(other, left_rows, right_rows)
+    }
+
+    /// Like `inner_join`, but keeps left rows without a match with nulls in the right columns
+    pub fn left_join(
+        &self,
+        other: &Record,
+        left_key: &str,
+        right_key: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let mut left_rows = Vec::new();
+        let mut right_rows = Vec::new();
+        for (left_row, matches) in self
+            .key_matches(other, left_key, right_key)?
+            .iter()
+            .enumerate()
+        {
+            if matches.is_empty() {
+                left_rows.push(left_row as u32);
+                right_rows.push(None);
+            }
+            for right_row in matches {
+                left_rows.push(left_row as u32);
+                right_rows.push(Some(*right_row));
+            }
+        }
+        self.take_joined(other, left_rows, right_rows)
+    }
+
+    /// Time series join: every row of `self` is matched with the row of `other` whose key is
+    /// nearest to its own, at most `tolerance` away, preferring the earlier row on a tie.
+    /// Keys must be integer or temporal columns, compared in their raw integer units.
+    /// Left rows without a match within the tolerance get nulls in the right columns.
+    pub fn asof_join(
+        &self,
+        other: &Record,
+        left_key: &str,
+        right_key: &str,
+        tolerance: i64,
+    ) -> Result<Self, anyhow::Error> {
+        if tolerance < 0 {
+            return Err(anyhow::anyhow!(
+                "As-of join tolerance must not be negative, got {}",
+                tolerance
+            ));
+        }
+        let left_keys = self.asof_key_values(left_key)?;
+        let right_keys = other.asof_key_values(right_key)?;
+
+        // Stable sort, so rows with equal keys stay in their original order
+        let mut right_sorted: Vec<(i64, u32)> = right_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(row, key)| key.map(|key| (key, row as u32)))
+            .collect();
+        right_sorted.sort_by_key(|(key, _)| *key);
+
+        let right_rows = left_keys
+            .iter()
+            .map(|key| {
+                let key = (*key)?;
+                let after = right_sorted.partition_point(|(right, _)| *right < key);
+                let before = after.checked_sub(1).map(|i| right_sorted[i]);
+                [before, right_sorted.get(after).copied()]
+                    .into_iter()
+                    .flatten()
+                    .filter(|(right, _)| right.abs_diff(key) <= tolerance as u64)
+                    .min_by_key(|(right, _)| right.abs_diff(key))
+                    .map(|(_, row)| row)
+            })
+            .collect();
+        let left_rows = (0..left_keys.len() as u32).collect();
+        self.take_joined(other, left_rows, right_rows)
+    }
+
+    /// For every row of `self`, the rows of `other` with an equal key.
+    /// The right key is cast to the type of the left key so e.g. Int32 and Int64 keys join.
+    fn key_matches(
+        &self,
+        other: &Record,
+        left_key: &str,
+        right_key: &str,
+    ) -> Result<Vec<Vec<u32>>, anyhow::Error> {
+        let left_keys = self
+            .record_batch
+            .column_by_name(left_key)
+            .ok_or_else(|| RecordError::ColumnNotFound(left_key.to_string()))?;
+        let right_keys = other
+            .record_batch
+            .column_by_name(right_key)
+            .ok_or_else(|| RecordError::ColumnNotFound(right_key.to_string()))?;
+        let right_keys = arrow::compute::cast(right_keys, left_keys.data_type())?;
+
+        let converter = RowConverter::new(vec![SortField::new(left_keys.data_type().clone())])?;
+        let left_rows = converter.convert_columns(std::slice::from_ref(left_keys))?;
+        let right_rows = converter.convert_columns(std::slice::from_ref(&right_keys))?;
+
+        let mut right_index: HashMap<Row, Vec<u32>> = HashMap::new();
+        for row in 0..right_rows.num_rows() {
+            if right_keys.is_valid(row) {
+                right_index
+                    .entry(right_rows.row(row))
+                    .or_default()
+                    .push(row as u32);
+            }
+        }
+        Ok((0..left_rows.num_rows())
+            .map(|row| {
+                if left_keys.is_valid(row) {
+                    right_index
+                        .get(&left_rows.row(row))
+                        .cloned()
+                        .unwrap_or_default()
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect())
+    }
+
+    fn asof_key_values(&self, column: &str) -> Result<Vec<Option<i64>>, anyhow::Error> {
+        let array = self.sortable_column(column)?;
+        if array.data_type().is_floating() {
+            return Err(anyhow::anyhow!(
+                "As-of join key '{}' must be an integer or temporal column, got {}",
+                column,
+                array.data_type()
+            ));
+        }
+        let keys = arrow::compute::cast(array, &DataType::Int64)?;
+        Ok(keys
+            .as_primitive::<arrow::datatypes::Int64Type>()
+            .iter()
+            .collect())
+    }
+
+    /// Joined record of the given row pairs, a `None` right row is all nulls
+    fn take_joined(
+        &self,
+        other: &Record,
+        left_rows: Vec<u32>,
+        right_rows: Vec<Option<u32>>,
+    ) -> Result<Self, anyhow::Error> {
+        let left_schema = self.record_batch.schema();
+        let right_schema = other.record_batch.schema();
+        let left_indices = UInt32Array::from(left_rows);
+        let right_indices = UInt32Array::from(right_rows);
+
+        let mut fields: Vec<Field> = Vec::new();
+        let mut columns: Vec<ArrayRef> = Vec::new();
+        for (field, column) in left_schema.fields().iter().zip(self.record_batch.columns()) {
+            let name = match right_schema.column_with_name(field.name()) {
+                Some(_) => format!("left_{}", field.name()),
+                None => field.name().clone(),
+            };
+            fields.push(field.as_ref().clone().with_name(name));
+            columns.push(arrow::compute::take(column, &left_indices, None)?);
+        }
+        for (field, column) in right_schema
+            .fields()
+            .iter()
+            .zip(other.record_batch.columns())
+        {
+            let name = match left_schema.column_with_name(field.name()) {
+                Some(_) => format!("right_{}", field.name()),
+                None => field.name().clone(),
+            };
+            // Unmatched rows of left and as-of joins leave the right columns null
+            fields.push(field.as_ref().clone().with_name(name).with_nullable(true));
+            columns.push(arrow::compute::take(column, &right_indices, None)?);
+        }
+
+        let schema = Schema::new_with_metadata(fields, left_schema.metadata().clone());
+        let record_batch = RecordBatch::try_new(Arc::new(schema), columns)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Path descriptor naming this record's topic, as served by `flight::FlightServer`
+    #[cfg(feature = "arrow-flight")]
+    pub fn to_arrow_flight_descriptor(
+        &self,
+    ) -> Result<arrow_flight::FlightDescriptor, RecordError> {
+        Ok(arrow_flight::FlightDescriptor::new_path(vec![
+            self.try_get_topic()?
+        ]))
+    }
+
+    /// Compute the frequency spectrum of a numeric column sampled at `sample_rate_hz`.
+    /// A Hann window is applied before the FFT and null values are skipped.
+    #[cfg(feature = "fft")]
+    pub fn compute_fft(
+        &self,
+        column: &str,
+        sample_rate_hz: f64,
+    ) -> Result<FftResult, anyhow::Error> {
+        use rustfft::{num_complex::Complex, FftPlanner};
+
+        let array = self
+            .record_batch
+            .column_by_name(column)
+            .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", column))?;
+        if !array.data_type().is_numeric() {
+            return Err(anyhow::anyhow!(
+                "Column '{}' is not numeric ({})",
+                column,
+                array.data_type()
+            ));
+        }
+        let values = arrow::compute::cast(array, &DataType::Float64)?;
+        let samples: Vec<f64> = values
+            .as_primitive::<Float64Type>()
+            .iter()
+            .flatten()
+            .collect();
+
+        let n = samples.len();
+        if n < 2 {
+            return Err(anyhow::anyhow!(
+                "At least two samples are needed for an FFT, column '{}' has {}",
+                column,
+                n
+            ));
+        }
+
+        // Hann window to reduce spectral leakage
+        let window: Vec<f64> = (0..n)
+            .map(|i| 0.5 * (1.0 - (2.0 * std::f64::consts::PI * i as f64 / (n - 1) as f64).cos()))
+            .collect();
+        let window_sum: f64 = window.iter().sum();
+        let mut buffer: Vec<Complex<f64>> = samples
+            .iter()
+            .zip(&window)
+            .map(|(sample, w)| Complex::new(sample * w, 0.0))
+            .collect();
+
+        FftPlanner::new().plan_fft_forward(n).process(&mut buffer);
+
+        // One-sided spectrum, scaled so a sine of amplitude A peaks at roughly A
+        let bins = n / 2 + 1;
+        let frequencies = (0..bins)
+            .map(|k| k as f64 * sample_rate_hz / n as f64)
+            .collect();
+        let magnitudes = buffer[..bins]
+            .iter()
+            .enumerate()
+            .map(|(k, value)| {
+                let one_sided = if k == 0 || (n.is_multiple_of(2) && k == n / 2) {
+                    1.0
+                } else {
+                    2.0
+                };
+                value.norm() * one_sided / window_sum
+            })
+            .collect();
+
+        Ok(FftResult {
+            frequencies,
+            magnitudes,
+        })
+    }
+
+    /// Run-length encode a column into a Record with a `values` column (one entry per run)
+    /// and an Int32 `lengths` column. Also returns the original row count, needed to decode.
+    pub fn encode_run_length(&self, column: &str) -> Result<(Record, usize), anyhow::Error> {
+        let array = self
+            .record_batch
+            .column_by_name(column)
+            .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", column))?;
+
+        let partitions = arrow::compute::partition(std::slice::from_ref(array))?;
+        let ranges = partitions.ranges();
+        let starts = UInt32Array::from_iter_values(ranges.iter().map(|r| r.start as u32));
+        let lengths = arrow::array::Int32Array::from_iter_values(
+            ranges.iter().map(|r| (r.end - r.start) as i32),
+        );
+        let values = arrow::compute::take(array.as_ref(), &starts, None)?;
+
+        let mut metadata = self.record_batch.schema().metadata().clone();
+        metadata.insert(RUN_LENGTH_COLUMN_METADATA.to_string(), column.to_string());
+        let schema = Schema::new_with_metadata(
+            vec![
+                Field::new("values", array.data_type().clone(), true),
+                Field::new("lengths", DataType::Int32, false),
+            ],
+            metadata,
+        );
+        let record_batch = RecordBatch::try_new(Arc::new(schema), vec![values, Arc::new(lengths)])?;
+
+        Ok((Self { record_batch }, array.len()))
+    }
+
+    /// Expand a Record created by `encode_run_length` back into the original column
+    pub fn decode_run_length(
+        encoded: &Record,
+        original_rows: usize,
+    ) -> Result<Record, anyhow::Error> {
+        let batch = &encoded.record_batch;
+        let values = batch
+            .column_by_name("values")
+            .ok_or_else(|| anyhow::anyhow!("Run-length record has no 'values' column"))?;
+        let lengths = batch
+            .column_by_name("lengths")
+            .and_then(|c| c.as_primitive_opt::<arrow::datatypes::Int32Type>())
+            .ok_or_else(|| anyhow::anyhow!("Run-length record has no Int32 'lengths' column"))?;
+
+        let total: usize = lengths.values().iter().map(|l| *l as usize).sum();
+        if total != original_rows {
+            return Err(anyhow::anyhow!(
+                "Run lengths add up to {} rows, expected {}",
+                total,
+                original_rows
+            ));
+        }
+
+        let indices = UInt32Array::from_iter_values(
+            lengths
+                .values()
+                .iter()
+                .enumerate()
+                .flat_map(|(i, length)| std::iter::repeat_n(i as u32, *length as usize)),
+        );
+        let decoded = arrow::compute::take(values.as_ref(), &indices, None)?;
+
+        let mut metadata = batch.schema().metadata().clone();
+        let column_name = metadata
+            .remove(RUN_LENGTH_COLUMN_METADATA)
+            .unwrap_or_else(|| "values".to_string());
+        let schema = Schema::new_with_metadata(
+            vec![Field::new(column_name, decoded.data_type().clone(), true)],
+            metadata,
+        );
+        let record_batch = RecordBatch::try_new(Arc::new(schema), vec![decoded])?;
+        Ok(Self { record_batch })
+    }
+
+    /// Compare the schemas of two sequential Records for added, removed or retyped fields.
+    /// Returns `None` if the schemas are identical.
+    pub fn detect_schema_drift(previous: &Record, current: &Record) -> Option<SchemaDrift> {
+        SchemaDrift::between(
+            previous.record_batch.schema().as_ref(),
+            current.record_batch.schema().as_ref(),
+        )
+    }
+
+    /// Append a Binary column holding one MessagePack-encoded value per row.
+    ///
+    /// Useful for payloads that would otherwise explode into many columns.
+    /// `values` must have one entry per row of the Record.
+    pub fn encode_binary_column<T: serde::Serialize>(
+        &self,
+        values: &[T],
+        column_name: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let num_rows = self.record_batch.num_rows();
+        if values.len() != num_rows {
+            return Err(anyhow::anyhow!(
+                "Cannot encode {} values into column '{}' of a record with {} rows",
+                values.len(),
+                column_name,
+                num_rows
+            ));
+        }
+
+        let encoded = values
+            .iter()
+            .map(rmp_serde::to_vec_named)
+            .collect::<Result<Vec<_>, _>>()?;
+        let binary_array = BinaryArray::from_iter_values(encoded.iter());
+
+        self.with_appended_column(
+            Field::new(column_name, DataType::Binary, false),
+            Arc::new(binary_array),
+        )
+    }
+
+    /// Append a column to a copy of this Record, keeping the schema metadata
+    fn with_appended_column(&self, field: Field, column: ArrayRef) -> Result<Self, anyhow::Error> {
+        let schema = self.record_batch.schema();
+        if schema.column_with_name(field.name()).is_some() {
+            return Err(anyhow::anyhow!("Column '{}' already exists", field.name()));
+        }
+
+        let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+        fields.push(field);
+        let mut columns = self.record_batch.columns().to_vec();
+        columns.push(column);
+
+        let new_schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+        let record_batch = RecordBatch::try_new(Arc::new(new_schema), columns)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Append a `Timestamp(Nanosecond)` column holding the current wall clock time.
+    /// Every row gets the same stamp, the instant of the call.
+    pub fn add_timestamp_column(&self, column_name: &str) -> Result<Self, anyhow::Error> {
+        let now_ns = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos() as i64;
+        let stamps = TimestampNanosecondArray::from(vec![now_ns; self.record_batch.num_rows()]);
+        self.with_appended_column(
+            Field::new(column_name, stamps.data_type().clone(), false),
+            Arc::new(stamps),
+        )
+    }
+
+    /// Append a `UInt64` column counting up from `start`, one per row
+    pub fn add_sequence_column(
+        &self,
+        column_name: &str,
+        start: u64,
+    ) -> Result<Self, anyhow::Error> {
+        let num_rows = self.record_batch.num_rows() as u64;
+        let sequence = UInt64Array::from_iter_values(start..start + num_rows);
+        self.with_appended_column(
+            Field::new(column_name, DataType::UInt64, false),
+            Arc::new(sequence),
+        )
+    }
+
+    /// Apply a compute kernel to `input_column` and append the result as `output_column`
+    pub fn apply_compute_kernel(
+        &self,
+        input_column: &str,
+        kernel: ComputeKernel,
+        output_column: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let input = self
+            .record_batch
+            .column_by_name(input_column)
+            .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", input_column))?;
+        let output = kernel.apply(input)?;
+
+        let field = Field::new(output_column, output.data_type().clone(), true);
+        self.with_appended_column(field, output)
+    }
+
+    /// Apply a sequence of `(input_column, kernel, output_column)` operations in order.
+    /// Later operations can use the output columns of earlier ones.
+    pub fn apply_compute_kernels(
+        &self,
+        ops: &[(String, ComputeKernel, String)],
+    ) -> Result<Self, anyhow::Error> {
+        let mut record = self.clone();
+        for (input_column, kernel, output_column) in ops {
+            record = record.apply_compute_kernel(input_column, *kernel, output_column)?;
+        }
+        Ok(record)
+    }
+
+    /// Slide a window of `window` rows over `column` and append `f` of each window as
+    /// `output_column`. The first `window - 1` rows, and windows containing a null, are null.
+    pub fn rolling_apply<F>(
+        &self,
+        window: usize,
+        column: &str,
+        output_column: &str,
+        f: F,
+    ) -> Result<Self, anyhow::Error>
+    where
+        F: Fn(&[f64]) -> f64,
+    {
+        if window == 0 {
+            return Err(anyhow::anyhow!("Rolling window must be at least 1 row"));
+        }
+        let array = self
+            .record_batch
+            .column_by_name(column)
+            .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", column))?;
+        if !array.data_type().is_numeric() {
+            return Err(anyhow::anyhow!(
+                "Column '{}' is not numeric ({})",
+                column,
+                array.data_type()
+            ));
+        }
+        let values = arrow::compute::cast(array, &DataType::Float64)?;
+        let values: Vec<Option<f64>> = values.as_primitive::<Float64Type>().iter().collect();
+
+        let mut builder = Float64Builder::with_capacity(values.len());
+        let mut buffer = Vec::with_capacity(window);
+        for end in 0..values.len() {
+            if end + 1 < window {
+                builder.append_null();
+                continue;
+            }
+            buffer.clear();
+            buffer.extend(values[end + 1 - window..=end].iter().map_while(|v| *v));
+            if buffer.len() == window {
+                builder.append_value(f(&buffer));
+            } else {
+                builder.append_null();
+            }
+        }
+
+        let field = Field::new(output_column, DataType::Float64, true);
+        self.with_appended_column(field, Arc::new(builder.finish()))
+    }
+
+    /// Resample to one row per `1 / target_hz` seconds of `time_column`.
+    /// Rows are bucketed by `floor(timestamp / bucket_ns)`, timestamps being timestamp columns
+    /// converted to nanoseconds or integer columns taken as nanoseconds, and each bucket is
+    /// reduced with `method`. Rows with a null timestamp are dropped.
+    /// The result is sorted by time and keeps the schema, including its metadata.
+    pub fn resample(
+        &self,
+        time_column: &str,
+        target_hz: f64,
+        method: ResampleMethod,
+    ) -> Result<Self, anyhow::Error> {
+        if !target_hz.is_finite() || target_hz <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Resample rate must be a positive number of Hz, got {}",
+                target_hz
+            ));
+        }
+        let bucket_ns = (1e9 / target_hz).round() as i64;
+        if bucket_ns < 1 {
+            return Err(anyhow::anyhow!(
+                "Resample rate {} Hz is above the nanosecond resolution",
+                target_hz
+            ));
+        }
+
+        let array = self.sortable_column(time_column)?;
+        let array = match array.data_type() {
+            DataType::Timestamp(_, tz) => arrow::compute::cast(
+                array,
+                &DataType::Timestamp(arrow::datatypes::TimeUnit::Nanosecond, tz.clone()),
+            )?,
+            data_type if data_type.is_integer() => array.clone(),
+            data_type => {
+                return Err(anyhow::anyhow!(
+                    "Resample time column '{}' must be a timestamp or integer column, got {}",
+                    time_column,
+                    data_type
+                ))
+            }
+        };
+        let timestamps = arrow::compute::cast(&array, &DataType::Int64)?;
+
+        // Stable sort so that rows with the same timestamp keep their order
+        let mut rows: Vec<(i64, u32)> = timestamps
+            .as_primitive::<arrow::datatypes::Int64Type>()
+            .iter()
+            .enumerate()
+            .filter_map(|(row, timestamp)| timestamp.map(|t| (t, row as u32)))
+            .collect();
+        rows.sort_by_key(|(timestamp, _)| *timestamp);
+
+        let mut buckets: Vec<(i64, Vec<u32>)> = Vec::new();
+        for (timestamp, row) in rows {
+            let bucket = timestamp.div_euclid(bucket_ns);
+            match buckets.last_mut() {
+                Some((last, bucket_rows)) if *last == bucket => bucket_rows.push(row),
+                _ => buckets.push((bucket, vec![row])),
+            }
+        }
+
+        let first_rows = UInt32Array::from_iter_values(buckets.iter().map(|(_, rows)| rows[0]));
+        let last_rows =
+            UInt32Array::from_iter_values(buckets.iter().map(|(_, rows)| rows[rows.len() - 1]));
+
+        let schema = self.record_batch.schema();
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(self.record_batch.columns())
+            .map(|(field, column)| match method {
+                ResampleMethod::Downsample => Ok(arrow::compute::take(column, &first_rows, None)?),
+                ResampleMethod::Mean
+                    if field.name() != time_column && column.data_type().is_numeric() =>
+                {
+                    bucket_means(column, &buckets)
+                }
+                ResampleMethod::Mean | ResampleMethod::Last => {
+                    Ok(arrow::compute::take(column, &last_rows, None)?)
+                }
+            })
+            .collect::<Result<Vec<ArrayRef>, anyhow::Error>>()?;
+
+        let record_batch = RecordBatch::try_new(schema, columns)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Replace the nulls of each column in `fill_values` with its fill value
+    pub fn mask_nulls(
+        &self,
+        fill_values: &HashMap<String, FillValue>,
+    ) -> Result<Self, anyhow::Error> {
+        let schema = self.record_batch.schema();
+        let mut columns = self.record_batch.columns().to_vec();
+        for (column, fill_value) in fill_values {
+            let (index, _) = schema
+                .column_with_name(column)
+                .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", column))?;
+            let array = &columns[index];
+            if array.null_count() == 0 {
+                continue;
+            }
+
+            let fill = fill_value.to_array(array.data_type())?;
+            let is_valid = arrow::compute::is_not_null(array)?;
+            columns[index] = arrow::compute::kernels::zip::zip(
+                &is_valid,
+                array,
+                &arrow::array::Scalar::new(fill),
+            )?;
+        }
+
+        let record_batch = RecordBatch::try_new(schema, columns)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Keep only the rows where every column is non-null
+    pub fn drop_null_rows(&self) -> Self {
+        let batch = &self.record_batch;
+        let mut keep = BooleanArray::from(vec![true; batch.num_rows()]);
+        for column in batch.columns() {
+            if column.null_count() > 0 {
+                let is_valid = arrow::compute::is_not_null(column)
+                    .expect("is_not_null works for every array type");
+                keep = arrow::compute::and(&keep, &is_valid)
+                    .expect("masks have the same length as the batch");
+            }
+        }
+
+        let record_batch = arrow::compute::filter_record_batch(batch, &keep)
+            .expect("mask has the same length as the batch");
+        Self { record_batch }
+    }
+
+    /// Keep the rows for which `predicate(row_index, batch)` returns true.
+    /// Schema metadata such as the topic and flag is preserved.
+    pub fn filter_rows<F>(&self, predicate: F) -> Result<Self, anyhow::Error>
+    where
+        F: Fn(usize, &RecordBatch) -> bool,
+    {
+        let batch = &self.record_batch;
+        let mask: BooleanArray = (0..batch.num_rows())
+            .map(|row| Some(predicate(row, batch)))
+            .collect();
+        let record_batch = arrow::compute::filter_record_batch(batch, &mask)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Keep the rows where `column` equals `value`, which is cast to the column type.
+    /// Supports bool, integer, float, `String` and `&str` values. Null cells never match.
+    pub fn filter_column_eq(&self, column: &str, value: &dyn Any) -> Result<Self, anyhow::Error> {
+        let array = self
+            .record_batch
+            .column_by_name(column)
+            .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", column))?;
+        let value = any_to_array(value)?;
+        let value = arrow::compute::cast(&value, array.data_type()).map_err(|e| {
+            anyhow::anyhow!(
+                "Cannot compare column '{}' of type {}: {}",
+                column,
+                array.data_type(),
+                e
+            )
+        })?;
+
+        let mask = arrow::compute::kernels::cmp::eq(array, &arrow::array::Scalar::new(value))?;
+        let record_batch = arrow::compute::filter_record_batch(&self.record_batch, &mask)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Keep only the named columns, in the requested order.
+    /// Schema metadata such as the topic and flag is preserved.
+    pub fn select_columns(&self, names: &[&str]) -> Result<Self, anyhow::Error> {
+        let schema = self.record_batch.schema();
+        let indices = names
+            .iter()
+            .map(|name| {
+                schema
+                    .index_of(name)
+                    .map_err(|_| anyhow::anyhow!("Column '{}' not found", name))
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        let record_batch = self.record_batch.project(&indices)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Remove the named columns, keeping the others in their current order.
+    /// Schema metadata such as the topic and flag is preserved.
+    pub fn drop_columns(&self, names: &[&str]) -> Result<Self, anyhow::Error> {
+        let schema = self.record_batch.schema();
+        if let Some(missing) = names.iter().find(|name| schema.index_of(name).is_err()) {
+            return Err(anyhow::anyhow!("Column '{}' not found", missing));
+        }
+        let indices: Vec<usize> = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| !names.contains(&field.name().as_str()))
+            .map(|(index, _)| index)
+            .collect();
+        let record_batch = self.record_batch.project(&indices)?;
+        Ok(Self { record_batch })
+    }
+
+    /// Decode a Binary column written by `encode_binary_column` back into its values
+    pub fn decode_binary_column<T: DeserializeOwned>(
+        &self,
+        column_name: &str,
+    ) -> Result<Vec<T>, anyhow::Error> {
+        let column = self
+            .record_batch
+            .column_by_name(column_name)
+            .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", column_name))?;
+        let binary_array = column.as_binary_opt::<i32>().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Column '{}' is not a Binary column ({})",
+                column_name,
+                column.data_type()
+            )
+        })?;
+
+        binary_array
+            .iter()
+            .map(|bytes| {
+                let bytes = bytes.ok_or_else(|| {
+                    anyhow::anyhow!("Null value in binary column '{}'", column_name)
+                })?;
+                Ok(rmp_serde::from_slice(bytes)?)
+            })
+            .collect()
+    }
+
+    pub fn to_serde<T: DeserializeOwned>(&self) -> Result<Vec<T>, anyhow::Error> {
+        let record_batch = self.to_record_batch_cloned();
+
+        // Use arrow_json::ArrayWriter to convert record batch to JSON
+        let buf = Vec::new();
+        let mut writer = arrow::json::writer::ArrayWriter::new(buf);
+        writer.write_batches(&[&record_batch])?;
+        writer.finish()?;
+        let json_data = writer.into_inner();
+
+        // Parse the JSON string as an array of objects
+        let json_values: Vec<serde_json::Value> = serde_json::from_slice(&json_data)?;
+
+        if json_values.is_empty() {
+            return Err(anyhow::anyhow!("No data in record batch"));
+        }
+
+        // Deserialize the first row
+        let result: Vec<T> = serde_json::from_slice(&json_data)?;
         Ok(result)
     }
-}
+}
+
+impl std::fmt::Debug for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Format the Record for debug output
+        let schema = self.record_batch.schema();
+        let num_rows = self.record_batch.num_rows();
+        let num_cols = self.record_batch.num_columns();
+
+        // Try to get topic if available
+        let topic_str = match self.try_get_topic() {
+            Ok(topic) => format!("topic: {}", topic),
+            Err(_) => "topic: <not set>".to_string(),
+        };
+
+        writeln!(
+            f,
+            "Record → [{}] ({} rows, {} columns)",
+            topic_str, num_rows, num_cols
+        )?;
+        writeln!(f, "Schema:")?;
+
+        // Print schema fields
+        for (i, field) in schema.fields().iter().enumerate() {
+            writeln!(f, "  {}: {} ({})", i, field.name(), field.data_type())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Fields, Schema};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestPose {
+        pub x: f64,
+        pub y: f64,
+        pub z: f64,
+    }
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestStruct {
+        pub id: i32,
+        pub name: String,
+        pub value: f64,
+        pub last_pose: TestPose,
+        pub poses: Vec<TestPose>,
+    }
+
+    #[test]
+    fn test_from_serde() {
+        let _ = pretty_env_logger::try_init();
+
+        let test_struct = TestStruct::default();
+        let record = Record::from_serde(&test_struct);
+        assert!(record.is_ok());
+        println!("{:?}", record);
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    struct TestMessage {
+        pub id: i64,
+        pub name: String,
+        pub value: f64,
+    }
+
+    fn test_messages(count: i64) -> Vec<TestMessage> {
+        (0..count)
+            .map(|i| TestMessage {
+                id: i,
+                name: format!("msg_{}", i),
+                value: i as f64 * 0.5,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_from_serde_batch() {
+        let messages = test_messages(1000);
+        let record = Record::from_serde_batch(&messages).unwrap();
+
+        let batch = record.to_record_batch();
+        assert_eq!(batch.num_rows(), 1000);
+        let schema = batch.schema();
+        assert_eq!(
+            schema.field_with_name("id").unwrap().data_type(),
+            &DataType::Int64
+        );
+        assert_eq!(
+            schema.field_with_name("name").unwrap().data_type(),
+            &DataType::Utf8
+        );
+        assert_eq!(
+            schema.field_with_name("value").unwrap().data_type(),
+            &DataType::Float64
+        );
+
+        let values = record.to_serde::<TestMessage>().unwrap();
+        assert_eq!(values.first(), messages.first());
+        assert_eq!(values.last(), messages.last());
+
+        assert!(Record::from_serde_batch::<TestMessage>(&[]).is_err());
+    }
+
+    #[test]
+    fn test_from_serde_iter() {
+        let messages = test_messages(1000);
+        let record = Record::from_serde_iter(messages.clone()).unwrap();
+
+        assert_eq!(record.to_record_batch().num_rows(), 1000);
+        assert_eq!(record.to_record_batch().num_columns(), 3);
+
+        let values = record.to_serde::<TestMessage>().unwrap();
+        assert_eq!(values.first(), messages.first());
+        assert_eq!(values.last(), messages.last());
+    }
+
+    /// Mirrors the layout of MAVLink's OPTICAL_FLOW_DATA
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    struct OpticalFlowData {
+        pub time_usec: u64,
+        pub flow_comp_m_x: f32,
+        pub flow_comp_m_y: f32,
+        pub ground_distance: f32,
+        pub flow_x: i16,
+        pub flow_y: i16,
+        pub sensor_id: u8,
+        pub quality: u8,
+    }
+
+    #[test]
+    fn test_binary_column_round_trip() {
+        let flows: Vec<OpticalFlowData> = (0..3)
+            .map(|i| OpticalFlowData {
+                time_usec: 1_000 * i as u64,
+                flow_comp_m_x: 0.1 * i as f32,
+                flow_comp_m_y: -0.2 * i as f32,
+                ground_distance: 1.5,
+                flow_x: 10 * i as i16,
+                flow_y: -5 * i as i16,
+                sensor_id: 1,
+                quality: 200 + i as u8,
+            })
+            .collect();
+
+        let mut record = Record::from_serde_batch(&test_messages(3)).unwrap();
+        record
+            .set_topic("mavlink/optical_flow".to_string())
+            .unwrap();
+
+        let encoded = record.encode_binary_column(&flows, "payload").unwrap();
+        let schema = encoded.to_record_batch().schema();
+        assert_eq!(
+            schema.field_with_name("payload").unwrap().data_type(),
+            &DataType::Binary
+        );
+        assert_eq!(encoded.try_get_topic().unwrap(), "mavlink/optical_flow");
+
+        let decoded: Vec<OpticalFlowData> = encoded.decode_binary_column("payload").unwrap();
+        assert_eq!(decoded, flows);
+        assert_eq!(decoded[2].quality, 202);
+        assert_eq!(decoded[1].flow_y, -5);
+
+        // Row count mismatch and non-binary columns are rejected
+        assert!(record.encode_binary_column(&flows[..2], "payload").is_err());
+        assert!(encoded
+            .decode_binary_column::<OpticalFlowData>("name")
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_environment_variables() {
+        std::env::set_var("GEOFENCE_TEST_MAX_RADIUS", "150.5");
+        std::env::set_var("GEOFENCE_TEST_MAX_ALT", "120");
+        std::env::set_var("GEOFENCE_TEST_ENABLED", "true");
+        std::env::set_var("GEOFENCE_TEST_NAME", "home_field");
+
+        let schema = Schema::new(vec![
+            Field::new("max_radius", DataType::Float64, false),
+            Field::new("max_alt", DataType::Int32, false),
+            Field::new("enabled", DataType::Boolean, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("min_alt", DataType::Int32, true),
+        ]);
+
+        let record = Record::from_environment_variables("geofence_test", &schema).unwrap();
+        let batch = record.to_record_batch();
+        assert_eq!(batch.num_rows(), 1);
+
+        let max_radius = batch.column(0).as_any().downcast_ref::<Float64Array>();
+        assert_eq!(max_radius.unwrap().value(0), 150.5);
+        let max_alt = batch.column(1).as_any().downcast_ref::<Int32Array>();
+        assert_eq!(max_alt.unwrap().value(0), 120);
+        assert!(batch.column(2).as_boolean().value(0));
+        assert_eq!(batch.column(3).as_string::<i32>().value(0), "home_field");
+        assert!(batch.column(4).is_null(0));
+
+        // Missing non-nullable fields and unparsable values are errors
+        let required = Schema::new(vec![Field::new("missing", DataType::Int32, false)]);
+        assert!(Record::from_environment_variables("geofence_test", &required).is_err());
+        std::env::set_var("GEOFENCE_TEST_BAD", "not a number");
+        let bad = Schema::new(vec![Field::new("bad", DataType::Float64, false)]);
+        assert!(Record::from_environment_variables("geofence_test", &bad).is_err());
+    }
+
+    #[test]
+    fn test_html_form_round_trip() {
+        let schema = Schema::new(vec![
+            Field::new("max_alt", DataType::Int32, false),
+            Field::new("max_radius", DataType::Float64, true),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("enabled", DataType::Boolean, false),
+            Field::new("rtl", DataType::Boolean, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema.clone()),
+            vec![
+                Arc::new(Int32Array::from(vec![120])),
+                Arc::new(Float64Array::from(vec![None::<f64>])),
+                Arc::new(StringArray::from(vec!["home \"field\""])),
+                Arc::new(arrow::array::BooleanArray::from(vec![true])),
+                Arc::new(arrow::array::BooleanArray::from(vec![false])),
+            ],
+        )
+        .unwrap();
+        let record = Record::from_record_batch(batch);
+
+        let html = record.to_html_form(0, "geofence").unwrap();
+        assert!(html.starts_with("<form id=\"geofence\">"));
+        assert!(html.contains(r#"<input type="number" step="any" name="max_alt" value="120">"#));
+        assert!(html
+            .contains(r#"<input type="number" step="any" name="max_radius" placeholder="null">"#));
+        assert!(html.contains(r#"<input type="text" name="name" value="home &quot;field&quot;">"#));
+        assert!(html.contains(r#"<input type="checkbox" name="enabled" value="true" checked>"#));
+        assert!(html.contains(r#"<input type="checkbox" name="rtl" value="true">"#));
+        assert!(record.to_html_form(1, "geofence").is_err());
+
+        // Submit the form like a browser: checked checkboxes and every other input's value
+        let input_regex = regex::Regex::new(
+            r#"<input type="(\w+)"(?: step="any")? name="(\w+)"(?: value="([^"]*)")?(?: placeholder="null")?( checked)?>"#,
+        )
+        .unwrap();
+        let mut form_data = HashMap::new();
+        for capture in input_regex.captures_iter(&html) {
+            if &capture[1] == "checkbox" && capture.get(4).is_none() {
+                continue;
+            }
+            let value = capture.get(3).map_or("", |v| v.as_str());
+            form_data.insert(capture[2].to_string(), value.replace("&quot;", "\""));
+        }
+        assert_eq!(form_data.len(), 4);
+
+        let parsed = Record::from_html_form_data(&form_data, &schema).unwrap();
+        assert_eq!(parsed.to_record_batch(), record.to_record_batch());
+
+        form_data.remove("max_alt");
+        assert!(Record::from_html_form_data(&form_data, &schema).is_err());
+    }
+
+    #[test]
+    fn test_add_timestamp_and_sequence_columns() {
+        let mut record =
+            Record::from_serde_batch(&[TestPose::default(), TestPose::default()]).unwrap();
+        record.set_topic("test/poses".to_string()).unwrap();
+
+        let before_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as i64;
+        let stamped = record
+            .add_timestamp_column("stamp_ns")
+            .unwrap()
+            .add_sequence_column("seq", 10)
+            .unwrap();
+        assert_eq!(stamped.try_get_topic().unwrap(), "test/poses");
+
+        let batch = stamped.to_record_batch();
+        let stamps = batch
+            .column_by_name("stamp_ns")
+            .unwrap()
+            .as_primitive::<arrow::datatypes::TimestampNanosecondType>();
+        assert_eq!(stamps.value(0), stamps.value(1));
+        assert!(stamps.value(0) >= before_ns);
+
+        let sequence = batch
+            .column_by_name("seq")
+            .unwrap()
+            .as_primitive::<arrow::datatypes::UInt64Type>();
+        assert_eq!(sequence.values().to_vec(), vec![10, 11]);
+
+        assert!(stamped.add_sequence_column("seq", 0).is_err());
+    }
+
+    #[test]
+    fn test_apply_compute_kernel_sin() {
+        use std::f64::consts::FRAC_PI_2;
+
+        let schema = Schema::new(vec![Field::new("angle", DataType::Float64, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Float64Array::from(vec![
+                0.0, FRAC_PI_2, -FRAC_PI_2,
+            ]))],
+        )
+        .unwrap();
+        let mut record = Record::from_record_batch(batch);
+        record.set_topic("test/angles".to_string()).unwrap();
+
+        let result = record
+            .apply_compute_kernel("angle", ComputeKernel::Sin, "angle_sin")
+            .unwrap();
+        assert_eq!(result.try_get_topic().unwrap(), "test/angles");
+
+        let batch = result.to_record_batch();
+        let values = batch
+            .column_by_name("angle_sin")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        let expected = [0.0, 1.0, -1.0];
+        for (value, expected) in values.values().iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+
+        assert!(record
+            .apply_compute_kernel("missing", ComputeKernel::Sin, "out")
+            .is_err());
+    }
+
+    #[test]
+    fn test_apply_compute_kernels_chain() {
+        let schema = Schema::new(vec![Field::new("value", DataType::Int32, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Int32Array::from(vec![4, -9, 16]))],
+        )
+        .unwrap();
+        let record = Record::from_record_batch(batch);
+
+        let result = record
+            .apply_compute_kernels(&[
+                (
+                    "value".to_string(),
+                    ComputeKernel::Negate,
+                    "negated".to_string(),
+                ),
+                ("negated".to_string(), ComputeKernel::Abs, "abs".to_string()),
+                ("abs".to_string(), ComputeKernel::Sqrt, "root".to_string()),
+                (
+                    "root".to_string(),
+                    ComputeKernel::Round(0),
+                    "rounded".to_string(),
+                ),
+            ])
+            .unwrap();
+
+        let batch = result.to_record_batch();
+        assert_eq!(batch.num_columns(), 5);
+        let negated = batch.column_by_name("negated").unwrap();
+        assert_eq!(negated.data_type(), &DataType::Int32);
+        let rounded = batch
+            .column_by_name("rounded")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        assert_eq!(rounded.values().to_vec(), vec![2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_rolling_apply_median() {
+        let schema = Schema::new(vec![Field::new("value", DataType::Float64, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]))],
+        )
+        .unwrap();
+        let record = Record::from_record_batch(batch);
+
+        let median = |window: &[f64]| {
+            let mut sorted = window.to_vec();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            sorted[sorted.len() / 2]
+        };
+        let result = record
+            .rolling_apply(3, "value", "value_median", median)
+            .unwrap();
+
+        let batch = result.to_record_batch();
+        let output = batch
+            .column_by_name("value_median")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        let output: Vec<Option<f64>> = output.iter().collect();
+        assert_eq!(output, vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+
+        let strings = Record::from_record_batch(
+            RecordBatch::try_new(
+                Arc::new(Schema::new(vec![Field::new("name", DataType::Utf8, false)])),
+                vec![Arc::new(StringArray::from(vec!["a", "b", "c"]))],
+            )
+            .unwrap(),
+        );
+        assert!(strings.rolling_apply(2, "name", "out", median).is_err());
+    }
+
+    /// One second of a 200 Hz topic, `timestamp_ns` in nanoseconds
+    fn high_rate_record() -> Record {
+        let timestamps: Vec<i64> = (0..200).map(|i| 1_000_000_000 + i * 5_000_000).collect();
+        let counters: Vec<i32> = (0..200).collect();
+        let modes: Vec<String> = (0..200).map(|i| format!("mode{}", i % 3)).collect();
+        let schema = Schema::new(vec![
+            Field::new("timestamp_ns", DataType::Int64, false),
+            Field::new("counter", DataType::Int32, false),
+            Field::new("mode", DataType::Utf8, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int64Array::from(timestamps)),
+                Arc::new(Int32Array::from(counters)),
+                Arc::new(StringArray::from(modes)),
+            ],
+        )
+        .unwrap();
+        let mut record = Record::from_record_batch(batch);
+        record.set_topic("mavlink/attitude".to_string()).unwrap();
+        record
+    }
+
+    fn assert_strictly_increasing(values: &[Option<i64>]) {
+        assert!(values
+            .windows(2)
+            .all(|pair| pair[0].unwrap() < pair[1].unwrap()));
+    }
 
-impl std::fmt::Debug for Record {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Format the Record for debug output
-        let schema = self.record_batch.schema();
-        let num_rows = self.record_batch.num_rows();
-        let num_cols = self.record_batch.num_columns();
+    #[test]
+    fn test_resample_methods() {
+        let record = high_rate_record();
+        let counters = |record: &Record| -> Vec<i32> {
+            record
+                .to_record_batch()
+                .column_by_name("counter")
+                .unwrap()
+                .as_primitive::<arrow::datatypes::Int32Type>()
+                .values()
+                .to_vec()
+        };
 
-        // Try to get topic if available
-        let topic_str = match self.try_get_topic() {
-            Ok(topic) => format!("topic: {}", topic),
-            Err(_) => "topic: <not set>".to_string(),
+        // 20 samples per 100 ms bucket
+        let downsampled = record
+            .resample("timestamp_ns", 10.0, ResampleMethod::Downsample)
+            .unwrap();
+        assert_eq!(downsampled.to_record_batch().num_rows(), 10);
+        assert_eq!(
+            downsampled.to_record_batch().schema(),
+            record.to_record_batch().schema()
+        );
+        assert_eq!(downsampled.try_get_topic().unwrap(), "mavlink/attitude");
+        assert_strictly_increasing(&i64_values(&downsampled, "timestamp_ns"));
+        assert_eq!(counters(&downsampled)[..3], [0, 20, 40]);
+
+        let last = record
+            .resample("timestamp_ns", 10.0, ResampleMethod::Last)
+            .unwrap();
+        assert_eq!(last.to_record_batch().num_rows(), 10);
+        assert_strictly_increasing(&i64_values(&last, "timestamp_ns"));
+        assert_eq!(counters(&last)[..3], [19, 39, 59]);
+
+        let mean = record
+            .resample("timestamp_ns", 10.0, ResampleMethod::Mean)
+            .unwrap();
+        assert_eq!(
+            mean.to_record_batch().schema(),
+            record.to_record_batch().schema()
+        );
+        assert_eq!(
+            i64_values(&mean, "timestamp_ns"),
+            i64_values(&last, "timestamp_ns")
+        );
+        // Mean of 0..=19 is 9.5, rounded back to an integer
+        assert_eq!(counters(&mean)[..3], [10, 30, 50]);
+        // Strings keep their last value
+        let batch = mean.to_record_batch();
+        let modes = batch.column_by_name("mode").unwrap().as_string::<i32>();
+        assert_eq!(modes.value(0), "mode1");
+
+        // Faster than the data keeps every row
+        let all = record
+            .resample("timestamp_ns", 1000.0, ResampleMethod::Downsample)
+            .unwrap();
+        assert_eq!(all.to_record_batch().num_rows(), 200);
+    }
+
+    #[test]
+    fn test_resample_unsorted_timestamps() {
+        // Millisecond timestamps, out of order
+        let record = timestamped_record(vec![250, 20, 130, 10, 260, 110]);
+        let resampled = record
+            .resample("timestamp", 10.0, ResampleMethod::Mean)
+            .unwrap();
+        assert_eq!(resampled.to_record_batch().num_rows(), 3);
+
+        let batch = resampled.to_record_batch();
+        let timestamps =
+            arrow::compute::cast(batch.column_by_name("timestamp").unwrap(), &DataType::Int64)
+                .unwrap();
+        let timestamps: Vec<Option<i64>> = timestamps
+            .as_primitive::<arrow::datatypes::Int64Type>()
+            .iter()
+            .collect();
+        assert_eq!(timestamps, vec![Some(20), Some(130), Some(260)]);
+        let values = batch
+            .column_by_name("value")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        assert_eq!(values.value(0), 7.5);
+        assert_eq!(values.value(2), 127.5);
+
+        assert!(record
+            .resample("timestamp", 0.0, ResampleMethod::Last)
+            .is_err());
+        assert!(record
+            .resample("value", 10.0, ResampleMethod::Last)
+            .is_err());
+        assert!(record
+            .resample("missing", 10.0, ResampleMethod::Last)
+            .is_err());
+    }
+
+    fn timestamped_record(timestamps: Vec<i64>) -> Record {
+        let values: Vec<f64> = timestamps.iter().map(|t| *t as f64 * 0.5).collect();
+        let schema = Schema::new(vec![
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(arrow::datatypes::TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("value", DataType::Float64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]);
+        let names: Vec<String> = timestamps.iter().map(|t| format!("t{}", t)).collect();
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(arrow::array::TimestampMillisecondArray::from(timestamps)),
+                Arc::new(Float64Array::from(values)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )
+        .unwrap();
+        let mut record = Record::from_record_batch(batch);
+        record.set_topic("mavlink/attitude".to_string()).unwrap();
+        record
+    }
+
+    #[test]
+    fn test_sort_by_column() {
+        let record = timestamped_record(vec![30, 10, 40, 20]);
+        assert!(!record.is_sorted_by_column("timestamp").unwrap());
+
+        let ascending = record.sort_by_column("timestamp", true).unwrap();
+        assert!(ascending.is_sorted_by_column("timestamp").unwrap());
+        assert_eq!(ascending.try_get_topic().unwrap(), "mavlink/attitude");
+        let batch = ascending.to_record_batch();
+        assert_eq!(
+            batch
+                .column(1)
+                .as_primitive::<Float64Type>()
+                .values()
+                .to_vec(),
+            vec![5.0, 10.0, 15.0, 20.0]
+        );
+        assert_eq!(batch.column(2).as_string::<i32>().value(0), "t10");
+
+        let descending = record.sort_by_column("value", false).unwrap();
+        assert!(!descending.is_sorted_by_column("timestamp").unwrap());
+        assert_eq!(
+            descending
+                .to_record_batch()
+                .column(1)
+                .as_primitive::<Float64Type>()
+                .values()
+                .to_vec(),
+            vec![20.0, 15.0, 10.0, 5.0]
+        );
+
+        // Already sorted input is left unchanged
+        let sorted = timestamped_record(vec![1, 2, 3]);
+        assert!(sorted.is_sorted_by_column("timestamp").unwrap());
+        let resorted = sorted.sort_by_column("timestamp", true).unwrap();
+        assert_eq!(resorted.to_record_batch(), sorted.to_record_batch());
+
+        assert!(matches!(
+            record.sort_by_column("name", true),
+            Err(RecordError::UnsupportedSortColumn { .. })
+        ));
+        assert!(matches!(
+            record.is_sorted_by_column("missing"),
+            Err(RecordError::ColumnNotFound(_))
+        ));
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct TestHeartbeat {
+        safety_armed: bool,
+        custom_mode: u32,
+    }
+
+    #[test]
+    fn test_filter_rows() {
+        let heartbeats: Vec<TestHeartbeat> = (0..4)
+            .map(|custom_mode| TestHeartbeat {
+                safety_armed: custom_mode % 2 == 1,
+                custom_mode,
+            })
+            .collect();
+        let mut record = Record::from_serde_batch(&heartbeats).unwrap();
+        record.set_topic("mavlink/heartbeat".to_string()).unwrap();
+        record.set_flag(RecordFlag::PublishPacket).unwrap();
+
+        let filtered = record
+            .filter_rows(|row, batch| row > 0 && batch.num_rows() == 4)
+            .unwrap();
+        assert_eq!(filtered.to_record_batch().num_rows(), 3);
+        assert_eq!(filtered.try_get_topic().unwrap(), "mavlink/heartbeat");
+        assert_eq!(filtered.get_flag().unwrap(), RecordFlag::PublishPacket);
+
+        let armed = record.filter_column_eq("safety_armed", &true).unwrap();
+        assert_eq!(
+            armed.to_serde::<TestHeartbeat>().unwrap(),
+            vec![heartbeats[1].clone(), heartbeats[3].clone()]
+        );
+        assert_eq!(armed.try_get_topic().unwrap(), "mavlink/heartbeat");
+
+        // Values are cast to the column type
+        let mode = record.filter_column_eq("custom_mode", &2i32).unwrap();
+        assert_eq!(
+            mode.to_serde::<TestHeartbeat>().unwrap(),
+            vec![heartbeats[2].clone()]
+        );
+
+        assert!(record.filter_column_eq("missing", &true).is_err());
+        assert!(record.filter_column_eq("safety_armed", &vec![1u8]).is_err());
+    }
+
+    #[test]
+    fn test_select_and_drop_columns() {
+        let heartbeats = vec![TestHeartbeat {
+            safety_armed: true,
+            custom_mode: 4,
+        }];
+        let mut record = Record::from_serde_batch(&heartbeats).unwrap();
+        record.set_topic("mavlink/heartbeat".to_string()).unwrap();
+        record.set_flag(RecordFlag::PublishPacket).unwrap();
+
+        let selected = record
+            .select_columns(&["custom_mode", "safety_armed"])
+            .unwrap();
+        let names: Vec<&String> = selected
+            .to_record_batch()
+            .schema_ref()
+            .fields()
+            .iter()
+            .map(|f| f.name())
+            .collect();
+        assert_eq!(names, vec!["custom_mode", "safety_armed"]);
+        assert_eq!(selected.try_get_topic().unwrap(), "mavlink/heartbeat");
+        assert_eq!(selected.get_flag().unwrap(), RecordFlag::PublishPacket);
+
+        let dropped = record.drop_columns(&["custom_mode"]).unwrap();
+        assert_eq!(dropped.to_record_batch().num_columns(), 1);
+        assert!(dropped
+            .to_record_batch()
+            .column_by_name("safety_armed")
+            .is_some());
+        assert_eq!(dropped.try_get_topic().unwrap(), "mavlink/heartbeat");
+        assert_eq!(dropped.get_flag().unwrap(), RecordFlag::PublishPacket);
+
+        assert!(record.select_columns(&["missing"]).is_err());
+        assert!(record.drop_columns(&["missing"]).is_err());
+    }
+
+    #[test]
+    fn test_mask_nulls_and_drop_null_rows() {
+        let schema = Schema::new(vec![
+            Field::new("roll", DataType::Float64, true),
+            Field::new("mode", DataType::Utf8, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Float64Array::from(vec![Some(1.0), None, Some(3.0)])),
+                Arc::new(StringArray::from(vec![Some("GUIDED"), Some("LAND"), None])),
+            ],
+        )
+        .unwrap();
+        let record = Record::from_record_batch(batch);
+
+        let fill_values = HashMap::from([("roll".to_string(), FillValue::F64(0.0))]);
+        let masked = record.mask_nulls(&fill_values).unwrap();
+        let roll: Vec<Option<f64>> = masked
+            .to_record_batch()
+            .column(0)
+            .as_primitive::<Float64Type>()
+            .iter()
+            .collect();
+        assert_eq!(roll, vec![Some(1.0), Some(0.0), Some(3.0)]);
+        assert_eq!(masked.to_record_batch().column(1).null_count(), 1);
+
+        let dropped = record.drop_null_rows();
+        assert_eq!(dropped.to_record_batch().num_rows(), 1);
+        assert_eq!(masked.drop_null_rows().to_record_batch().num_rows(), 2);
+
+        let bad_fill = HashMap::from([("roll".to_string(), FillValue::Str("x".to_string()))]);
+        assert!(record.mask_nulls(&bad_fill).is_err());
+    }
+
+    fn joinable_record(columns: &[&str]) -> Record {
+        let fields: Vec<Field> = columns
+            .iter()
+            .map(|name| Field::new(*name, DataType::Int32, false))
+            .collect();
+        let arrays: Vec<ArrayRef> = (0..columns.len())
+            .map(|i| {
+                Arc::new(Int32Array::from_iter_values(
+                    (0..5).map(|r| r * 10 + i as i32),
+                )) as _
+            })
+            .collect();
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap();
+        Record::from_record_batch(batch)
+    }
+
+    #[test]
+    fn test_join_on_row_index() {
+        let mut left = joinable_record(&["roll", "pitch"]);
+        left.set_topic("mavlink/attitude".to_string()).unwrap();
+        let right = joinable_record(&["lat", "lon"]);
+
+        let joined =
+            Record::join_on_row_index(&left, &right, ColumnCollisionPolicy::Error).unwrap();
+        let schema = joined.to_record_batch().schema();
+        let names: Vec<&String> = schema.fields().iter().map(|f| f.name()).collect();
+        assert_eq!(names, vec!["roll", "pitch", "lat", "lon"]);
+        assert_eq!(joined.to_record_batch().num_rows(), 5);
+        assert_eq!(joined.try_get_topic().unwrap(), "mavlink/attitude");
+
+        let short = Record::from_record_batch(right.to_record_batch().slice(0, 3));
+        assert!(Record::join_on_row_index(&left, &short, ColumnCollisionPolicy::Error).is_err());
+    }
+
+    #[test]
+    fn test_join_on_row_index_collisions() {
+        let left = joinable_record(&["time", "roll"]);
+        let right = joinable_record(&["time", "lat"]);
+
+        assert!(Record::join_on_row_index(&left, &right, ColumnCollisionPolicy::Error).is_err());
+
+        let names = |policy| {
+            let joined = Record::join_on_row_index(&left, &right, policy).unwrap();
+            let schema = joined.to_record_batch().schema();
+            schema
+                .fields()
+                .iter()
+                .map(|f| f.name().clone())
+                .collect::<Vec<_>>()
         };
+        assert_eq!(
+            names(ColumnCollisionPolicy::PrefixLeft),
+            vec!["left_time", "roll", "time", "lat"]
+        );
+        assert_eq!(
+            names(ColumnCollisionPolicy::PrefixRight),
+            vec!["time", "roll", "right_time", "lat"]
+        );
+    }
 
-        writeln!(
-            f,
-            "Record → [{}] ({} rows, {} columns)",
-            topic_str, num_rows, num_cols
-        )?;
-        writeln!(f, "Schema:")?;
+    #[derive(Serialize)]
+    struct TestHeartbeatStatus {
+        timestamp_us: i64,
+        status: i64,
+    }
 
-        // Print schema fields
-        for (i, field) in schema.fields().iter().enumerate() {
-            writeln!(f, "  {}: {} ({})", i, field.name(), field.data_type())?;
-        }
+    #[derive(Serialize)]
+    struct TestGps {
+        timestamp_us: i64,
+        lat: f64,
+    }
 
-        Ok(())
+    fn heartbeat_and_gps() -> (Record, Record) {
+        let mut heartbeat =
+            Record::from_serde_batch(&[(100, 1), (200, 2), (300, 3), (400, 4)].map(
+                |(timestamp_us, status)| TestHeartbeatStatus {
+                    timestamp_us,
+                    status,
+                },
+            ))
+            .unwrap();
+        heartbeat
+            .set_topic("mavlink/heartbeat".to_string())
+            .unwrap();
+        let gps = Record::from_serde_batch(
+            &[(95, 47.0), (200, 47.1), (200, 47.2), (330, 47.3)]
+                .map(|(timestamp_us, lat)| TestGps { timestamp_us, lat }),
+        )
+        .unwrap();
+        (heartbeat, gps)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use arrow::array::{Float64Array, Int32Array, StringArray};
-    use arrow::datatypes::{DataType, Field, Fields, Schema};
-    use serde::{Deserialize, Serialize};
+    fn i64_values(record: &Record, column: &str) -> Vec<Option<i64>> {
+        record
+            .to_record_batch()
+            .column_by_name(column)
+            .unwrap()
+            .as_primitive::<arrow::datatypes::Int64Type>()
+            .iter()
+            .collect()
+    }
 
-    #[derive(Serialize, Deserialize, Debug, Default)]
-    struct TestPose {
-        pub x: f64,
-        pub y: f64,
-        pub z: f64,
+    #[test]
+    fn test_inner_and_left_join() {
+        let (heartbeat, gps) = heartbeat_and_gps();
+
+        let joined = heartbeat
+            .inner_join(&gps, "timestamp_us", "timestamp_us")
+            .unwrap();
+        let schema = joined.to_record_batch().schema();
+        let names: Vec<&String> = schema.fields().iter().map(|f| f.name()).collect();
+        assert_eq!(
+            names,
+            vec!["status", "left_timestamp_us", "lat", "right_timestamp_us"]
+        );
+        assert_eq!(i64_values(&joined, "status"), vec![Some(2), Some(2)]);
+        assert_eq!(joined.try_get_topic().unwrap(), "mavlink/heartbeat");
+
+        let joined = heartbeat
+            .left_join(&gps, "timestamp_us", "timestamp_us")
+            .unwrap();
+        assert_eq!(
+            i64_values(&joined, "status"),
+            vec![Some(1), Some(2), Some(2), Some(3), Some(4)]
+        );
+        assert_eq!(
+            i64_values(&joined, "right_timestamp_us"),
+            vec![None, Some(200), Some(200), None, None]
+        );
+
+        assert!(heartbeat.inner_join(&gps, "timestamp_us", "time").is_err());
     }
-    #[derive(Serialize, Deserialize, Debug, Default)]
-    struct TestStruct {
-        pub id: i32,
-        pub name: String,
-        pub value: f64,
-        pub last_pose: TestPose,
-        pub poses: Vec<TestPose>,
+
+    #[test]
+    fn test_asof_join() {
+        let (heartbeat, gps) = heartbeat_and_gps();
+
+        let joined = heartbeat
+            .asof_join(&gps, "timestamp_us", "timestamp_us", 30)
+            .unwrap();
+        assert_eq!(joined.to_record_batch().num_rows(), 4);
+        assert_eq!(
+            i64_values(&joined, "right_timestamp_us"),
+            vec![Some(95), Some(200), Some(330), None]
+        );
+        // The first of the rows with an equal key is used
+        let lat = joined
+            .to_record_batch()
+            .column_by_name("lat")
+            .unwrap()
+            .as_primitive::<Float64Type>()
+            .value(1);
+        assert_eq!(lat, 47.1);
+
+        assert!(heartbeat
+            .asof_join(&gps, "timestamp_us", "lat", 30)
+            .is_err());
+        assert!(heartbeat
+            .asof_join(&gps, "timestamp_us", "timestamp_us", -1)
+            .is_err());
     }
 
+    #[cfg(feature = "fft")]
     #[test]
-    fn test_from_serde() {
-        let _ = pretty_env_logger::try_init();
+    fn test_compute_fft_peak() {
+        let sample_rate = 200.0;
+        let frequency = 12.5;
+        let samples: Vec<f64> = (0..400)
+            .map(|i| 3.0 * (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate).sin())
+            .collect();
+        let schema = Schema::new(vec![Field::new("accel_x", DataType::Float64, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(Float64Array::from(samples))],
+        )
+        .unwrap();
+        let record = Record::from_record_batch(batch);
 
-        let test_struct = TestStruct::default();
-        let record = Record::from_serde(&test_struct);
-        assert!(record.is_ok());
-        println!("{:?}", record);
+        let spectrum = record.compute_fft("accel_x", sample_rate).unwrap();
+        assert_eq!(spectrum.frequencies.len(), 201);
+        assert_eq!(spectrum.magnitudes.len(), 201);
+        assert_eq!(*spectrum.frequencies.last().unwrap(), sample_rate / 2.0);
+
+        let (peak_bin, peak_magnitude) = spectrum
+            .magnitudes
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        let peak_frequency = spectrum.frequencies[peak_bin];
+        assert!((peak_frequency - frequency).abs() <= sample_rate / 400.0);
+        assert!((peak_magnitude - 3.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_run_length_round_trip() {
+        let schema = Schema::new(vec![Field::new("flight_mode", DataType::Utf8, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(StringArray::from(vec![
+                "A", "A", "A", "B", "B", "A",
+            ]))],
+        )
+        .unwrap();
+        let mut record = Record::from_record_batch(batch);
+        record.set_topic("mavlink/mode".to_string()).unwrap();
+
+        let (encoded, original_rows) = record.encode_run_length("flight_mode").unwrap();
+        assert_eq!(original_rows, 6);
+
+        let encoded_batch = encoded.to_record_batch();
+        let values = encoded_batch
+            .column_by_name("values")
+            .unwrap()
+            .as_string::<i32>();
+        assert_eq!(
+            values.iter().flatten().collect::<Vec<_>>(),
+            vec!["A", "B", "A"]
+        );
+        let lengths = encoded_batch
+            .column_by_name("lengths")
+            .unwrap()
+            .as_primitive::<arrow::datatypes::Int32Type>();
+        assert_eq!(lengths.values().to_vec(), vec![3, 2, 1]);
+
+        let decoded = Record::decode_run_length(&encoded, original_rows).unwrap();
+        let decoded_batch = decoded.to_record_batch();
+        assert_eq!(decoded_batch.schema().field(0).name(), "flight_mode");
+        assert_eq!(decoded_batch.column(0), record.to_record_batch().column(0));
+        assert_eq!(decoded.try_get_topic().unwrap(), "mavlink/mode");
+
+        assert!(Record::decode_run_length(&encoded, 5).is_err());
     }
 
     #[test]
@@ -574,6 +3118,37 @@ mod tests {
         assert_eq!(record.get_flag().unwrap(), RecordFlag::PublishPacket);
     }
 
+    #[test]
+    fn test_detect_schema_drift() {
+        #[derive(Serialize)]
+        struct Before {
+            id: i32,
+            value: f64,
+        }
+        #[derive(Serialize)]
+        struct After {
+            id: String,
+            reading: f64,
+        }
+
+        let previous = Record::from_serde(&Before { id: 1, value: 1.0 }).unwrap();
+        let current = Record::from_serde(&After {
+            id: "1".to_string(),
+            reading: 1.0,
+        })
+        .unwrap();
+
+        assert!(Record::detect_schema_drift(&previous, &previous).is_none());
+
+        let drift = Record::detect_schema_drift(&previous, &current).unwrap();
+        assert_eq!(drift.added_fields, vec!["reading".to_string()]);
+        assert_eq!(drift.removed_fields, vec!["value".to_string()]);
+        assert_eq!(
+            drift.type_changes,
+            vec![("id".to_string(), DataType::Int64, DataType::Utf8)]
+        );
+    }
+
     #[test]
     fn test_flatten_record_batch_simple() {
         let _ = pretty_env_logger::try_init();
@@ -824,6 +3399,148 @@ mod tests {
         assert_eq!(flattened_batch.num_rows(), 2);
     }
 
+    fn build_test_map_array() -> MapArray {
+        use arrow::array::{MapBuilder, StringBuilder};
+
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), Float64Builder::new());
+        // Row 0: {a: 1.0, b: 2.0}
+        builder.keys().append_value("a");
+        builder.values().append_value(1.0);
+        builder.keys().append_value("b");
+        builder.values().append_value(2.0);
+        builder.append(true).unwrap();
+        // Row 1: {b: 3.0}
+        builder.keys().append_value("b");
+        builder.values().append_value(3.0);
+        builder.append(true).unwrap();
+        builder.finish()
+    }
+
+    #[test]
+    fn test_flatten_record_batch_map_known_keys() {
+        let map_array = build_test_map_array();
+        let map_field = Field::new("gains", map_array.data_type().clone(), true).with_metadata(
+            HashMap::from([(MAP_KEYS_METADATA.to_string(), "a,b".to_string())]),
+        );
+        let schema = Arc::new(Schema::new(vec![map_field]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(map_array)]).unwrap();
+
+        let flattened = flatten_record_batch(&batch).unwrap();
+        let schema = flattened.schema();
+        assert_eq!(flattened.num_columns(), 2);
+        assert_eq!(schema.field(0).name(), "gains.a");
+        assert_eq!(schema.field(1).name(), "gains.b");
+        assert_eq!(schema.field(0).data_type(), &DataType::Float64);
+
+        let a = flattened
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        let b = flattened
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(a.value(0), 1.0);
+        assert!(a.is_null(1));
+        assert_eq!(b.value(0), 2.0);
+        assert_eq!(b.value(1), 3.0);
+    }
+
+    #[test]
+    fn test_flatten_record_batch_map_runtime_keys() {
+        let map_array = build_test_map_array();
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "gains",
+            map_array.data_type().clone(),
+            true,
+        )]));
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(map_array)]).unwrap();
+
+        let flattened = flatten_record_batch(&batch).unwrap();
+        assert_eq!(flattened.schema().field(0).data_type(), &DataType::Utf8);
+
+        let json = flattened.column(0).as_string::<i32>();
+        let row0: serde_json::Value = serde_json::from_str(json.value(0)).unwrap();
+        assert_eq!(row0, serde_json::json!({"a": 1.0, "b": 2.0}));
+        let row1: serde_json::Value = serde_json::from_str(json.value(1)).unwrap();
+        assert_eq!(row1, serde_json::json!({"b": 3.0}));
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct TestTrajectory {
+        id: i64,
+        pose: Vec<f64>,
+        target: TestTarget,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct TestTarget {
+        waypoints: Vec<i64>,
+    }
+
+    #[test]
+    fn test_flatten_record_batch_lists() {
+        let trajectories = vec![
+            TestTrajectory {
+                id: 0,
+                pose: vec![1.0, 2.0, 3.0],
+                target: TestTarget { waypoints: vec![7] },
+            },
+            TestTrajectory {
+                id: 1,
+                pose: vec![4.0],
+                target: TestTarget { waypoints: vec![] },
+            },
+        ];
+        let record = Record::from_serde_batch(&trajectories).unwrap();
+        let batch = record.to_record_batch();
+
+        // Kept as-is by default, with a note on the field
+        let kept = flatten_record_batch(batch).unwrap();
+        let pose_field = kept.schema().field_with_name("pose").unwrap().clone();
+        assert!(matches!(pose_field.data_type(), DataType::List(_)));
+        assert!(pose_field.metadata().contains_key(FLATTEN_NOTE_METADATA));
+        assert!(kept.schema().field_with_name("target.waypoints").is_ok());
+
+        let config = FlattenConfig {
+            expand_lists: true,
+            ..Default::default()
+        };
+        let expanded = flatten_record_batch_with_config(batch, &config).unwrap();
+        let names: Vec<String> = expanded
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect();
+        for name in ["pose.0", "pose.1", "pose.2", "target.waypoints.0"] {
+            assert!(names.contains(&name.to_string()), "missing {}", name);
+        }
+        let pose_2 = expanded.column_by_name("pose.2").unwrap();
+        assert_eq!(pose_2.as_primitive::<Float64Type>().value(0), 3.0);
+        assert!(pose_2.is_null(1));
+
+        let truncated = flatten_record_batch_with_config(
+            batch,
+            &FlattenConfig {
+                expand_lists: true,
+                max_list_len: 2,
+            },
+        )
+        .unwrap();
+        assert!(truncated.column_by_name("pose.1").is_some());
+        assert!(truncated.column_by_name("pose.2").is_none());
+
+        // Numbered columns are rebuilt into lists
+        let unflattened = unflatten_record_batch(&expanded).unwrap();
+        let round_trip = Record::from_record_batch(unflattened)
+            .to_serde::<TestTrajectory>()
+            .unwrap();
+        assert_eq!(round_trip, trajectories);
+    }
+
     #[test]
     fn test_unflatten_record_batch_simple() {
         #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -0,0 +1,469 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch};
+use arrow::datatypes::{DataType, Float64Type, TimeUnit};
+use log::{info, warn};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use crate::message::record::{Record, RecordFlag};
+use crate::tasks::observer::TaskObserver;
+use crate::tasks::runner::{Runner, DEFAULT_TICK_RATE_HZ};
+use crate::tasks::state::{collect_parquet_files, log_file_topic};
+use crate::tasks::task::Task;
+
+/// The rows recorded for one topic, sorted by time
+pub(crate) struct ReplayTopic {
+    topic: String,
+    batch: RecordBatch,
+    /// Milliseconds, one per row of `batch`
+    timestamps: Vec<f64>,
+    /// Next row to publish
+    cursor: usize,
+}
+
+/// Injects recorded topics into a `Runner` as the recording clock advances, the recording
+/// clock running `speed` times faster than the wall clock from the first injection.
+/// `ReplayRunner` ticks it from its own `run`, `Runner::replay_from_parquet` plays a whole
+/// log with `play`.
+pub(crate) struct ReplaySchedule {
+    topics: Vec<ReplayTopic>,
+    speed: f64,
+    /// Earliest recorded time over all topics, in milliseconds
+    log_start_ms: f64,
+    /// Wall clock time of the first injection
+    started_at: Option<Instant>,
+}
+
+impl ReplaySchedule {
+    pub(crate) fn new(topics: Vec<ReplayTopic>, speed: f64) -> Result<Self, anyhow::Error> {
+        if speed <= 0.0 || speed.is_nan() {
+            return Err(anyhow::anyhow!(
+                "Replay speed must be positive, got {}",
+                speed
+            ));
+        }
+        let log_start_ms = topics
+            .iter()
+            .filter_map(|t| t.timestamps.first().copied())
+            .reduce(f64::min)
+            .unwrap_or_default();
+        Ok(Self {
+            topics,
+            speed,
+            log_start_ms,
+            started_at: None,
+        })
+    }
+
+    pub(crate) fn topic_count(&self) -> usize {
+        self.topics.len()
+    }
+
+    pub(crate) fn row_count(&self) -> usize {
+        self.topics.iter().map(|t| t.timestamps.len()).sum()
+    }
+
+    /// Whether every recorded row has been injected
+    pub(crate) fn is_finished(&self) -> bool {
+        self.next_topic().is_none()
+    }
+
+    /// Inject every row recorded up to the current recording time, one record per row in time
+    /// order. Rows recorded at the same time keep the order of the topics.
+    pub(crate) fn inject_due(&mut self, runner: &mut Runner) -> Result<(), anyhow::Error> {
+        let now_ms = self.now_ms();
+        while let Some(index) = self.next_topic() {
+            let topic = &mut self.topics[index];
+            if topic.timestamps[topic.cursor] > now_ms {
+                break;
+            }
+
+            let mut record = Record::from_record_batch(topic.batch.slice(topic.cursor, 1));
+            record.set_topic(topic.topic.clone())?;
+            record.set_flag(RecordFlag::PublishPacket)?;
+            topic.cursor += 1;
+            runner.inject_record(record)?;
+        }
+        Ok(())
+    }
+
+    /// Inject rows as they become due and run `runner` in between, until every row has been
+    /// injected. `run` paces itself to the tick rate, without one this waits for the next
+    /// row instead of spinning, ticking at the default rate meanwhile.
+    pub(crate) fn play(&mut self, runner: &mut Runner) -> Result<(), anyhow::Error> {
+        while !self.is_finished() {
+            self.inject_due(runner)?;
+            runner.run()?;
+
+            if let (None, Some(index)) = (runner.tick_rate(), self.next_topic()) {
+                let topic = &self.topics[index];
+                let wait_ms = ((topic.timestamps[topic.cursor] - self.now_ms()) / self.speed)
+                    .clamp(0.0, 1000.0 / DEFAULT_TICK_RATE_HZ);
+                std::thread::sleep(Duration::from_secs_f64(wait_ms / 1000.0));
+            }
+        }
+        Ok(())
+    }
+
+    /// Recording time reached so far, in milliseconds
+    fn now_ms(&mut self) -> f64 {
+        let started_at = *self.started_at.get_or_insert_with(Instant::now);
+        self.log_start_ms + started_at.elapsed().as_secs_f64() * 1000.0 * self.speed
+    }
+
+    /// Index of the topic whose next row was recorded first, the earlier topic on ties
+    fn next_topic(&self) -> Option<usize> {
+        let mut next: Option<(usize, f64)> = None;
+        for (index, topic) in self.topics.iter().enumerate() {
+            let Some(&time) = topic.timestamps.get(topic.cursor) else {
+                continue;
+            };
+            if next.is_none_or(|(_, next_time)| time < next_time) {
+                next = Some((index, time));
+            }
+        }
+        next.map(|(index, _)| index)
+    }
+}
+
+/// Wraps a `Runner` and publishes the parquet logs of a directory as if they were live.
+///
+/// Each file is one topic, named after its path relative to the log directory
+/// (`mavlink/attitude.parquet` and `mavlink/attitude_final.parquet` both replay
+/// `mavlink/attitude`), matching the layout written by `RunnerLogger`.
+/// Every tick publishes the rows whose recorded time has been reached, with the
+/// recording clock running `speed` times faster than the wall clock.
+pub struct ReplayRunner {
+    runner: Runner,
+    schedule: ReplaySchedule,
+}
+
+impl ReplayRunner {
+    /// Load every parquet file under `log_dir`, timed by `time_column`.
+    /// The column may be an arrow timestamp or a number of milliseconds.
+    /// Files without the column are skipped with a warning.
+    pub fn new(
+        runner: Runner,
+        log_dir: PathBuf,
+        time_column: &str,
+        speed: f64,
+    ) -> Result<Self, anyhow::Error> {
+        let mut files = Vec::new();
+        collect_parquet_files(&log_dir, &mut files)?;
+        files.sort();
+
+        let mut topics: Vec<ReplayTopic> = Vec::new();
+        for path in files {
+            let topic = log_file_topic(&log_dir, &path)?;
+            let Some((batch, timestamps)) = load_timed_file(&path, time_column)? else {
+                warn!(
+                    "Skipping {:?}, it has no '{}' column to replay by",
+                    path, time_column
+                );
+                continue;
+            };
+
+            match topics.iter_mut().find(|t| t.topic == topic) {
+                Some(existing) => {
+                    let batch = arrow::compute::concat_batches(
+                        &existing.batch.schema(),
+                        &[existing.batch.clone(), batch],
+                    )?;
+                    let mut timestamps = [existing.timestamps.clone(), timestamps].concat();
+                    let (batch, sorted) = sort_by_time(&batch, &timestamps)?;
+                    timestamps = sorted;
+                    existing.batch = batch;
+                    existing.timestamps = timestamps;
+                }
+                None => topics.push(ReplayTopic {
+                    topic,
+                    batch,
+                    timestamps,
+                    cursor: 0,
+                }),
+            }
+        }
+
+        if topics.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No parquet files with a '{}' column found in {:?}",
+                time_column,
+                log_dir
+            ));
+        }
+
+        let schedule = ReplaySchedule::new(topics, speed)?;
+        info!(
+            "Replaying {} topics from {:?} at {}x speed",
+            schedule.topic_count(),
+            log_dir,
+            speed
+        );
+
+        Ok(Self { runner, schedule })
+    }
+
+    pub fn add_task(&mut self, task: Arc<Mutex<dyn Task>>) {
+        self.runner.add_task(task);
+    }
+
+    pub fn add_observer(&mut self, observer: Arc<dyn TaskObserver>) {
+        self.runner.add_observer(observer);
+    }
+
+    pub fn init(&mut self) -> Result<(), anyhow::Error> {
+        self.runner.init()
+    }
+
+    /// Publish the rows that are due, then run one cycle of the wrapped runner
+    pub fn run(&mut self) -> Result<(), anyhow::Error> {
+        self.schedule.inject_due(&mut self.runner)?;
+        self.runner.run()
+    }
+
+    pub fn run_n_cycles(&mut self, n: usize) -> Result<(), anyhow::Error> {
+        for _ in 0..n {
+            self.run()?;
+        }
+        Ok(())
+    }
+
+    /// Run until every recorded row has been published, plus one cycle to deliver the last ones
+    pub fn run_until_finished(&mut self) -> Result<(), anyhow::Error> {
+        self.schedule.play(&mut self.runner)?;
+        self.runner.run()
+    }
+
+    pub fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        self.runner.cleanup()
+    }
+
+    /// Whether every recorded row has been published
+    pub fn is_finished(&self) -> bool {
+        self.schedule.is_finished()
+    }
+
+    pub fn runner(&self) -> &Runner {
+        &self.runner
+    }
+
+    pub fn runner_mut(&mut self) -> &mut Runner {
+        &mut self.runner
+    }
+
+    pub fn into_runner(self) -> Runner {
+        self.runner
+    }
+}
+
+/// The rows of `topic` logged in `log_dir` sorted by `time_column`. Reads
+/// `{topic}.parquet`, or `{topic}_final.parquet` when the logger only wrote the file at
+/// cleanup.
+pub(crate) fn load_topic(
+    log_dir: &Path,
+    topic: &str,
+    time_column: &str,
+) -> Result<ReplayTopic, anyhow::Error> {
+    let path = [
+        format!("{}.parquet", topic),
+        format!("{}_final.parquet", topic),
+    ]
+    .into_iter()
+    .map(|file| log_dir.join(file))
+    .find(|path| path.is_file())
+    .ok_or_else(|| anyhow::anyhow!("No log of topic '{}' in {:?}", topic, log_dir))?;
+    let (batch, timestamps) = load_timed_file(&path, time_column)?.ok_or_else(|| {
+        anyhow::anyhow!("{:?} has no '{}' column to replay by", path, time_column)
+    })?;
+
+    Ok(ReplayTopic {
+        topic: topic.to_string(),
+        batch,
+        timestamps,
+        cursor: 0,
+    })
+}
+
+/// Read a whole file sorted by `time_column`, dropping rows without a time.
+/// Returns None if the file has no such column.
+fn load_timed_file(
+    path: &Path,
+    time_column: &str,
+) -> Result<Option<(RecordBatch, Vec<f64>)>, anyhow::Error> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    let schema = builder.schema().clone();
+    if schema.column_with_name(time_column).is_none() {
+        return Ok(None);
+    }
+    let batches = builder.build()?.collect::<Result<Vec<_>, _>>()?;
+    let batch = arrow::compute::concat_batches(&schema, &batches)?;
+
+    let column = batch
+        .column_by_name(time_column)
+        .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", time_column))?;
+    let batch = arrow::compute::filter_record_batch(&batch, &arrow::compute::is_not_null(column)?)?;
+    let timestamps = timestamps_ms(
+        batch
+            .column_by_name(time_column)
+            .ok_or_else(|| anyhow::anyhow!("Column '{}' not found", time_column))?,
+    )?;
+    Ok(Some(sort_by_time(&batch, &timestamps)?))
+}
+
+/// Convert a time column to milliseconds
+fn timestamps_ms(column: &ArrayRef) -> Result<Vec<f64>, anyhow::Error> {
+    let scale = match column.data_type() {
+        DataType::Timestamp(TimeUnit::Second, _) => 1000.0,
+        DataType::Timestamp(TimeUnit::Millisecond, _) => 1.0,
+        DataType::Timestamp(TimeUnit::Microsecond, _) => 1e-3,
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => 1e-6,
+        data_type if data_type.is_numeric() => 1.0,
+        other => {
+            return Err(anyhow::anyhow!(
+                "Replay time column must be a timestamp or a number, found {}",
+                other
+            ))
+        }
+    };
+    let values = match column.data_type() {
+        DataType::Timestamp(_, _) => arrow::compute::cast(column, &DataType::Int64)?,
+        _ => column.clone(),
+    };
+    let values = arrow::compute::cast(&values, &DataType::Float64)?;
+    Ok(values
+        .as_primitive::<Float64Type>()
+        .values()
+        .iter()
+        .map(|value| value * scale)
+        .collect())
+}
+
+/// Sort the rows of a batch (and their times) by time, keeping equal times in file order
+fn sort_by_time(
+    batch: &RecordBatch,
+    timestamps: &[f64],
+) -> Result<(RecordBatch, Vec<f64>), anyhow::Error> {
+    let mut order: Vec<u32> = (0..timestamps.len() as u32).collect();
+    order.sort_by(|a, b| timestamps[*a as usize].total_cmp(&timestamps[*b as usize]));
+    let sorted_timestamps = order.iter().map(|i| timestamps[*i as usize]).collect();
+    let indices = arrow::array::UInt32Array::from(order);
+    Ok((
+        arrow::compute::take_record_batch(batch, &indices)?,
+        sorted_timestamps,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subscribe;
+    use crate::tasks::info::TaskInfo;
+    use crate::tasks::task::{MetaTaskChannel, TaskChannel};
+    use parquet::arrow::ArrowWriter;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct TestSample {
+        timestamp: i64,
+        value: i64,
+    }
+
+    struct TestCollector {
+        info: TaskInfo,
+        received: Arc<Mutex<Vec<(String, TestSample)>>>,
+    }
+
+    impl Task for TestCollector {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            tx.send(subscribe!("mavlink/attitude"))?;
+            tx.send(subscribe!("exec/stage"))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            let mut received = self.received.lock().unwrap();
+            for record in inputs {
+                let topic = record.try_get_topic()?;
+                for sample in record.to_serde::<TestSample>()? {
+                    received.push((topic.clone(), sample));
+                }
+            }
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    fn write_samples(path: &Path, timestamps: &[i64]) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let samples: Vec<TestSample> = timestamps
+            .iter()
+            .enumerate()
+            .map(|(value, &timestamp)| TestSample {
+                timestamp,
+                value: value as i64,
+            })
+            .collect();
+        let batch = Record::from_serde_batch(&samples)
+            .unwrap()
+            .to_record_batch_cloned();
+        let mut writer =
+            ArrowWriter::try_new(File::create(path).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_replay_log_directory() {
+        let log_dir = std::env::temp_dir().join(format!("replay_runner_{}", uuid::Uuid::new_v4()));
+        // Out of order rows are sorted by time
+        write_samples(&log_dir.join("mavlink/attitude.parquet"), &[0, 200, 100]);
+        write_samples(&log_dir.join("exec/stage_final.parquet"), &[50, 150]);
+        std::fs::write(log_dir.join("notes.txt"), "not a log").unwrap();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut replay =
+            ReplayRunner::new(Runner::new(), log_dir.clone(), "timestamp", 10.0).unwrap();
+        replay.add_task(Arc::new(Mutex::new(TestCollector {
+            info: TaskInfo::new("TestCollector").with_insta_spawn(),
+            received: received.clone(),
+        })));
+        replay.init().unwrap();
+
+        let start = Instant::now();
+        replay.run_until_finished().unwrap();
+        // 200ms of recording at 10x speed
+        assert!(start.elapsed() >= std::time::Duration::from_millis(20));
+        assert!(replay.is_finished());
+
+        let received = received.lock().unwrap();
+        let times = |topic: &str| -> Vec<i64> {
+            received
+                .iter()
+                .filter(|(t, _)| t == topic)
+                .map(|(_, sample)| sample.timestamp)
+                .collect()
+        };
+        assert_eq!(times("mavlink/attitude"), vec![0, 100, 200]);
+        assert_eq!(times("exec/stage"), vec![50, 150]);
+
+        assert!(ReplayRunner::new(Runner::new(), log_dir.clone(), "missing", 1.0).is_err());
+        assert!(ReplayRunner::new(Runner::new(), log_dir.clone(), "timestamp", 0.0).is_err());
+
+        std::fs::remove_dir_all(&log_dir).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::message::record::{Record, RecordFlag};
+use crate::subscribe;
+
+use super::info::TaskInfo;
+use super::task::{MetaTaskChannel, Task, TaskChannel};
+
+pub type ConditionFn = Arc<dyn Fn(&Record) -> bool + Send + Sync>;
+
+/// What a `ConditionGateTask` does with source records while its condition is false
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClosedGateBehavior {
+    /// Keep them and forward them once the condition becomes true again
+    Queue,
+    /// Discard them
+    Drop,
+}
+
+/// Holds back `source_topic` records until a `condition_topic` record passes `condition_fn`,
+/// then forwards them to `output_topic`.
+///
+/// Records that arrive before any condition record are always queued. After the condition
+/// turns false, new records are queued or dropped depending on `ClosedGateBehavior`.
+pub struct ConditionGateTask {
+    info: TaskInfo,
+    source_topic: String,
+    condition_topic: String,
+    output_topic: String,
+    condition_fn: ConditionFn,
+    closed_behavior: ClosedGateBehavior,
+    /// None until the first condition record arrives
+    open: Option<bool>,
+    queued: Vec<Record>,
+}
+
+impl ConditionGateTask {
+    pub fn new(
+        source_topic: impl Into<String>,
+        condition_topic: impl Into<String>,
+        output_topic: impl Into<String>,
+        condition_fn: ConditionFn,
+    ) -> Self {
+        let output_topic = output_topic.into();
+        Self {
+            info: TaskInfo::new(format!("ConditionGate_{}", output_topic)).with_insta_spawn(),
+            source_topic: source_topic.into(),
+            condition_topic: condition_topic.into(),
+            output_topic,
+            condition_fn,
+            closed_behavior: ClosedGateBehavior::Drop,
+            open: None,
+            queued: Vec::new(),
+        }
+    }
+
+    pub fn with_closed_behavior(mut self, behavior: ClosedGateBehavior) -> Self {
+        self.closed_behavior = behavior;
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open == Some(true)
+    }
+
+    pub fn queued_count(&self) -> usize {
+        self.queued.len()
+    }
+
+    fn forward(&self, mut record: Record, tx: &TaskChannel) -> Result<(), anyhow::Error> {
+        record.set_topic(self.output_topic.clone())?;
+        record.set_flag(RecordFlag::PublishPacket)?;
+        tx.send(record)?;
+        Ok(())
+    }
+}
+
+impl Task for ConditionGateTask {
+    fn init(&mut self, tx: TaskChannel, _meta_tx: MetaTaskChannel) -> Result<(), anyhow::Error> {
+        self.open = None;
+        self.queued.clear();
+
+        tx.send(subscribe!(self.source_topic))?;
+        tx.send(subscribe!(self.condition_topic))?;
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<Record>,
+        tx: TaskChannel,
+        _meta_tx: MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in inputs {
+            let topic = record.try_get_topic()?;
+            if topic == self.output_topic {
+                continue;
+            }
+
+            if topic == self.condition_topic {
+                let open = (self.condition_fn)(&record);
+                if self.open != Some(open) {
+                    debug!(
+                        "Condition gate for {} is now {}",
+                        self.output_topic,
+                        if open { "open" } else { "closed" }
+                    );
+                }
+                self.open = Some(open);
+
+                if open {
+                    for queued in std::mem::take(&mut self.queued) {
+                        self.forward(queued, &tx)?;
+                    }
+                }
+            } else if topic == self.source_topic {
+                match self.open {
+                    Some(true) => self.forward(record, &tx)?,
+                    Some(false) if self.closed_behavior == ClosedGateBehavior::Drop => {}
+                    _ => self.queued.push(record),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publish;
+    use std::sync::mpsc;
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestAttitude {
+        roll: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestArmStatus {
+        armed: bool,
+    }
+
+    fn armed_gate() -> ConditionGateTask {
+        ConditionGateTask::new(
+            "mavlink/attitude",
+            "mavlink/arm_status",
+            "gated/attitude",
+            Arc::new(|record: &Record| {
+                record
+                    .to_serde::<TestArmStatus>()
+                    .is_ok_and(|status| status.iter().any(|s| s.armed))
+            }),
+        )
+    }
+
+    fn attitude(roll: f64) -> Record {
+        publish!("mavlink/attitude", &TestAttitude { roll })
+    }
+
+    fn arm_status(armed: bool) -> Record {
+        publish!("mavlink/arm_status", &TestArmStatus { armed })
+    }
+
+    fn forwarded_rolls(rx: &mpsc::Receiver<Record>) -> Vec<f64> {
+        rx.try_iter()
+            .map(|record| {
+                assert_eq!(record.try_get_topic().unwrap(), "gated/attitude");
+                record.to_serde::<TestAttitude>().unwrap()[0].roll
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_releases_after_arm() {
+        let mut gate = armed_gate();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        gate.run(
+            vec![attitude(1.0), arm_status(false), attitude(2.0)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert!(forwarded_rolls(&rx).is_empty());
+        assert_eq!(gate.queued_count(), 1);
+
+        gate.run(
+            vec![arm_status(true), attitude(3.0)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert!(gate.is_open());
+        assert_eq!(forwarded_rolls(&rx), vec![1.0, 3.0]);
+
+        gate.run(vec![arm_status(false), attitude(4.0)], tx, meta_tx)
+            .unwrap();
+        assert!(forwarded_rolls(&rx).is_empty());
+        assert_eq!(gate.queued_count(), 0);
+    }
+
+    #[test]
+    fn test_queue_while_closed() {
+        let mut gate = armed_gate().with_closed_behavior(ClosedGateBehavior::Queue);
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        gate.run(
+            vec![arm_status(false), attitude(1.0), attitude(2.0)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert_eq!(gate.queued_count(), 2);
+
+        gate.run(vec![arm_status(true)], tx, meta_tx).unwrap();
+        assert_eq!(forwarded_rolls(&rx), vec![1.0, 2.0]);
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::info::TaskInfo;
+use super::task::Task;
+
+/// Tasks that can be built from a serialized config instead of type specific builders
+pub trait Configurable: Sized {
+    type Config: DeserializeOwned;
+
+    fn new_from_config(config: Self::Config) -> Result<Self, anyhow::Error>;
+}
+
+/// Build a task from a JSON config
+pub fn task_from_json<T: Task + Configurable>(config_json: &str) -> Result<T, anyhow::Error> {
+    let config: T::Config = serde_json::from_str(config_json)?;
+    T::new_from_config(config)
+}
+
+type TaskFactory =
+    Box<dyn Fn(serde_json::Value) -> Result<Arc<Mutex<dyn Task>>, anyhow::Error> + Send + Sync>;
+
+/// Maps task type names to constructors so tasks can be listed in a JSON file
+#[derive(Default)]
+pub struct TaskRegistry {
+    factories: HashMap<String, TaskFactory>,
+}
+
+/// One entry of a JSON task list: `{"type": "AutoTaskTakeoff", "config": {...}}`
+#[derive(Debug, Deserialize)]
+pub struct TaskSpec {
+    #[serde(rename = "type")]
+    pub task_type: String,
+    #[serde(default = "empty_config")]
+    pub config: serde_json::Value,
+}
+
+fn empty_config() -> serde_json::Value {
+    serde_json::Value::Object(Default::default())
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `T` under its type name, see `TaskInfo::from_type_name`
+    pub fn register<T: Task + Configurable + 'static>(&mut self) {
+        let type_name = TaskInfo::from_type_name::<T>().name;
+        self.register_as::<T>(&type_name);
+    }
+
+    /// Register `T` under a custom type name
+    pub fn register_as<T: Task + Configurable + 'static>(&mut self, type_name: &str) {
+        self.factories.insert(
+            type_name.to_string(),
+            Box::new(|config| {
+                let config: T::Config = serde_json::from_value(config)?;
+                let task: Arc<Mutex<dyn Task>> = Arc::new(Mutex::new(T::new_from_config(config)?));
+                Ok(task)
+            }),
+        );
+    }
+
+    pub fn is_registered(&self, type_name: &str) -> bool {
+        self.factories.contains_key(type_name)
+    }
+
+    /// Build the task described by `spec`
+    pub fn create(&self, spec: TaskSpec) -> Result<Arc<Mutex<dyn Task>>, anyhow::Error> {
+        let factory = self
+            .factories
+            .get(&spec.task_type)
+            .ok_or_else(|| anyhow::anyhow!("Unknown task type '{}'", spec.task_type))?;
+        factory(spec.config)
+            .map_err(|e| anyhow::anyhow!("Failed to configure '{}': {}", spec.task_type, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::record::Record;
+    use crate::task_info;
+    use crate::tasks::runner::Runner;
+    use crate::tasks::task::{MetaTaskChannel, TaskChannel};
+
+    #[derive(Debug, Deserialize)]
+    struct TestConfig {
+        rate_hz: f64,
+    }
+
+    struct TestConfiguredTask {
+        info: TaskInfo,
+        rate_hz: f64,
+    }
+
+    impl Configurable for TestConfiguredTask {
+        type Config = TestConfig;
+
+        fn new_from_config(config: Self::Config) -> Result<Self, anyhow::Error> {
+            if config.rate_hz <= 0.0 {
+                return Err(anyhow::anyhow!("rate_hz must be positive"));
+            }
+            Ok(Self {
+                info: TaskInfo::from_type_name::<Self>(),
+                rate_hz: config.rate_hz,
+            })
+        }
+    }
+
+    impl Task for TestConfiguredTask {
+        fn init(
+            &mut self,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_task_from_json() {
+        let task = task_from_json::<TestConfiguredTask>(r#"{"rate_hz": 20.0}"#).unwrap();
+        assert_eq!(task.rate_hz, 20.0);
+        assert!(task_from_json::<TestConfiguredTask>(r#"{"rate_hz": -1.0}"#).is_err());
+        assert!(task_from_json::<TestConfiguredTask>(r#"{"rate": 1.0}"#).is_err());
+    }
+
+    #[test]
+    fn test_add_tasks_from_json_array() {
+        let mut runner = Runner::new();
+        runner.register_task_type::<TestConfiguredTask>();
+
+        runner
+            .add_tasks_from_json_array(
+                r#"[{"type": "TestConfiguredTask", "config": {"rate_hz": 5.0}}]"#,
+            )
+            .unwrap();
+        assert_eq!(runner.task_infos(), vec![task_info!(TestConfiguredTask)]);
+
+        assert!(runner
+            .add_tasks_from_json_array(r#"[{"type": "MissingTask", "config": {}}]"#)
+            .is_err());
+    }
+}
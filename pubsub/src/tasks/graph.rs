@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use super::info::TaskInfo;
+use super::runner::Runner;
+
+/// Escape characters that have a meaning inside a DOT record label
+fn escape_record_label(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn node_id(task_info: &TaskInfo) -> String {
+    format!("task_{}", task_info.id)
+}
+
+/// Generates a Graphviz DOT digraph of the runner's tasks.
+///
+/// Each task is a `record` node showing its name and subscription patterns, colored by
+/// status: running (green), queued to spawn (yellow) or stopped (red).
+/// Edges go from publishing tasks to subscribing tasks and are labeled with the topic.
+/// Publishers are only known once they have published, so call this after `init`.
+pub fn generate_task_graph_dot(runner: &Runner) -> String {
+    let mut tasks = runner.task_infos();
+    tasks.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut dot = String::new();
+    dot.push_str("digraph tasks {\n");
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=record, style=filled];\n");
+
+    for task_info in &tasks {
+        let color = if runner.is_task_running(task_info) {
+            "green"
+        } else if runner.is_task_pending_spawn(task_info) {
+            "yellow"
+        } else {
+            "red"
+        };
+
+        let subscriptions = runner.task_subscriptions(task_info);
+        let subscription_label: String = subscriptions
+            .iter()
+            .map(|pattern| format!("{}\\l", escape_record_label(pattern)))
+            .collect();
+
+        let _ = writeln!(
+            dot,
+            "    {} [label=\"{{{}|{}}}\", fillcolor={}];",
+            node_id(task_info),
+            escape_record_label(&task_info.name),
+            subscription_label,
+            color
+        );
+    }
+
+    for publisher in &tasks {
+        let mut topics: Vec<String> = runner
+            .task_published_topics(publisher)
+            .into_iter()
+            .collect();
+        topics.sort();
+
+        for topic in topics {
+            for subscriber in &tasks {
+                let subscribed = runner
+                    .task_subscriptions(subscriber)
+                    .iter()
+                    .any(|pattern| runner.subscription_matches(pattern, &topic));
+                if subscribed {
+                    let _ = writeln!(
+                        dot,
+                        "    {} -> {} [label=\"{}\"];",
+                        node_id(publisher),
+                        node_id(subscriber),
+                        topic.replace('"', "\\\"")
+                    );
+                }
+            }
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::record::Record;
+    use crate::tasks::task::{MetaTaskChannel, Task, TaskChannel};
+    use crate::{publish, subscribe};
+    use serde::Serialize;
+    use std::io::Write as IoWrite;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Serialize)]
+    struct TestMessage {
+        value: i32,
+    }
+
+    struct TestTask {
+        info: TaskInfo,
+        subscription: Option<&'static str>,
+        publication: Option<&'static str>,
+    }
+
+    impl Task for TestTask {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            if let Some(pattern) = self.subscription {
+                tx.send(subscribe!(pattern))?;
+            }
+            if let Some(topic) = self.publication {
+                tx.send(publish!(topic, &TestMessage { value: 1 }))?;
+            }
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_generate_task_graph_dot() {
+        let mut runner = Runner::new();
+        runner.add_task(Arc::new(Mutex::new(TestTask {
+            info: TaskInfo::new("Publisher"),
+            subscription: None,
+            publication: Some("mavlink/attitude"),
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestTask {
+            info: TaskInfo::new("Subscriber").with_insta_spawn(),
+            subscription: Some("mavlink/*"),
+            publication: None,
+        })));
+        runner.init().unwrap();
+
+        let dot = generate_task_graph_dot(&runner);
+        assert!(dot.starts_with("digraph tasks {"));
+        assert!(dot.contains("{Publisher|}"));
+        assert!(dot.contains("{Subscriber|mavlink/*\\l}"));
+        assert!(dot.contains("fillcolor=yellow"));
+        assert!(dot.contains("fillcolor=red"));
+        assert!(dot.contains("[label=\"mavlink/attitude\"]"));
+
+        // Validate with graphviz when it is installed
+        let child = std::process::Command::new("dot")
+            .arg("-Tcanon")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn();
+        match child {
+            Ok(mut child) => {
+                child
+                    .stdin
+                    .take()
+                    .unwrap()
+                    .write_all(dot.as_bytes())
+                    .unwrap();
+                let output = child.wait_with_output().unwrap();
+                assert!(
+                    output.status.success(),
+                    "dot failed to parse graph: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+            Err(_) => eprintln!("graphviz `dot` not found, skipping parse check"),
+        }
+    }
+}
@@ -1,10 +1,24 @@
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
+
+/// Priority of a task that doesn't set one
+pub const DEFAULT_TASK_PRIORITY: u8 = 128;
+
+fn default_priority() -> u8 {
+    DEFAULT_TASK_PRIORITY
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskInfo {
     pub name: String,
     pub id: u32,
     pub insta_spawn: bool,
+    /// Only the newest N records of each subscription are delivered per run cycle
+    #[serde(default)]
+    pub max_inputs_per_cycle: Option<usize>,
+    /// Higher priority tasks run earlier in each runner tick
+    #[serde(default = "default_priority")]
+    pub priority: u8,
 }
 
 impl TaskInfo {
@@ -18,12 +32,63 @@ impl TaskInfo {
             name,
             id: id as u32,
             insta_spawn: false,
+            max_inputs_per_cycle: None,
+            priority: DEFAULT_TASK_PRIORITY,
+        }
+    }
+    /// Name the task after the Rust type `T`, without its module path.
+    /// The id is a hash of the full type path so identically named types in different modules don't collide.
+    pub fn from_type_name<T: 'static>() -> Self {
+        let full_name = std::any::type_name::<T>();
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        full_name.hash(&mut hasher);
+        let id = hasher.finish();
+        Self {
+            name: strip_module_paths(full_name),
+            id: id as u32,
+            insta_spawn: false,
+            max_inputs_per_cycle: None,
+            priority: DEFAULT_TASK_PRIORITY,
         }
     }
     pub fn with_insta_spawn(mut self) -> Self {
         self.insta_spawn = true;
         self
     }
+    pub fn with_max_inputs_per_cycle(mut self, max_inputs: usize) -> Self {
+        self.max_inputs_per_cycle = Some(max_inputs);
+        self
+    }
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Drop the module path of every type in a type name, e.g. `a::Foo<b::Bar>` becomes `Foo<Bar>`
+fn strip_module_paths(type_name: &str) -> String {
+    let mut stripped = String::with_capacity(type_name.len());
+    let mut segment = String::new();
+    for c in type_name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+            continue;
+        }
+        stripped.push_str(segment.rsplit("::").next().unwrap_or_default());
+        segment.clear();
+        stripped.push(c);
+    }
+    stripped.push_str(segment.rsplit("::").next().unwrap_or_default());
+    stripped
+}
+
+/// Build a `TaskInfo` named after a task type, `task_info!(MyTask)` is
+/// shorthand for `TaskInfo::from_type_name::<MyTask>()`
+#[macro_export]
+macro_rules! task_info {
+    ($task:ty) => {
+        $crate::tasks::info::TaskInfo::from_type_name::<$task>()
+    };
 }
 
 // Hash based off the id
@@ -48,3 +113,31 @@ impl std::fmt::Display for TaskInfo {
         write!(f, "{}", self.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestTask;
+
+    mod nested {
+        pub struct TestTask;
+    }
+
+    struct TestWrapper<T>(T);
+
+    #[test]
+    fn test_from_type_name() {
+        let info = TaskInfo::from_type_name::<TestTask>();
+        assert_eq!(info.name, "TestTask");
+        assert_eq!(task_info!(TestTask), info);
+
+        // Same short name, different module
+        let nested = task_info!(nested::TestTask);
+        assert_eq!(nested.name, "TestTask");
+        assert_ne!(nested, info);
+
+        let wrapper = task_info!(TestWrapper<nested::TestTask>);
+        assert_eq!(wrapper.name, "TestWrapper<TestTask>");
+    }
+}
@@ -0,0 +1,376 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::message::record::{Record, RecordFlag};
+use crate::subscribe;
+
+use super::info::TaskInfo;
+use super::task::{MetaTaskChannel, Task, TaskChannel};
+
+/// How a `LoadBalancerTask` picks the worker for each record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DispatchStrategy {
+    /// Cycle through the workers in order
+    RoundRobin,
+    /// Send to the worker with the fewest records waiting to be processed
+    LeastLoaded,
+}
+
+/// Topic a pool worker listens on. Kept outside of the source topic's namespace
+/// so the balancer's own subscription never matches it.
+pub fn worker_topic(source_topic: &str, index: usize) -> String {
+    format!(
+        "load_balancer/worker_{}_{}",
+        index,
+        source_topic.replace('/', "_")
+    )
+}
+
+/// Drains `source_topic` and spreads its records over a set of worker topics
+pub struct LoadBalancerTask {
+    info: TaskInfo,
+    source_topic: String,
+    worker_topics: Vec<String>,
+    strategy: DispatchStrategy,
+    next_worker: usize,
+    /// Records sent to each worker that it has not consumed yet
+    pending: Arc<Vec<AtomicUsize>>,
+}
+
+impl LoadBalancerTask {
+    pub fn new(
+        source_topic: impl Into<String>,
+        worker_topics: Vec<String>,
+        strategy: DispatchStrategy,
+    ) -> Self {
+        let source_topic = source_topic.into();
+        let pending = Arc::new(worker_topics.iter().map(|_| AtomicUsize::new(0)).collect());
+        Self {
+            info: TaskInfo::new(format!("LoadBalancer_{}", source_topic)).with_insta_spawn(),
+            source_topic,
+            worker_topics,
+            strategy,
+            next_worker: 0,
+            pending,
+        }
+    }
+
+    /// Shared per-worker backlog counters, handed to `LoadBalancedWorker`s so
+    /// `DispatchStrategy::LeastLoaded` sees what they have consumed
+    pub fn pending_counters(&self) -> Arc<Vec<AtomicUsize>> {
+        self.pending.clone()
+    }
+
+    pub fn worker_topics(&self) -> &[String] {
+        &self.worker_topics
+    }
+
+    fn select_worker(&mut self) -> usize {
+        match self.strategy {
+            DispatchStrategy::RoundRobin => {
+                let worker = self.next_worker;
+                self.next_worker = (self.next_worker + 1) % self.worker_topics.len();
+                worker
+            }
+            DispatchStrategy::LeastLoaded => self
+                .pending
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, pending)| pending.load(Ordering::Relaxed))
+                .map(|(index, _)| index)
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl Task for LoadBalancerTask {
+    fn init(&mut self, tx: TaskChannel, _meta_tx: MetaTaskChannel) -> Result<(), anyhow::Error> {
+        if self.worker_topics.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Load balancer for {} has no workers",
+                self.source_topic
+            ));
+        }
+        tx.send(subscribe!(self.source_topic))?;
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<Record>,
+        tx: TaskChannel,
+        _meta_tx: MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for mut record in inputs {
+            let topic = record.try_get_topic()?;
+            if self.worker_topics.contains(&topic) {
+                continue;
+            }
+
+            let worker = self.select_worker();
+            record.set_topic(self.worker_topics[worker].clone())?;
+            record.set_flag(RecordFlag::PublishPacket)?;
+            tx.send(record)?;
+            self.pending[worker].fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &TaskInfo {
+        &self.info
+    }
+}
+
+/// Wraps a task so it consumes its share of a load balanced topic.
+///
+/// Subscriptions the inner task makes to the source topic are redirected to the
+/// worker topic, and records are handed back to it under the source topic name.
+pub struct LoadBalancedWorker {
+    info: TaskInfo,
+    index: usize,
+    source_topic: String,
+    worker_topic: String,
+    inner: Box<dyn Task + Send>,
+    pending: Arc<Vec<AtomicUsize>>,
+}
+
+impl LoadBalancedWorker {
+    pub fn new(
+        index: usize,
+        source_topic: impl Into<String>,
+        inner: Box<dyn Task + Send>,
+        pending: Arc<Vec<AtomicUsize>>,
+    ) -> Self {
+        let source_topic = source_topic.into();
+        let inner_info = inner.get_task_info().clone();
+        let mut info = TaskInfo::new(format!("{}_worker_{}", inner_info.name, index));
+        info.insta_spawn = inner_info.insta_spawn;
+        info.max_inputs_per_cycle = inner_info.max_inputs_per_cycle;
+
+        Self {
+            info,
+            index,
+            worker_topic: worker_topic(&source_topic, index),
+            source_topic,
+            inner,
+            pending,
+        }
+    }
+}
+
+impl Task for LoadBalancedWorker {
+    fn init(&mut self, tx: TaskChannel, meta_tx: MetaTaskChannel) -> Result<(), anyhow::Error> {
+        let inner_channel = mpsc::channel();
+        self.inner.init(inner_channel.0, meta_tx)?;
+
+        for record in inner_channel.1.try_iter() {
+            let redirected = record.get_flag()? == RecordFlag::SubscribePacket
+                && record.try_get_topic()? == self.source_topic;
+            if redirected {
+                debug!(
+                    "{} subscription to {} redirected to {}",
+                    self.info, self.source_topic, self.worker_topic
+                );
+                continue;
+            }
+            tx.send(record)?;
+        }
+
+        tx.send(subscribe!(self.worker_topic))?;
+        Ok(())
+    }
+
+    fn should_run(&self) -> Result<bool, anyhow::Error> {
+        self.inner.should_run()
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<Record>,
+        tx: TaskChannel,
+        meta_tx: MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        let mut consumed = 0;
+        let mut restored = Vec::with_capacity(inputs.len());
+        for mut record in inputs {
+            if record.try_get_topic()? == self.worker_topic {
+                record.set_topic(self.source_topic.clone())?;
+                consumed += 1;
+            }
+            restored.push(record);
+        }
+
+        match self.pending.get(self.index) {
+            Some(pending) => {
+                let _ = pending.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                    Some(count.saturating_sub(consumed))
+                });
+            }
+            None => warn!("{} has no pending counter", self.info),
+        }
+
+        self.inner.run(restored, tx, meta_tx)
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        self.inner.cleanup()
+    }
+
+    fn get_task_info(&self) -> &TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publish;
+    use crate::tasks::runner::Runner;
+    use std::sync::Mutex;
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestSample {
+        value: i64,
+    }
+
+    struct TestBurstPublisher {
+        info: TaskInfo,
+        count: i64,
+        published: bool,
+    }
+
+    impl Task for TestBurstPublisher {
+        fn init(
+            &mut self,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            if !self.published {
+                for value in 0..self.count {
+                    tx.send(publish!("sensors/sample", &TestSample { value }))?;
+                }
+                self.published = true;
+            }
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    struct TestWorker {
+        info: TaskInfo,
+        received: Arc<Mutex<Vec<Record>>>,
+    }
+
+    impl Task for TestWorker {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            tx.send(subscribe!("sensors/sample"))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            self.received.lock().unwrap().extend(inputs);
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    fn run_pool(strategy: DispatchStrategy) -> Vec<Vec<Record>> {
+        let received: Vec<Arc<Mutex<Vec<Record>>>> =
+            (0..2).map(|_| Arc::new(Mutex::new(Vec::new()))).collect();
+
+        let mut runner = Runner::new();
+        runner.add_task(Arc::new(Mutex::new(TestBurstPublisher {
+            info: TaskInfo::new("TestBurstPublisher").with_insta_spawn(),
+            count: 100,
+            published: false,
+        })));
+        let worker_received = received.clone();
+        runner.add_load_balanced_pool_with_strategy("sensors/sample", 2, strategy, |index| {
+            Box::new(TestWorker {
+                info: TaskInfo::new("TestWorker").with_insta_spawn(),
+                received: worker_received[index].clone(),
+            })
+        });
+
+        runner.init().unwrap();
+        runner.run_n_cycles(5).unwrap();
+
+        received
+            .iter()
+            .map(|received| received.lock().unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_round_robin_pool_splits_evenly() {
+        let received = run_pool(DispatchStrategy::RoundRobin);
+
+        for worker in &received {
+            assert!((45..=55).contains(&worker.len()), "got {}", worker.len());
+            assert!(worker
+                .iter()
+                .all(|record| record.try_get_topic().unwrap() == "sensors/sample"));
+        }
+        assert_eq!(received.iter().map(Vec::len).sum::<usize>(), 100);
+    }
+
+    #[test]
+    fn test_least_loaded_pool_splits_evenly() {
+        let received = run_pool(DispatchStrategy::LeastLoaded);
+
+        for worker in &received {
+            assert!((45..=55).contains(&worker.len()), "got {}", worker.len());
+        }
+        assert_eq!(received.iter().map(Vec::len).sum::<usize>(), 100);
+    }
+
+    #[test]
+    fn test_least_loaded_prefers_idle_worker() {
+        let mut balancer = LoadBalancerTask::new(
+            "sensors/sample",
+            vec![
+                worker_topic("sensors/sample", 0),
+                worker_topic("sensors/sample", 1),
+            ],
+            DispatchStrategy::LeastLoaded,
+        );
+        balancer.pending[0].store(10, Ordering::Relaxed);
+
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+        let inputs = (0..3)
+            .map(|value| publish!("sensors/sample", &TestSample { value }))
+            .collect();
+        balancer.run(inputs, tx, meta_tx).unwrap();
+
+        let topics: Vec<String> = rx.try_iter().map(|r| r.try_get_topic().unwrap()).collect();
+        assert_eq!(topics, vec![worker_topic("sensors/sample", 1); 3]);
+    }
+}
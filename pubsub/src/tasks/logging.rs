@@ -1,35 +1,69 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Context;
 use arrow::csv::writer::Writer as CsvWriter;
+use arrow::json::writer::ArrayWriter as JsonWriter;
 use arrow::record_batch::RecordBatch;
 use chrono::Local;
 use parquet::arrow::arrow_writer::ArrowWriter;
+use parquet::basic::Compression;
 use parquet::file::properties::WriterProperties;
 
-use crate::message::record::flatten_record_batch;
+use crate::message::record::{flatten_record_batch_with_config, FlattenConfig};
 use crate::tasks::state::RunnerState;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum OutputFormat {
     Parquet,
     Csv,
+    /// One JSON array of flattened row objects per file
+    Json,
+}
+
+/// When `RunnerLogger::process_state` writes a topic to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerPolicy {
+    /// Once the topic holds this many rows
+    RowCount(usize),
+    /// Once this long has passed since the topic was last written, whatever its row count
+    WallClock(Duration),
+    /// Whichever of the row count and the wall clock trigger comes first
+    RowCountOrTime(usize, Duration),
+}
+
+/// A plain row count, as `RunnerLogger::new` took before trigger policies
+impl From<usize> for TriggerPolicy {
+    fn from(rows: usize) -> Self {
+        TriggerPolicy::RowCount(rows)
+    }
 }
 
 pub struct RunnerLogger {
     output_path: PathBuf, // Base directory for all logs
     session_id: String,   // Unique ID for this run (e.g., timestamp)
-    trigger_rows: usize,
+    trigger: TriggerPolicy,
     history_rows: usize,
     formats: HashSet<OutputFormat>,
+    // Per-topic overrides of `formats`, the longest matching pattern wins
+    topic_formats: Vec<(String, HashSet<OutputFormat>)>,
+    // How nested columns are flattened for CSV and JSON
+    flatten_config: FlattenConfig,
+    // Codec used for parquet output
+    compression: Compression,
+    // Rows of each topic's state already appended to its parquet file
+    written_rows: HashMap<String, usize>,
+    // When each topic was last written, or first seen if it wasn't written yet
+    last_write_time_per_topic: HashMap<String, Instant>,
 }
 
 impl RunnerLogger {
     pub fn new(
         output_path: impl Into<PathBuf>,
-        trigger_rows: usize,
+        trigger: impl Into<TriggerPolicy>,
         history_rows: usize,
         formats: HashSet<OutputFormat>,
         session_id: Option<String>,
@@ -48,23 +82,196 @@ impl RunnerLogger {
         Ok(Self {
             output_path,
             session_id,
-            trigger_rows,
+            trigger: trigger.into(),
             history_rows,
             formats,
+            topic_formats: Vec::new(),
+            flatten_config: FlattenConfig::default(),
+            compression: Compression::UNCOMPRESSED,
+            written_rows: HashMap::new(),
+            last_write_time_per_topic: HashMap::new(),
         })
     }
 
+    /// Compress parquet output with `codec`, uncompressed by default
+    pub fn with_compression(mut self, codec: Compression) -> Self {
+        self.compression = codec;
+        self
+    }
+
+    /// Write topics matching `pattern` (prefix or `*` wildcard) with `formats` instead of the defaults.
+    /// When several patterns match a topic the longest one is used.
+    pub fn set_output_format_per_topic(&mut self, pattern: &str, formats: HashSet<OutputFormat>) {
+        match self.topic_formats.iter_mut().find(|(p, _)| p == pattern) {
+            Some((_, existing)) => *existing = formats,
+            None => self.topic_formats.push((pattern.to_string(), formats)),
+        }
+    }
+
+    /// Set how nested columns are flattened for CSV and JSON output,
+    /// e.g. to expand list columns that CSV can't hold
+    pub fn set_flatten_config(&mut self, config: FlattenConfig) {
+        self.flatten_config = config;
+    }
+
+    /// Get the formats a topic should be written in
+    fn formats_for_topic(&self, topic: &str) -> &HashSet<OutputFormat> {
+        self.topic_formats
+            .iter()
+            .filter(|(pattern, _)| Self::topic_matches(pattern, topic))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, formats)| formats)
+            .unwrap_or(&self.formats)
+    }
+
+    fn topic_matches(pattern: &str, topic: &str) -> bool {
+        if pattern.contains('*') {
+            let pattern = format!("^{}$", regex::escape(pattern).replace("\\*", ".*"));
+            regex::Regex::new(&pattern).is_ok_and(|regex| regex.is_match(topic))
+        } else {
+            topic.starts_with(pattern)
+        }
+    }
+
+    /// Check if a topic with `rows` rows in the state is due to be written under the trigger policy.
+    /// The wall clock only triggers when the topic has rows that were not written yet.
+    fn should_trigger(&mut self, topic: &str, rows: usize, now: Instant) -> bool {
+        let last_write = *self
+            .last_write_time_per_topic
+            .entry(topic.to_string())
+            .or_insert(now);
+        let unwritten = rows > self.written_rows.get(topic).copied().unwrap_or(0);
+        let time_due = |interval: Duration| unwritten && now.duration_since(last_write) >= interval;
+        match self.trigger {
+            TriggerPolicy::RowCount(trigger_rows) => rows >= trigger_rows,
+            TriggerPolicy::WallClock(interval) => time_due(interval),
+            TriggerPolicy::RowCountOrTime(trigger_rows, interval) => {
+                rows >= trigger_rows || time_due(interval)
+            }
+        }
+    }
+
+    /// True if no topic can be written in any format
+    fn has_no_formats(&self) -> bool {
+        self.formats.is_empty() && self.topic_formats.iter().all(|(_, f)| f.is_empty())
+    }
+
     // Helper function to write Parquet
-    fn write_parquet(batch: &RecordBatch, path: &Path) -> Result<(), anyhow::Error> {
+    fn write_parquet(
+        batch: &RecordBatch,
+        path: &Path,
+        compression: Compression,
+    ) -> Result<(), anyhow::Error> {
         let file = File::create(path)
             .with_context(|| format!("Failed to create parquet file: {:?}", path))?;
-        let props = WriterProperties::builder().build();
+        let props = WriterProperties::builder()
+            .set_compression(compression)
+            .build();
         let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
         writer.write(batch)?;
         writer.close()?;
         Ok(())
     }
 
+    // Helper function to append a batch as a new row group of an existing Parquet file.
+    // Existing row groups are copied without decoding into a temporary file which then
+    // replaces the original, so the file on disk is always complete.
+    fn append_parquet(
+        batch: &RecordBatch,
+        path: &Path,
+        compression: Compression,
+    ) -> Result<(), anyhow::Error> {
+        use parquet::arrow::arrow_writer::{compute_leaves, get_column_writers};
+        use parquet::column::writer::ColumnCloseResult;
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::file::writer::SerializedFileWriter;
+
+        if !path.exists() {
+            return Self::write_parquet(batch, path, compression);
+        }
+
+        let existing =
+            File::open(path).with_context(|| format!("Failed to open parquet file: {:?}", path))?;
+        let metadata = SerializedFileReader::new(existing.try_clone()?)?
+            .metadata()
+            .clone();
+        let file_metadata = metadata.file_metadata();
+        let schema = Arc::new(parquet::arrow::parquet_to_arrow_schema(
+            file_metadata.schema_descr(),
+            file_metadata.key_value_metadata(),
+        )?);
+        if batch.schema().fields() != schema.fields() {
+            return Err(anyhow::anyhow!(
+                "Schema of {:?} does not match the new rows",
+                path
+            ));
+        }
+
+        let props = Arc::new(
+            WriterProperties::builder()
+                .set_compression(compression)
+                .set_key_value_metadata(file_metadata.key_value_metadata().cloned())
+                .build(),
+        );
+        let tmp_path = path.with_extension("parquet.tmp");
+        let result = (|| -> Result<(), anyhow::Error> {
+            let tmp_file = File::create(&tmp_path)
+                .with_context(|| format!("Failed to create parquet file: {:?}", tmp_path))?;
+            let mut writer = SerializedFileWriter::new(
+                tmp_file,
+                file_metadata.schema_descr().root_schema_ptr(),
+                props.clone(),
+            )?;
+
+            for row_group in metadata.row_groups() {
+                let mut row_group_writer = writer.next_row_group()?;
+                for column in row_group.columns() {
+                    row_group_writer.append_column(
+                        &existing,
+                        ColumnCloseResult {
+                            bytes_written: column.compressed_size() as u64,
+                            rows_written: row_group.num_rows() as u64,
+                            metadata: column.clone(),
+                            bloom_filter: None,
+                            column_index: None,
+                            offset_index: None,
+                        },
+                    )?;
+                }
+                row_group_writer.close()?;
+            }
+
+            let mut column_writers =
+                get_column_writers(file_metadata.schema_descr(), &props, &schema)?;
+            let mut leaf_writers = column_writers.iter_mut();
+            for (field, column) in schema.fields().iter().zip(batch.columns()) {
+                for leaf in compute_leaves(field, column)? {
+                    leaf_writers
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("More leaf columns than column writers"))?
+                        .write(&leaf)?;
+                }
+            }
+            let mut row_group_writer = writer.next_row_group()?;
+            for column_writer in column_writers {
+                column_writer
+                    .close()?
+                    .append_to_row_group(&mut row_group_writer)?;
+            }
+            row_group_writer.close()?;
+            writer.close()?;
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace parquet file: {:?}", path))?;
+        Ok(())
+    }
+
     // Helper function to write CSV
     fn write_csv(batch: &RecordBatch, path: &Path) -> Result<(), anyhow::Error> {
         let file =
@@ -86,26 +293,37 @@ impl RunnerLogger {
         Ok(())
     }
 
-    pub fn process_state(&self, state: &mut RunnerState) -> Result<(), anyhow::Error> {
-        if self.formats.is_empty() {
+    // Helper function to write a JSON array of row objects
+    fn write_json(batch: &RecordBatch, path: &Path) -> Result<(), anyhow::Error> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create json file: {:?}", path))?;
+        let mut writer = JsonWriter::new(file);
+        writer.write(batch)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    pub fn process_state(&mut self, state: &mut RunnerState) -> Result<(), anyhow::Error> {
+        if self.has_no_formats() {
             return Ok(()); // Nothing to do if no formats are configured
         }
 
+        let now = Instant::now();
         let topics_to_process: Vec<String> = state
             .get_topics()
             .into_iter()
             .filter(|topic| {
                 state
                     .get_topic_row_count(topic)
-                    .map_or(false, |count| count >= self.trigger_rows)
+                    .is_some_and(|count| self.should_trigger(topic, count, now))
             })
             .collect();
 
         for topic in topics_to_process {
             log::info!(
-                "Topic '{}' reached trigger threshold ({}), processing...",
+                "Topic '{}' reached trigger ({:?}), processing...",
                 topic,
-                self.trigger_rows
+                self.trigger
             );
 
             if let Some(record_ref_to_write) = state.get_topic_record(&topic) {
@@ -135,12 +353,60 @@ impl RunnerLogger {
                 let mut files_written: Vec<String> = Vec::new();
 
                 // 2. Write configured formats
-                for format in &self.formats {
+                for format in self.formats_for_topic(&topic) {
                     match format {
                         OutputFormat::Parquet => {
                             let file_path = topic_dir.join(format!("{}.parquet", file_stem));
-                            log::debug!("Writing Parquet to: {:?}", file_path);
-                            match Self::write_parquet(record_batch_to_write, &file_path) {
+                            // Only the rows not written by an earlier trigger are appended,
+                            // the rewrite is kept for when the schema changed under the file
+                            let already_written = self.written_rows.get(&topic).copied();
+                            let result = match already_written {
+                                Some(written) if written <= record_batch_to_write.num_rows() => {
+                                    log::debug!("Appending Parquet to: {:?}", file_path);
+                                    let new_rows = record_batch_to_write
+                                        .slice(written, record_batch_to_write.num_rows() - written);
+                                    if new_rows.num_rows() == 0 {
+                                        Ok(())
+                                    } else {
+                                        Self::append_parquet(
+                                            &new_rows,
+                                            &file_path,
+                                            self.compression,
+                                        )
+                                    }
+                                }
+                                // Nothing of this state was written yet
+                                None => {
+                                    log::debug!("Appending Parquet to: {:?}", file_path);
+                                    Self::append_parquet(
+                                        record_batch_to_write,
+                                        &file_path,
+                                        self.compression,
+                                    )
+                                }
+                                // The state was replaced since the last trigger
+                                Some(_) => {
+                                    log::debug!("Writing Parquet to: {:?}", file_path);
+                                    Self::write_parquet(
+                                        record_batch_to_write,
+                                        &file_path,
+                                        self.compression,
+                                    )
+                                }
+                            };
+                            match result.or_else(|e| {
+                                log::warn!(
+                                    "Failed to append Parquet for topic '{}', rewriting {:?}: {}",
+                                    topic,
+                                    file_path,
+                                    e
+                                );
+                                Self::write_parquet(
+                                    record_batch_to_write,
+                                    &file_path,
+                                    self.compression,
+                                )
+                            }) {
                                 Ok(_) => files_written.push(file_path.display().to_string()),
                                 Err(e) => log::error!(
                                     "Failed to write Parquet for topic '{}' to {:?}: {}",
@@ -154,8 +420,11 @@ impl RunnerLogger {
                             let file_path = topic_dir.join(format!("{}.csv", file_stem));
                             log::debug!("Writing CSV to: {:?}", file_path);
 
-                            // Use flatten_record_batch for CSV
-                            match flatten_record_batch(record_batch_to_write) {
+                            // Flatten nested columns for CSV
+                            match flatten_record_batch_with_config(
+                                record_batch_to_write,
+                                &self.flatten_config,
+                            ) {
                                 Ok(flattened_batch) => {
                                     match Self::write_csv(&flattened_batch, &file_path) {
                                         Ok(_) => {
@@ -172,11 +441,35 @@ impl RunnerLogger {
                                 }
                             }
                         }
+                        OutputFormat::Json => {
+                            let file_path = topic_dir.join(format!("{}.json", file_stem));
+                            log::debug!("Writing JSON to: {:?}", file_path);
+
+                            // Flatten like CSV so nested structs become flat objects
+                            match flatten_record_batch_with_config(
+                                record_batch_to_write,
+                                &self.flatten_config,
+                            ) {
+                                Ok(flattened_batch) => {
+                                    match Self::write_json(&flattened_batch, &file_path) {
+                                        Ok(_) => {
+                                            files_written.push(file_path.display().to_string())
+                                        }
+                                        Err(e) => log::error!("Failed to write JSON: {}", e),
+                                    }
+                                }
+                                Err(e) => {
+                                    log::error!("Failed to flatten record batch for JSON: {}", e);
+                                }
+                            }
+                        }
                     }
                 }
 
                 // Only proceed with state trimming if at least one format was written successfully
                 if !files_written.is_empty() {
+                    self.last_write_time_per_topic.insert(topic.clone(), now);
+
                     // 3. Trim history and update state
                     if self.history_rows > 0 && record_batch_to_write.num_rows() > self.history_rows
                     {
@@ -188,9 +481,14 @@ impl RunnerLogger {
                         let history_record =
                             record_to_write.get_n_latest_rows(self.history_rows)?;
                         state.replace_topic_record(topic.clone(), history_record);
+                        self.written_rows.insert(topic.clone(), self.history_rows);
                     } else if self.history_rows == 0 {
                         log::debug!("Removing topic '{}' from state as history_rows is 0", topic);
                         state.remove_topic(&topic);
+                        self.written_rows.remove(&topic);
+                    } else {
+                        self.written_rows
+                            .insert(topic.clone(), record_batch_to_write.num_rows());
                     }
 
                     log::info!(
@@ -218,7 +516,7 @@ impl RunnerLogger {
     pub fn dump_remaining_state(&self, state: &mut RunnerState) -> Result<(), anyhow::Error> {
         let topics_to_process: Vec<String> = state.get_topics().into_iter().collect();
 
-        if self.formats.is_empty() {
+        if self.has_no_formats() {
             return Ok(()); // Nothing to do if no formats are configured
         }
 
@@ -260,12 +558,16 @@ impl RunnerLogger {
                 let mut files_written: Vec<String> = Vec::new();
 
                 // Write configured formats
-                for format in &self.formats {
+                for format in self.formats_for_topic(&topic) {
                     match format {
                         OutputFormat::Parquet => {
                             let file_path = topic_dir.join(format!("{}_final.parquet", file_stem));
                             log::debug!("Writing final Parquet to: {:?}", file_path);
-                            match Self::write_parquet(record_batch_to_write, &file_path) {
+                            match Self::write_parquet(
+                                record_batch_to_write,
+                                &file_path,
+                                self.compression,
+                            ) {
                                 Ok(_) => files_written.push(file_path.display().to_string()),
                                 Err(e) => log::error!(
                                     "Failed to write final Parquet for topic '{}' to {:?}: {}",
@@ -279,8 +581,11 @@ impl RunnerLogger {
                             let file_path = topic_dir.join(format!("{}_final.csv", file_stem));
                             log::debug!("Writing final CSV to: {:?}", file_path);
 
-                            // Use flatten_record_batch for CSV
-                            match flatten_record_batch(record_batch_to_write) {
+                            // Flatten nested columns for CSV
+                            match flatten_record_batch_with_config(
+                                record_batch_to_write,
+                                &self.flatten_config,
+                            ) {
                                 Ok(flattened_batch) => {
                                     match Self::write_csv(&flattened_batch, &file_path) {
                                         Ok(_) => {
@@ -300,6 +605,31 @@ impl RunnerLogger {
                                 }
                             }
                         }
+                        OutputFormat::Json => {
+                            let file_path = topic_dir.join(format!("{}_final.json", file_stem));
+                            log::debug!("Writing final JSON to: {:?}", file_path);
+
+                            // Flatten like CSV so nested structs become flat objects
+                            match flatten_record_batch_with_config(
+                                record_batch_to_write,
+                                &self.flatten_config,
+                            ) {
+                                Ok(flattened_batch) => {
+                                    match Self::write_json(&flattened_batch, &file_path) {
+                                        Ok(_) => {
+                                            files_written.push(file_path.display().to_string())
+                                        }
+                                        Err(e) => log::error!("Failed to write final JSON: {}", e),
+                                    }
+                                }
+                                Err(e) => {
+                                    log::error!(
+                                        "Failed to flatten record batch for final JSON: {}",
+                                        e
+                                    );
+                                }
+                            }
+                        }
                     }
                 }
 
@@ -324,9 +654,303 @@ impl RunnerLogger {
     }
 }
 
-// Basic tests can be added here later if needed
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//     // ... test setup ...
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::record::{unflatten_record_batch, Record};
+    use crate::publish;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestMessage {
+        value: i32,
+    }
+
+    #[test]
+    fn test_output_format_per_topic() {
+        let output_path =
+            std::env::temp_dir().join(format!("runner_logger_{}", uuid::Uuid::new_v4()));
+        let mut logger = RunnerLogger::new(
+            &output_path,
+            5000,
+            10,
+            [OutputFormat::Parquet, OutputFormat::Csv].into(),
+            Some("session".to_string()),
+        )
+        .unwrap();
+        logger.set_output_format_per_topic("mavlink/*", [OutputFormat::Parquet].into());
+        logger.set_output_format_per_topic("exec/*", [OutputFormat::Csv].into());
+
+        let mut state = RunnerState::new();
+        for topic in ["mavlink/attitude", "exec/stage", "auto/stage"] {
+            state
+                .apply_record(&publish!(topic, &TestMessage { value: 1 }))
+                .unwrap();
+        }
+        logger.dump_remaining_state(&mut state).unwrap();
+
+        let session_dir = output_path.join("session");
+        assert!(session_dir.join("mavlink/attitude_final.parquet").exists());
+        assert!(!session_dir.join("mavlink/attitude_final.csv").exists());
+        assert!(session_dir.join("exec/stage_final.csv").exists());
+        assert!(!session_dir.join("exec/stage_final.parquet").exists());
+
+        // Unmatched topics fall back to the default formats
+        assert!(session_dir.join("auto/stage_final.parquet").exists());
+        assert!(session_dir.join("auto/stage_final.csv").exists());
+
+        std::fs::remove_dir_all(&output_path).unwrap();
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    struct TestPosition {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+    struct TestNestedMessage {
+        id: i64,
+        position: TestPosition,
+    }
+
+    #[test]
+    fn test_json_output_round_trip() {
+        let output_path =
+            std::env::temp_dir().join(format!("runner_logger_{}", uuid::Uuid::new_v4()));
+        let mut logger = RunnerLogger::new(
+            &output_path,
+            2,
+            0,
+            [OutputFormat::Json].into(),
+            Some("session".to_string()),
+        )
+        .unwrap();
+
+        let messages: Vec<TestNestedMessage> = (0..2)
+            .map(|id| TestNestedMessage {
+                id,
+                position: TestPosition {
+                    x: id as f64,
+                    y: 0.5,
+                },
+            })
+            .collect();
+        let mut state = RunnerState::new();
+        for message in &messages {
+            state
+                .apply_record(&publish!("exec/position", message))
+                .unwrap();
+        }
+        logger.process_state(&mut state).unwrap();
+        assert!(state.get_topics().is_empty());
+
+        let json = std::fs::read_to_string(output_path.join("session/exec/position.json")).unwrap();
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert!(rows[0].get("position.x").is_some());
+
+        let record = Record::from_json(&json).unwrap();
+        let unflattened = unflatten_record_batch(record.to_record_batch()).unwrap();
+        let round_trip = Record::from_record_batch(unflattened)
+            .to_serde::<TestNestedMessage>()
+            .unwrap();
+        assert_eq!(round_trip, messages);
+
+        std::fs::remove_dir_all(&output_path).unwrap();
+    }
+
+    #[test]
+    fn test_parquet_appended_across_triggers() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let output_path =
+            std::env::temp_dir().join(format!("runner_logger_{}", uuid::Uuid::new_v4()));
+        let mut logger = RunnerLogger::new(
+            &output_path,
+            2,
+            1,
+            [OutputFormat::Parquet].into(),
+            Some("session".to_string()),
+        )
+        .unwrap();
+
+        // The kept history row was already written and must not be appended again
+        let mut state = RunnerState::new();
+        for value in 0..4 {
+            state
+                .apply_record(&publish!("exec/counter", &TestMessage { value }))
+                .unwrap();
+            logger.process_state(&mut state).unwrap();
+        }
+
+        let file = File::open(output_path.join("session/exec/counter.parquet")).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        assert_eq!(builder.metadata().num_row_groups(), 3);
+        let values: Vec<i32> = builder
+            .build()
+            .unwrap()
+            .flat_map(|batch| {
+                Record::from_record_batch(batch.unwrap())
+                    .to_serde::<TestMessage>()
+                    .unwrap()
+            })
+            .map(|message| message.value)
+            .collect();
+        assert_eq!(values, vec![0, 1, 2, 3]);
+
+        std::fs::remove_dir_all(&output_path).unwrap();
+    }
+
+    #[test]
+    fn test_trigger_policy() {
+        let output_path =
+            std::env::temp_dir().join(format!("runner_logger_{}", uuid::Uuid::new_v4()));
+        let mut logger = RunnerLogger::new(
+            &output_path,
+            TriggerPolicy::RowCountOrTime(3, Duration::from_millis(50)),
+            0,
+            [OutputFormat::Parquet].into(),
+            Some("session".to_string()),
+        )
+        .unwrap();
+        let session_dir = output_path.join("session");
+
+        // The high rate topic is written by row count before the interval passes
+        let mut state = RunnerState::new();
+        for value in 0..3 {
+            state
+                .apply_record(&publish!("mavlink/imu", &TestMessage { value }))
+                .unwrap();
+        }
+        state
+            .apply_record(&publish!("exec/stage", &TestMessage { value: 0 }))
+            .unwrap();
+        logger.process_state(&mut state).unwrap();
+        assert!(session_dir.join("mavlink/imu.parquet").exists());
+        assert!(!session_dir.join("exec/stage.parquet").exists());
+
+        // The low rate topic is written once the interval passed
+        std::thread::sleep(Duration::from_millis(60));
+        logger.process_state(&mut state).unwrap();
+        assert!(session_dir.join("exec/stage.parquet").exists());
+        assert!(state.get_topics().is_empty());
+
+        // A plain row count still works as before
+        let logger = RunnerLogger::new(&output_path, 10, 0, [OutputFormat::Csv].into(), None);
+        assert_eq!(logger.unwrap().trigger, TriggerPolicy::RowCount(10));
+
+        std::fs::remove_dir_all(&output_path).unwrap();
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestListMessage {
+        pose: Vec<f64>,
+    }
+
+    #[test]
+    fn test_csv_expands_lists() {
+        let output_path =
+            std::env::temp_dir().join(format!("runner_logger_{}", uuid::Uuid::new_v4()));
+        let mut logger = RunnerLogger::new(
+            &output_path,
+            5000,
+            10,
+            [OutputFormat::Csv].into(),
+            Some("session".to_string()),
+        )
+        .unwrap();
+        logger.set_flatten_config(FlattenConfig {
+            expand_lists: true,
+            max_list_len: 4,
+        });
+
+        let mut state = RunnerState::new();
+        state
+            .apply_record(&publish!(
+                "exec/pose",
+                &TestListMessage {
+                    pose: vec![1.0, 2.0]
+                }
+            ))
+            .unwrap();
+        logger.dump_remaining_state(&mut state).unwrap();
+
+        let csv = std::fs::read_to_string(output_path.join("session/exec/pose_final.csv")).unwrap();
+        assert_eq!(csv.lines().next().unwrap(), "pose.0,pose.1");
+
+        std::fs::remove_dir_all(&output_path).unwrap();
+    }
+
+    #[test]
+    fn test_zstd_compression() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        use parquet::basic::ZstdLevel;
+
+        let output_path =
+            std::env::temp_dir().join(format!("runner_logger_{}", uuid::Uuid::new_v4()));
+        let write = |session: &str, compression: Compression| {
+            let logger = RunnerLogger::new(
+                &output_path,
+                5000,
+                10,
+                [OutputFormat::Parquet].into(),
+                Some(session.to_string()),
+            )
+            .unwrap()
+            .with_compression(compression);
+
+            // Repetitive data compresses well
+            let messages: Vec<TestMessage> =
+                (0..5000).map(|i| TestMessage { value: i % 4 }).collect();
+            let mut state = RunnerState::new();
+            let mut record = Record::from_serde_batch(&messages).unwrap();
+            record.set_topic("exec/counter".to_string()).unwrap();
+            state.apply_record(&record).unwrap();
+            logger.dump_remaining_state(&mut state).unwrap();
+            output_path.join(session).join("exec/counter_final.parquet")
+        };
+
+        let uncompressed = write("uncompressed", Compression::UNCOMPRESSED);
+        let zstd = write("zstd", Compression::ZSTD(ZstdLevel::default()));
+        let size = |path: &PathBuf| std::fs::metadata(path).unwrap().len();
+        assert!(size(&zstd) < size(&uncompressed));
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&zstd).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        let values: Vec<TestMessage> = batches
+            .into_iter()
+            .flat_map(|batch| {
+                Record::from_record_batch(batch)
+                    .to_serde::<TestMessage>()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(values.len(), 5000);
+        assert!(values
+            .iter()
+            .enumerate()
+            .all(|(i, m)| m.value == i as i32 % 4));
+
+        std::fs::remove_dir_all(&output_path).unwrap();
+    }
+
+    #[test]
+    fn test_longest_topic_pattern_wins() {
+        let mut logger =
+            RunnerLogger::new("logs", 10, 0, [OutputFormat::Csv].into(), None).unwrap();
+        logger.set_output_format_per_topic("mavlink/*", [OutputFormat::Parquet].into());
+        logger.set_output_format_per_topic("mavlink/attitude*", [OutputFormat::Csv].into());
+
+        assert_eq!(
+            logger.formats_for_topic("mavlink/gps"),
+            &[OutputFormat::Parquet].into()
+        );
+        assert_eq!(
+            logger.formats_for_topic("mavlink/attitude"),
+            &[OutputFormat::Csv].into()
+        );
+    }
+}
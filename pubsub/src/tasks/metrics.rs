@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::message::record::Record;
+
+use super::info::TaskInfo;
+use super::observer::TaskObserver;
+
+/// Topic a `Runner` with a metrics interval publishes its `RunnerMetrics` on
+pub const METRICS_TOPIC: &str = "metrics/runner";
+
+/// Timing and throughput of a single task run, collected by `MetricsObserver` or by a
+/// `Runner` configured with `Runner::with_metrics_interval`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunnerMetrics {
+    pub task_name: String,
+    pub run_duration_us: u64,
+    pub input_count: u32,
+    pub output_count: u32,
+    pub error_count: u32,
+}
+
+/// Observer that records a `RunnerMetrics` entry for every task run
+#[derive(Default)]
+pub struct MetricsObserver {
+    metrics: Arc<Mutex<Vec<RunnerMetrics>>>,
+    // Input counts of runs that have started but not finished yet
+    pending_inputs: Mutex<HashMap<String, u32>>,
+}
+
+impl MetricsObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shared buffer the metrics are written to
+    pub fn metrics_buffer(&self) -> Arc<Mutex<Vec<RunnerMetrics>>> {
+        self.metrics.clone()
+    }
+
+    /// Take all metrics collected so far, clearing the buffer
+    pub fn drain_metrics(&self) -> Vec<RunnerMetrics> {
+        std::mem::take(&mut *self.metrics.lock().unwrap())
+    }
+}
+
+impl TaskObserver for MetricsObserver {
+    fn on_before_run(&self, task: &TaskInfo, inputs: &[Record]) {
+        self.pending_inputs
+            .lock()
+            .unwrap()
+            .insert(task.name.clone(), inputs.len() as u32);
+    }
+
+    fn on_after_run(&self, task: &TaskInfo, outputs: &[Record], elapsed: Duration) {
+        self.record_run(task, elapsed, outputs.len() as u32, 0);
+    }
+
+    fn on_error(&self, task: &TaskInfo, _error: &anyhow::Error, elapsed: Duration) {
+        self.record_run(task, elapsed, 0, 1);
+    }
+}
+
+impl MetricsObserver {
+    fn record_run(&self, task: &TaskInfo, elapsed: Duration, output_count: u32, error_count: u32) {
+        let input_count = self
+            .pending_inputs
+            .lock()
+            .unwrap()
+            .remove(&task.name)
+            .unwrap_or_default();
+
+        self.metrics.lock().unwrap().push(RunnerMetrics {
+            task_name: task.name.clone(),
+            run_duration_us: elapsed.as_micros() as u64,
+            input_count,
+            output_count,
+            error_count,
+        });
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::warn;
+
+use crate::message::record::Record;
+
+/// Schema metadata key `TimestampMiddleware` writes the publish time to
+pub const PUBLISHED_AT_METADATA: &str = "_published_at_ns";
+
+/// What happens to a record after a `PublishMiddleware` processed it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiddlewareAction {
+    /// Pass the record on to the next middleware, then to the subscribers
+    Continue,
+    /// Discard the record, it is neither stored nor routed
+    Drop,
+}
+
+/// Hook called by the `Runner` on every published record, after the publishing task ran
+/// and before the record is stored and routed to subscribers.
+/// Middleware is registered with `Runner::add_publish_middleware` and runs in that order.
+pub trait PublishMiddleware: Send + Sync {
+    fn process(&self, record: &mut Record) -> MiddlewareAction;
+}
+
+/// Stamps every published record with the wall clock time in nanoseconds since the
+/// Unix epoch, stored in the `_published_at_ns` schema metadata
+#[derive(Debug, Default)]
+pub struct TimestampMiddleware;
+
+impl TimestampMiddleware {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Publish time written by this middleware, if the record has one
+    pub fn published_at_ns(record: &Record) -> Option<i64> {
+        record.get_metadata(PUBLISHED_AT_METADATA)?.parse().ok()
+    }
+}
+
+impl PublishMiddleware for TimestampMiddleware {
+    fn process(&self, record: &mut Record) -> MiddlewareAction {
+        let now_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as i64)
+            .unwrap_or_default();
+        if let Err(err) = record.set_metadata(PUBLISHED_AT_METADATA, now_ns.to_string()) {
+            warn!("Failed to stamp publish time: {}", err);
+        }
+        MiddlewareAction::Continue
+    }
+}
+
+/// Appends a `Timestamp(Nanosecond)` column with the publish time to every published record,
+/// see `Record::add_timestamp_column`
+#[derive(Debug)]
+pub struct TimestampColumnMiddleware {
+    column_name: String,
+}
+
+impl TimestampColumnMiddleware {
+    pub fn new(column_name: &str) -> Self {
+        Self {
+            column_name: column_name.to_string(),
+        }
+    }
+}
+
+impl PublishMiddleware for TimestampColumnMiddleware {
+    fn process(&self, record: &mut Record) -> MiddlewareAction {
+        match record.add_timestamp_column(&self.column_name) {
+            Ok(stamped) => *record = stamped,
+            Err(err) => warn!("Failed to add timestamp column: {}", err),
+        }
+        MiddlewareAction::Continue
+    }
+}
+
+/// Appends a `UInt64` sequence column to every published record. The sequence is shared by
+/// all topics and keeps counting up across records, see `Record::add_sequence_column`
+#[derive(Debug)]
+pub struct SequenceColumnMiddleware {
+    column_name: String,
+    next: AtomicU64,
+}
+
+impl SequenceColumnMiddleware {
+    pub fn new(column_name: &str, start: u64) -> Self {
+        Self {
+            column_name: column_name.to_string(),
+            next: AtomicU64::new(start),
+        }
+    }
+}
+
+impl PublishMiddleware for SequenceColumnMiddleware {
+    fn process(&self, record: &mut Record) -> MiddlewareAction {
+        let num_rows = record.to_record_batch().num_rows() as u64;
+        let start = self.next.fetch_add(num_rows, Ordering::Relaxed);
+        match record.add_sequence_column(&self.column_name, start) {
+            Ok(sequenced) => *record = sequenced,
+            Err(err) => warn!("Failed to add sequence column: {}", err),
+        }
+        MiddlewareAction::Continue
+    }
+}
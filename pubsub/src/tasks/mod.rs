@@ -1,7 +1,16 @@
+pub mod condition_gate;
+pub mod configurable;
+pub mod graph;
 pub mod info;
+pub mod load_balancer;
 pub mod logging;
 pub mod meta_control;
+pub mod metrics;
+pub mod middleware;
+pub mod observer;
 pub mod runner;
+pub mod schema_registry;
+pub mod snapshot;
 pub mod state;
 pub mod subscription_queue;
 pub mod task;
@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use crate::message::record::Record;
+
+use super::info::TaskInfo;
+
+/// Side-effect hooks called by the `Runner` around every `Task::run`.
+/// Observers are registered with `Runner::add_observer` and see every task.
+pub trait TaskObserver: Send + Sync {
+    /// Called right before a task runs with the inputs it is about to receive
+    fn on_before_run(&self, task: &TaskInfo, inputs: &[Record]);
+
+    /// Called after a task ran successfully with the records it sent and how long it took
+    fn on_after_run(&self, task: &TaskInfo, outputs: &[Record], elapsed: Duration);
+
+    /// Called instead of `on_after_run` when a task run returned an error
+    fn on_error(&self, _task: &TaskInfo, _error: &anyhow::Error, _elapsed: Duration) {}
+}
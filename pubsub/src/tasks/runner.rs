@@ -1,25 +1,70 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use arrow::array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
 use log::debug;
 use log::error;
 use log::info;
 use log::trace;
+use log::warn;
 
+use crate::message::error_record::{ErrorRecord, ERROR_TOPIC_PREFIX};
 use crate::message::record::Record;
 use crate::message::record::RecordFlag;
+use crate::replay;
 use crate::tasks::meta_control::MetaCommand;
-use crate::tasks::subscription_queue::SubscriptionQueue;
+use crate::tasks::subscription_queue::{DropPolicy, SubscriptionPolicy, SubscriptionQueue};
 
+use super::configurable::{task_from_json, Configurable, TaskRegistry, TaskSpec};
 use super::info::TaskInfo;
+use super::load_balancer::{worker_topic, DispatchStrategy, LoadBalancedWorker, LoadBalancerTask};
 use super::logging::OutputFormat;
 use super::logging::RunnerLogger;
+use super::metrics::{RunnerMetrics, METRICS_TOPIC};
+use super::middleware::{MiddlewareAction, PublishMiddleware};
+use super::observer::TaskObserver;
+use super::schema_registry::{SchemaError, SchemaRegistry};
+use super::snapshot;
 use super::state::RunnerState;
-use super::task::Task;
+use super::task::{ErrorAction, Task};
+
+/// Column used to pace replayed records, in milliseconds
+pub const REPLAY_TIMESTAMP_COLUMN: &str = "timestamp";
+
+/// Tick rate of a new `Runner`
+pub const DEFAULT_TICK_RATE_HZ: f64 = 200.0;
+
+/// Number of recent iterations `Runner::actual_hz` is measured over
+const TICK_RATE_WINDOW: usize = 50;
+
+/// A subscription a task asked for or gave up, applied once the task finished sending
+enum SubscriptionChange {
+    Subscribe(TaskInfo, String),
+    Unsubscribe(TaskInfo, String),
+}
+
+/// Pattern bridged out of a runner and the channel of the runner it is delivered to
+type BridgeOutlet = (String, mpsc::Sender<Record>);
+
+/// One delivery of a message, as reported by `Runner::trace_message_path`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageHop {
+    /// A task that has published on the topic, if any has
+    pub from_task: Option<TaskInfo>,
+    pub to_task: TaskInfo,
+    pub topic: String,
+    /// The subscription pattern of `to_task` that matched the topic
+    pub pattern: String,
+    /// Records already waiting in the matching subscription queue
+    pub queue_depth_before: usize,
+}
+
 pub struct Runner {
     tasks: HashMap<TaskInfo, Arc<Mutex<dyn Task>>>,
     spawn_tasks: HashSet<TaskInfo>,
@@ -27,7 +72,43 @@ pub struct Runner {
     state: Arc<Mutex<RunnerState>>,
     subscriptions: HashMap<TaskInfo, Vec<String>>,
     subscription_queues: HashMap<TaskInfo, Vec<SubscriptionQueue>>,
-    logger: Arc<Mutex<RunnerLogger>>,
+    /// Writes the runner state to disk, nothing is logged when None
+    logger: Option<Arc<Mutex<RunnerLogger>>>,
+    /// `logger` is still the implicit one created by `new`, warned about in `init`
+    default_logger: bool,
+    known_topics: Arc<Mutex<HashSet<String>>>,
+    published_topics: HashMap<TaskInfo, HashSet<String>>,
+    observers: Vec<Arc<dyn TaskObserver>>,
+    publish_middleware: Vec<Box<dyn PublishMiddleware>>,
+    task_registry: TaskRegistry,
+    /// Bound applied to every new subscription queue, unbounded when None
+    default_queue_policy: Option<(usize, DropPolicy)>,
+    /// Iterations per second `run` paces itself to, no sleeping when None
+    target_hz: Option<f64>,
+    /// Start times of the most recent iterations, for `actual_hz`
+    tick_times: VecDeque<std::time::Instant>,
+    /// Prefix of every topic the tasks of this runner publish and subscribe to
+    namespace: Option<String>,
+    /// Patterns other runners bridged from this one, with the channel to send matches on
+    bridge_outlets: Arc<Mutex<Vec<BridgeOutlet>>>,
+    /// Records bridged in from other runners, delivered at the start of the next `run`
+    bridge_tx: mpsc::Sender<Record>,
+    bridge_rx: mpsc::Receiver<Record>,
+    /// Stage topics kept in snapshots, with the latest record published on each
+    stage_records: HashMap<String, Option<Record>>,
+    /// Schemas published records must follow, by topic pattern
+    schema_registry: SchemaRegistry,
+    /// Fail `run` on a record breaking its registered schema instead of dropping it
+    strict_schema_validation: bool,
+    /// Ticks between two publishes on `metrics/runner`, no metrics are collected when None
+    metrics_interval: Option<usize>,
+    /// Metrics of the task runs since the last publish
+    metrics: Arc<Mutex<Vec<RunnerMetrics>>>,
+    /// Metrics published last, for `get_last_metrics`
+    last_metrics: Vec<RunnerMetrics>,
+    ticks_since_metrics: usize,
+    #[cfg(feature = "arrow-flight")]
+    flight_servers: Vec<crate::flight::FlightServerHandle>,
 }
 
 impl Default for Runner {
@@ -38,6 +119,7 @@ impl Default for Runner {
 
 impl Runner {
     pub fn new() -> Self {
+        let (bridge_tx, bridge_rx) = mpsc::channel();
         Self {
             tasks: HashMap::new(),
             spawn_tasks: HashSet::new(),
@@ -45,7 +127,7 @@ impl Runner {
             state: Arc::new(Mutex::new(RunnerState::new())),
             subscriptions: HashMap::new(),
             subscription_queues: HashMap::new(),
-            logger: Arc::new(Mutex::new(
+            logger: Some(Arc::new(Mutex::new(
                 RunnerLogger::new(
                     PathBuf::from("logs"),
                     5000,
@@ -54,7 +136,131 @@ impl Runner {
                     None,
                 )
                 .unwrap(),
-            )),
+            ))),
+            default_logger: true,
+            known_topics: Arc::new(Mutex::new(HashSet::new())),
+            published_topics: HashMap::new(),
+            observers: Vec::new(),
+            publish_middleware: Vec::new(),
+            task_registry: TaskRegistry::new(),
+            default_queue_policy: None,
+            target_hz: Some(DEFAULT_TICK_RATE_HZ),
+            tick_times: VecDeque::with_capacity(TICK_RATE_WINDOW),
+            namespace: None,
+            bridge_outlets: Arc::new(Mutex::new(Vec::new())),
+            bridge_tx,
+            bridge_rx,
+            stage_records: HashMap::new(),
+            schema_registry: SchemaRegistry::new(),
+            strict_schema_validation: false,
+            metrics_interval: None,
+            metrics: Arc::new(Mutex::new(Vec::new())),
+            last_metrics: Vec::new(),
+            ticks_since_metrics: 0,
+            #[cfg(feature = "arrow-flight")]
+            flight_servers: Vec::new(),
+        }
+    }
+
+    /// Log the runner state with `logger` instead of the default one writing parquet and CSV
+    /// files to `logs/` every 5000 rows
+    pub fn with_logger(mut self, logger: RunnerLogger) -> Self {
+        self.logger = Some(Arc::new(Mutex::new(logger)));
+        self.default_logger = false;
+        self
+    }
+
+    /// Don't write anything to disk, e.g. in tests.
+    /// Without a logger the topics of the runner state are never trimmed to their history.
+    pub fn disable_logging(mut self) -> Self {
+        self.logger = None;
+        self.default_logger = false;
+        self
+    }
+
+    /// Fail `init` and `run` when a task publishes a record breaking the schema registered
+    /// for its topic. By default the error is logged and the record dropped.
+    pub fn with_strict_schema_validation(mut self) -> Self {
+        self.strict_schema_validation = true;
+        self
+    }
+
+    /// Require the records published on topics matching `topic_pattern` to follow `schema`,
+    /// see `SchemaRegistry`. The pattern is scoped to the namespace of this runner.
+    pub fn register_topic_schema(
+        &mut self,
+        topic_pattern: &str,
+        schema: Arc<Schema>,
+    ) -> Result<(), anyhow::Error> {
+        let topic_pattern = self.scoped_topic(topic_pattern);
+        self.schema_registry.register_topic(&topic_pattern, schema)
+    }
+
+    pub fn schema_registry(&self) -> &SchemaRegistry {
+        &self.schema_registry
+    }
+
+    /// Collect a `RunnerMetrics` entry for every task run and publish them as one batch on
+    /// `metrics/runner` every `ticks` calls to `run`, at least every tick
+    pub fn with_metrics_interval(mut self, ticks: usize) -> Self {
+        self.metrics_interval = Some(ticks.max(1));
+        self
+    }
+
+    /// The batch of metrics published last on `metrics/runner`, empty before the first one
+    pub fn get_last_metrics(&self) -> Vec<RunnerMetrics> {
+        self.last_metrics.clone()
+    }
+
+    /// Bound every subscription queue to `max_size` records.
+    /// With `DropPolicy::Block` a task is not run while a queue it publishes to is full.
+    pub fn with_default_queue_policy(mut self, max_size: usize, policy: DropPolicy) -> Self {
+        self.default_queue_policy = Some((max_size, policy));
+        self
+    }
+
+    /// Prefix every topic the tasks publish and subscribe to with `{ns}/`, so runners
+    /// of different vehicles can share tasks without their messages mixing.
+    /// Use `bridge_topic` to route shared topics between namespaces.
+    pub fn with_namespace(mut self, ns: &str) -> Self {
+        self.namespace = Some(ns.trim_matches('/').to_string());
+        self
+    }
+
+    /// The namespace set with `with_namespace`
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    /// Pace `run` to `hz` iterations per second, see `set_tick_rate`
+    pub fn with_tick_rate(mut self, hz: f64) -> Self {
+        self.set_tick_rate(hz);
+        self
+    }
+
+    /// Pace `run` to `hz` iterations per second. Only time spent in tasks that ran
+    /// counts against the tick budget. A rate that is not positive disables the sleep.
+    pub fn set_tick_rate(&mut self, hz: f64) {
+        self.target_hz = (hz > 0.0 && hz.is_finite()).then_some(hz);
+    }
+
+    /// The configured tick rate, None when `run` does not sleep
+    pub fn tick_rate(&self) -> Option<f64> {
+        self.target_hz
+    }
+
+    /// Measured iterations per second over the last few calls to `run`, 0 until two have run
+    pub fn actual_hz(&self) -> f64 {
+        match (self.tick_times.front(), self.tick_times.back()) {
+            (Some(first), Some(last)) if self.tick_times.len() > 1 => {
+                let elapsed = last.duration_since(*first).as_secs_f64();
+                if elapsed > 0.0 {
+                    (self.tick_times.len() - 1) as f64 / elapsed
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
         }
     }
 
@@ -71,7 +277,154 @@ impl Runner {
         self.tasks.insert(task_info.clone(), task);
     }
 
+    /// Build a task of type `T` from its JSON config and add it
+    pub fn add_task_with_config<T: Task + Configurable + 'static>(
+        &mut self,
+        config_json: &str,
+    ) -> Result<(), anyhow::Error> {
+        let task = task_from_json::<T>(config_json)?;
+        self.add_task(Arc::new(Mutex::new(task)));
+        Ok(())
+    }
+
+    /// Allow `T` to be created by `add_tasks_from_json_array` under its type name
+    pub fn register_task_type<T: Task + Configurable + 'static>(&mut self) {
+        self.task_registry.register::<T>();
+    }
+
+    /// Add every task of a JSON array of `{"type": ..., "config": {...}}` objects.
+    /// Nothing is added if any entry fails to build.
+    pub fn add_tasks_from_json_array(&mut self, json: &str) -> Result<(), anyhow::Error> {
+        let specs: Vec<TaskSpec> = serde_json::from_str(json)?;
+        let tasks = specs
+            .into_iter()
+            .map(|spec| self.task_registry.create(spec))
+            .collect::<Result<Vec<_>, _>>()?;
+        for task in tasks {
+            self.add_task(task);
+        }
+        Ok(())
+    }
+
+    /// Spread the records of `source` over `n_workers` instances built by `task_factory`,
+    /// dispatching round robin. Returns the info of the balancer task.
+    pub fn add_load_balanced_pool(
+        &mut self,
+        source: &str,
+        n_workers: usize,
+        task_factory: impl Fn(usize) -> Box<dyn Task + Send>,
+    ) -> TaskInfo {
+        self.add_load_balanced_pool_with_strategy(
+            source,
+            n_workers,
+            DispatchStrategy::RoundRobin,
+            task_factory,
+        )
+    }
+
+    /// Same as `add_load_balanced_pool` with an explicit dispatch strategy
+    pub fn add_load_balanced_pool_with_strategy(
+        &mut self,
+        source: &str,
+        n_workers: usize,
+        strategy: DispatchStrategy,
+        task_factory: impl Fn(usize) -> Box<dyn Task + Send>,
+    ) -> TaskInfo {
+        let worker_topics = (0..n_workers)
+            .map(|index| worker_topic(source, index))
+            .collect();
+        let balancer = LoadBalancerTask::new(source, worker_topics, strategy);
+        let balancer_info = balancer.get_task_info().clone();
+        let pending = balancer.pending_counters();
+        self.add_task(Arc::new(Mutex::new(balancer)));
+
+        for index in 0..n_workers {
+            let worker =
+                LoadBalancedWorker::new(index, source, task_factory(index), pending.clone());
+            self.add_task(Arc::new(Mutex::new(worker)));
+        }
+        balancer_info
+    }
+
+    /// Deliver records the tasks of `src_runner` publish on `pattern` to this runner.
+    /// `pattern` and the delivered topics are relative to each runner's namespace, so
+    /// `errors/*` bridged from `vehicle-1` arrives here as `vehicle-2/errors/...`.
+    /// Bridged records are delivered at the start of the next `run`.
+    pub fn bridge_topic(&mut self, src_runner: &Runner, pattern: &str) {
+        let pattern = src_runner.scoped_topic(pattern);
+        info!(
+            "Bridging topic {} into namespace {}",
+            pattern,
+            self.namespace().unwrap_or("<none>")
+        );
+        src_runner
+            .bridge_outlets
+            .lock()
+            .unwrap()
+            .push((pattern, self.bridge_tx.clone()));
+    }
+
+    /// Keep the latest record published on `topic` in every snapshot, even once the logger
+    /// trimmed the topic from the state, so stage-aware tasks resume after a restore
+    pub fn add_stage_topic(&mut self, topic: &str) {
+        let topic = self.scoped_topic(topic);
+        let latest = self
+            .state
+            .lock()
+            .unwrap()
+            .get_latest_topic_data(&topic)
+            .ok();
+        self.stage_records.insert(topic, latest);
+    }
+
+    /// Write the current state to `path`, one parquet file per topic plus a
+    /// `snapshot.json` manifest listing the topics, their row counts and schema hashes
+    pub fn snapshot(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let manifest =
+            snapshot::write_snapshot(path, &self.state.lock().unwrap(), &self.stage_records)?;
+        info!(
+            "Wrote snapshot of {} topics to {:?}",
+            manifest.topics.len(),
+            path
+        );
+        Ok(())
+    }
+
+    /// Read a state written by `snapshot`, install it with `load_state`
+    pub fn restore(path: &Path) -> Result<RunnerState, anyhow::Error> {
+        snapshot::read_snapshot(path)
+    }
+
+    /// Replace the state, e.g. with one from `restore`. Call before `init` so subscribers
+    /// receive the latest record of every topic they subscribe to.
+    pub fn load_state(&mut self, state: RunnerState) {
+        for (topic, latest) in self.stage_records.iter_mut() {
+            *latest = state.get_latest_topic_data(topic).ok();
+        }
+        *self.state.lock().unwrap() = state;
+    }
+
+    /// Register an observer that is called around every task run
+    pub fn add_observer(&mut self, observer: Arc<dyn TaskObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Run `middleware` on every published record, after any middleware added before it
+    pub fn add_publish_middleware(&mut self, middleware: Box<dyn PublishMiddleware>) {
+        self.publish_middleware.push(middleware);
+    }
+
     pub fn add_subscription(&mut self, task_info: &TaskInfo, topic: String) {
+        self.add_subscription_with_policy(task_info, topic, SubscriptionPolicy::All);
+    }
+
+    /// Subscribe a task to `topic`, handing it only the records selected by `policy`
+    pub fn add_subscription_with_policy(
+        &mut self,
+        task_info: &TaskInfo,
+        topic: String,
+        policy: SubscriptionPolicy,
+    ) {
         info!(
             "Adding subscription for task {} with topic {}",
             task_info, topic
@@ -84,7 +437,11 @@ impl Runner {
             .push(topic.clone());
 
         // Create a new subscription queue for this task and topic
-        let sub_queue = SubscriptionQueue::new(task_info.clone(), topic.clone());
+        let mut sub_queue = SubscriptionQueue::new(task_info.clone(), topic.clone())
+            .with_subscription_policy(policy);
+        if let Some((max_size, policy)) = self.default_queue_policy {
+            sub_queue = sub_queue.with_max_size(max_size, policy);
+        }
 
         // Add the subscription queue to the map
         self.subscription_queues
@@ -98,12 +455,58 @@ impl Runner {
         if let Ok(state_lock) = self.state.lock() {
             if let Ok(records) = state_lock.query_latest_topic_data(&topic) {
                 for record in records {
-                    sub_queue.push(record);
+                    sub_queue.try_push(record);
                 }
             }
         }
     }
 
+    /// Remove the subscriptions of a task to `pattern`. A wildcard pattern also removes
+    /// the subscriptions it matches, e.g. `mavlink/*` removes `mavlink/attitude`.
+    /// Returns the number of subscriptions removed.
+    pub fn remove_subscription(&mut self, task_info: &TaskInfo, pattern: &str) -> usize {
+        let matches = |subscribed: &str| {
+            subscribed == pattern
+                || (pattern.contains('*') && self.pattern_matches(pattern, subscribed))
+        };
+
+        let (removed, kept): (Vec<SubscriptionQueue>, Vec<SubscriptionQueue>) = self
+            .subscription_queues
+            .get(task_info)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .partition(|queue| matches(queue.topic_pattern()));
+        let kept_patterns: Vec<String> = self
+            .subscriptions
+            .get(task_info)
+            .into_iter()
+            .flatten()
+            .filter(|subscribed| !matches(subscribed))
+            .cloned()
+            .collect();
+        let removed = removed.len();
+        self.subscription_queues.insert(task_info.clone(), kept);
+        self.subscriptions.insert(task_info.clone(), kept_patterns);
+
+        info!(
+            "Removed {} subscriptions of task {} matching {}",
+            removed, task_info, pattern
+        );
+        removed
+    }
+
+    fn apply_subscription_change(&mut self, change: SubscriptionChange) {
+        match change {
+            SubscriptionChange::Subscribe(task_info, topic) => {
+                self.add_subscription(&task_info, topic)
+            }
+            SubscriptionChange::Unsubscribe(task_info, topic) => {
+                self.remove_subscription(&task_info, &topic);
+            }
+        }
+    }
+
     pub fn start_task(&mut self, task_info: &TaskInfo) -> Result<(), anyhow::Error> {
         if !self.tasks.contains_key(task_info) {
             return Err(anyhow::anyhow!("Task {} not found", task_info));
@@ -130,61 +533,163 @@ impl Runner {
         self.running_tasks.contains(task_info)
     }
 
+    /// Check if a task is queued to be spawned on the next run
+    pub fn is_task_pending_spawn(&self, task_info: &TaskInfo) -> bool {
+        self.spawn_tasks.contains(task_info)
+    }
+
+    /// Get the info of every registered task
+    pub fn task_infos(&self) -> Vec<TaskInfo> {
+        self.tasks.keys().cloned().collect()
+    }
+
+    /// Get the subscription patterns of a task
+    pub fn task_subscriptions(&self, task_info: &TaskInfo) -> Vec<String> {
+        self.subscriptions
+            .get(task_info)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Get the topics a task has published so far
+    pub fn task_published_topics(&self, task_info: &TaskInfo) -> HashSet<String> {
+        self.published_topics
+            .get(task_info)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn init(&mut self) -> Result<(), anyhow::Error> {
+        if self.default_logger {
+            warn!(
+                "Runner is using the default RunnerLogger writing to logs/, relying on it is \
+                 deprecated: configure logging with Runner::with_logger or Runner::disable_logging"
+            );
+        }
+
         let mut new_subscriptions = Vec::new();
-        for (task_id, task) in &self.tasks {
+        let tasks: Vec<(TaskInfo, Arc<Mutex<dyn Task>>)> = self
+            .tasks
+            .iter()
+            .map(|(task_info, task)| (task_info.clone(), task.clone()))
+            .collect();
+        for (task_id, task) in tasks {
             let mut task = task.lock().unwrap();
-            let tx = mpsc::channel();
-            let meta_tx = mpsc::channel();
-            task.init(tx.0, meta_tx.0)?;
-
-            while let Ok(record_msg) = tx.1.recv() {
-                let record_type = record_msg.get_flag()?;
-                match record_type {
-                    RecordFlag::SubscribePacket => {
-                        let task_info = task_id.clone();
-                        let topic = record_msg.try_get_topic()?;
-                        new_subscriptions.push((task_info, topic));
-                    }
-                    RecordFlag::PublishPacket => {
-                        // Store in state for logging/persistence
-                        self.state.lock().unwrap().apply_record(&record_msg)?;
+            new_subscriptions.extend(self.init_task(&task_id, &mut *task)?);
+        }
+        for change in new_subscriptions {
+            self.apply_subscription_change(change);
+        }
+        Ok(())
+    }
+
+    /// Call `init` on one task and apply what it sent, returning the subscription changes
+    /// it asked for
+    fn init_task(
+        &mut self,
+        task_id: &TaskInfo,
+        task: &mut dyn Task,
+    ) -> Result<Vec<SubscriptionChange>, anyhow::Error> {
+        let mut new_subscriptions = Vec::new();
+        let tx = mpsc::channel();
+        let meta_tx = mpsc::channel();
+        task.init(tx.0, meta_tx.0)?;
+
+        while let Ok(record_msg) = tx.1.recv() {
+            let record_type = record_msg.get_flag()?;
+            match record_type {
+                RecordFlag::SubscribePacket => {
+                    let topic = self.scoped_topic(&record_msg.try_get_topic()?);
+                    new_subscriptions.push(SubscriptionChange::Subscribe(task_id.clone(), topic));
+                }
+                RecordFlag::UnsubscribePacket => {
+                    let topic = self.scoped_topic(&record_msg.try_get_topic()?);
+                    new_subscriptions.push(SubscriptionChange::Unsubscribe(task_id.clone(), topic));
+                }
+                RecordFlag::PublishPacket | RecordFlag::ErrorPacket => {
+                    let record_msg = if record_type == RecordFlag::ErrorPacket {
+                        Self::prepare_error_packet(task_id, record_msg)
+                    } else {
+                        record_msg
+                    };
+                    let record_msg = self.scope_record(record_msg)?;
+                    let Some(record_msg) = self.apply_publish_middleware(record_msg) else {
+                        continue;
+                    };
 
-                        // Route to any existing subscribers
-                        let topic = record_msg.try_get_topic()?;
-                        self.route_message_to_subscribers(&topic, record_msg.clone())?;
+                    // Route to any existing subscribers, records that fail are not stored
+                    let topic = record_msg.try_get_topic()?;
+                    if let Err(err) = self.route_message_to_subscribers(&topic, record_msg.clone())
+                    {
+                        self.drop_unrouted_record(&task_id.name, err)?;
+                        continue;
                     }
+
+                    // Store in state for logging/persistence
+                    self.state.lock().unwrap().apply_record(&record_msg)?;
+                    self.forward_to_bridges(&topic, &record_msg);
+                    self.track_stage(&topic, &record_msg);
+                    self.published_topics
+                        .entry(task_id.clone())
+                        .or_default()
+                        .insert(topic);
                 }
             }
+        }
 
-            while let Ok(meta_msg) = meta_tx.1.recv() {
-                match &meta_msg.command {
-                    MetaCommand::SpawnTask => {
-                        info!("Spawning task: {}", meta_msg.task_info);
-                        if !self.running_tasks.contains(&meta_msg.task_info) {
-                            self.spawn_tasks.insert(meta_msg.task_info.clone());
-                        }
+        while let Ok(meta_msg) = meta_tx.1.recv() {
+            match &meta_msg.command {
+                MetaCommand::SpawnTask => {
+                    info!("Spawning task: {}", meta_msg.task_info);
+                    if !self.running_tasks.contains(&meta_msg.task_info) {
+                        self.spawn_tasks.insert(meta_msg.task_info.clone());
                     }
-                    MetaCommand::KillTask => {
-                        if self.running_tasks.contains(&meta_msg.task_info) {
-                            info!("Killing task: {}", meta_msg.task_info);
-                            self.running_tasks.remove(&meta_msg.task_info);
-                        }
+                }
+                MetaCommand::KillTask => {
+                    if self.running_tasks.contains(&meta_msg.task_info) {
+                        info!("Killing task: {}", meta_msg.task_info);
+                        self.running_tasks.remove(&meta_msg.task_info);
                     }
                 }
             }
         }
-        for (task_info, topic) in new_subscriptions {
-            self.add_subscription(&task_info, topic);
+        Ok(new_subscriptions)
+    }
+
+    /// Re-initialize a task after a failure, replacing its subscriptions with the ones
+    /// `init` asks for
+    fn restart_task(
+        &mut self,
+        task_id: &TaskInfo,
+        task: &mut dyn Task,
+    ) -> Result<(), anyhow::Error> {
+        self.subscriptions.remove(task_id);
+        self.subscription_queues.remove(task_id);
+        for change in self.init_task(task_id, task)? {
+            self.apply_subscription_change(change);
         }
         Ok(())
     }
 
     pub fn run(&mut self) -> Result<(), anyhow::Error> {
+        if self.tick_times.len() == TICK_RATE_WINDOW {
+            self.tick_times.pop_front();
+        }
+        self.tick_times.push_back(std::time::Instant::now());
+
+        while let Ok(record) = self.bridge_rx.try_recv() {
+            if let Err(err) = self.inject_record(record) {
+                error!("Failed to deliver bridged record: {}", err);
+            }
+        }
+
+        // Time spent in tasks that actually ran this iteration
+        let mut busy = std::time::Duration::ZERO;
         let mut new_subscriptions = Vec::new();
         let mut debug_inputs = Vec::new();
         let mut debug_n_output_map = HashMap::new();
-        for (task_id, task) in &self.tasks {
+        for (task_id, task) in self.tasks_by_priority() {
+            let task_id = &task_id;
             // Skip tasks that are not in the running set
             if !self.running_tasks.contains(task_id) && !self.spawn_tasks.contains(task_id) {
                 continue;
@@ -209,6 +714,11 @@ impl Runner {
                 continue;
             }
 
+            if self.is_backpressured(task_id) {
+                trace!("Task '{}' held back by a full subscription queue", task_id);
+                continue;
+            }
+
             // New approach: Get inputs by draining all subscription queues for this task
             let mut inputs: Vec<Record> = Vec::new();
             let queues = self
@@ -219,48 +729,111 @@ impl Runner {
             let mut total_inputs = 0;
 
             for queue in &queues {
-                let records = queue.drain();
+                let records = match (queue.subscription_policy(), task_id.max_inputs_per_cycle) {
+                    (SubscriptionPolicy::All, Some(max_inputs)) => queue.drain_n(max_inputs),
+                    _ => queue.drain_by_policy(),
+                };
                 total_inputs += records.len();
                 inputs.extend(records);
             }
 
             debug_inputs.push((task_id.clone(), total_inputs));
 
+            for observer in &self.observers {
+                observer.on_before_run(task_id, &inputs);
+            }
+
             let out_channel = mpsc::channel();
             let meta_channel = mpsc::channel();
-            if let Err(err) = task.run(inputs, out_channel.0, meta_channel.0) {
+            let run_start = std::time::Instant::now();
+            let result = task.run(inputs, out_channel.0, meta_channel.0);
+            let run_elapsed = run_start.elapsed();
+            busy += run_elapsed;
+            if let Err(err) = result {
                 error!("Task '{}' failed during execution: {}", task_id, err);
+                for observer in &self.observers {
+                    observer.on_error(task_id, &err, run_elapsed);
+                }
+                self.collect_metrics(task_id, run_elapsed, total_inputs, 0, 1);
+                match task.on_error(&err) {
+                    ErrorAction::Continue => {}
+                    ErrorAction::Restart => {
+                        info!("Restarting task: {}", task_id);
+                        if let Err(init_err) = self.restart_task(task_id, &mut *task) {
+                            error!("Task '{}' failed to restart: {}", task_id, init_err);
+                        }
+                    }
+                    ErrorAction::KillTask => {
+                        info!("Killing task: {}", task_id);
+                        self.running_tasks.remove(task_id);
+                    }
+                    ErrorAction::Fatal => {
+                        return Err(err.context(format!("Task '{}' failed fatally", task_id)));
+                    }
+                }
                 continue;
             }
 
+            let outputs: Vec<Record> = out_channel.1.try_iter().collect();
+            for observer in &self.observers {
+                observer.on_after_run(task_id, &outputs, run_elapsed);
+            }
+            self.collect_metrics(task_id, run_elapsed, total_inputs, outputs.len(), 0);
+
             let mut n_messages = 0;
-            while let Ok(msg) = out_channel.1.recv() {
+            for msg in outputs {
                 match &msg.get_flag() {
                     Ok(flag) => {
                         match flag {
                             RecordFlag::SubscribePacket => {
-                                let task_info = task_id.clone();
                                 match msg.try_get_topic() {
-                                Ok(topic) => new_subscriptions.push((task_info, topic)),
+                                Ok(topic) => new_subscriptions.push(SubscriptionChange::Subscribe(task_id.clone(), self.scoped_topic(&topic))),
                                 Err(err) => error!("Failed to get topic from subscription message for task '{}': {}", task_id, err)
                             }
                             }
-                            RecordFlag::PublishPacket => {
-                                // Add to state for persistence/logging
-                                if let Err(err) = self.state.lock().unwrap().apply_record(&msg) {
-                                    error!(
-                                        "Failed to apply record to state for task '{}': {}",
-                                        task_id, err
-                                    );
+                            RecordFlag::UnsubscribePacket => {
+                                match msg.try_get_topic() {
+                                Ok(topic) => new_subscriptions.push(SubscriptionChange::Unsubscribe(task_id.clone(), self.scoped_topic(&topic))),
+                                Err(err) => error!("Failed to get topic from unsubscribe message for task '{}': {}", task_id, err)
+                            }
+                            }
+                            RecordFlag::PublishPacket | RecordFlag::ErrorPacket => {
+                                let msg = if *flag == RecordFlag::ErrorPacket {
+                                    Self::prepare_error_packet(task_id, msg)
+                                } else {
+                                    msg
+                                };
+                                let msg = match self.scope_record(msg) {
+                                    Ok(msg) => msg,
+                                    Err(err) => {
+                                        error!("Failed to get topic from publish message for task '{}': {}", task_id, err);
+                                        continue;
+                                    }
+                                };
+                                let Some(msg) = self.apply_publish_middleware(msg) else {
                                     continue;
-                                }
+                                };
 
                                 // Route the message to all matching subscription queues
                                 match msg.try_get_topic() {
                                 Ok(topic) => {
+                                    // Records that fail routing are neither stored nor forwarded
                                     if let Err(err) = self.route_message_to_subscribers(&topic, msg.clone()) {
-                                        error!("Failed to route message from task '{}': {}", task_id, err);
+                                        self.drop_unrouted_record(&task_id.name, err)?;
+                                        continue;
                                     }
+
+                                    // Add to state for persistence/logging
+                                    if let Err(err) = self.state.lock().unwrap().apply_record(&msg) {
+                                        error!(
+                                            "Failed to apply record to state for task '{}': {}",
+                                            task_id, err
+                                        );
+                                        continue;
+                                    }
+                                    self.forward_to_bridges(&topic, &msg);
+                                    self.track_stage(&topic, &msg);
+                                    self.published_topics.entry(task_id.clone()).or_default().insert(topic);
                                 },
                                 Err(err) => error!("Failed to get topic from publish message for task '{}': {}", task_id, err)
                             }
@@ -288,7 +861,7 @@ impl Runner {
                 }
             }
 
-            debug_n_output_map.insert(task_id, n_messages);
+            debug_n_output_map.insert(task_id.clone(), n_messages);
         }
 
         let mut debug_str = String::new();
@@ -298,7 +871,7 @@ impl Runner {
                 task_info,
                 debug_inputs
                     .iter()
-                    .find(|(t, _)| t == task_info)
+                    .find(|(t, _)| *t == task_info)
                     .unwrap_or(&(task_info.clone(), 0))
                     .1,
                 n_messages
@@ -306,41 +879,358 @@ impl Runner {
         }
         trace!("{}", debug_str);
 
-        for (task_info, topic) in new_subscriptions {
-            self.add_subscription(&task_info, topic);
+        for change in new_subscriptions {
+            self.apply_subscription_change(change);
         }
 
-        if let Err(err) = self
-            .logger
-            .lock()
-            .unwrap()
-            .process_state(&mut self.state.lock().unwrap())
-        {
-            error!("Failed to process state in logger: {}", err);
+        if let Err(err) = self.publish_metrics() {
+            error!("Failed to publish runner metrics: {}", err);
+        }
+
+        if let Some(logger) = &self.logger {
+            if let Err(err) = logger
+                .lock()
+                .unwrap()
+                .process_state(&mut self.state.lock().unwrap())
+            {
+                error!("Failed to process state in logger: {}", err);
+            }
+        }
+
+        // Sleep for what is left of the tick to avoid CPU overuse
+        if let Some(hz) = self.target_hz {
+            let tick = std::time::Duration::from_secs_f64(1.0 / hz);
+            std::thread::sleep(tick.saturating_sub(busy));
+        }
+        Ok(())
+    }
+
+    fn collect_metrics(
+        &self,
+        task_id: &TaskInfo,
+        run_elapsed: std::time::Duration,
+        input_count: usize,
+        output_count: usize,
+        error_count: u32,
+    ) {
+        if self.metrics_interval.is_none() {
+            return;
+        }
+        self.metrics.lock().unwrap().push(RunnerMetrics {
+            task_name: task_id.name.clone(),
+            run_duration_us: run_elapsed.as_micros() as u64,
+            input_count: input_count as u32,
+            output_count: output_count as u32,
+            error_count,
+        });
+    }
+
+    /// Publish the collected metrics on `metrics/runner` once every metrics interval
+    fn publish_metrics(&mut self) -> Result<(), anyhow::Error> {
+        let Some(interval) = self.metrics_interval else {
+            return Ok(());
+        };
+        self.ticks_since_metrics += 1;
+        if self.ticks_since_metrics < interval {
+            return Ok(());
+        }
+        self.ticks_since_metrics = 0;
+
+        let metrics = std::mem::take(&mut *self.metrics.lock().unwrap());
+        if metrics.is_empty() {
+            return Ok(());
+        }
+        let mut record = Record::from_serde_batch(&metrics)?;
+        record.set_topic(METRICS_TOPIC.to_string())?;
+        record.set_flag(RecordFlag::PublishPacket)?;
+        self.last_metrics = metrics;
+        self.inject_record(record)
+    }
+
+    /// All tasks, highest priority first. Ties are ordered by name so each tick runs
+    /// tasks in the same order.
+    fn tasks_by_priority(&self) -> Vec<(TaskInfo, Arc<Mutex<dyn Task>>)> {
+        let mut tasks: Vec<(TaskInfo, Arc<Mutex<dyn Task>>)> = self
+            .tasks
+            .iter()
+            .map(|(task_info, task)| (task_info.clone(), task.clone()))
+            .collect();
+        tasks.sort_by(|(a, _), (b, _)| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        tasks
+    }
+
+    /// Run `n` cycles of the task graph back to back
+    pub fn run_n_cycles(&mut self, n: usize) -> Result<(), anyhow::Error> {
+        for _ in 0..n {
+            self.run()?;
+        }
+        Ok(())
+    }
+
+    /// Replay the records of a historical state through the current task graph.
+    ///
+    /// Every row is injected as if it was just published, followed by a single run cycle.
+    /// If every topic has a `timestamp` column (milliseconds) rows are replayed in time order
+    /// and spaced by their recorded interval divided by `speed`. Otherwise rows are
+    /// interleaved across topics by index and paced only by the runner's own cycle time.
+    /// `init` must have been called so that subscriptions are in place.
+    ///
+    /// Unlike `replay_from_parquet`, which ticks at the runner's rate while the recording
+    /// clock runs, every row here gets exactly one cycle of its own. States don't need a
+    /// time column, so there is no recording clock to follow.
+    pub fn replay_from_state(
+        &mut self,
+        state: RunnerState,
+        speed: f64,
+    ) -> Result<(), anyhow::Error> {
+        if speed <= 0.0 || speed.is_nan() {
+            return Err(anyhow::anyhow!(
+                "Replay speed must be positive, got {}",
+                speed
+            ));
+        }
+
+        let mut topics = state.get_topics();
+        topics.sort();
+
+        let mut events = Vec::new();
+        let mut all_timed = true;
+        for topic in &topics {
+            let Some(record) = state.get_topic_record(topic) else {
+                continue;
+            };
+            let batch = record.to_record_batch();
+            let timestamps = Self::replay_timestamps(batch)?;
+            all_timed &= timestamps.is_some();
+
+            for row in 0..batch.num_rows() {
+                let mut row_record = Record::from_record_batch(batch.slice(row, 1));
+                row_record.set_topic(topic.clone())?;
+                row_record.set_flag(RecordFlag::PublishPacket)?;
+                let timestamp = timestamps.as_ref().and_then(|ts| ts[row]);
+                events.push((row, timestamp, row_record));
+            }
+        }
+
+        if all_timed {
+            events.sort_by(|a, b| a.1.unwrap_or_default().total_cmp(&b.1.unwrap_or_default()));
+        } else {
+            events.sort_by_key(|(row, _, _)| *row);
+        }
+
+        info!(
+            "Replaying {} records from {} topics at {}x speed",
+            events.len(),
+            topics.len(),
+            speed
+        );
+
+        let mut last_timestamp: Option<f64> = None;
+        for (_, timestamp, record) in events {
+            if let (true, Some(previous), Some(current)) = (all_timed, last_timestamp, timestamp) {
+                let delay_ms = (current - previous).max(0.0) / speed;
+                std::thread::sleep(std::time::Duration::from_secs_f64(delay_ms / 1000.0));
+            }
+            last_timestamp = timestamp.or(last_timestamp);
+
+            self.inject_record(record)?;
+            self.run_n_cycles(1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Play the topics recorded by a `RunnerLogger` session in `log_dir` back through the task
+    /// graph, e.g. to drive the exec stages from a flight log without a MAVLink device.
+    ///
+    /// Each topic is read from `{topic}.parquet`, or `{topic}_final.parquet`, and sorted by
+    /// `time_column`, an arrow timestamp or a number of milliseconds. Every row is injected as
+    /// its own record once its recorded time is reached, with the recording clock running
+    /// `speed` times faster than the wall clock, while `run` keeps ticking in between.
+    /// The pacing is shared with `ReplayRunner`.
+    /// `init` must have been called so that subscriptions are in place.
+    pub fn replay_from_parquet(
+        &mut self,
+        log_dir: PathBuf,
+        topics: Vec<String>,
+        time_column: &str,
+        speed: f64,
+    ) -> Result<(), anyhow::Error> {
+        let topics = topics
+            .iter()
+            .map(|topic| replay::load_topic(&log_dir, topic, time_column))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut schedule = replay::ReplaySchedule::new(topics, speed)?;
+        info!(
+            "Replaying {} records from {} topics in {:?} at {}x speed",
+            schedule.row_count(),
+            schedule.topic_count(),
+            log_dir,
+            speed
+        );
+        schedule.play(self)
+    }
+
+    /// Store and route a record as if a task of this runner had just published it.
+    /// The topic is moved under the namespace of this runner unless it already is, so
+    /// records logged by a namespaced runner replay into the same namespace.
+    pub(crate) fn inject_record(&mut self, mut record: Record) -> Result<(), anyhow::Error> {
+        if self.namespace.is_some() {
+            let topic = record.try_get_topic()?;
+            record.set_topic(self.scoped_topic(self.unscoped_topic(&topic)))?;
         }
+        let Some(record) = self.apply_publish_middleware(record) else {
+            return Ok(());
+        };
+        let topic = record.try_get_topic()?;
+        if let Err(err) = self.route_message_to_subscribers(&topic, record.clone()) {
+            return self.drop_unrouted_record("injected record", err);
+        }
+        self.state.lock().unwrap().apply_record(&record)?;
+        self.track_stage(&topic, &record);
+        Ok(())
+    }
 
-        // Sleep for 5ms to avoid CPU overuse
-        std::thread::sleep(std::time::Duration::from_millis(5));
+    /// Log and drop a record that could not be routed. Schema violations are returned as
+    /// errors instead when strict schema validation is on.
+    fn drop_unrouted_record(&self, source: &str, err: anyhow::Error) -> Result<(), anyhow::Error> {
+        if self.strict_schema_validation && err.is::<SchemaError>() {
+            return Err(err.context(format!("Invalid record published by {}", source)));
+        }
+        error!("Dropping record published by {}: {}", source, err);
         Ok(())
     }
 
+    /// Read the replay timestamp column of a batch as milliseconds, if it has one
+    fn replay_timestamps(batch: &RecordBatch) -> Result<Option<Vec<Option<f64>>>, anyhow::Error> {
+        let Some(column) = batch.column_by_name(REPLAY_TIMESTAMP_COLUMN) else {
+            return Ok(None);
+        };
+        let values = arrow::compute::cast(column, &DataType::Float64)?;
+        let values = values
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .ok_or_else(|| anyhow::anyhow!("Failed to read replay timestamps"))?;
+        Ok(Some(values.iter().collect()))
+    }
+
+    /// Log an error packet and make sure it is routed under `errors/`
+    fn prepare_error_packet(task_id: &TaskInfo, mut record: Record) -> Record {
+        match record.to_serde::<ErrorRecord>() {
+            Ok(errors) => {
+                for err in errors {
+                    warn!(
+                        "Task '{}' reported {:?} error during {}: {}",
+                        task_id, err.severity, err.stage, err.message
+                    );
+                }
+            }
+            Err(_) => warn!("Task '{}' reported an error", task_id),
+        }
+
+        let topic = record
+            .try_get_topic()
+            .unwrap_or_else(|_| task_id.name.clone());
+        if !topic.starts_with(&format!("{}/", ERROR_TOPIC_PREFIX)) {
+            if let Err(err) = record.set_topic(format!("{}/{}", ERROR_TOPIC_PREFIX, topic)) {
+                error!("Failed to set error topic for task '{}': {}", task_id, err);
+            }
+        }
+        record
+    }
+
+    /// `topic` under the namespace of this runner
+    fn scoped_topic(&self, topic: &str) -> String {
+        match &self.namespace {
+            Some(ns) => format!("{}/{}", ns, topic),
+            None => topic.to_string(),
+        }
+    }
+
+    /// `topic` with the namespace of this runner removed
+    fn unscoped_topic<'a>(&self, topic: &'a str) -> &'a str {
+        match &self.namespace {
+            Some(ns) => topic
+                .strip_prefix(ns.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+                .unwrap_or(topic),
+            None => topic,
+        }
+    }
+
+    /// Move a record published by a task of this runner under its namespace
+    fn scope_record(&self, mut record: Record) -> Result<Record, anyhow::Error> {
+        if self.namespace.is_some() {
+            let topic = self.scoped_topic(&record.try_get_topic()?);
+            record.set_topic(topic)?;
+        }
+        Ok(record)
+    }
+
+    /// Remember the latest record of a stage topic for snapshots
+    fn track_stage(&mut self, topic: &str, record: &Record) {
+        if let Some(latest) = self.stage_records.get_mut(topic) {
+            *latest = record.get_n_latest_rows(1).ok();
+        }
+    }
+
+    /// Send a published record to every runner that bridged a matching pattern
+    fn forward_to_bridges(&self, topic: &str, record: &Record) {
+        let mut outlets = self.bridge_outlets.lock().unwrap();
+        // Runners that were dropped close their channel and are forgotten
+        outlets.retain(|(pattern, tx)| {
+            if !self.subscription_matches(pattern, topic) {
+                return true;
+            }
+            let mut bridged = record.clone();
+            if let Err(err) = bridged.set_topic(self.unscoped_topic(topic).to_string()) {
+                error!("Failed to bridge record on {}: {}", topic, err);
+                return true;
+            }
+            tx.send(bridged).is_ok()
+        });
+    }
+
+    /// Pass a published record through the middleware chain, None if one of them dropped it
+    fn apply_publish_middleware(&self, mut record: Record) -> Option<Record> {
+        for middleware in &self.publish_middleware {
+            if middleware.process(&mut record) == MiddlewareAction::Drop {
+                trace!("Record dropped by publish middleware");
+                return None;
+            }
+        }
+        Some(record)
+    }
+
     /// Route a published message to all matching subscription queues
     fn route_message_to_subscribers(
         &self,
         topic: &str,
         message: Record,
     ) -> Result<(), anyhow::Error> {
+        self.schema_registry.validate_record(&message)?;
+
+        // The first publish on a topic is delivered by scanning all existing
+        // patterns, so wildcard subscribers made before the topic existed still see it
+        let is_new_topic = self.known_topics.lock().unwrap().insert(topic.to_string());
+        if is_new_topic {
+            debug!("First publish on topic {}, scanning subscriptions", topic);
+            self.broadcast_new_topic(topic, message);
+            return Ok(());
+        }
+
         for queues in self.subscription_queues.values() {
             for queue in queues {
-                let pattern = queue.topic_pattern();
-
-                // Check if this subscription matches the topic
-                if topic.starts_with(pattern)
-                    || (pattern.contains('*') && self.pattern_matches(pattern, topic))
-                    || (pattern.contains('/') && topic.contains(pattern))
-                {
-                    // Add the message to the queue
-                    queue.push(message.clone());
+                if self.subscription_matches(queue.topic_pattern(), topic) {
+                    // Add the message to the queue, never waiting on a full one
+                    // since the queues are drained from this same thread
+                    if !queue.try_push(message.clone()) {
+                        trace!("Subscription queue full, dropped message on {}", topic);
+                    }
                 }
             }
         }
@@ -348,21 +1238,159 @@ impl Runner {
         Ok(())
     }
 
-    /// Helper method to check if a topic matches a pattern with wildcards
-    fn pattern_matches(&self, pattern: &str, topic: &str) -> bool {
-        if let Ok(regex) = regex::Regex::new(&pattern.replace('*', ".*")) {
-            regex.is_match(topic)
-        } else {
-            false
+    /// Push the first record of a new topic to every subscription whose pattern matches it
+    fn broadcast_new_topic(&self, topic: &str, message: Record) {
+        for queues in self.subscription_queues.values() {
+            for queue in queues {
+                if self.subscription_matches(queue.topic_pattern(), topic) {
+                    queue.mark_retroactive_topic(topic);
+                    queue.try_push(message.clone());
+                }
+            }
         }
     }
 
-    pub fn cleanup(&mut self) -> Result<(), anyhow::Error> {
-        // Process and dump any remaining state data
-        self.logger
-            .lock()
-            .unwrap()
-            .dump_remaining_state(&mut self.state.lock().unwrap())?;
+    /// Whether a `DropPolicy::Block` queue subscribed to a topic the task publishes is full
+    fn is_backpressured(&self, task_info: &TaskInfo) -> bool {
+        let Some(topics) = self.published_topics.get(task_info) else {
+            return false;
+        };
+        self.subscription_queues
+            .values()
+            .flatten()
+            .filter(|queue| queue.drop_policy() == DropPolicy::Block && queue.is_full())
+            .any(|queue| {
+                topics
+                    .iter()
+                    .any(|topic| self.subscription_matches(queue.topic_pattern(), topic))
+            })
+    }
+
+    /// Dry-run the routing of a message on `topic`: lists every subscription queue that
+    /// would receive it, without publishing anything. Hops are sorted by task name.
+    pub fn trace_message_path(&self, topic: &str) -> Vec<MessageHop> {
+        let mut publishers: Vec<&TaskInfo> = self
+            .published_topics
+            .iter()
+            .filter(|(_, topics)| topics.contains(topic))
+            .map(|(task_info, _)| task_info)
+            .collect();
+        publishers.sort_by(|a, b| a.name.cmp(&b.name));
+        let from_task = publishers.first().map(|task_info| (*task_info).clone());
+
+        let mut hops: Vec<MessageHop> = self
+            .subscription_queues
+            .values()
+            .flatten()
+            .filter(|queue| self.subscription_matches(queue.topic_pattern(), topic))
+            .map(|queue| MessageHop {
+                from_task: from_task.clone(),
+                to_task: queue.task_info().clone(),
+                topic: topic.to_string(),
+                pattern: queue.topic_pattern().to_string(),
+                queue_depth_before: queue.len(),
+            })
+            .collect();
+        hops.sort_by(|a, b| (&a.to_task.name, &a.pattern).cmp(&(&b.to_task.name, &b.pattern)));
+        hops
+    }
+
+    /// Check if a subscription pattern matches a topic
+    pub(crate) fn subscription_matches(&self, pattern: &str, topic: &str) -> bool {
+        topic.starts_with(pattern)
+            || (pattern.contains('*') && self.pattern_matches(pattern, topic))
+            || (pattern.contains('/') && topic.contains(pattern))
+    }
+
+    /// Helper method to check if a topic matches a pattern with wildcards
+    fn pattern_matches(&self, pattern: &str, topic: &str) -> bool {
+        if let Ok(regex) = regex::Regex::new(&pattern.replace('*', ".*")) {
+            regex.is_match(topic)
+        } else {
+            false
+        }
+    }
+
+    /// Capture the latest value of every topic in the state as a single wide Record.
+    /// Each column is named after a topic and holds its last row as a JSON string,
+    /// so topics with different schemas can live side by side.
+    pub fn snapshot_to_record(&self) -> Result<Record, anyhow::Error> {
+        let state = self.state.lock().unwrap();
+        let mut topics = state.get_topics();
+        topics.sort();
+
+        let mut fields = Vec::new();
+        let mut columns: Vec<ArrayRef> = Vec::new();
+        for topic in topics {
+            let latest = state.get_latest_topic_data(&topic)?;
+            let value = latest
+                .to_serde::<serde_json::Value>()?
+                .into_iter()
+                .next()
+                .unwrap_or(serde_json::Value::Null);
+
+            fields.push(Field::new(&topic, DataType::Utf8, false));
+            columns.push(Arc::new(StringArray::from(vec![value.to_string()])));
+        }
+
+        let schema = Arc::new(Schema::new(fields));
+        let batch = if columns.is_empty() {
+            RecordBatch::new_empty(schema)
+        } else {
+            RecordBatch::try_new(schema, columns)?
+        };
+
+        let mut snapshot = Record::from_record_batch(batch);
+        snapshot.set_topic("runner/snapshot".to_string())?;
+        Ok(snapshot)
+    }
+
+    /// Re-populate the state from a Record created by `snapshot_to_record`.
+    /// Each restored topic holds a single row with the snapshotted value.
+    pub fn restore_from_snapshot_record(&mut self, snapshot: &Record) -> Result<(), anyhow::Error> {
+        let batch = snapshot.to_record_batch();
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().unwrap();
+        for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+            let topic = field.name();
+            let values = column
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| anyhow::anyhow!("Snapshot column {} is not a string", topic))?;
+
+            let mut record = Record::from_json(values.value(0))?;
+            record.set_topic(topic.clone())?;
+            record.set_flag(RecordFlag::PublishPacket)?;
+            state.replace_topic_record(topic.clone(), record);
+            debug!("Restored topic {} from snapshot", topic);
+        }
+
+        Ok(())
+    }
+
+    /// Serve every topic in the state over Arrow Flight from a background runtime.
+    /// The server is stopped by `cleanup`.
+    #[cfg(feature = "arrow-flight")]
+    pub fn serve_flight(
+        &mut self,
+        addr: std::net::SocketAddr,
+    ) -> Result<crate::flight::FlightServerHandle, anyhow::Error> {
+        let handle = crate::flight::spawn_flight_server(self.state.clone(), addr)?;
+        self.flight_servers.push(handle.clone());
+        Ok(handle)
+    }
+
+    pub fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        // Process and dump any remaining state data
+        if let Some(logger) = &self.logger {
+            logger
+                .lock()
+                .unwrap()
+                .dump_remaining_state(&mut self.state.lock().unwrap())?;
+        }
 
         // Clean up all tasks
         for task in self.tasks.values() {
@@ -373,6 +1401,1326 @@ impl Runner {
         // Clear subscription queues
         self.subscription_queues.clear();
 
+        #[cfg(feature = "arrow-flight")]
+        for server in self.flight_servers.drain(..) {
+            server.shutdown();
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::builders::{publish::PublishBuilder, RecordBuilder};
+    use crate::tasks::task::{MetaTaskChannel, TaskChannel};
+    use crate::{publish, subscribe, unsubscribe};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestAttitude {
+        roll: f64,
+    }
+
+    struct TestPublisher {
+        info: TaskInfo,
+        published: bool,
+    }
+
+    impl Task for TestPublisher {
+        fn init(
+            &mut self,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            if !self.published {
+                tx.send(publish!("mavlink/attitude", &TestAttitude { roll: 1.0 }))?;
+                self.published = true;
+            }
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    struct TestSubscriber {
+        info: TaskInfo,
+        received: Arc<Mutex<Vec<Record>>>,
+    }
+
+    impl Task for TestSubscriber {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            tx.send(subscribe!("mavlink/*"))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            self.received.lock().unwrap().extend(inputs);
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    struct TestPatternSubscriber {
+        info: TaskInfo,
+        pattern: String,
+    }
+
+    impl Task for TestPatternSubscriber {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            tx.send(subscribe!(self.pattern))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+
+        fn should_run(&self) -> Result<bool, anyhow::Error> {
+            // Never drain, so queued records show up in the trace
+            Ok(false)
+        }
+    }
+
+    struct TestStreamPublisher {
+        info: TaskInfo,
+        runs: Arc<Mutex<usize>>,
+    }
+
+    impl Task for TestStreamPublisher {
+        fn init(
+            &mut self,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            let mut runs = self.runs.lock().unwrap();
+            *runs += 1;
+            tx.send(publish!(
+                "mavlink/attitude",
+                &TestAttitude { roll: *runs as f64 }
+            ))?;
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    /// Run a stream publisher against a subscriber that never drains, returning
+    /// the publisher run count and the subscriber queue
+    fn run_against_stalled_subscriber(policy: DropPolicy) -> (usize, SubscriptionQueue) {
+        let runs = Arc::new(Mutex::new(0));
+        let subscriber_info = TaskInfo::new("StalledSubscriber").with_insta_spawn();
+
+        let mut runner = Runner::new().with_default_queue_policy(2, policy);
+        runner.add_task(Arc::new(Mutex::new(TestStreamPublisher {
+            info: TaskInfo::new("TestStreamPublisher").with_insta_spawn(),
+            runs: runs.clone(),
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestPatternSubscriber {
+            info: subscriber_info.clone(),
+            pattern: "mavlink/attitude".to_string(),
+        })));
+        runner.init().unwrap();
+        runner.run_n_cycles(5).unwrap();
+
+        let queue = runner.subscription_queues[&subscriber_info][0].clone();
+        let runs = *runs.lock().unwrap();
+        (runs, queue)
+    }
+
+    #[test]
+    fn test_default_queue_policy() {
+        let (runs, queue) = run_against_stalled_subscriber(DropPolicy::DropOldest);
+        assert_eq!(runs, 5);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.dropped_count(), 3);
+        let rolls: Vec<f64> = queue
+            .drain()
+            .iter()
+            .map(|record| record.to_serde::<TestAttitude>().unwrap()[0].roll)
+            .collect();
+        assert_eq!(rolls, vec![4.0, 5.0]);
+
+        // Blocking holds the publisher back instead of dropping
+        let (runs, queue) = run_against_stalled_subscriber(DropPolicy::Block);
+        assert_eq!(runs, 2);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.dropped_count(), 0);
+    }
+
+    /// Logs its name and input count on every run, optionally publishing or subscribing
+    struct TestOrderedTask {
+        info: TaskInfo,
+        runs: Arc<Mutex<Vec<(String, usize)>>>,
+        publishes: bool,
+    }
+
+    impl Task for TestOrderedTask {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            if !self.publishes {
+                tx.send(subscribe!("exec/heartbeat"))?;
+            }
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            self.runs
+                .lock()
+                .unwrap()
+                .push((self.info.name.clone(), inputs.len()));
+            if self.publishes {
+                tx.send(publish!("exec/heartbeat", &TestAttitude { roll: 0.0 }))?;
+            }
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let mut runner = Runner::new().with_tick_rate(0.0);
+        // Named so that name order alone would run the subscriber first
+        runner.add_task(Arc::new(Mutex::new(TestOrderedTask {
+            info: TaskInfo::new("B_Heartbeat")
+                .with_insta_spawn()
+                .with_priority(200),
+            runs: runs.clone(),
+            publishes: true,
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestOrderedTask {
+            info: TaskInfo::new("A_Consumer").with_insta_spawn(),
+            runs: runs.clone(),
+            publishes: false,
+        })));
+        runner.init().unwrap();
+        runner.run_n_cycles(3).unwrap();
+
+        // The consumer sees the heartbeat published earlier in the same tick
+        let runs = runs.lock().unwrap();
+        let expected: Vec<(String, usize)> = (0..3)
+            .flat_map(|_| {
+                [
+                    ("B_Heartbeat".to_string(), 0),
+                    ("A_Consumer".to_string(), 1),
+                ]
+            })
+            .collect();
+        assert_eq!(*runs, expected);
+    }
+
+    struct TestFailingTask {
+        info: TaskInfo,
+        action: ErrorAction,
+        init_count: Arc<Mutex<usize>>,
+        run_count: Arc<Mutex<usize>>,
+    }
+
+    impl Task for TestFailingTask {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            *self.init_count.lock().unwrap() += 1;
+            tx.send(subscribe!("exec/heartbeat"))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            *self.run_count.lock().unwrap() += 1;
+            Err(anyhow::anyhow!("Sensor unplugged"))
+        }
+
+        fn on_error(&mut self, _err: &anyhow::Error) -> ErrorAction {
+            self.action
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_on_error_actions() {
+        // Runs three cycles and returns (init count, run count, still running, result)
+        let run_failing = |action: ErrorAction| {
+            let init_count = Arc::new(Mutex::new(0));
+            let run_count = Arc::new(Mutex::new(0));
+            let info = TaskInfo::new("TestFailingTask").with_insta_spawn();
+            let mut runner = Runner::new().with_tick_rate(0.0);
+            runner.add_task(Arc::new(Mutex::new(TestFailingTask {
+                info: info.clone(),
+                action,
+                init_count: init_count.clone(),
+                run_count: run_count.clone(),
+            })));
+            runner.init().unwrap();
+            let result = runner.run_n_cycles(3);
+            assert_eq!(runner.task_subscriptions(&info).len(), 1);
+            let init_count = *init_count.lock().unwrap();
+            let run_count = *run_count.lock().unwrap();
+            (init_count, run_count, runner.is_task_running(&info), result)
+        };
+
+        let (inits, runs, running, result) = run_failing(ErrorAction::Continue);
+        assert_eq!((inits, runs, running), (1, 3, true));
+        assert!(result.is_ok());
+
+        let (inits, runs, running, result) = run_failing(ErrorAction::Restart);
+        assert_eq!((inits, runs, running), (4, 3, true));
+        assert!(result.is_ok());
+
+        let (inits, runs, running, result) = run_failing(ErrorAction::KillTask);
+        assert_eq!((inits, runs, running), (1, 1, false));
+        assert!(result.is_ok());
+
+        let (inits, runs, _, result) = run_failing(ErrorAction::Fatal);
+        assert_eq!((inits, runs), (1, 1));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("TestFailingTask"));
+        assert!(err.root_cause().to_string().contains("Sensor unplugged"));
+    }
+
+    #[test]
+    fn test_tick_rate() {
+        let mut runner = Runner::new().with_tick_rate(100.0);
+        assert_eq!(runner.tick_rate(), Some(100.0));
+        assert_eq!(runner.actual_hz(), 0.0);
+
+        runner.run_n_cycles(6).unwrap();
+        let hz = runner.actual_hz();
+        assert!(hz > 50.0 && hz <= 101.0, "measured {} Hz", hz);
+
+        runner.set_tick_rate(0.0);
+        assert_eq!(runner.tick_rate(), None);
+        let start = std::time::Instant::now();
+        runner.run_n_cycles(100).unwrap();
+        assert!(start.elapsed() < std::time::Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_trace_message_path() {
+        let publisher_info = TaskInfo::new("TestPublisher").with_insta_spawn();
+        let exact_info = TaskInfo::new("ExactSubscriber").with_insta_spawn();
+        let wildcard_info = TaskInfo::new("WildcardSubscriber").with_insta_spawn();
+
+        let mut runner = Runner::new();
+        runner.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: publisher_info.clone(),
+            published: false,
+        })));
+        for (info, pattern) in [
+            (&exact_info, "mavlink/attitude"),
+            (&wildcard_info, "mavlink/*"),
+            (
+                &TaskInfo::new("GpsSubscriber").with_insta_spawn(),
+                "sensors/gps",
+            ),
+        ] {
+            runner.add_task(Arc::new(Mutex::new(TestPatternSubscriber {
+                info: info.clone(),
+                pattern: pattern.to_string(),
+            })));
+        }
+
+        runner.init().unwrap();
+        runner.run_n_cycles(2).unwrap();
+
+        let hops = runner.trace_message_path("mavlink/attitude");
+        assert_eq!(
+            hops,
+            vec![
+                MessageHop {
+                    from_task: Some(publisher_info.clone()),
+                    to_task: exact_info,
+                    topic: "mavlink/attitude".to_string(),
+                    pattern: "mavlink/attitude".to_string(),
+                    queue_depth_before: 1,
+                },
+                MessageHop {
+                    from_task: Some(publisher_info),
+                    to_task: wildcard_info,
+                    topic: "mavlink/attitude".to_string(),
+                    pattern: "mavlink/*".to_string(),
+                    queue_depth_before: 1,
+                },
+            ]
+        );
+
+        // Tracing must not publish anything
+        runner.trace_message_path("mavlink/attitude");
+        let state = runner.state.lock().unwrap();
+        assert_eq!(state.get_topic_row_count("mavlink/attitude"), Some(1));
+        assert!(runner
+            .trace_message_path("mavlink/gps")
+            .iter()
+            .all(|hop| hop.pattern == "mavlink/*"));
+    }
+
+    #[test]
+    fn test_wildcard_subscriber_receives_first_publish() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let subscriber_info = TaskInfo::new("TestSubscriber").with_insta_spawn();
+
+        let mut runner = Runner::new();
+        runner.add_task(Arc::new(Mutex::new(TestSubscriber {
+            info: subscriber_info.clone(),
+            received: received.clone(),
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+            published: false,
+        })));
+
+        runner.init().unwrap();
+        for _ in 0..3 {
+            runner.run().unwrap();
+        }
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].try_get_topic().unwrap(), "mavlink/attitude");
+        assert_eq!(received[0].to_serde::<TestAttitude>().unwrap()[0].roll, 1.0);
+
+        let queues = runner.subscription_queues.get(&subscriber_info).unwrap();
+        assert!(queues[0].retroactive_topics().contains("mavlink/attitude"));
+    }
+
+    #[test]
+    fn test_with_logger_and_disable_logging() {
+        let output_path =
+            std::env::temp_dir().join(format!("runner_logger_{}", uuid::Uuid::new_v4()));
+        let logger = RunnerLogger::new(
+            &output_path,
+            5000,
+            10,
+            [OutputFormat::Parquet].into(),
+            Some("session".to_string()),
+        )
+        .unwrap();
+
+        let mut runner = Runner::new().with_tick_rate(0.0).with_logger(logger);
+        runner.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+            published: false,
+        })));
+        runner.init().unwrap();
+        runner.run_n_cycles(2).unwrap();
+        runner.cleanup().unwrap();
+        assert!(output_path
+            .join("session/mavlink/attitude_final.parquet")
+            .exists());
+
+        let mut runner = Runner::new().with_tick_rate(0.0).disable_logging();
+        assert!(runner.logger.is_none());
+        runner.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+            published: false,
+        })));
+        runner.init().unwrap();
+        runner.run_n_cycles(2).unwrap();
+        runner.cleanup().unwrap();
+        assert!(runner
+            .state
+            .lock()
+            .unwrap()
+            .get_topic_record("mavlink/attitude")
+            .is_some());
+    }
+
+    struct TestTaggedPublisher {
+        info: TaskInfo,
+    }
+
+    impl Task for TestTaggedPublisher {
+        fn init(
+            &mut self,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            _inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            tx.send(
+                PublishBuilder::new("mavlink/attitude".to_string())
+                    .with_serde_content(&TestAttitude { roll: 1.0 })?
+                    .with_metadata("session_id", "flight-42")
+                    .with_metadata("software_version", "0.3.1")
+                    .build(),
+            )?;
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_subscriber_receives_publish_metadata() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let mut runner = Runner::new();
+        runner.add_task(Arc::new(Mutex::new(TestSubscriber {
+            info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+            received: received.clone(),
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestTaggedPublisher {
+            info: TaskInfo::new("TestTaggedPublisher").with_insta_spawn(),
+        })));
+
+        runner.init().unwrap();
+        runner.run_n_cycles(3).unwrap();
+
+        let received = received.lock().unwrap();
+        assert!(!received.is_empty());
+        for record in received.iter() {
+            assert_eq!(record.try_get_topic().unwrap(), "mavlink/attitude");
+            assert_eq!(record.get_metadata("session_id"), Some("flight-42"));
+            assert_eq!(record.get_metadata("software_version"), Some("0.3.1"));
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestPosition {
+        x: f64,
+        label: String,
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let runner = Runner::new();
+        {
+            let mut state = runner.state.lock().unwrap();
+            for roll in [1.0, 2.0, 3.0] {
+                state
+                    .apply_record(&publish!("mavlink/attitude", &TestAttitude { roll }))
+                    .unwrap();
+            }
+            let position = TestPosition {
+                x: 4.5,
+                label: "home".to_string(),
+            };
+            state
+                .apply_record(&publish!("mavlink/position", &position))
+                .unwrap();
+        }
+
+        let snapshot = runner.snapshot_to_record().unwrap();
+        assert_eq!(snapshot.try_get_topic().unwrap(), "runner/snapshot");
+        assert_eq!(snapshot.to_record_batch().num_rows(), 1);
+        assert_eq!(snapshot.to_record_batch().num_columns(), 2);
+
+        let mut restored = Runner::new();
+        restored.restore_from_snapshot_record(&snapshot).unwrap();
+
+        let state = restored.state.lock().unwrap();
+        let mut topics = state.get_topics();
+        topics.sort();
+        assert_eq!(topics, vec!["mavlink/attitude", "mavlink/position"]);
+
+        let attitude = state.get_latest_topic_data("mavlink/attitude").unwrap();
+        assert_eq!(attitude.to_serde::<TestAttitude>().unwrap()[0].roll, 3.0);
+
+        let position = state.get_latest_topic_data("mavlink/position").unwrap();
+        let position = &position.to_serde::<TestPosition>().unwrap()[0];
+        assert_eq!(position.x, 4.5);
+        assert_eq!(position.label, "home");
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TimedAttitude {
+        timestamp: u64,
+        roll: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TimedGps {
+        timestamp: u64,
+        lat: f64,
+    }
+
+    struct TestCounter {
+        info: TaskInfo,
+        counts: Arc<Mutex<HashMap<String, usize>>>,
+    }
+
+    impl Task for TestCounter {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            tx.send(subscribe!("mavlink/*"))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            let mut counts = self.counts.lock().unwrap();
+            for input in inputs {
+                *counts.entry(input.try_get_topic()?).or_default() += 1;
+            }
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_replay_from_state() {
+        let mut history = RunnerState::new();
+        for (timestamp, roll) in [(0, 0.1), (20, 0.2), (40, 0.3)] {
+            history
+                .apply_record(&publish!(
+                    "mavlink/attitude",
+                    &TimedAttitude { timestamp, roll }
+                ))
+                .unwrap();
+        }
+        for (timestamp, lat) in [(10, 47.1), (30, 47.2)] {
+            history
+                .apply_record(&publish!("mavlink/gps", &TimedGps { timestamp, lat }))
+                .unwrap();
+        }
+
+        // Only the counter is registered, no MAVLink connection task
+        let counts = Arc::new(Mutex::new(HashMap::new()));
+        let mut runner = Runner::new();
+        runner.add_task(Arc::new(Mutex::new(TestCounter {
+            info: TaskInfo::new("TestCounter").with_insta_spawn(),
+            counts: counts.clone(),
+        })));
+        runner.init().unwrap();
+        assert_eq!(runner.task_infos().len(), 1);
+
+        runner.replay_from_state(history, 10.0).unwrap();
+
+        let counts = counts.lock().unwrap();
+        assert_eq!(counts.get("mavlink/attitude"), Some(&3));
+        assert_eq!(counts.get("mavlink/gps"), Some(&2));
+
+        let state = runner.state.lock().unwrap();
+        assert_eq!(state.get_topic_row_count("mavlink/attitude"), Some(3));
+        let latest = state.get_latest_topic_data("mavlink/gps").unwrap();
+        assert_eq!(latest.to_serde::<TimedGps>().unwrap()[0].lat, 47.2);
+    }
+
+    #[test]
+    fn test_replay_into_namespace() {
+        let mut history = RunnerState::new();
+        history
+            .apply_record(&publish!("mavlink/attitude", &TestAttitude { roll: 1.0 }))
+            .unwrap();
+        // Logged by a runner of the same namespace, already scoped
+        history
+            .apply_record(&publish!(
+                "vehicle-1/mavlink/gps",
+                &TimedGps {
+                    timestamp: 0,
+                    lat: 47.1
+                }
+            ))
+            .unwrap();
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut runner = Runner::new()
+            .with_tick_rate(0.0)
+            .with_namespace("vehicle-1");
+        runner.add_task(Arc::new(Mutex::new(TestSubscriber {
+            info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+            received: received.clone(),
+        })));
+        runner.init().unwrap();
+        runner.replay_from_state(history, 10.0).unwrap();
+
+        let mut topics: Vec<String> = received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|record| record.try_get_topic().unwrap())
+            .collect();
+        topics.sort();
+        assert_eq!(
+            topics,
+            vec!["vehicle-1/mavlink/attitude", "vehicle-1/mavlink/gps"]
+        );
+        let state = runner.state.lock().unwrap();
+        assert_eq!(
+            state.get_topic_row_count("vehicle-1/mavlink/attitude"),
+            Some(1)
+        );
+        assert_eq!(state.get_topic_row_count("mavlink/attitude"), None);
+    }
+
+    fn write_replay_log(path: &Path, record: &Record) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let batch = record.to_record_batch();
+        let mut writer = parquet::arrow::ArrowWriter::try_new(
+            std::fs::File::create(path).unwrap(),
+            batch.schema(),
+            None,
+        )
+        .unwrap();
+        writer.write(batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_replay_from_parquet() {
+        let log_dir = std::env::temp_dir().join(format!("runner_replay_{}", uuid::Uuid::new_v4()));
+        let attitude: Vec<TimedAttitude> = [(40, 0.3), (0, 0.1), (20, 0.2)]
+            .into_iter()
+            .map(|(timestamp, roll)| TimedAttitude { timestamp, roll })
+            .collect();
+        write_replay_log(
+            &log_dir.join("mavlink/attitude.parquet"),
+            &Record::from_serde_batch(&attitude).unwrap(),
+        );
+        let gps: Vec<TimedGps> = [(10, 47.1), (30, 47.2)]
+            .into_iter()
+            .map(|(timestamp, lat)| TimedGps { timestamp, lat })
+            .collect();
+        // Only written at cleanup
+        write_replay_log(
+            &log_dir.join("mavlink/gps_final.parquet"),
+            &Record::from_serde_batch(&gps).unwrap(),
+        );
+
+        let counts = Arc::new(Mutex::new(HashMap::new()));
+        let mut runner = Runner::new().with_tick_rate(0.0).disable_logging();
+        runner.add_task(Arc::new(Mutex::new(TestCounter {
+            info: TaskInfo::new("TestCounter").with_insta_spawn(),
+            counts: counts.clone(),
+        })));
+        runner.init().unwrap();
+
+        let topics = vec!["mavlink/attitude".to_string(), "mavlink/gps".to_string()];
+        runner
+            .replay_from_parquet(log_dir.clone(), topics, "timestamp", 10.0)
+            .unwrap();
+
+        let counts = counts.lock().unwrap();
+        assert_eq!(counts.get("mavlink/attitude"), Some(&3));
+        assert_eq!(counts.get("mavlink/gps"), Some(&2));
+
+        // Rows were played back in time order
+        let state = runner.state.lock().unwrap();
+        let attitude = state.get_topic_record("mavlink/attitude").unwrap();
+        let timestamps: Vec<u64> = attitude
+            .to_serde::<TimedAttitude>()
+            .unwrap()
+            .iter()
+            .map(|a| a.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![0, 20, 40]);
+        drop(state);
+
+        let missing = vec!["mavlink/heartbeat".to_string()];
+        assert!(runner
+            .replay_from_parquet(log_dir.clone(), missing, "timestamp", 1.0)
+            .is_err());
+        let topics = vec!["mavlink/gps".to_string()];
+        assert!(runner
+            .replay_from_parquet(log_dir.clone(), topics, "lat_ms", 1.0)
+            .is_err());
+
+        std::fs::remove_dir_all(&log_dir).unwrap();
+    }
+
+    #[test]
+    fn test_replay_rejects_non_positive_speed() {
+        let mut runner = Runner::new();
+        assert!(runner.replay_from_state(RunnerState::new(), 0.0).is_err());
+    }
+
+    #[derive(Default)]
+    struct CountingObserver {
+        before_runs: Mutex<usize>,
+        after_runs: Mutex<usize>,
+        outputs: Mutex<usize>,
+        errors: Mutex<usize>,
+    }
+
+    impl TaskObserver for CountingObserver {
+        fn on_before_run(&self, _task: &TaskInfo, _inputs: &[Record]) {
+            *self.before_runs.lock().unwrap() += 1;
+        }
+
+        fn on_after_run(
+            &self,
+            _task: &TaskInfo,
+            outputs: &[Record],
+            _elapsed: std::time::Duration,
+        ) {
+            *self.after_runs.lock().unwrap() += 1;
+            *self.outputs.lock().unwrap() += outputs.len();
+        }
+
+        fn on_error(
+            &self,
+            _task: &TaskInfo,
+            _error: &anyhow::Error,
+            _elapsed: std::time::Duration,
+        ) {
+            *self.errors.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn test_observers_called_around_runs() {
+        let observer = Arc::new(CountingObserver::default());
+        let metrics = Arc::new(crate::tasks::metrics::MetricsObserver::new());
+
+        let mut runner = Runner::new();
+        runner.add_observer(observer.clone());
+        runner.add_observer(metrics.clone());
+        runner.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+            published: false,
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestSubscriber {
+            info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+            received: Arc::new(Mutex::new(Vec::new())),
+        })));
+
+        runner.init().unwrap();
+        runner.run_n_cycles(2).unwrap();
+
+        // Two tasks over two cycles
+        assert_eq!(*observer.before_runs.lock().unwrap(), 4);
+        assert_eq!(*observer.after_runs.lock().unwrap(), 4);
+        assert_eq!(*observer.outputs.lock().unwrap(), 1);
+
+        let collected = metrics.drain_metrics();
+        assert_eq!(collected.len(), 4);
+        let publisher_outputs: u32 = collected
+            .iter()
+            .filter(|m| m.task_name == "TestPublisher")
+            .map(|m| m.output_count)
+            .sum();
+        assert_eq!(publisher_outputs, 1);
+        let subscriber_inputs: u32 = collected
+            .iter()
+            .filter(|m| m.task_name == "TestSubscriber")
+            .map(|m| m.input_count)
+            .sum();
+        assert_eq!(subscriber_inputs, 1);
+        assert!(collected.iter().all(|m| m.error_count == 0));
+    }
+
+    #[test]
+    fn test_observers_see_failed_runs() {
+        let observer = Arc::new(CountingObserver::default());
+        let metrics = Arc::new(crate::tasks::metrics::MetricsObserver::new());
+
+        let mut runner = Runner::new();
+        runner.add_observer(observer.clone());
+        runner.add_observer(metrics.clone());
+        runner.add_task(Arc::new(Mutex::new(TestFailingTask {
+            info: TaskInfo::new("TestFailingTask").with_insta_spawn(),
+            action: ErrorAction::Continue,
+            init_count: Arc::new(Mutex::new(0)),
+            run_count: Arc::new(Mutex::new(0)),
+        })));
+
+        runner.init().unwrap();
+        runner.run_n_cycles(2).unwrap();
+
+        assert_eq!(*observer.before_runs.lock().unwrap(), 2);
+        assert_eq!(*observer.after_runs.lock().unwrap(), 0);
+        assert_eq!(*observer.errors.lock().unwrap(), 2);
+
+        let collected = metrics.drain_metrics();
+        assert_eq!(collected.len(), 2);
+        assert!(collected.iter().all(|m| m.error_count == 1));
+        assert!(collected.iter().all(|m| m.output_count == 0));
+    }
+
+    #[test]
+    fn test_metrics_interval() {
+        let monitor_info = TaskInfo::new("TestMonitor").with_insta_spawn();
+        let mut runner = Runner::new().with_tick_rate(0.0).with_metrics_interval(2);
+        runner.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+            published: false,
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestPatternSubscriber {
+            info: monitor_info.clone(),
+            pattern: METRICS_TOPIC.to_string(),
+        })));
+
+        runner.init().unwrap();
+        runner.run_n_cycles(1).unwrap();
+        assert!(runner.get_last_metrics().is_empty());
+
+        // The monitor never runs, so only the publisher shows up, once per tick
+        runner.run_n_cycles(1).unwrap();
+        let metrics = runner.get_last_metrics();
+        assert_eq!(metrics.len(), 2);
+        assert!(metrics.iter().all(|m| m.task_name == "TestPublisher"));
+        assert_eq!(metrics[0].output_count, 1);
+        assert_eq!(metrics[1].output_count, 0);
+        assert_eq!(metrics[0].error_count, 0);
+
+        let hops = runner.trace_message_path(METRICS_TOPIC);
+        assert_eq!(hops.len(), 1);
+        assert_eq!(hops[0].to_task, monitor_info);
+        assert_eq!(hops[0].queue_depth_before, 1);
+
+        // Failed runs are counted as errors
+        let mut runner = Runner::new().with_tick_rate(0.0).with_metrics_interval(1);
+        runner.add_task(Arc::new(Mutex::new(TestFailingTask {
+            info: TaskInfo::new("TestFailingTask").with_insta_spawn(),
+            action: ErrorAction::Continue,
+            init_count: Arc::new(Mutex::new(0)),
+            run_count: Arc::new(Mutex::new(0)),
+        })));
+        runner.init().unwrap();
+        runner.run_n_cycles(1).unwrap();
+        assert_eq!(runner.get_last_metrics()[0].error_count, 1);
+    }
+
+    struct DropTopicMiddleware {
+        topic: String,
+    }
+
+    impl PublishMiddleware for DropTopicMiddleware {
+        fn process(&self, record: &mut Record) -> MiddlewareAction {
+            if record
+                .try_get_topic()
+                .is_ok_and(|topic| topic == self.topic)
+            {
+                MiddlewareAction::Drop
+            } else {
+                MiddlewareAction::Continue
+            }
+        }
+    }
+
+    #[test]
+    fn test_publish_middleware() {
+        use crate::tasks::middleware::{SequenceColumnMiddleware, TimestampMiddleware};
+        use arrow::array::AsArray;
+
+        let run_with = |middleware: Box<dyn PublishMiddleware>| {
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let mut runner = Runner::new().with_tick_rate(0.0);
+            runner.add_publish_middleware(middleware);
+            runner.add_task(Arc::new(Mutex::new(TestPublisher {
+                info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+                published: false,
+            })));
+            runner.add_task(Arc::new(Mutex::new(TestSubscriber {
+                info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+                received: received.clone(),
+            })));
+            runner.init().unwrap();
+            runner.run_n_cycles(2).unwrap();
+            let received = received.lock().unwrap().clone();
+            received
+        };
+
+        let received = run_with(Box::new(TimestampMiddleware::new()));
+        assert_eq!(received.len(), 1);
+        let published_at = TimestampMiddleware::published_at_ns(&received[0]).unwrap();
+        assert!(published_at > 0);
+        assert_eq!(received[0].try_get_topic().unwrap(), "mavlink/attitude");
+
+        let received = run_with(Box::new(DropTopicMiddleware {
+            topic: "mavlink/attitude".to_string(),
+        }));
+        assert!(received.is_empty());
+
+        let received = run_with(Box::new(DropTopicMiddleware {
+            topic: "mavlink/heartbeat".to_string(),
+        }));
+        assert_eq!(received.len(), 1);
+        assert_eq!(TimestampMiddleware::published_at_ns(&received[0]), None);
+
+        let received = run_with(Box::new(SequenceColumnMiddleware::new("seq", 5)));
+        let sequence = received[0]
+            .to_record_batch()
+            .column_by_name("seq")
+            .unwrap()
+            .as_primitive::<arrow::datatypes::UInt64Type>()
+            .value(0);
+        assert_eq!(sequence, 5);
+    }
+
+    #[test]
+    fn test_schema_validation() {
+        let runner_with = |strict: bool, roll_type: DataType| {
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let mut runner = Runner::new().with_tick_rate(0.0);
+            if strict {
+                runner = runner.with_strict_schema_validation();
+            }
+            runner
+                .register_topic_schema(
+                    "mavlink/attitude",
+                    Arc::new(Schema::new(vec![Field::new("roll", roll_type, true)])),
+                )
+                .unwrap();
+            runner.add_task(Arc::new(Mutex::new(TestPublisher {
+                info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+                published: false,
+            })));
+            runner.add_task(Arc::new(Mutex::new(TestSubscriber {
+                info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+                received: received.clone(),
+            })));
+            runner.init().unwrap();
+            (runner, received)
+        };
+
+        let (mut runner, received) = runner_with(false, DataType::Float64);
+        runner.run_n_cycles(2).unwrap();
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // Without strict validation the invalid record is dropped, not stored
+        let (mut runner, received) = runner_with(false, DataType::Int32);
+        runner.run_n_cycles(2).unwrap();
+        assert!(received.lock().unwrap().is_empty());
+        assert!(runner
+            .state
+            .lock()
+            .unwrap()
+            .get_topic_record("mavlink/attitude")
+            .is_none());
+
+        let (mut runner, _) = runner_with(true, DataType::Int32);
+        let err = runner.run_n_cycles(2).unwrap_err();
+        assert!(err.root_cause().is::<SchemaError>());
+    }
+
+    #[test]
+    fn test_namespace_and_bridge_topic() {
+        let vehicle_runner = |ns: &str, received: &Arc<Mutex<Vec<Record>>>| {
+            let mut runner = Runner::new().with_tick_rate(0.0).with_namespace(ns);
+            runner.add_task(Arc::new(Mutex::new(TestSubscriber {
+                info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+                received: received.clone(),
+            })));
+            runner
+        };
+
+        let received_1 = Arc::new(Mutex::new(Vec::new()));
+        let received_2 = Arc::new(Mutex::new(Vec::new()));
+        let received_3 = Arc::new(Mutex::new(Vec::new()));
+        let mut runner_1 = vehicle_runner("vehicle-1", &received_1);
+        runner_1.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+            published: false,
+        })));
+        let mut runner_2 = vehicle_runner("vehicle-2", &received_2);
+        let mut runner_3 = vehicle_runner("vehicle-3", &received_3);
+        runner_2.bridge_topic(&runner_1, "mavlink/*");
+
+        for runner in [&mut runner_1, &mut runner_2, &mut runner_3] {
+            runner.init().unwrap();
+        }
+        assert_eq!(
+            runner_1.task_subscriptions(&TaskInfo::new("TestSubscriber")),
+            vec!["vehicle-1/mavlink/*".to_string()]
+        );
+
+        runner_1.run_n_cycles(2).unwrap();
+        runner_2.run_n_cycles(2).unwrap();
+        runner_3.run_n_cycles(2).unwrap();
+
+        let topics = |received: &Arc<Mutex<Vec<Record>>>| {
+            received
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|record| record.try_get_topic().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(topics(&received_1), vec!["vehicle-1/mavlink/attitude"]);
+        assert_eq!(topics(&received_2), vec!["vehicle-2/mavlink/attitude"]);
+        assert!(topics(&received_3).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let snapshot_dir =
+            std::env::temp_dir().join(format!("runner_snapshot_{}", uuid::Uuid::new_v4()));
+        let mut runner = Runner::new().with_tick_rate(0.0);
+        runner.add_stage_topic("mavlink/attitude");
+        runner.add_task(Arc::new(Mutex::new(TestPublisher {
+            info: TaskInfo::new("TestPublisher").with_insta_spawn(),
+            published: false,
+        })));
+        runner.init().unwrap();
+        runner.run().unwrap();
+        runner
+            .state
+            .lock()
+            .unwrap()
+            .apply_record(&publish!("exec/setpoint", &TestAttitude { roll: 3.0 }))
+            .unwrap();
+
+        // The stage survives the logger trimming its topic
+        runner.snapshot(&snapshot_dir).unwrap();
+        runner
+            .state
+            .lock()
+            .unwrap()
+            .remove_topic("mavlink/attitude");
+        let trimmed_dir = snapshot_dir.join("trimmed");
+        runner.snapshot(&trimmed_dir).unwrap();
+
+        let manifest: snapshot::SnapshotManifest = serde_json::from_str(
+            &std::fs::read_to_string(trimmed_dir.join(snapshot::SNAPSHOT_MANIFEST)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest.topics.len(), 1);
+        assert_eq!(manifest.topics[0].topic, "exec/setpoint");
+        assert_eq!(manifest.topics[0].rows, 1);
+        assert_eq!(manifest.stages[0].topic, "mavlink/attitude");
+
+        for dir in [&snapshot_dir, &trimmed_dir] {
+            let state = Runner::restore(dir).unwrap();
+            let mut topics = state.get_topics();
+            topics.sort();
+            assert_eq!(topics, vec!["exec/setpoint", "mavlink/attitude"]);
+            let attitude = state
+                .get_topic_record("mavlink/attitude")
+                .unwrap()
+                .to_serde::<TestAttitude>()
+                .unwrap();
+            assert_eq!(attitude[0].roll, 1.0);
+        }
+
+        // Subscribers see the restored stage once initialized
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut restored = Runner::new().with_tick_rate(0.0);
+        restored.load_state(Runner::restore(&trimmed_dir).unwrap());
+        restored.add_task(Arc::new(Mutex::new(TestSubscriber {
+            info: TaskInfo::new("TestSubscriber").with_insta_spawn(),
+            received: received.clone(),
+        })));
+        restored.init().unwrap();
+        restored.run().unwrap();
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // A schema that no longer matches the manifest is rejected
+        let mut manifest = manifest;
+        manifest.topics[0].schema_hash = "0".to_string();
+        std::fs::write(
+            trimmed_dir.join(snapshot::SNAPSHOT_MANIFEST),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        assert!(Runner::restore(&trimmed_dir).is_err());
+
+        std::fs::remove_dir_all(&snapshot_dir).unwrap();
+    }
+
+    struct TestDynamicSubscriber {
+        info: TaskInfo,
+        received: Arc<Mutex<Vec<Record>>>,
+        runs: usize,
+    }
+
+    impl Task for TestDynamicSubscriber {
+        fn init(
+            &mut self,
+            _tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            self.runs += 1;
+            if self.runs == 1 {
+                tx.send(subscribe!("mavlink/*"))?;
+            }
+            // Unsubscribe as soon as the first message arrived
+            if !inputs.is_empty() {
+                tx.send(unsubscribe!("mavlink/*"))?;
+            }
+            self.received.lock().unwrap().extend(inputs);
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_subscribe_and_unsubscribe_during_run() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let subscriber_info = TaskInfo::new("TestDynamicSubscriber").with_insta_spawn();
+        let mut runner = Runner::new().with_tick_rate(0.0);
+        runner.add_task(Arc::new(Mutex::new(TestDynamicSubscriber {
+            info: subscriber_info.clone(),
+            received: received.clone(),
+            runs: 0,
+        })));
+        runner.add_task(Arc::new(Mutex::new(TestStreamPublisher {
+            info: TaskInfo::new("TestStreamPublisher").with_insta_spawn(),
+            runs: Arc::new(Mutex::new(0)),
+        })));
+        runner.init().unwrap();
+        assert!(runner.task_subscriptions(&subscriber_info).is_empty());
+
+        runner.run().unwrap();
+        assert_eq!(
+            runner.task_subscriptions(&subscriber_info),
+            vec!["mavlink/*".to_string()]
+        );
+
+        runner.run_n_cycles(5).unwrap();
+        assert!(runner.task_subscriptions(&subscriber_info).is_empty());
+        assert_eq!(received.lock().unwrap().len(), 1);
+        assert!(runner.trace_message_path("mavlink/attitude").is_empty());
+    }
+
+    struct TestErrorReporter {
+        info: TaskInfo,
+        received: Arc<Mutex<Vec<Record>>>,
+    }
+
+    impl Task for TestErrorReporter {
+        fn init(
+            &mut self,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            tx.send(subscribe!("errors/*"))?;
+            Ok(())
+        }
+
+        fn run(
+            &mut self,
+            inputs: Vec<Record>,
+            tx: TaskChannel,
+            _meta_tx: MetaTaskChannel,
+        ) -> Result<(), anyhow::Error> {
+            let mut received = self.received.lock().unwrap();
+            if received.is_empty() && inputs.is_empty() {
+                tx.send(crate::publish_error!(
+                    self.info.name.clone(),
+                    "Preflight",
+                    "Compass not calibrated",
+                    crate::message::error_record::ErrorSeverity::Critical
+                ))?;
+            }
+            received.extend(inputs);
+            Ok(())
+        }
+
+        fn get_task_info(&self) -> &TaskInfo {
+            &self.info
+        }
+    }
+
+    #[test]
+    fn test_error_packets_routed_to_error_subscribers() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut runner = Runner::new().with_tick_rate(0.0);
+        runner.add_task(Arc::new(Mutex::new(TestErrorReporter {
+            info: TaskInfo::new("TestErrorReporter").with_insta_spawn(),
+            received: received.clone(),
+        })));
+        runner.init().unwrap();
+        runner.run_n_cycles(2).unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(
+            received[0].try_get_topic().unwrap(),
+            "errors/TestErrorReporter"
+        );
+        assert_eq!(received[0].get_flag().unwrap(), RecordFlag::ErrorPacket);
+        let errors: Vec<ErrorRecord> = received[0].to_serde().unwrap();
+        assert_eq!(errors[0].message, "Compass not calibrated");
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Schema};
+use regex::Regex;
+
+use crate::message::record::Record;
+
+/// A record that breaks the schema registered for its topic
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SchemaError {
+    #[error(
+        "Record on '{topic}' is missing column '{column}' of the schema registered for '{pattern}'"
+    )]
+    MissingColumn {
+        topic: String,
+        pattern: String,
+        column: String,
+    },
+
+    #[error("Column '{column}' on '{topic}' is {actual}, the schema registered for '{pattern}' expects {expected}")]
+    TypeMismatch {
+        topic: String,
+        pattern: String,
+        column: String,
+        expected: DataType,
+        actual: DataType,
+    },
+}
+
+struct TopicSchema {
+    pattern: String,
+    /// Anchored regex of a wildcard pattern, plain patterns match as a prefix
+    wildcard: Option<Regex>,
+    schema: Arc<Schema>,
+}
+
+impl TopicSchema {
+    fn matches(&self, topic: &str) -> bool {
+        match &self.wildcard {
+            Some(regex) => regex.is_match(topic),
+            None => topic.starts_with(&self.pattern),
+        }
+    }
+}
+
+/// Schemas the records of a topic must follow, registered by topic pattern.
+/// A record is valid if it has every column of each schema registered for a pattern matching
+/// its topic, with the same data type. Extra columns, e.g. added by middleware, are allowed.
+#[derive(Default)]
+pub struct SchemaRegistry {
+    topics: Vec<TopicSchema>,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the schema of the topics matching `topic_pattern`, a prefix or `*` wildcard.
+    /// Each pattern can only be registered once.
+    pub fn register_topic(
+        &mut self,
+        topic_pattern: &str,
+        schema: Arc<Schema>,
+    ) -> Result<(), anyhow::Error> {
+        if self.topics.iter().any(|t| t.pattern == topic_pattern) {
+            return Err(anyhow::anyhow!(
+                "Topic pattern '{}' already has a registered schema",
+                topic_pattern
+            ));
+        }
+        let wildcard = if topic_pattern.contains('*') {
+            let regex = format!("^{}$", regex::escape(topic_pattern).replace("\\*", ".*"));
+            Some(Regex::new(&regex)?)
+        } else {
+            None
+        };
+        self.topics.push(TopicSchema {
+            pattern: topic_pattern.to_string(),
+            wildcard,
+            schema,
+        });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty()
+    }
+
+    /// Schemas registered for patterns matching `topic`, in registration order
+    pub fn schemas_for(&self, topic: &str) -> Vec<Arc<Schema>> {
+        self.topics
+            .iter()
+            .filter(|t| t.matches(topic))
+            .map(|t| t.schema.clone())
+            .collect()
+    }
+
+    /// Check `record` against every schema registered for its topic.
+    /// Records without a topic or without a registered schema are valid.
+    pub fn validate_record(&self, record: &Record) -> Result<(), SchemaError> {
+        let Ok(topic) = record.try_get_topic() else {
+            return Ok(());
+        };
+        let record_schema = record.to_record_batch().schema();
+
+        for registered in self.topics.iter().filter(|t| t.matches(&topic)) {
+            for field in registered.schema.fields() {
+                let Ok(actual) = record_schema.field_with_name(field.name()) else {
+                    return Err(SchemaError::MissingColumn {
+                        topic,
+                        pattern: registered.pattern.clone(),
+                        column: field.name().clone(),
+                    });
+                };
+                if actual.data_type() != field.data_type() {
+                    return Err(SchemaError::TypeMismatch {
+                        topic,
+                        pattern: registered.pattern.clone(),
+                        column: field.name().clone(),
+                        expected: field.data_type().clone(),
+                        actual: actual.data_type().clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::datatypes::Field;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct TestAttitude {
+        roll: f64,
+        pitch: f64,
+    }
+
+    fn attitude_record(topic: &str) -> Record {
+        let mut record = Record::from_serde(&TestAttitude {
+            roll: 0.1,
+            pitch: 0.2,
+        })
+        .unwrap();
+        record.set_topic(topic.to_string()).unwrap();
+        record
+    }
+
+    #[test]
+    fn test_validate_record() {
+        let mut registry = SchemaRegistry::new();
+        registry
+            .register_topic(
+                "mavlink/attitude",
+                Arc::new(Schema::new(vec![Field::new(
+                    "roll",
+                    DataType::Float64,
+                    true,
+                )])),
+            )
+            .unwrap();
+        registry
+            .register_topic(
+                "vehicle/*/pose",
+                Arc::new(Schema::new(vec![Field::new("x", DataType::Float64, true)])),
+            )
+            .unwrap();
+        assert!(registry
+            .register_topic("mavlink/attitude", Arc::new(Schema::empty()))
+            .is_err());
+
+        // Extra columns are allowed, unregistered topics are not checked
+        assert!(registry
+            .validate_record(&attitude_record("mavlink/attitude"))
+            .is_ok());
+        assert!(registry
+            .validate_record(&attitude_record("mavlink/heartbeat"))
+            .is_ok());
+
+        assert_eq!(
+            registry
+                .validate_record(&attitude_record("vehicle/1/pose"))
+                .unwrap_err(),
+            SchemaError::MissingColumn {
+                topic: "vehicle/1/pose".to_string(),
+                pattern: "vehicle/*/pose".to_string(),
+                column: "x".to_string(),
+            }
+        );
+        assert!(registry
+            .validate_record(&attitude_record("vehicle/1/pose/raw"))
+            .is_ok());
+
+        let mut pitch_registry = SchemaRegistry::new();
+        pitch_registry
+            .register_topic(
+                "mavlink/attitude",
+                Arc::new(Schema::new(vec![Field::new(
+                    "pitch",
+                    DataType::Int32,
+                    true,
+                )])),
+            )
+            .unwrap();
+        assert!(matches!(
+            pitch_registry.validate_record(&attitude_record("mavlink/attitude")),
+            Err(SchemaError::TypeMismatch {
+                expected: DataType::Int32,
+                actual: DataType::Float64,
+                ..
+            })
+        ));
+    }
+}
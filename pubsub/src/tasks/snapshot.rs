@@ -0,0 +1,174 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use arrow::datatypes::Schema;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::arrow_writer::ArrowWriter;
+use serde::{Deserialize, Serialize};
+
+use crate::message::record::{Record, RecordFlag};
+use crate::tasks::state::RunnerState;
+
+/// File name of the manifest at the root of a snapshot directory
+pub const SNAPSHOT_MANIFEST: &str = "snapshot.json";
+
+/// Directory of a snapshot holding the latest record of every stage topic
+pub const SNAPSHOT_STAGE_DIR: &str = "_stages";
+
+/// One topic written to a snapshot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotTopic {
+    pub topic: String,
+    /// Parquet file holding the topic, relative to the snapshot directory
+    pub file: PathBuf,
+    pub rows: usize,
+    /// `schema_hash` of the topic's schema when it was written
+    pub schema_hash: String,
+}
+
+/// Contents of `snapshot.json`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SnapshotManifest {
+    pub topics: Vec<SnapshotTopic>,
+    /// Latest record of the stage topics registered with `Runner::add_stage_topic`,
+    /// kept even if the logger already trimmed the topic from the state
+    pub stages: Vec<SnapshotTopic>,
+}
+
+/// Stable hash of the fields of a schema, ignoring its metadata
+pub fn schema_hash(schema: &Schema) -> String {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", schema.fields()).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Write every topic of `state` and the latest `stages` records to `dir`
+pub(crate) fn write_snapshot(
+    dir: &Path,
+    state: &RunnerState,
+    stages: &HashMap<String, Option<Record>>,
+) -> Result<SnapshotManifest, anyhow::Error> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create snapshot directory: {:?}", dir))?;
+
+    let mut topics = state.get_topics();
+    topics.sort();
+    let mut manifest = SnapshotManifest::default();
+    for topic in topics {
+        if let Some(record) = state.get_topic_record(&topic) {
+            manifest
+                .topics
+                .push(write_topic(dir, &topic, topic_file(&topic), record)?);
+        }
+    }
+
+    let mut stage_topics: Vec<(&String, &Record)> = stages
+        .iter()
+        .filter_map(|(topic, record)| record.as_ref().map(|record| (topic, record)))
+        .collect();
+    stage_topics.sort_by(|a, b| a.0.cmp(b.0));
+    for (topic, record) in stage_topics {
+        let file = Path::new(SNAPSHOT_STAGE_DIR).join(topic_file(topic));
+        manifest.stages.push(write_topic(dir, topic, file, record)?);
+    }
+
+    let manifest_path = dir.join(SNAPSHOT_MANIFEST);
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write snapshot manifest: {:?}", manifest_path))?;
+    Ok(manifest)
+}
+
+/// Rebuild the state written by `write_snapshot`. Stage records are restored to their
+/// topic when the state itself no longer had it.
+pub(crate) fn read_snapshot(dir: &Path) -> Result<RunnerState, anyhow::Error> {
+    let manifest_path = dir.join(SNAPSHOT_MANIFEST);
+    let manifest: SnapshotManifest = serde_json::from_str(
+        &fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read snapshot manifest: {:?}", manifest_path))?,
+    )?;
+
+    let mut state = RunnerState::new();
+    for entry in &manifest.topics {
+        for record in read_topic(dir, entry)? {
+            state.apply_record(&record)?;
+        }
+    }
+    for entry in &manifest.stages {
+        if state.get_topic_record(&entry.topic).is_some() {
+            continue;
+        }
+        for record in read_topic(dir, entry)? {
+            state.apply_record(&record)?;
+        }
+    }
+    Ok(state)
+}
+
+/// Path of a topic's file relative to the snapshot directory
+fn topic_file(topic: &str) -> PathBuf {
+    let mut file: PathBuf = topic.split('/').collect();
+    file.set_extension("parquet");
+    file
+}
+
+fn write_topic(
+    dir: &Path,
+    topic: &str,
+    file: PathBuf,
+    record: &Record,
+) -> Result<SnapshotTopic, anyhow::Error> {
+    let path = dir.join(&file);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let batch = record.to_record_batch();
+    let output = File::create(&path)
+        .with_context(|| format!("Failed to create snapshot file: {:?}", path))?;
+    let mut writer = ArrowWriter::try_new(output, batch.schema(), None)?;
+    writer.write(batch)?;
+    writer.close()?;
+
+    Ok(SnapshotTopic {
+        topic: topic.to_string(),
+        file,
+        rows: batch.num_rows(),
+        schema_hash: schema_hash(&batch.schema()),
+    })
+}
+
+fn read_topic(dir: &Path, entry: &SnapshotTopic) -> Result<Vec<Record>, anyhow::Error> {
+    let path = dir.join(&entry.file);
+    let file =
+        File::open(&path).with_context(|| format!("Failed to open snapshot file: {:?}", path))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    if schema_hash(builder.schema()) != entry.schema_hash {
+        return Err(anyhow::anyhow!(
+            "Schema of snapshot file {:?} does not match the manifest for topic '{}'",
+            path,
+            entry.topic
+        ));
+    }
+
+    let mut records = Vec::new();
+    let mut rows = 0;
+    for batch in builder.build()? {
+        let mut record = Record::from_record_batch(batch?);
+        record.set_topic(entry.topic.clone())?;
+        record.set_flag(RecordFlag::PublishPacket)?;
+        rows += record.to_record_batch().num_rows();
+        records.push(record);
+    }
+    if rows != entry.rows {
+        return Err(anyhow::anyhow!(
+            "Snapshot file {:?} has {} rows, the manifest lists {}",
+            path,
+            rows,
+            entry.rows
+        ));
+    }
+    Ok(records)
+}
@@ -1,9 +1,17 @@
 use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
 
-use crate::message::record::Record;
+use anyhow::Context;
+use arrow::datatypes::SchemaRef;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+use crate::message::record::{Record, RecordFlag, SchemaDrift};
 
 pub struct RunnerState {
     logs: HashMap<String, Record>,
+    // Topics with drift detection enabled, mapped to their last-seen schema
+    drift_watch: HashMap<String, Option<SchemaRef>>,
 }
 
 impl Default for RunnerState {
@@ -16,10 +24,40 @@ impl RunnerState {
     pub fn new() -> Self {
         Self {
             logs: HashMap::new(),
+            drift_watch: HashMap::new(),
         }
     }
 
+    /// Load a state from a directory of parquet logs written by `RunnerLogger`.
+    /// The topic of each file is its path relative to `dir`, without the extension
+    /// (and without the `_final` suffix used for end-of-run dumps).
+    pub fn from_log_dir(dir: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let dir = dir.as_ref();
+        let mut files = Vec::new();
+        collect_parquet_files(dir, &mut files)?;
+        files.sort();
+
+        let mut state = Self::new();
+        for file_path in files {
+            let topic = log_file_topic(dir, &file_path)?;
+            let file = File::open(&file_path)
+                .with_context(|| format!("Failed to open log file: {:?}", file_path))?;
+            let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+
+            for batch in reader {
+                let mut record = Record::from_record_batch(batch?);
+                record.set_topic(topic.clone())?;
+                record.set_flag(RecordFlag::PublishPacket)?;
+                state.append_record(&record)?;
+            }
+            log::debug!("Loaded topic '{}' from {:?}", topic, file_path);
+        }
+
+        Ok(state)
+    }
+
     pub fn apply_record(&mut self, record: &Record) -> Result<(), anyhow::Error> {
+        self.check_topic_drift(record);
         self.append_record(record)?;
         Ok(())
     }
@@ -103,6 +141,34 @@ impl RunnerState {
         Ok(last_n_rows)
     }
 
+    /// Enable schema drift detection for a topic.
+    /// A warning is logged whenever a record's schema differs from the last one seen on the topic.
+    pub fn watch_topic_drift(&mut self, topic: &str) {
+        let last_schema = self
+            .logs
+            .get(topic)
+            .map(|record| record.to_record_batch().schema());
+        self.drift_watch
+            .entry(topic.to_string())
+            .or_insert(last_schema);
+    }
+
+    fn check_topic_drift(&mut self, record: &Record) -> Option<SchemaDrift> {
+        let topic = record.try_get_topic().ok()?;
+        let last_schema = self.drift_watch.get_mut(&topic)?;
+        let current_schema = record.to_record_batch().schema();
+
+        let drift = last_schema
+            .as_ref()
+            .and_then(|previous| SchemaDrift::between(previous, &current_schema));
+        if let Some(drift) = &drift {
+            log::warn!("Schema drift detected on topic '{}': {}", topic, drift);
+        }
+
+        *last_schema = Some(current_schema);
+        drift
+    }
+
     fn append_record(&mut self, record: &Record) -> Result<(), anyhow::Error> {
         let topic = record.try_get_topic()?;
         let entry = self.logs.entry(topic);
@@ -123,6 +189,39 @@ impl RunnerState {
     }
 }
 
+/// Recursively collect all parquet files under a directory
+pub(crate) fn collect_parquet_files(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), anyhow::Error> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read log dir: {:?}", dir))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_parquet_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "parquet") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Rebuild the topic name of a log file from its path relative to the log dir,
+/// `log_dir/mavlink/attitude_final.parquet` is `mavlink/attitude`
+pub(crate) fn log_file_topic(dir: &Path, file_path: &Path) -> Result<String, anyhow::Error> {
+    let relative = file_path.strip_prefix(dir)?.with_extension("");
+    let topic = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Ok(topic
+        .strip_suffix("_final")
+        .map(str::to_string)
+        .unwrap_or(topic))
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -137,6 +236,11 @@ mod tests {
         value: i32,
     }
 
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct RenamedTestMessage {
+        reading: i32,
+    }
+
     #[test]
     fn test_new_runner_state() {
         let state = RunnerState::new();
@@ -251,4 +355,52 @@ mod tests {
             assert_eq!(read_values[i].value, i as i32 + 1);
         }
     }
+
+    #[test]
+    fn test_watch_topic_drift() {
+        let mut state = RunnerState::new();
+        state.watch_topic_drift("test_topic");
+
+        let first = publish!("test_topic", &TestMessage { value: 1 });
+        assert!(state.check_topic_drift(&first).is_none());
+        assert!(state.check_topic_drift(&first).is_none());
+
+        let renamed = publish!("test_topic", &RenamedTestMessage { reading: 1 });
+        let drift = state.check_topic_drift(&renamed).unwrap();
+        assert_eq!(drift.added_fields, vec!["reading".to_string()]);
+        assert_eq!(drift.removed_fields, vec!["value".to_string()]);
+        assert!(drift.type_changes.is_empty());
+
+        // Unwatched topics are never checked
+        let other = publish!("other_topic", &TestMessage { value: 1 });
+        assert!(state.check_topic_drift(&other).is_none());
+    }
+
+    #[test]
+    fn test_from_log_dir() {
+        let dir = std::env::temp_dir().join(format!("runner_state_{}", uuid::Uuid::new_v4()));
+        let topic_dir = dir.join("mavlink");
+        std::fs::create_dir_all(&topic_dir).unwrap();
+
+        let mut record = publish!("mavlink/attitude", &TestMessage { value: 1 });
+        record = record
+            .concat(&publish!("mavlink/attitude", &TestMessage { value: 2 }))
+            .unwrap();
+        let batch = record.to_record_batch();
+        let file = File::create(topic_dir.join("attitude_final.parquet")).unwrap();
+        let mut writer =
+            parquet::arrow::arrow_writer::ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+        writer.write(batch).unwrap();
+        writer.close().unwrap();
+
+        let state = RunnerState::from_log_dir(&dir).unwrap();
+        assert_eq!(state.get_topics(), vec!["mavlink/attitude"]);
+        assert_eq!(state.get_topic_row_count("mavlink/attitude"), Some(2));
+
+        let latest = state.get_latest_topic_data("mavlink/attitude").unwrap();
+        assert_eq!(latest.try_get_topic().unwrap(), "mavlink/attitude");
+        assert_eq!(latest.to_serde::<TestMessage>().unwrap()[0].value, 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,9 +1,34 @@
-use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 
 use crate::message::record::Record;
 use crate::tasks::info::TaskInfo;
 
+/// What a bounded `SubscriptionQueue` does with a record pushed while it is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropPolicy {
+    /// Discard the oldest queued record to make room
+    #[default]
+    DropOldest,
+    /// Discard the record being pushed
+    DropNewest,
+    /// Make `push` wait until the consumer drains the queue
+    Block,
+}
+
+/// Which of its queued records a subscription hands to the task on each run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubscriptionPolicy {
+    /// Every queued record, oldest first
+    #[default]
+    All,
+    /// Only the newest record, for tasks that only care about the current value
+    Latest,
+    /// The newest `n` records, oldest first
+    LatestN(usize),
+}
+
 /// A queue that holds messages for a specific subscription
 /// This is used to implement an event-based subscription model
 /// where each subscription has its own queue of messages
@@ -18,6 +43,26 @@ pub struct SubscriptionQueue {
     /// The queue of messages for this subscription
     /// Using a VecDeque for efficient push and pop operations
     queue: Arc<Mutex<VecDeque<Record>>>,
+
+    /// Topics first published after this subscription was made,
+    /// which were delivered by the runner's new-topic scan
+    retroactive_topics: Arc<Mutex<HashSet<String>>>,
+
+    /// Maximum number of queued records, unbounded when None
+    max_size: Option<usize>,
+    policy: DropPolicy,
+
+    /// Records discarded because the queue was full
+    dropped: Arc<AtomicUsize>,
+
+    /// Which records `drain_by_policy` returns
+    subscription_policy: SubscriptionPolicy,
+
+    /// Records discarded by `drain_latest` and `drain_n` for a newer one
+    dropped_stale: Arc<AtomicUsize>,
+
+    /// Signalled when records are drained, wakes blocked pushers
+    space_available: Arc<Condvar>,
 }
 
 impl SubscriptionQueue {
@@ -27,22 +72,120 @@ impl SubscriptionQueue {
             task_info,
             topic_pattern,
             queue: Arc::new(Mutex::new(VecDeque::new())),
+            retroactive_topics: Arc::new(Mutex::new(HashSet::new())),
+            max_size: None,
+            policy: DropPolicy::default(),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            subscription_policy: SubscriptionPolicy::default(),
+            dropped_stale: Arc::new(AtomicUsize::new(0)),
+            space_available: Arc::new(Condvar::new()),
         }
     }
 
-    /// Add a record to the queue
+    /// Bound the queue to `max_size` records, applying `policy` once it is full
+    pub fn with_max_size(mut self, max_size: usize, policy: DropPolicy) -> Self {
+        assert!(
+            max_size > 0,
+            "Subscription queue max size must be at least 1"
+        );
+        self.max_size = Some(max_size);
+        self.policy = policy;
+        self
+    }
+
+    /// Hand the task only the records selected by `policy`, see `drain_by_policy`
+    pub fn with_subscription_policy(mut self, policy: SubscriptionPolicy) -> Self {
+        self.subscription_policy = policy;
+        self
+    }
+
+    /// Add a record to the queue.
+    /// With `DropPolicy::Block` this waits until there is room for it.
     pub fn push(&self, record: Record) {
         let mut queue = self.queue.lock().unwrap();
+        if self.policy == DropPolicy::Block {
+            while self.is_full_locked(&queue) {
+                queue = self.space_available.wait(queue).unwrap();
+            }
+        }
+        self.push_locked(&mut queue, record);
+    }
+
+    /// Add a record to the queue without ever waiting.
+    /// A full `DropPolicy::Block` queue discards the record instead.
+    /// Returns false if the pushed record was discarded.
+    pub fn try_push(&self, record: Record) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+        if self.policy == DropPolicy::Block && self.is_full_locked(&queue) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        self.push_locked(&mut queue, record)
+    }
+
+    fn push_locked(&self, queue: &mut VecDeque<Record>, record: Record) -> bool {
+        if self.is_full_locked(queue) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            match self.policy {
+                DropPolicy::DropNewest => return false,
+                DropPolicy::DropOldest | DropPolicy::Block => {
+                    queue.pop_front();
+                }
+            }
+        }
         queue.push_back(record);
+        true
+    }
+
+    fn is_full_locked(&self, queue: &VecDeque<Record>) -> bool {
+        self.max_size
+            .is_some_and(|max_size| queue.len() >= max_size)
     }
 
     /// Drain the queue and return all records
     pub fn drain(&self) -> Vec<Record> {
         let mut queue = self.queue.lock().unwrap();
         let records: Vec<Record> = queue.drain(..).collect();
+        self.space_available.notify_all();
+        records
+    }
+
+    /// Drain the queue but only return the newest `n` records, oldest first.
+    /// Older records are discarded and counted in `dropped_stale_count`.
+    pub fn drain_n(&self, n: usize) -> Vec<Record> {
+        let mut queue = self.queue.lock().unwrap();
+        let skip = queue.len().saturating_sub(n);
+        self.dropped_stale.fetch_add(skip, Ordering::Relaxed);
+        let records = queue.drain(..).skip(skip).collect();
+        self.space_available.notify_all();
         records
     }
 
+    /// Drain the queue and return only the newest record, if any.
+    /// Older records are discarded and counted in `dropped_stale_count`.
+    pub fn drain_latest(&self) -> Option<Record> {
+        self.drain_n(1).pop()
+    }
+
+    /// Same as `drain_n`
+    pub fn drain_latest_n(&self, n: usize) -> Vec<Record> {
+        self.drain_n(n)
+    }
+
+    /// Same as `drain_latest`
+    pub fn drain_latest_one(&self) -> Option<Record> {
+        self.drain_latest()
+    }
+
+    /// Drain the queue, returning the records selected by the subscription policy
+    pub fn drain_by_policy(&self) -> Vec<Record> {
+        match self.subscription_policy {
+            SubscriptionPolicy::All => self.drain(),
+            SubscriptionPolicy::Latest => self.drain_latest().into_iter().collect(),
+            SubscriptionPolicy::LatestN(n) => self.drain_n(n),
+        }
+    }
+
     /// Check if the queue is empty
     pub fn is_empty(&self) -> bool {
         let queue = self.queue.lock().unwrap();
@@ -55,6 +198,32 @@ impl SubscriptionQueue {
         queue.len()
     }
 
+    /// Whether the queue is bounded and holds `max_size` records
+    pub fn is_full(&self) -> bool {
+        let queue = self.queue.lock().unwrap();
+        self.is_full_locked(&queue)
+    }
+
+    /// Get the number of records discarded because the queue was full
+    pub fn dropped_count(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of records discarded by `drain_latest` and `drain_n` for newer ones
+    pub fn dropped_stale_count(&self) -> usize {
+        self.dropped_stale.load(Ordering::Relaxed)
+    }
+
+    /// Get the policy selecting which records are handed to the task
+    pub fn subscription_policy(&self) -> SubscriptionPolicy {
+        self.subscription_policy
+    }
+
+    /// Get the policy applied when the queue is full
+    pub fn drop_policy(&self) -> DropPolicy {
+        self.policy
+    }
+
     /// Get the task info for this subscription
     pub fn task_info(&self) -> &TaskInfo {
         &self.task_info
@@ -64,4 +233,146 @@ impl SubscriptionQueue {
     pub fn topic_pattern(&self) -> &str {
         &self.topic_pattern
     }
+
+    /// Record that a newly seen topic was matched by this subscription's pattern
+    pub fn mark_retroactive_topic(&self, topic: &str) {
+        let mut topics = self.retroactive_topics.lock().unwrap();
+        topics.insert(topic.to_string());
+    }
+
+    /// Get the topics this subscription picked up when they were first published
+    pub fn retroactive_topics(&self) -> HashSet<String> {
+        let topics = self.retroactive_topics.lock().unwrap();
+        topics.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::publish;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct TestMessage {
+        value: i32,
+    }
+
+    fn filled_queue(count: i32) -> SubscriptionQueue {
+        let queue = SubscriptionQueue::new(TaskInfo::new("TestTask"), "test".to_string());
+        for value in 0..count {
+            queue.push(publish!("test/topic", &TestMessage { value }));
+        }
+        queue
+    }
+
+    fn bounded_queue(policy: DropPolicy) -> SubscriptionQueue {
+        SubscriptionQueue::new(TaskInfo::new("TestTask"), "test".to_string())
+            .with_max_size(3, policy)
+    }
+
+    fn queued_values(queue: &SubscriptionQueue) -> Vec<i32> {
+        queue
+            .drain()
+            .iter()
+            .map(|record| record.to_serde::<TestMessage>().unwrap()[0].value)
+            .collect()
+    }
+
+    #[test]
+    fn test_drain_latest_n() {
+        let queue = filled_queue(100);
+
+        let records = queue.drain_latest_n(10);
+        assert_eq!(records.len(), 10);
+        assert!(queue.is_empty());
+
+        let values: Vec<i32> = records
+            .iter()
+            .map(|record| record.to_serde::<TestMessage>().unwrap()[0].value)
+            .collect();
+        assert_eq!(values, (90..100).collect::<Vec<i32>>());
+
+        // Asking for more than is buffered returns everything
+        assert_eq!(filled_queue(3).drain_latest_n(10).len(), 3);
+    }
+
+    #[test]
+    fn test_drain_latest_one() {
+        let queue = filled_queue(5);
+        let record = queue.drain_latest_one().unwrap();
+        assert_eq!(record.to_serde::<TestMessage>().unwrap()[0].value, 4);
+        assert!(queue.drain_latest_one().is_none());
+    }
+
+    #[test]
+    fn test_drain_by_policy() {
+        let latest = filled_queue(5).with_subscription_policy(SubscriptionPolicy::Latest);
+        let records = latest.drain_by_policy();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].to_serde::<TestMessage>().unwrap()[0].value, 4);
+        assert_eq!(latest.dropped_stale_count(), 4);
+        assert!(latest.drain_by_policy().is_empty());
+
+        let latest_n = filled_queue(5).with_subscription_policy(SubscriptionPolicy::LatestN(2));
+        assert_eq!(latest_n.drain_by_policy().len(), 2);
+        assert_eq!(latest_n.dropped_stale_count(), 3);
+
+        let all = filled_queue(5);
+        assert_eq!(all.subscription_policy(), SubscriptionPolicy::All);
+        assert_eq!(all.drain_by_policy().len(), 5);
+        assert_eq!(all.dropped_stale_count(), 0);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let queue = bounded_queue(DropPolicy::DropOldest);
+        for value in 0..5 {
+            queue.push(publish!("test/topic", &TestMessage { value }));
+        }
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.dropped_count(), 2);
+        assert_eq!(queued_values(&queue), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let queue = bounded_queue(DropPolicy::DropNewest);
+        for value in 0..5 {
+            queue.push(publish!("test/topic", &TestMessage { value }));
+        }
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.dropped_count(), 2);
+        assert_eq!(queued_values(&queue), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_block_until_drained() {
+        let queue = bounded_queue(DropPolicy::Block);
+        let producer = {
+            let queue = queue.clone();
+            std::thread::spawn(move || {
+                for value in 0..5 {
+                    queue.push(publish!("test/topic", &TestMessage { value }));
+                }
+            })
+        };
+
+        let mut values = Vec::new();
+        while values.len() < 5 {
+            assert!(queue.len() <= 3);
+            values.extend(queued_values(&queue));
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        producer.join().unwrap();
+        assert_eq!(values, vec![0, 1, 2, 3, 4]);
+        assert_eq!(queue.dropped_count(), 0);
+
+        // Without waiting a full queue refuses the record
+        for value in 0..4 {
+            queue.try_push(publish!("test/topic", &TestMessage { value }));
+        }
+        assert_eq!(queue.dropped_count(), 1);
+        assert_eq!(queued_values(&queue), vec![0, 1, 2]);
+    }
 }
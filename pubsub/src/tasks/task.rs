@@ -7,6 +7,19 @@ use super::{info::TaskInfo, meta_control::MetaMessage};
 pub type TaskChannel = mpsc::Sender<Record>;
 pub type MetaTaskChannel = mpsc::Sender<MetaMessage>;
 
+/// What the `Runner` does after a task returns an error from `run`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    /// Log the error and run the task again next cycle
+    Continue,
+    /// Drop the task's subscriptions and call `init` again
+    Restart,
+    /// Stop running the task
+    KillTask,
+    /// Stop the whole runner, `Runner::run` returns the error
+    Fatal,
+}
+
 pub trait Task {
     fn init(&mut self, tx: TaskChannel, meta_tx: MetaTaskChannel) -> Result<(), anyhow::Error>;
 
@@ -21,6 +34,11 @@ pub trait Task {
         meta_tx: MetaTaskChannel,
     ) -> Result<(), anyhow::Error>;
 
+    /// Called when `run` fails, decides how the `Runner` recovers
+    fn on_error(&mut self, _err: &anyhow::Error) -> ErrorAction {
+        ErrorAction::Continue
+    }
+
     fn cleanup(&mut self) -> Result<(), anyhow::Error> {
         Ok(())
     }
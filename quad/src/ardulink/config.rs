@@ -1,8 +1,31 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+/// Reconnection attempts after the link drops, before the connection gives up
+pub const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+/// Wait before the first reconnection attempt, doubled for every further attempt
+pub const DEFAULT_BASE_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait between two reconnection attempts
+pub const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+fn default_max_reconnect_attempts() -> u32 {
+    DEFAULT_MAX_RECONNECT_ATTEMPTS
+}
+
+fn default_base_reconnect_delay() -> Duration {
+    DEFAULT_BASE_RECONNECT_DELAY
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ArdulinkConfig {
     pub connection: ArdulinkConnectionType,
+    #[serde(default = "default_max_reconnect_attempts")]
+    pub max_reconnect_attempts: u32,
+    #[serde(default = "default_base_reconnect_delay")]
+    pub base_reconnect_delay: Duration,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -15,7 +38,32 @@ pub enum ArdulinkConnectionType {
 
 impl ArdulinkConfig {
     pub fn new(connection: ArdulinkConnectionType) -> Self {
-        Self { connection }
+        Self {
+            connection,
+            max_reconnect_attempts: DEFAULT_MAX_RECONNECT_ATTEMPTS,
+            base_reconnect_delay: DEFAULT_BASE_RECONNECT_DELAY,
+        }
+    }
+
+    /// Give up after `n` failed reconnection attempts, 0 disables reconnecting
+    pub fn with_max_reconnect_attempts(mut self, n: u32) -> Self {
+        self.max_reconnect_attempts = n;
+        self
+    }
+
+    /// Wait `d` before the first reconnection attempt
+    pub fn with_base_reconnect_delay(mut self, d: Duration) -> Self {
+        self.base_reconnect_delay = d;
+        self
+    }
+
+    /// Wait before reconnection attempt `attempt` (0 based): `base_reconnect_delay * 2^attempt`,
+    /// capped at `MAX_RECONNECT_DELAY`
+    pub fn reconnect_delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.base_reconnect_delay
+            .saturating_mul(factor)
+            .min(MAX_RECONNECT_DELAY)
     }
 }
 
@@ -1,20 +1,39 @@
 use anyhow::Error;
 use crossbeam_channel::{Receiver, Sender};
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use mavlink::ardupilotmega::MavMessage;
+use mavlink::Message;
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, RwLock,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use crate::ardulink::config::ArdulinkConnectionType;
+use crate::ardulink::config::{ArdulinkConfig, ArdulinkConnectionType};
 
 type MavlinkMessageType = MavMessage;
 
+type MavConnection = Box<dyn mavlink::MavConnection<MavlinkMessageType> + Send + Sync>;
+
+/// Callback invoked on the receive thread for every message of a registered ID
+pub type MessageHandler = Arc<dyn Fn(MavlinkMessageType) + Send + Sync>;
+
+type MessageHandlers = Arc<RwLock<HashMap<u32, Vec<MessageHandler>>>>;
+
+/// Published on `mavlink/reconnected` once a dropped link is re-established
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReconnectEvent {
+    /// Reconnection attempt that succeeded, starting at 1
+    pub attempt: u32,
+    /// Seconds from losing the link to reconnecting
+    pub elapsed_s: f64,
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ArdulinkError {
     #[error("Connection error: {0}")]
@@ -28,22 +47,31 @@ pub struct ArdulinkConnection {
     transmit_channels: (Sender<MavlinkMessageType>, Receiver<MavlinkMessageType>),
     connection_string: String,
     should_stop: Arc<AtomicBool>,
-    connection_type: ArdulinkConnectionType,
+    config: ArdulinkConfig,
     thread_handles: Vec<thread::JoinHandle<()>>,
+    message_handlers: MessageHandlers,
+    reconnect_channels: (Sender<ReconnectEvent>, Receiver<ReconnectEvent>),
 }
 
 impl ArdulinkConnection {
     pub fn new(connection_type: ArdulinkConnectionType) -> Result<Self, Error> {
+        Self::from_config(ArdulinkConfig::new(connection_type))
+    }
+
+    /// Create a connection that reconnects with the back-off settings of `config`
+    pub fn from_config(config: ArdulinkConfig) -> Result<Self, Error> {
         let (recv_tx, recv_rx): (Sender<_>, Receiver<_>) = crossbeam_channel::bounded(500);
         let (transmit_tx, transmit_rx): (Sender<_>, Receiver<_>) = crossbeam_channel::bounded(500);
 
         Ok(Self {
             recv_channels: (recv_tx, recv_rx),
             transmit_channels: (transmit_tx, transmit_rx),
-            connection_string: connection_type.connection_string(),
+            connection_string: config.connection.connection_string(),
             should_stop: Arc::new(AtomicBool::new(false)),
-            connection_type,
+            config,
             thread_handles: Vec::new(),
+            message_handlers: Arc::new(RwLock::new(HashMap::new())),
+            reconnect_channels: crossbeam_channel::unbounded(),
         })
     }
 
@@ -52,7 +80,9 @@ impl ArdulinkConnection {
         let recv_channels = self.recv_channels.clone();
         let transmit_channels = self.transmit_channels.clone();
         let should_stop = self.should_stop.clone();
-        let connection_type = self.connection_type.clone();
+        let config = self.config.clone();
+        let message_handlers = self.message_handlers.clone();
+        let reconnect_tx = self.reconnect_channels.0.clone();
 
         let thread_handle = thread::spawn(move || {
             if let Err(e) = Self::start_thread_inner(
@@ -60,7 +90,9 @@ impl ArdulinkConnection {
                 recv_channels,
                 transmit_channels,
                 should_stop,
-                connection_type,
+                config,
+                message_handlers,
+                reconnect_tx,
             ) {
                 error!(
                     "ArduLink => Error starting thread for connection string: {}",
@@ -98,24 +130,114 @@ impl ArdulinkConnection {
         recv_channels: (Sender<MavlinkMessageType>, Receiver<MavlinkMessageType>),
         transmit_channels: (Sender<MavlinkMessageType>, Receiver<MavlinkMessageType>),
         should_stop: Arc<AtomicBool>,
-        _connection_type: ArdulinkConnectionType,
+        config: ArdulinkConfig,
+        message_handlers: MessageHandlers,
+        reconnect_tx: Sender<ReconnectEvent>,
     ) -> Result<(), ArdulinkError> {
-        // Make the connection
+        let mut mav_con = Self::connect(&con_string).map_err(ArdulinkError::ConnectionError)?;
+
+        // Request streams now handled by ExecTaskRequestStream
+
+        loop {
+            let link_lost = Self::run_session(
+                Arc::new(mav_con),
+                &recv_channels,
+                &transmit_channels,
+                &should_stop,
+                &message_handlers,
+            );
+            if !link_lost || should_stop.load(Ordering::SeqCst) {
+                break;
+            }
+
+            warn!("ArduLink => Link lost, reconnecting to {}", con_string);
+            match Self::reconnect(|| Self::connect(&con_string), &config, &should_stop) {
+                Some((new_con, event)) => {
+                    info!(
+                        "ArduLink => Reconnected on attempt {} after {:.1}s",
+                        event.attempt, event.elapsed_s
+                    );
+                    let _ = reconnect_tx.send(event);
+                    mav_con = new_con;
+                }
+                None => {
+                    if !should_stop.load(Ordering::SeqCst) {
+                        error!(
+                            "ArduLink => Giving up on {} after {} reconnection attempts",
+                            con_string, config.max_reconnect_attempts
+                        );
+                    }
+                    break;
+                }
+            }
+        }
+
+        info!("ArduLink => All threads exited");
+        Ok(())
+    }
+
+    fn connect(con_string: &str) -> Result<MavConnection, Error> {
         info!(
             "ArduLink => Connecting to MAVLink with connection string: {}",
             con_string
         );
-
-        let mut mav_con: Box<dyn mavlink::MavConnection<MavlinkMessageType> + Send + Sync> =
-            mavlink::connect::<MavlinkMessageType>(&con_string)
-                .map_err(|e| ArdulinkError::ConnectionError(e.into()))?;
+        let mut mav_con = mavlink::connect::<MavlinkMessageType>(con_string)?;
 
         info!("ArduLink => Setting up connection parameters");
         mav_con.set_protocol_version(mavlink::MavlinkVersion::V2);
+        Ok(mav_con)
+    }
 
-        // Request streams now handled by ExecTaskRequestStream
+    /// Call `connect` until it succeeds, waiting `config.reconnect_delay(attempt)` before each
+    /// attempt. None once `config.max_reconnect_attempts` attempts failed or a stop was requested.
+    fn reconnect<C>(
+        mut connect: impl FnMut() -> Result<C, Error>,
+        config: &ArdulinkConfig,
+        should_stop: &AtomicBool,
+    ) -> Option<(C, ReconnectEvent)> {
+        let lost_at = Instant::now();
+        for attempt in 0..config.max_reconnect_attempts {
+            // Sleep in short steps so a long back-off doesn't hold up stop_thread
+            let wake_at = Instant::now() + config.reconnect_delay(attempt);
+            while Instant::now() < wake_at {
+                if should_stop.load(Ordering::SeqCst) {
+                    return None;
+                }
+                thread::sleep(
+                    wake_at
+                        .saturating_duration_since(Instant::now())
+                        .min(Duration::from_millis(100)),
+                );
+            }
+
+            match connect() {
+                Ok(con) => {
+                    let event = ReconnectEvent {
+                        attempt: attempt + 1,
+                        elapsed_s: lost_at.elapsed().as_secs_f64(),
+                    };
+                    return Some((con, event));
+                }
+                Err(e) => warn!(
+                    "ArduLink => Reconnection attempt {} failed: {}",
+                    attempt + 1,
+                    e
+                ),
+            }
+        }
+        None
+    }
 
-        let mav_con = Arc::new(mav_con);
+    /// Run the send and receive threads on `mav_con` until a stop is requested or the link
+    /// drops. Returns whether the link dropped.
+    fn run_session(
+        mav_con: Arc<MavConnection>,
+        recv_channels: &(Sender<MavlinkMessageType>, Receiver<MavlinkMessageType>),
+        transmit_channels: &(Sender<MavlinkMessageType>, Receiver<MavlinkMessageType>),
+        should_stop: &Arc<AtomicBool>,
+        message_handlers: &MessageHandlers,
+    ) -> bool {
+        let link_lost = Arc::new(AtomicBool::new(false));
 
         info!("ArduLink => Starting main threads...");
 
@@ -124,25 +246,24 @@ impl ArdulinkConnection {
         let send_handle = thread::spawn({
             let vehicle = mav_con.clone();
             let should_stop = should_stop.clone();
+            let link_lost = link_lost.clone();
+            let transmit_channels = transmit_channels.clone();
             move || {
                 let (_, rx) = &transmit_channels;
-                while !should_stop.load(Ordering::SeqCst) {
+                let stopping =
+                    || should_stop.load(Ordering::SeqCst) || link_lost.load(Ordering::SeqCst);
+                while !stopping() {
                     match rx.recv_timeout(Duration::from_millis(100)) {
                         Ok(msg) => {
-                            if should_stop.load(Ordering::SeqCst) {
+                            // Only attempt to send if we're not stopping
+                            if stopping() {
                                 break;
                             }
                             trace!("ArduLink => Sending message to MAVLink: {msg:?}");
-                            // Only attempt to send if we're not stopping
-                            if !should_stop.load(Ordering::SeqCst) {
-                                let _ = vehicle.send(&mavlink::MavHeader::default(), &msg);
-                            }
+                            let _ = vehicle.send(&mavlink::MavHeader::default(), &msg);
                         }
                         Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
-                            // Check if we should stop
-                            if should_stop.load(Ordering::SeqCst) {
-                                break;
-                            }
+                            // Loop condition checks if we should stop
                         }
                         Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
                             break;
@@ -158,19 +279,19 @@ impl ArdulinkConnection {
         let receive_handle = thread::spawn({
             let vehicle = mav_con.clone();
             let should_stop = should_stop.clone();
+            let link_lost = link_lost.clone();
+            let recv_channels = recv_channels.clone();
+            let message_handlers = message_handlers.clone();
             move || {
                 while !should_stop.load(Ordering::SeqCst) {
-                    if should_stop.load(Ordering::SeqCst) {
-                        break;
-                    }
-
                     // Use standard receive with a timeout by checking the flag frequently
                     let recv_result = vehicle.recv();
 
                     match recv_result {
                         Ok((_header, msg)) => {
                             let (recv_tx, _) = &recv_channels;
-                            if let Err(e) = recv_tx.send(msg) {
+                            if let Err(e) = Self::dispatch_message(&message_handlers, recv_tx, msg)
+                            {
                                 error!(
                                     "ArduLink => Failed to send received message to channel: {:?}",
                                     e
@@ -187,17 +308,13 @@ impl ArdulinkConnection {
                             } else if !should_stop.load(Ordering::SeqCst) {
                                 // Only log errors if we're not stopping
                                 error!("ArduLink => Receive error: {e:?}");
+                                link_lost.store(true, Ordering::SeqCst);
                                 break;
                             }
                         }
                         // Messages that didn't get through due to parser errors are ignored
                         _ => {}
                     }
-
-                    // Check stop flag more frequently
-                    if should_stop.load(Ordering::SeqCst) {
-                        break;
-                    }
                 }
                 debug!("ArduLink => Receive thread exiting");
             }
@@ -207,8 +324,36 @@ impl ArdulinkConnection {
         let _ = send_handle.join();
         let _ = receive_handle.join();
 
-        info!("ArduLink => All threads exited");
-        Ok(())
+        link_lost.load(Ordering::SeqCst)
+    }
+
+    /// Hand a received message to its registered handlers, or queue it if it has none
+    fn dispatch_message(
+        message_handlers: &MessageHandlers,
+        recv_tx: &Sender<MavlinkMessageType>,
+        msg: MavlinkMessageType,
+    ) -> Result<(), crossbeam_channel::SendError<MavlinkMessageType>> {
+        let handlers = message_handlers.read().unwrap();
+        match handlers.get(&msg.message_id()) {
+            Some(handlers) if !handlers.is_empty() => {
+                for handler in handlers {
+                    handler(msg.clone());
+                }
+                Ok(())
+            }
+            _ => recv_tx.send(msg),
+        }
+    }
+
+    /// Register a callback for a MAVLink message ID.
+    /// Messages with a handler are processed on the receive thread and are not queued for `recv`.
+    pub fn register_message_handler(&self, message_id: u32, handler: MessageHandler) {
+        self.message_handlers
+            .write()
+            .unwrap()
+            .entry(message_id)
+            .or_default()
+            .push(handler);
     }
 
     pub fn send(&self, msg: &MavlinkMessageType) -> Result<(), ArdulinkError> {
@@ -236,4 +381,130 @@ impl ArdulinkConnection {
         }
         Ok(data)
     }
+
+    /// Take the reconnections that happened since the last call
+    pub fn recv_reconnect_events(&self) -> Vec<ReconnectEvent> {
+        self.reconnect_channels.1.try_iter().collect()
+    }
+
+    /// Drain the receive queue, keeping only messages with one of the given IDs.
+    /// Messages with other IDs are discarded.
+    pub fn receive_filtered(&self, message_ids: &[u32]) -> Vec<MavlinkMessageType> {
+        let (_, rx) = &self.recv_channels;
+
+        // Don't attempt to receive if we're stopping
+        if self.should_stop.load(Ordering::SeqCst) {
+            return Vec::new();
+        }
+
+        rx.try_iter()
+            .filter(|msg| message_ids.contains(&msg.message_id()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ardulink::config::MAX_RECONNECT_DELAY;
+    use mavlink::ardupilotmega::{ATTITUDE_DATA, HEARTBEAT_DATA};
+    use mavlink::MessageData;
+    use std::sync::Mutex;
+
+    fn test_connection() -> ArdulinkConnection {
+        ArdulinkConnection::new(ArdulinkConnectionType::Udp("127.0.0.1".to_string(), 14550))
+            .unwrap()
+    }
+
+    fn dispatch(connection: &ArdulinkConnection, msg: MavlinkMessageType) {
+        ArdulinkConnection::dispatch_message(
+            &connection.message_handlers,
+            &connection.recv_channels.0,
+            msg,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_message_handler_called_for_heartbeat() {
+        let connection = test_connection();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let handler_received = received.clone();
+        connection.register_message_handler(
+            HEARTBEAT_DATA::ID,
+            Arc::new(move |msg| handler_received.lock().unwrap().push(msg)),
+        );
+
+        dispatch(
+            &connection,
+            MavMessage::HEARTBEAT(HEARTBEAT_DATA::default()),
+        );
+        dispatch(&connection, MavMessage::ATTITUDE(ATTITUDE_DATA::default()));
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(matches!(received[0], MavMessage::HEARTBEAT(_)));
+
+        // Handled messages are not buffered, others still are
+        let queued = connection.recv().unwrap();
+        assert_eq!(queued.len(), 1);
+        assert!(matches!(queued[0], MavMessage::ATTITUDE(_)));
+    }
+
+    #[test]
+    fn test_receive_filtered() {
+        let connection = test_connection();
+        dispatch(
+            &connection,
+            MavMessage::HEARTBEAT(HEARTBEAT_DATA::default()),
+        );
+        dispatch(&connection, MavMessage::ATTITUDE(ATTITUDE_DATA::default()));
+        dispatch(
+            &connection,
+            MavMessage::HEARTBEAT(HEARTBEAT_DATA::default()),
+        );
+
+        let heartbeats = connection.receive_filtered(&[HEARTBEAT_DATA::ID]);
+        assert_eq!(heartbeats.len(), 2);
+        assert!(connection.recv().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let config =
+            ArdulinkConfig::new(ArdulinkConnectionType::Tcp("127.0.0.1".to_string(), 5760))
+                .with_base_reconnect_delay(Duration::from_millis(1))
+                .with_max_reconnect_attempts(5);
+        assert_eq!(config.reconnect_delay(3), Duration::from_millis(8));
+        let should_stop = AtomicBool::new(false);
+
+        // Fails three times, then connects
+        let mut calls = 0;
+        let mock_connect = || {
+            calls += 1;
+            if calls <= 3 {
+                Err(anyhow::anyhow!("Connection refused"))
+            } else {
+                Ok(calls)
+            }
+        };
+        let (connection, event) =
+            ArdulinkConnection::reconnect(mock_connect, &config, &should_stop).unwrap();
+        assert_eq!(connection, 4);
+        assert_eq!(event.attempt, 4);
+        // Waited 1 + 2 + 4 + 8 ms before the attempts
+        assert!(event.elapsed_s >= 0.015);
+
+        let config = config.with_max_reconnect_attempts(2);
+        let mut calls = 0;
+        let always_fails = || -> Result<(), Error> {
+            calls += 1;
+            Err(anyhow::anyhow!("Connection refused"))
+        };
+        assert!(ArdulinkConnection::reconnect(always_fails, &config, &should_stop).is_none());
+        assert_eq!(calls, 2);
+
+        // Long back-offs are capped
+        assert_eq!(config.reconnect_delay(40), MAX_RECONNECT_DELAY);
+    }
 }
@@ -1,17 +1,23 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
 use anyhow::Error;
 use log::{debug, error, info};
 use mavlink::ardupilotmega::{
     MavMessage, MavModeFlag, MavSeverity, HEARTBEAT_DATA, STATUSTEXT_DATA,
 };
+use mavlink::Message;
 use serde::{Deserialize, Serialize};
 
+use pubsub::message::builders::publish::PublishBuilder;
+use pubsub::message::builders::RecordBuilder;
 use pubsub::message::record::Record;
 use pubsub::subscribe;
 use pubsub::tasks::info::TaskInfo;
 use pubsub::tasks::task::{MetaTaskChannel, Task, TaskChannel};
-use pubsub::{publish, publish_json};
+use pubsub::{publish, publish_json, task_info};
 
-use crate::ardulink::config::ArdulinkConnectionType;
+use crate::ardulink::config::{ArdulinkConfig, ArdulinkConnectionType};
 use crate::ardulink::connection::ArdulinkConnection;
 use crate::exec::tasks::exec_task_watchdog::ConnectionStatus;
 
@@ -36,6 +42,16 @@ pub struct StatusTextMessage {
 pub struct HeartbeatFlag {
     pub value: bool,
 }
+/// Published on `mavlink/config/filter` to change which message types MavlinkTask
+/// publishes at runtime. Replaces both the filter and the blocklist.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct MavlinkFilterConfig {
+    /// Only publish these message types (e.g. `HEARTBEAT`), all types when empty
+    pub allow: Vec<String>,
+    /// Never publish these message types
+    pub block: Vec<String>,
+}
+
 /// Represents the autopilot status flags from a heartbeat message
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AutopilotStatus {
@@ -77,33 +93,156 @@ impl From<&MavMessage> for MavlinkMessageWrapper {
 /// Task responsible for managing a MAVLink connection and publishing received messages
 pub struct MavlinkTask {
     /// The connection configuration
-    connection_type: ArdulinkConnectionType,
+    config: ArdulinkConfig,
     /// The actual connection (created during init)
     connection: Option<ArdulinkConnection>,
     info: TaskInfo,
+    /// Window to accumulate messages of the same type into one Record (0 disables batching)
+    batch_window: Duration,
+    /// Accumulated messages keyed by topic, waiting for the window to close
+    pending_batches: HashMap<String, Vec<serde_json::Value>>,
+    /// When the first message of the current window was accumulated
+    batch_started: Option<Instant>,
+    /// Message types to publish, all types when empty
+    message_filter: HashSet<String>,
+    /// Message types never published
+    message_blocklist: HashSet<String>,
 }
 
 impl MavlinkTask {
     /// Create a new MavlinkTask with the specified connection type
     pub fn new(connection_type: ArdulinkConnectionType) -> Self {
+        Self::new_with_batch_window(connection_type, 0)
+    }
+
+    /// Create a new MavlinkTask that publishes messages of the same type as one multi-row
+    /// Record every `batch_window_ms`, instead of one Record per received message
+    pub fn new_with_batch_window(
+        connection_type: ArdulinkConnectionType,
+        batch_window_ms: u64,
+    ) -> Self {
+        Self::from_config(ArdulinkConfig::new(connection_type))
+            .with_batch_window(Duration::from_millis(batch_window_ms))
+    }
+
+    /// Create a new MavlinkTask from a full config, including its reconnection back-off.
+    /// Reconnections are published on `mavlink/reconnected` as a `ReconnectEvent`.
+    pub fn from_config(config: ArdulinkConfig) -> Self {
         Self {
-            connection_type,
+            config,
             connection: None,
-            info: TaskInfo::new("MavlinkTask"),
+            info: task_info!(MavlinkTask),
+            batch_window: Duration::ZERO,
+            pending_batches: HashMap::new(),
+            batch_started: None,
+            message_filter: HashSet::new(),
+            message_blocklist: HashSet::new(),
         }
     }
 
-    /// Helper method to publish a MAVLink message to the pubsub system
-    fn publish_message(&self, msg: &MavMessage, tx: &TaskChannel) -> Result<(), Error> {
-        // Convert the MAVLink message to our serializable wrapper
-        let wrapper = MavlinkMessageWrapper::from(msg);
+    /// Publish messages of the same type as one multi-row Record every `batch_window`
+    pub fn with_batch_window(mut self, batch_window: Duration) -> Self {
+        self.batch_window = batch_window;
+        self
+    }
 
-        // Create topic name in format mavlink/{message_type}
-        let topic = format!("mavlink/{}", wrapper.message_type.to_ascii_lowercase());
+    /// Only publish the given MAVLink message types, e.g. `HEARTBEAT` or `SYS_STATUS`
+    pub fn with_message_filter(mut self, types: HashSet<&str>) -> Self {
+        self.message_filter = types.into_iter().map(str::to_ascii_uppercase).collect();
+        self
+    }
 
-        // Create and send publish packet
-        let pub_packet = publish_json!(&topic, wrapper.message.as_str());
-        tx.send(pub_packet)?;
+    /// Never publish the given MAVLink message types, e.g. high rate `RAW_IMU` or `ATTITUDE`
+    pub fn with_message_blocklist(mut self, types: HashSet<&str>) -> Self {
+        self.message_blocklist = types.into_iter().map(str::to_ascii_uppercase).collect();
+        self
+    }
+
+    /// Check if a message type passes the filter and blocklist
+    fn publishes_type(&self, message_type: &str) -> bool {
+        (self.message_filter.is_empty() || self.message_filter.contains(message_type))
+            && !self.message_blocklist.contains(message_type)
+    }
+
+    /// Replace the filter and blocklist with a `MavlinkFilterConfig` record
+    fn apply_filter_config(&mut self, record: &Record) -> Result<(), Error> {
+        for config in record.to_serde::<MavlinkFilterConfig>()? {
+            info!(
+                "MavlinkTask message filter: allow {:?}, block {:?}",
+                config.allow, config.block
+            );
+            self.message_filter = config
+                .allow
+                .iter()
+                .map(|t| t.to_ascii_uppercase())
+                .collect();
+            self.message_blocklist = config
+                .block
+                .iter()
+                .map(|t| t.to_ascii_uppercase())
+                .collect();
+        }
+        Ok(())
+    }
+
+    /// Get the topic a MAVLink message is published on
+    fn message_topic(wrapper: &MavlinkMessageWrapper) -> String {
+        format!("mavlink/{}", wrapper.message_type.to_ascii_lowercase())
+    }
+
+    /// Add a message to the pending batch for its topic
+    fn accumulate_message(&mut self, wrapper: &MavlinkMessageWrapper) -> Result<(), Error> {
+        let value: serde_json::Value = serde_json::from_str(&wrapper.message)?;
+        self.pending_batches
+            .entry(Self::message_topic(wrapper))
+            .or_default()
+            .push(value);
+        self.batch_started.get_or_insert_with(Instant::now);
+        Ok(())
+    }
+
+    /// Check if the current batch window has elapsed
+    fn batch_window_elapsed(&self) -> bool {
+        self.batch_started
+            .is_some_and(|started| started.elapsed() >= self.batch_window)
+    }
+
+    /// Publish every pending batch as a multi-row Record
+    fn flush_batches(&mut self, tx: &TaskChannel) -> Result<(), Error> {
+        for (topic, values) in self.pending_batches.drain() {
+            if values.is_empty() {
+                continue;
+            }
+            debug!("Publishing batch of {} messages to {}", values.len(), topic);
+            let record = Record::from_serde_batch(&values)?;
+            let pub_packet = PublishBuilder::new(topic)
+                .with_record_content(record)
+                .build();
+            tx.send(pub_packet)?;
+        }
+        self.batch_started = None;
+        Ok(())
+    }
+
+    /// Helper method to publish a MAVLink message to the pubsub system.
+    /// Filtered message types are still used for the reprocessed statustext and heartbeat topics.
+    fn publish_message(&mut self, msg: &MavMessage, tx: &TaskChannel) -> Result<(), Error> {
+        // Check the filter before paying for the conversion
+        if self.publishes_type(msg.message_name()) {
+            // Convert the MAVLink message to our serializable wrapper
+            let wrapper = MavlinkMessageWrapper::from(msg);
+
+            if self.batch_window.is_zero() {
+                // Create topic name in format mavlink/{message_type}
+                let topic = Self::message_topic(&wrapper);
+
+                // Create and send publish packet
+                let pub_packet = publish_json!(&topic, wrapper.message.as_str());
+                tx.send(pub_packet)?;
+            } else {
+                self.accumulate_message(&wrapper)?;
+            }
+        }
 
         // Special handling for statustext messages
         if let MavMessage::STATUSTEXT(status_text) = msg {
@@ -190,11 +329,11 @@ impl Task for MavlinkTask {
     fn init(&mut self, tx: TaskChannel, meta_tx: MetaTaskChannel) -> Result<(), Error> {
         info!(
             "MavlinkTask initializing with connection: {}",
-            self.connection_type.connection_string()
+            self.config.connection.connection_string()
         );
 
         // Create the connection
-        let mut connection = ArdulinkConnection::new(self.connection_type.clone())?;
+        let mut connection = ArdulinkConnection::from_config(self.config.clone())?;
 
         // Start the connection thread
         connection.start_thread()?;
@@ -204,6 +343,7 @@ impl Task for MavlinkTask {
 
         // Set up topic subscription for command messages
         tx.send(subscribe!("mavlink/send/*"))?;
+        tx.send(subscribe!("mavlink/config/filter"))?;
 
         // Publish connection status for ExecTaskWatchdog
         let connection_status = ConnectionStatus { connected: true };
@@ -238,21 +378,30 @@ impl Task for MavlinkTask {
                         debug!("Mavlink Sending Command: {:?}", msg);
                         self.connection.as_ref().unwrap().send(&msg)?;
                     }
+                } else if topic == "mavlink/config/filter" {
+                    self.apply_filter_config(record)?;
                 }
             }
         }
 
         // Check for new MAVLink messages
-        if let Some(connection) = &self.connection {
-            let messages = connection.recv()?;
-
-            for msg in messages {
-                // Publish each message to the pubsub system
-                self.publish_message(&msg, &tx)?;
+        let messages = if let Some(connection) = &self.connection {
+            for event in connection.recv_reconnect_events() {
+                tx.send(publish!("mavlink/reconnected", &event))?;
             }
+            connection.recv()?
         } else {
             error!("MavlinkTask has no active connection");
             return Err(anyhow::anyhow!("MavlinkTask has no active connection"));
+        };
+
+        for msg in messages {
+            // Publish each message to the pubsub system
+            self.publish_message(&msg, &tx)?;
+        }
+
+        if self.batch_window_elapsed() {
+            self.flush_batches(&tx)?;
         }
 
         Ok(())
@@ -269,3 +418,90 @@ impl Task for MavlinkTask {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mavlink::ardupilotmega::ATTITUDE_DATA;
+    use std::sync::mpsc;
+
+    fn attitude(roll: f32) -> MavMessage {
+        MavMessage::ATTITUDE(ATTITUDE_DATA {
+            roll,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_batch_window_groups_by_type() {
+        let mut task = MavlinkTask::new_with_batch_window(
+            ArdulinkConnectionType::Udp("127.0.0.1".to_string(), 14550),
+            100,
+        );
+        let (tx, rx) = mpsc::channel();
+
+        for i in 0..5 {
+            task.publish_message(&attitude(i as f32), &tx).unwrap();
+        }
+        task.publish_message(&MavMessage::HEARTBEAT(HEARTBEAT_DATA::default()), &tx)
+            .unwrap();
+
+        // Only the reprocessed heartbeat topics are published before the window closes
+        let immediate: Vec<Record> = rx.try_iter().collect();
+        assert!(immediate
+            .iter()
+            .all(|r| r.try_get_topic().unwrap().starts_with("mavlink/reproc/")));
+
+        task.flush_batches(&tx).unwrap();
+        let batches: HashMap<String, Record> = rx
+            .try_iter()
+            .map(|r| (r.try_get_topic().unwrap(), r))
+            .collect();
+
+        assert_eq!(batches.len(), 2);
+        let attitude_batch = &batches["mavlink/attitude"];
+        assert_eq!(attitude_batch.to_record_batch().num_rows(), 5);
+        let rolls: Vec<ATTITUDE_DATA> = attitude_batch.to_serde().unwrap();
+        assert_eq!(rolls[4].roll, 4.0);
+        assert_eq!(batches["mavlink/heartbeat"].to_record_batch().num_rows(), 1);
+        assert!(task.pending_batches.is_empty());
+    }
+
+    #[test]
+    fn test_message_filter_and_blocklist() {
+        let connection = ArdulinkConnectionType::Udp("127.0.0.1".to_string(), 14550);
+        let (tx, rx) = mpsc::channel();
+        let published_topics = |task: &mut MavlinkTask| -> Vec<String> {
+            task.publish_message(&attitude(0.1), &tx).unwrap();
+            task.publish_message(&MavMessage::HEARTBEAT(HEARTBEAT_DATA::default()), &tx)
+                .unwrap();
+            rx.try_iter()
+                .map(|r| r.try_get_topic().unwrap())
+                .filter(|topic| !topic.starts_with("mavlink/reproc/"))
+                .collect()
+        };
+
+        let mut task = MavlinkTask::new(connection.clone());
+        assert_eq!(
+            published_topics(&mut task),
+            vec!["mavlink/attitude", "mavlink/heartbeat"]
+        );
+
+        let mut task =
+            MavlinkTask::new(connection.clone()).with_message_filter(HashSet::from(["heartbeat"]));
+        assert_eq!(published_topics(&mut task), vec!["mavlink/heartbeat"]);
+
+        let mut task =
+            MavlinkTask::new(connection).with_message_blocklist(HashSet::from(["ATTITUDE"]));
+        assert_eq!(published_topics(&mut task), vec!["mavlink/heartbeat"]);
+
+        // Reconfigure at runtime, the new config replaces the blocklist
+        let config = MavlinkFilterConfig {
+            allow: vec!["ATTITUDE".to_string()],
+            block: vec![],
+        };
+        task.apply_filter_config(&publish!("mavlink/config/filter", &config))
+            .unwrap();
+        assert_eq!(published_topics(&mut task), vec!["mavlink/attitude"]);
+    }
+}
@@ -1,10 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use pubsub::tasks::info::TaskInfo;
 
 use super::auto_stage::AutoStage;
+use super::tasks::auto_task_orbit::Orbit;
+use crate::exec::exec_config::{extend_unique, merge_stage_task_names, ConfigError};
 
+#[derive(Debug)]
 pub struct AutoConfig {
     pub stage_task_names: HashMap<AutoStage, Vec<String>>,
     pub script_task_name: String,
+    /// Circle flown by `AutoTaskOrbit`, see `with_orbit`
+    pub orbit: Option<Orbit>,
 }
 
 impl AutoConfig {
@@ -12,6 +19,7 @@ impl AutoConfig {
         Self {
             stage_task_names: HashMap::new(),
             script_task_name: String::new(),
+            orbit: None,
         }
     }
 
@@ -28,6 +36,18 @@ impl AutoConfig {
         self
     }
 
+    /// Fly `orbit` with `AutoTaskOrbit` in the `AutoOrbit` stage
+    pub fn with_orbit(mut self, orbit: Orbit) -> Self {
+        self.orbit = Some(orbit);
+        extend_unique(
+            self.stage_task_names
+                .entry(AutoStage::AutoOrbit)
+                .or_default(),
+            vec!["AutoTaskOrbit".to_string()],
+        );
+        self
+    }
+
     pub fn with_script_task(mut self, task_name: String) -> Self {
         self.script_task_name = task_name;
         self
@@ -51,4 +71,120 @@ impl AutoConfig {
     pub fn get_stage_tasks(&self, stage: AutoStage) -> Option<&Vec<String>> {
         self.stage_task_names.get(&stage)
     }
+
+    /// Combine two configs, concatenating stage task lists without duplicates.
+    /// Fails if both configs set a different script task. The orbit of `other` is used
+    /// if `self` has none.
+    pub fn merge(mut self, other: AutoConfig) -> Result<AutoConfig, ConfigError> {
+        if !other.script_task_name.is_empty() && other.script_task_name != self.script_task_name {
+            if !self.script_task_name.is_empty() {
+                return Err(ConfigError::ConflictingScriptTask {
+                    first: self.script_task_name,
+                    second: other.script_task_name,
+                });
+            }
+            self.script_task_name = other.script_task_name;
+        }
+
+        merge_stage_task_names(&mut self.stage_task_names, other.stage_task_names);
+        self.orbit = self.orbit.or(other.orbit);
+        Ok(self)
+    }
+
+    /// Check that the script task and every stage task is registered and that no stage
+    /// is configured empty. All problems are returned, not just the first.
+    pub fn validate(&self, registered_tasks: &[TaskInfo]) -> Result<(), Vec<ConfigError>> {
+        let registered: HashSet<&str> = registered_tasks.iter().map(|t| t.name.as_str()).collect();
+        let mut errors = Vec::new();
+
+        if !self.script_task_name.is_empty() && !registered.contains(self.script_task_name.as_str())
+        {
+            errors.push(ConfigError::UnknownTask {
+                stage: "script".to_string(),
+                task_name: self.script_task_name.clone(),
+            });
+        }
+
+        // Sort stages so errors are reported in a stable order
+        let mut stages: Vec<(&AutoStage, &Vec<String>)> = self.stage_task_names.iter().collect();
+        stages.sort_by_key(|(stage, _)| stage.to_string());
+
+        for (stage, task_names) in stages {
+            if task_names.is_empty() {
+                errors.push(ConfigError::EmptyStageConfig {
+                    stage: stage.to_string(),
+                });
+            }
+            for task_name in task_names {
+                if !registered.contains(task_name.as_str()) {
+                    errors.push(ConfigError::UnknownTask {
+                        stage: stage.to_string(),
+                        task_name: task_name.clone(),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto::tasks::{
+        auto_task_runscript::RunScriptTask, auto_task_takeoff::AutoTaskTakeoff,
+    };
+    use pubsub::task_info;
+
+    #[test]
+    fn test_merge() {
+        let takeoff = AutoConfig::new()
+            .with_script_task("RunScriptTask".to_string())
+            .with_stage_task(AutoStage::AutoTakeoff, "AutoTaskTakeoff".to_string());
+        let control = AutoConfig::new()
+            .with_stage_task(AutoStage::AutoTakeoff, "AutoTaskTakeoff".to_string())
+            .with_stage_task(AutoStage::AutoLand, "AutoTaskLand".to_string());
+
+        let merged = takeoff.merge(control).unwrap();
+        assert_eq!(merged.script_task_name, "RunScriptTask");
+        assert_eq!(
+            merged.get_stage_tasks(AutoStage::AutoTakeoff).unwrap(),
+            &vec!["AutoTaskTakeoff"]
+        );
+        assert_eq!(
+            merged.get_stage_tasks(AutoStage::AutoLand).unwrap(),
+            &vec!["AutoTaskLand"]
+        );
+
+        let other_script = AutoConfig::new().with_script_task("OtherScriptTask".to_string());
+        assert_eq!(
+            merged.merge(other_script).unwrap_err(),
+            ConfigError::ConflictingScriptTask {
+                first: "RunScriptTask".to_string(),
+                second: "OtherScriptTask".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_unknown_task() {
+        let registered = vec![task_info!(RunScriptTask), task_info!(AutoTaskTakeoff)];
+        let config = AutoConfig::new()
+            .with_script_task("RunScriptTask".to_string())
+            .with_stage_task(AutoStage::AutoTakeoff, "AutoTaskTakeof".to_string());
+
+        let errors = config.validate(&registered).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ConfigError::UnknownTask {
+                stage: "AutoTakeoff".to_string(),
+                task_name: "AutoTaskTakeof".to_string()
+            }]
+        );
+    }
 }
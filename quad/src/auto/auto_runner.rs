@@ -1,8 +1,10 @@
 pub use core::task;
 
-use log::info;
+use std::path::{Path, PathBuf};
+
+use log::{error, info, warn};
 use pubsub::{
-    subscribe,
+    subscribe, task_info,
     tasks::{
         info::TaskInfo,
         meta_control::{MetaCommand, MetaMessage},
@@ -10,12 +12,48 @@ use pubsub::{
     },
 };
 
-use super::{auto_config::AutoConfig, auto_stage::AutoStage, message::AutoStageMessage};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    auto_config::AutoConfig,
+    auto_stage::AutoStage,
+    message::{AutoStageMessage, AutoWaypointMessage},
+};
+use crate::exec::exec_config::config_errors_to_anyhow;
+
+/// Mission progress persisted to the state file so a restarted runner can resume
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AutoRunnerState {
+    pub stage: AutoStage,
+    pub last_waypoint_index: Option<usize>,
+}
+
+impl AutoRunnerState {
+    /// Load a state file, returning `None` if it does not exist
+    pub fn load(path: &Path) -> Result<Option<Self>, anyhow::Error> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    /// Atomically write the state file by writing to a `.tmp` file and renaming it
+    pub fn save(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
 
 pub struct AutoRunner {
     pub config: AutoConfig,
     pub stage: AutoStage,
+    pub last_waypoint_index: Option<usize>,
     spawned_tasks: Vec<TaskInfo>,
+    registered_tasks: Option<Vec<TaskInfo>>,
+    state_file: Option<PathBuf>,
     info: TaskInfo,
 }
 
@@ -24,8 +62,78 @@ impl AutoRunner {
         Self {
             config,
             stage: AutoStage::AutoShadow, // Start in shadow mode as per README
+            last_waypoint_index: None,
             spawned_tasks: vec![],
-            info: TaskInfo::new("AutoRunner").with_insta_spawn(),
+            registered_tasks: None,
+            state_file: None,
+            info: task_info!(AutoRunner).with_insta_spawn(),
+        }
+    }
+
+    /// Tasks registered with the runner, used to validate the config on init and to
+    /// resolve task names to spawnable task infos. Without them no task can be spawned.
+    pub fn with_registered_tasks(mut self, registered_tasks: Vec<TaskInfo>) -> Self {
+        self.registered_tasks = Some(registered_tasks);
+        self
+    }
+
+    /// Find a registered task by name. Task ids are derived from the full type path, so
+    /// an unregistered name can't be turned into an info the runner would spawn.
+    fn resolve_task_info(&self, task_name: &str) -> Result<TaskInfo, anyhow::Error> {
+        let registered_tasks = self.registered_tasks.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot spawn task '{}': no tasks registered, see with_registered_tasks",
+                task_name
+            )
+        })?;
+        registered_tasks
+            .iter()
+            .find(|task| task.name == task_name)
+            .map(|task| task.clone().with_insta_spawn())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Task '{}' is not registered with the runner", task_name)
+            })
+    }
+
+    /// Persist the mission stage to `path` and resume from it if the file already exists
+    pub fn with_state_file(mut self, path: PathBuf) -> Self {
+        match AutoRunnerState::load(&path) {
+            Ok(Some(state)) => {
+                info!(
+                    "Resuming auto mission from {:?} in stage {}",
+                    path, state.stage
+                );
+                self.stage = state.stage;
+                self.last_waypoint_index = state.last_waypoint_index;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load auto state file {:?}: {}", path, e),
+        }
+        self.state_file = Some(path);
+        self
+    }
+
+    /// Write the current progress to the state file, or remove it once back in shadow mode
+    fn persist_state(&self) {
+        let Some(path) = &self.state_file else {
+            return;
+        };
+
+        let result = if self.stage == AutoStage::AutoShadow {
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        } else {
+            AutoRunnerState {
+                stage: self.stage,
+                last_waypoint_index: self.last_waypoint_index,
+            }
+            .save(path)
+        };
+
+        if let Err(e) = result {
+            error!("Failed to update auto state file {:?}: {}", path, e);
         }
     }
 }
@@ -36,11 +144,18 @@ impl Task for AutoRunner {
         tx: pubsub::tasks::task::TaskChannel,
         meta_tx: pubsub::tasks::task::MetaTaskChannel,
     ) -> Result<(), anyhow::Error> {
+        if let Some(registered_tasks) = &self.registered_tasks {
+            if let Err(errors) = self.config.validate(registered_tasks) {
+                return Err(config_errors_to_anyhow("Invalid auto config", &errors));
+            }
+        }
+
         // No default tasks to spawn in auto mode
         // We start in shadow mode and wait for external command
 
         // Subscribe to auto/stage
         tx.send(subscribe!("auto/stage"))?;
+        tx.send(subscribe!("auto/waypoint_reached"))?;
 
         Ok(())
     }
@@ -56,18 +171,35 @@ impl Task for AutoRunner {
         meta_tx: pubsub::tasks::task::MetaTaskChannel,
     ) -> Result<(), anyhow::Error> {
         // Check for any new auto/stage messages
+        let mut progress_changed = false;
         for record in &inputs {
             if let Ok(topic) = record.try_get_topic() {
                 if topic.starts_with("auto/stage") {
                     let stage: Vec<AutoStageMessage> = record.to_serde().unwrap();
                     for s in stage {
                         info!("Received auto/stage update: {}", s.stage);
+                        progress_changed |= self.stage != s.stage;
                         self.stage = s.stage;
                     }
+                } else if topic.starts_with("auto/waypoint_reached") {
+                    let waypoints: Vec<AutoWaypointMessage> = record.to_serde()?;
+                    for waypoint in waypoints {
+                        info!("Waypoint {} reached", waypoint.index);
+                        progress_changed |= self.last_waypoint_index != Some(waypoint.index);
+                        self.last_waypoint_index = Some(waypoint.index);
+                    }
                 }
             }
         }
 
+        if progress_changed {
+            if self.stage == AutoStage::AutoShadow {
+                // Mission complete, nothing left to resume
+                self.last_waypoint_index = None;
+            }
+            self.persist_state();
+        }
+
         // Depending on the current stage:
         // - Get the task desired for said stage via config
         // - If the task is not in the spawned_tasks list, spawn it
@@ -114,7 +246,7 @@ impl Task for AutoRunner {
         // Spawn new tasks
         for task_name in tasks_to_spawn {
             info!("Spawning task for stage {}: {}", self.stage, task_name);
-            let task_config = TaskInfo::new(task_name).with_insta_spawn();
+            let task_config = self.resolve_task_info(&task_name)?;
             let new_task_packet = MetaMessage::new(MetaCommand::SpawnTask, task_config.clone());
             meta_tx.send(new_task_packet)?;
             self.spawned_tasks.push(task_config);
@@ -150,3 +282,81 @@ impl Task for AutoRunner {
         &self.info
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto::tasks::auto_task_takeoff::AutoTaskTakeoff;
+    use pubsub::publish;
+    use std::sync::mpsc;
+
+    fn temp_state_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_resume_from_state_file() {
+        let path = temp_state_file("auto_runner_resume");
+        AutoRunnerState {
+            stage: AutoStage::AutoTakeoff,
+            last_waypoint_index: Some(3),
+        }
+        .save(&path)
+        .unwrap();
+
+        let runner = AutoRunner::new(AutoConfig::new()).with_state_file(path.clone());
+        assert_eq!(runner.stage, AutoStage::AutoTakeoff);
+        assert_eq!(runner.last_waypoint_index, Some(3));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_state_file_follows_stage_transitions() {
+        let path = temp_state_file("auto_runner_transitions");
+        let _ = std::fs::remove_file(&path);
+
+        let mut runner = AutoRunner::new(AutoConfig::new()).with_state_file(path.clone());
+        assert_eq!(runner.stage, AutoStage::AutoShadow);
+
+        let (tx, _rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+        let stage_update = publish!("auto/stage", &AutoStageMessage::new(AutoStage::AutoHover));
+        runner
+            .run(vec![stage_update], tx.clone(), meta_tx.clone())
+            .unwrap();
+
+        let saved = AutoRunnerState::load(&path).unwrap().unwrap();
+        assert_eq!(saved.stage, AutoStage::AutoHover);
+
+        // Returning to shadow mode completes the mission and removes the file
+        let stage_update = publish!("auto/stage", &AutoStageMessage::new(AutoStage::AutoShadow));
+        runner.run(vec![stage_update], tx, meta_tx).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_stage_tasks_resolve_through_registered_tasks() {
+        let config =
+            || AutoConfig::new().with_stage_task(AutoStage::AutoTakeoff, "AutoTaskTakeoff".into());
+        let (tx, _rx) = mpsc::channel();
+        let (meta_tx, meta_rx) = mpsc::channel();
+        let stage_update =
+            || publish!("auto/stage", &AutoStageMessage::new(AutoStage::AutoTakeoff));
+
+        // Without registered tasks the name can't be mapped to a spawnable task id
+        let mut runner = AutoRunner::new(config());
+        let err = runner
+            .run(vec![stage_update()], tx.clone(), meta_tx.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("AutoTaskTakeoff"));
+        assert!(meta_rx.try_recv().is_err());
+
+        let registered = task_info!(AutoTaskTakeoff);
+        let mut runner = AutoRunner::new(config()).with_registered_tasks(vec![registered.clone()]);
+        runner.run(vec![stage_update()], tx, meta_tx).unwrap();
+        let spawn = meta_rx.try_recv().unwrap();
+        assert!(matches!(spawn.command, MetaCommand::SpawnTask));
+        assert_eq!(spawn.task_info.id, registered.id);
+    }
+}
@@ -22,6 +22,14 @@ pub enum AutoStage {
     /// Guided stage, continuously sends position guidance commands to Ardupilot and listens for land command.
     AutoGuided,
 
+    /// Waypoint stage, flies the loaded GPS mission in guided mode and publishes
+    /// auto/mission_complete after the last waypoint.
+    AutoWaypoint,
+
+    /// Orbit stage, circles a GPS point in guided mode and, after a set number of laps,
+    /// publishes auto/orbit_complete and promotes to AutoHover.
+    AutoOrbit,
+
     /// Land stage, sends landing command to Ardupilot and monitors descent.
     AutoLand,
 }
@@ -12,3 +12,53 @@ impl AutoStageMessage {
         Self { stage }
     }
 }
+
+/// Published on `auto/waypoint_reached` when a mission waypoint has been completed
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AutoWaypointMessage {
+    pub index: usize,
+}
+
+impl AutoWaypointMessage {
+    pub fn new(index: usize) -> Self {
+        Self { index }
+    }
+}
+
+/// Published on `auto/mission_complete` once every waypoint of a mission was reached
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AutoMissionCompleteMessage {
+    pub waypoints: usize,
+}
+
+impl AutoMissionCompleteMessage {
+    pub fn new(waypoints: usize) -> Self {
+        Self { waypoints }
+    }
+}
+
+/// Published on `auto/orbit_complete` once an orbit flew all of its laps
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AutoOrbitCompleteMessage {
+    pub laps: u32,
+}
+
+impl AutoOrbitCompleteMessage {
+    pub fn new(laps: u32) -> Self {
+        Self { laps }
+    }
+}
+
+/// Local NED position (meters) published on `auto/waypoint` and `auto/waypoint_resume`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct AutoWaypointTarget {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl AutoWaypointTarget {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+}
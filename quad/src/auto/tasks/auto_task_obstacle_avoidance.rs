@@ -0,0 +1,347 @@
+// Watches proximity sensor data while guided
+// Publishes an evasive waypoint when an obstacle is ahead, and resumes the original one once clear
+
+use log::{debug, info, warn};
+use mavlink::ardupilotmega::{ATTITUDE_DATA, LOCAL_POSITION_NED_DATA, OBSTACLE_DISTANCE_DATA};
+use pubsub::{
+    publish, subscribe, task_info,
+    tasks::{configurable::Configurable, info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auto::message::AutoWaypointTarget;
+
+/// Obstacles within this many degrees either side of the heading are "ahead"
+const AHEAD_HALF_ANGLE_DEG: f32 = 45.0;
+
+/// JSON config for `AutoTaskObstacleAvoidance`, missing fields use the defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObstacleAvoidanceConfig {
+    /// Obstacles closer than this (meters) trigger an evasion
+    pub min_clearance_m: f32,
+    /// Distance (meters) from the current position to the evasive waypoint
+    pub reaction_distance_m: f32,
+    /// Angle (degrees) between the heading and the evasion direction
+    pub evasion_angle_deg: f32,
+}
+
+impl Default for ObstacleAvoidanceConfig {
+    fn default() -> Self {
+        Self {
+            min_clearance_m: 3.0,
+            reaction_distance_m: 5.0,
+            evasion_angle_deg: 90.0,
+        }
+    }
+}
+
+/// Wrap an angle in degrees to [-180, 180)
+fn wrap_degrees(angle: f32) -> f32 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Find the closest obstacle within ±45° of `heading_deg` that is nearer than `min_clearance_m`.
+/// Returns its bearing relative to the heading (degrees, positive is right) and distance (meters).
+pub fn closest_obstacle_ahead(
+    data: &OBSTACLE_DISTANCE_DATA,
+    heading_deg: f32,
+    min_clearance_m: f32,
+) -> Option<(f32, f32)> {
+    // mavlink 0.13 does not decode the MAVLink 2 extensions (`increment_f`, `angle_offset`,
+    // `frame`), so sectors use their defaults: whole degrees, north aligned, starting north
+    let increment = data.increment as f32;
+
+    data.distances
+        .iter()
+        .enumerate()
+        .filter(|(_, &distance_cm)| {
+            // 0 is invalid, u16::MAX is unknown and max_distance + 1 is "nothing seen"
+            distance_cm != 0 && distance_cm != u16::MAX && distance_cm <= data.max_distance
+        })
+        .map(|(sector, &distance_cm)| {
+            let angle = sector as f32 * increment;
+            (wrap_degrees(angle - heading_deg), distance_cm as f32 / 100.0)
+        })
+        .filter(|(bearing, distance_m)| {
+            bearing.abs() <= AHEAD_HALF_ANGLE_DEG && *distance_m < min_clearance_m
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Task that steers around obstacles reported by `OBSTACLE_DISTANCE`
+pub struct AutoTaskObstacleAvoidance {
+    info: TaskInfo,
+    config: ObstacleAvoidanceConfig,
+    heading_deg: f32,
+    position: Option<LOCAL_POSITION_NED_DATA>,
+    /// Last waypoint published by another task, resumed once clear
+    original_waypoint: Option<AutoWaypointTarget>,
+    /// Waypoint we are evading to, None when not evading
+    evasion_waypoint: Option<AutoWaypointTarget>,
+    /// Where the evasion started, resumed if no original waypoint is known
+    evasion_start: Option<AutoWaypointTarget>,
+}
+
+impl AutoTaskObstacleAvoidance {
+    pub fn new() -> Self {
+        Self {
+            info: task_info!(AutoTaskObstacleAvoidance),
+            config: ObstacleAvoidanceConfig::default(),
+            heading_deg: 0.0,
+            position: None,
+            original_waypoint: None,
+            evasion_waypoint: None,
+            evasion_start: None,
+        }
+    }
+
+    pub fn config(&self) -> &ObstacleAvoidanceConfig {
+        &self.config
+    }
+
+    pub fn is_evading(&self) -> bool {
+        self.evasion_waypoint.is_some()
+    }
+
+    /// Waypoint `reaction_distance_m` away, turned away from the obstacle, at the current altitude
+    fn build_evasion_waypoint(
+        &self,
+        position: &LOCAL_POSITION_NED_DATA,
+        obstacle_bearing_deg: f32,
+    ) -> AutoWaypointTarget {
+        // Obstacle on the right (or dead ahead) turns left, otherwise right
+        let turn = if obstacle_bearing_deg >= 0.0 {
+            -self.config.evasion_angle_deg
+        } else {
+            self.config.evasion_angle_deg
+        };
+        let direction = (self.heading_deg + turn).to_radians();
+        AutoWaypointTarget::new(
+            position.x + self.config.reaction_distance_m * direction.cos(),
+            position.y + self.config.reaction_distance_m * direction.sin(),
+            position.z,
+        )
+    }
+}
+
+impl Configurable for AutoTaskObstacleAvoidance {
+    type Config = ObstacleAvoidanceConfig;
+
+    fn new_from_config(config: Self::Config) -> Result<Self, anyhow::Error> {
+        if config.min_clearance_m <= 0.0 || config.reaction_distance_m <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Clearance and reaction distance must be positive, got {} and {}",
+                config.min_clearance_m,
+                config.reaction_distance_m
+            ));
+        }
+
+        let mut task = Self::new();
+        task.config = config;
+        Ok(task)
+    }
+}
+
+impl Task for AutoTaskObstacleAvoidance {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!("AutoTaskObstacleAvoidance initialized");
+        self.original_waypoint = None;
+        self.evasion_waypoint = None;
+        self.evasion_start = None;
+
+        tx.send(subscribe!("mavlink/obstacle_distance"))?;
+        tx.send(subscribe!("mavlink/attitude"))?;
+        tx.send(subscribe!("mavlink/local_position_ned"))?;
+        // Also matches `auto/waypoint_resume`, which is skipped below
+        tx.send(subscribe!("auto/waypoint"))?;
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            let topic = record.try_get_topic()?;
+            match topic.as_str() {
+                "mavlink/attitude" => {
+                    let attitudes: Vec<ATTITUDE_DATA> = record.to_serde().unwrap_or_default();
+                    if let Some(attitude) = attitudes.last() {
+                        self.heading_deg = attitude.yaw.to_degrees();
+                    }
+                }
+                "mavlink/local_position_ned" => {
+                    let positions: Vec<LOCAL_POSITION_NED_DATA> =
+                        record.to_serde().unwrap_or_default();
+                    if let Some(position) = positions.last() {
+                        self.position = Some(position.clone());
+                    }
+                }
+                "auto/waypoint" => {
+                    let waypoints: Vec<AutoWaypointTarget> = record.to_serde().unwrap_or_default();
+                    // Our own evasive waypoints come back on the same topic
+                    for waypoint in waypoints {
+                        if Some(waypoint) != self.evasion_waypoint {
+                            self.original_waypoint = Some(waypoint);
+                        }
+                    }
+                }
+                "mavlink/obstacle_distance" => {
+                    let readings: Vec<OBSTACLE_DISTANCE_DATA> =
+                        record.to_serde().unwrap_or_default();
+                    for reading in &readings {
+                        let obstacle = closest_obstacle_ahead(
+                            reading,
+                            self.heading_deg,
+                            self.config.min_clearance_m,
+                        );
+
+                        match (obstacle, self.is_evading()) {
+                            (Some((bearing, distance)), false) => {
+                                let Some(position) = &self.position else {
+                                    warn!("Obstacle {:.1} m ahead but position unknown", distance);
+                                    continue;
+                                };
+                                let waypoint = self.build_evasion_waypoint(position, bearing);
+                                warn!(
+                                    "Obstacle {:.1} m ahead at {:.0} deg, evading to {:?}",
+                                    distance, bearing, waypoint
+                                );
+                                self.evasion_start = Some(AutoWaypointTarget::new(
+                                    position.x, position.y, position.z,
+                                ));
+                                self.evasion_waypoint = Some(waypoint);
+                                tx.send(publish!("auto/waypoint", &waypoint))?;
+                            }
+                            (None, true) => {
+                                let resume = self.original_waypoint.or(self.evasion_start);
+                                self.evasion_waypoint = None;
+                                self.evasion_start = None;
+                                if let Some(resume) = resume {
+                                    info!("Obstacle cleared, resuming {:?}", resume);
+                                    tx.send(publish!("auto/waypoint_resume", &resume))?;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("AutoTaskObstacleAvoidance cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// 72 sectors of 5 degrees starting north, nothing seen except `obstacles`
+    fn obstacle_data(obstacles: &[(usize, u16)]) -> OBSTACLE_DISTANCE_DATA {
+        let mut distances = [1001u16; 72];
+        for &(sector, distance_cm) in obstacles {
+            distances[sector] = distance_cm;
+        }
+        OBSTACLE_DISTANCE_DATA {
+            distances,
+            increment: 5,
+            min_distance: 20,
+            max_distance: 1000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_closest_obstacle_ahead() {
+        // 10 degrees right at 2 m, 20 degrees left (sector 68) at 1.5 m
+        let data = obstacle_data(&[(2, 200), (68, 150)]);
+        assert_eq!(closest_obstacle_ahead(&data, 0.0, 3.0), Some((-20.0, 1.5)));
+
+        // Behind the vehicle (180 degrees) and outside the clearance are ignored
+        let data = obstacle_data(&[(36, 50), (1, 900)]);
+        assert_eq!(closest_obstacle_ahead(&data, 0.0, 3.0), None);
+
+        // Unknown readings are ignored
+        let data = obstacle_data(&[(0, u16::MAX), (1, 0)]);
+        assert_eq!(closest_obstacle_ahead(&data, 0.0, 3.0), None);
+
+        // Sectors are north aligned and compared to the heading
+        let data = obstacle_data(&[(18, 100)]);
+        assert_eq!(closest_obstacle_ahead(&data, 0.0, 3.0), None);
+        assert_eq!(closest_obstacle_ahead(&data, 80.0, 3.0), Some((10.0, 1.0)));
+    }
+
+    #[test]
+    fn test_evades_and_resumes() {
+        let mut task = AutoTaskObstacleAvoidance::new();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        let position = LOCAL_POSITION_NED_DATA {
+            x: 10.0,
+            y: 0.0,
+            z: -5.0,
+            ..Default::default()
+        };
+        let original = AutoWaypointTarget::new(50.0, 0.0, -5.0);
+        task.run(
+            vec![
+                publish!("mavlink/local_position_ned", &position),
+                publish!("auto/waypoint", &original),
+                publish!("mavlink/obstacle_distance", &obstacle_data(&[(1, 200)])),
+            ],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert!(task.is_evading());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].try_get_topic().unwrap(), "auto/waypoint");
+        // Heading north with the obstacle on the right, evade 5 m to the west
+        let evasion = sent[0].to_serde::<AutoWaypointTarget>().unwrap()[0];
+        assert!((evasion.x - 10.0).abs() < 1e-4);
+        assert!((evasion.y + 5.0).abs() < 1e-4);
+        assert_eq!(evasion.z, -5.0);
+
+        task.run(
+            vec![
+                publish!("auto/waypoint", &evasion),
+                publish!("mavlink/obstacle_distance", &obstacle_data(&[])),
+            ],
+            tx,
+            meta_tx,
+        )
+        .unwrap();
+        assert!(!task.is_evading());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].try_get_topic().unwrap(), "auto/waypoint_resume");
+        assert_eq!(
+            sent[0].to_serde::<AutoWaypointTarget>().unwrap(),
+            vec![original]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+// Circles a GPS point in guided mode
+// Publishes auto/orbit_complete and promotes to AutoHover after the requested laps
+
+use log::{debug, info};
+use mavlink::ardupilotmega::{
+    MavFrame, MavMessage, PositionTargetTypemask, GLOBAL_POSITION_INT_DATA,
+    SET_POSITION_TARGET_GLOBAL_INT_DATA,
+};
+use pubsub::{
+    publish, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auto::auto_config::AutoConfig;
+use crate::auto::auto_stage::AutoStage;
+use crate::auto::message::{AutoOrbitCompleteMessage, AutoStageMessage};
+
+/// Distance in meters within which the current point on the circle counts as reached
+pub const ORBIT_ACCEPTANCE_RADIUS_M: f32 = 3.0;
+
+/// Points the circle is split into, the vehicle is sent from one to the next
+pub const ORBIT_POINTS_PER_LAP: u32 = 12;
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// A GPS position, altitude relative to home
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GlobalPosition {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_m: f32,
+}
+
+impl GlobalPosition {
+    pub fn new(lat: f64, lon: f64, alt_m: f32) -> Self {
+        Self { lat, lon, alt_m }
+    }
+
+    fn from_position_int(position: &GLOBAL_POSITION_INT_DATA) -> Self {
+        Self::new(
+            position.lat as f64 / 1e7,
+            position.lon as f64 / 1e7,
+            position.relative_alt as f32 / 1000.0,
+        )
+    }
+
+    /// Great circle distance in meters, ignoring the altitude
+    pub fn haversine_distance_m(&self, other: &GlobalPosition) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+    }
+
+    /// Initial bearing towards `other` in degrees clockwise from north, in [0, 360)
+    pub fn bearing_deg(&self, other: &GlobalPosition) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lon = (other.lon - self.lon).to_radians();
+        let y = d_lon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Position `distance_m` away along `bearing_deg`, at the same altitude
+    pub fn destination(&self, bearing_deg: f64, distance_m: f64) -> GlobalPosition {
+        let lat1 = self.lat.to_radians();
+        let bearing = bearing_deg.to_radians();
+        let delta = distance_m / EARTH_RADIUS_M;
+
+        let lat2 = (lat1.sin() * delta.cos() + lat1.cos() * delta.sin() * bearing.cos()).asin();
+        let lon2 = self.lon.to_radians()
+            + (bearing.sin() * delta.sin() * lat1.cos())
+                .atan2(delta.cos() - lat1.sin() * lat2.sin());
+        GlobalPosition::new(lat2.to_degrees(), lon2.to_degrees(), self.alt_m)
+    }
+}
+
+/// Circle flown by `AutoTaskOrbit`, at the altitude of `center`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Orbit {
+    pub center: GlobalPosition,
+    pub radius_m: f32,
+    pub speed_m_s: f32,
+    /// Direction seen from above
+    pub clockwise: bool,
+    /// Laps before `auto/orbit_complete`, orbits until the stage changes when None
+    pub laps: Option<u32>,
+}
+
+/// Task that flies around `orbit.center` by commanding the next point on the circle every time
+/// the vehicle is within `ORBIT_ACCEPTANCE_RADIUS_M` of the current one.
+/// The vehicle joins the circle at the point closest to where it is when the task starts.
+pub struct AutoTaskOrbit {
+    info: TaskInfo,
+    orbit: Orbit,
+    /// Bearing from the center of the point currently commanded, None before the first position
+    target_bearing_deg: Option<f64>,
+    /// Points of the circle reached so far, including the one the vehicle joined at
+    points_reached: u32,
+    complete: bool,
+}
+
+impl AutoTaskOrbit {
+    pub fn new(orbit: Orbit) -> Self {
+        Self {
+            info: task_info!(AutoTaskOrbit),
+            orbit,
+            target_bearing_deg: None,
+            points_reached: 0,
+            complete: false,
+        }
+    }
+
+    /// Use the orbit of an auto config, `None` if it has none
+    pub fn from_config(config: &AutoConfig) -> Option<Self> {
+        config.orbit.map(Self::new)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Completed laps, not counting the way to the circle
+    pub fn laps_completed(&self) -> u32 {
+        self.points_reached.saturating_sub(1) / ORBIT_POINTS_PER_LAP
+    }
+
+    fn step_deg(&self) -> f64 {
+        let step = 360.0 / ORBIT_POINTS_PER_LAP as f64;
+        if self.orbit.clockwise {
+            step
+        } else {
+            -step
+        }
+    }
+
+    /// Position target for the point at `bearing_deg` on the circle, with the velocity along
+    /// the circle at that point as feed forward
+    fn build_target_message(&self, bearing_deg: f64) -> MavMessage {
+        let target = self
+            .orbit
+            .center
+            .destination(bearing_deg, self.orbit.radius_m as f64);
+        let heading = (bearing_deg + self.step_deg().signum() * 90.0).to_radians();
+
+        let type_mask = PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+
+        MavMessage::SET_POSITION_TARGET_GLOBAL_INT(SET_POSITION_TARGET_GLOBAL_INT_DATA {
+            lat_int: (target.lat * 1e7) as i32,
+            lon_int: (target.lon * 1e7) as i32,
+            alt: target.alt_m,
+            vx: self.orbit.speed_m_s * heading.cos() as f32,
+            vy: self.orbit.speed_m_s * heading.sin() as f32,
+            vz: 0.0,
+            type_mask,
+            coordinate_frame: MavFrame::MAV_FRAME_GLOBAL_RELATIVE_ALT_INT,
+            target_system: 0,
+            target_component: 0,
+            ..Default::default()
+        })
+    }
+
+    fn command_target(
+        &self,
+        bearing_deg: f64,
+        tx: &pubsub::tasks::task::TaskChannel,
+    ) -> anyhow::Result<()> {
+        debug!("Commanding orbit point at {:.0} deg", bearing_deg);
+        tx.send(publish!(
+            "mavlink/send/set_position_target_global_int",
+            &self.build_target_message(bearing_deg)
+        ))?;
+        Ok(())
+    }
+
+    fn check_progress(
+        &mut self,
+        position: &GlobalPosition,
+        tx: &pubsub::tasks::task::TaskChannel,
+    ) -> anyhow::Result<()> {
+        let center = self.orbit.center;
+        let Some(bearing_deg) = self.target_bearing_deg else {
+            // Join the circle at the point closest to the vehicle
+            let bearing_deg = center.bearing_deg(position);
+            info!(
+                "Starting {:.0} m orbit around {:.7}, {:.7}",
+                self.orbit.radius_m, center.lat, center.lon
+            );
+            self.command_target(bearing_deg, tx)?;
+            self.target_bearing_deg = Some(bearing_deg);
+            return Ok(());
+        };
+
+        let target = center.destination(bearing_deg, self.orbit.radius_m as f64);
+        let horizontal = target.haversine_distance_m(position);
+        let distance = horizontal.hypot((position.alt_m - target.alt_m) as f64) as f32;
+        if distance > ORBIT_ACCEPTANCE_RADIUS_M {
+            return Ok(());
+        }
+
+        self.points_reached += 1;
+        if let Some(laps) = self.orbit.laps {
+            // The joining point is reached again after each lap
+            if self.points_reached > laps * ORBIT_POINTS_PER_LAP {
+                info!("Orbit complete after {} laps", laps);
+                tx.send(publish!(
+                    "auto/orbit_complete",
+                    &AutoOrbitCompleteMessage::new(laps)
+                ))?;
+                tx.send(publish!(
+                    "auto/stage",
+                    &AutoStageMessage::new(AutoStage::AutoHover)
+                ))?;
+                self.complete = true;
+                return Ok(());
+            }
+        }
+
+        let next_bearing_deg = (bearing_deg + self.step_deg()).rem_euclid(360.0);
+        self.command_target(next_bearing_deg, tx)?;
+        self.target_bearing_deg = Some(next_bearing_deg);
+        Ok(())
+    }
+}
+
+impl Task for AutoTaskOrbit {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!(
+            "AutoTaskOrbit initialized ({:.0} m radius, {:.1} m/s, {})",
+            self.orbit.radius_m,
+            self.orbit.speed_m_s,
+            if self.orbit.clockwise {
+                "clockwise"
+            } else {
+                "counterclockwise"
+            }
+        );
+        self.target_bearing_deg = None;
+        self.points_reached = 0;
+        self.complete = false;
+
+        tx.send(subscribe!("mavlink/global_position_int"))?;
+
+        Ok(())
+    }
+
+    fn should_run(&self) -> Result<bool, anyhow::Error> {
+        Ok(!self.complete)
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        let mut position = None;
+        for record in &inputs {
+            if record.try_get_topic().ok().as_deref() != Some("mavlink/global_position_int") {
+                continue;
+            }
+            let positions: Vec<GLOBAL_POSITION_INT_DATA> = record.to_serde().unwrap_or_default();
+            position = positions.last().cloned().or(position);
+        }
+
+        if let Some(position) = position {
+            self.check_progress(&GlobalPosition::from_position_int(&position), &tx)?;
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("AutoTaskOrbit cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn position_record(position: &GlobalPosition) -> pubsub::message::record::Record {
+        let position = GLOBAL_POSITION_INT_DATA {
+            lat: (position.lat * 1e7).round() as i32,
+            lon: (position.lon * 1e7).round() as i32,
+            relative_alt: (position.alt_m * 1000.0) as i32,
+            ..Default::default()
+        };
+        publish!("mavlink/global_position_int", &position)
+    }
+
+    fn commanded_position(record: &pubsub::message::record::Record) -> GlobalPosition {
+        let MavMessage::SET_POSITION_TARGET_GLOBAL_INT(target) =
+            &record.to_serde::<MavMessage>().unwrap()[0]
+        else {
+            panic!("Expected a global position target");
+        };
+        GlobalPosition::new(
+            target.lat_int as f64 / 1e7,
+            target.lon_int as f64 / 1e7,
+            target.alt,
+        )
+    }
+
+    #[test]
+    fn test_haversine_helpers() {
+        let center = GlobalPosition::new(47.397742, 8.545594, 10.0);
+        let east = center.destination(90.0, 100.0);
+        assert!((center.haversine_distance_m(&east) - 100.0).abs() < 0.01);
+        assert!((center.bearing_deg(&east) - 90.0).abs() < 0.01);
+        assert!(east.lon > center.lon);
+
+        let north = center.destination(0.0, 111.2);
+        assert!((north.lat - 47.398742).abs() < 1e-5);
+        assert!(center.bearing_deg(&north) < 0.01);
+    }
+
+    #[test]
+    fn test_orbit_laps_complete() {
+        let center = GlobalPosition::new(47.397742, 8.545594, 10.0);
+        let orbit = Orbit {
+            center,
+            radius_m: 20.0,
+            speed_m_s: 5.0,
+            clockwise: true,
+            laps: Some(1),
+        };
+        let config = AutoConfig::new().with_orbit(orbit);
+        assert_eq!(
+            config.get_stage_tasks(AutoStage::AutoOrbit).unwrap(),
+            &vec!["AutoTaskOrbit"]
+        );
+        let mut task = AutoTaskOrbit::from_config(&config).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        // Start 50 m north of the center, the vehicle joins the circle there
+        task.run(
+            vec![position_record(&center.destination(0.0, 50.0))],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+
+        let mut targets = Vec::new();
+        let mut sent = Vec::new();
+        while !task.is_complete() && targets.len() < 20 {
+            let new: Vec<_> = rx.try_iter().collect();
+            let Some(last) = new.last() else { break };
+            let target = commanded_position(last);
+            targets.push(target);
+            sent.extend(new);
+            task.run(vec![position_record(&target)], tx.clone(), meta_tx.clone())
+                .unwrap();
+        }
+        sent.extend(rx.try_iter());
+
+        // The joining point, 12 points around the circle and back to the joining point
+        assert_eq!(targets.len(), ORBIT_POINTS_PER_LAP as usize + 1);
+        assert!(task.is_complete());
+        assert_eq!(task.laps_completed(), 1);
+        for target in &targets {
+            assert!((center.haversine_distance_m(target) - 20.0).abs() < 0.1);
+            assert_eq!(target.alt_m, 10.0);
+        }
+        let join_bearing = center.bearing_deg(&targets[0]);
+        assert!(!(0.1..359.9).contains(&join_bearing));
+        // Clockwise seen from above, so the second point is east of north
+        assert!((center.bearing_deg(&targets[1]) - 30.0).abs() < 0.1);
+
+        let topics: Vec<String> = sent
+            .iter()
+            .map(|r| r.try_get_topic().unwrap())
+            .skip(targets.len())
+            .collect();
+        assert_eq!(topics, vec!["auto/orbit_complete", "auto/stage"]);
+        let stage: Vec<AutoStageMessage> = sent.last().unwrap().to_serde().unwrap();
+        assert_eq!(stage[0].stage, AutoStage::AutoHover);
+    }
+}
@@ -1,14 +1,55 @@
 use anyhow::{Context, Result};
 use log::info;
+use pubsub::task_info;
 use pubsub::tasks::info::TaskInfo;
 use pubsub::tasks::task::Task;
+use serde::Deserialize;
 use serde_json::Value;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-/// A task that reads a JSON file containing an array of [time, topic, message] entries
+/// File format of a script
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptFormat {
+    /// Array of `[time, topic, message]` arrays, the message being a JSON string
+    Json,
+    /// List of `[time, topic, message]` entries or `{at, topic, payload}` maps,
+    /// the message or payload being a YAML value or a JSON string
+    Yaml,
+}
+
+impl ScriptFormat {
+    /// Format of a script file from its extension, `.json`, `.yaml` or `.yml`
+    pub fn from_path(file_path: &Path) -> Result<Self> {
+        let extension = file_path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("json") => Ok(ScriptFormat::Json),
+            Some("yaml") | Some("yml") => Ok(ScriptFormat::Yaml),
+            _ => Err(anyhow::anyhow!(
+                "Unknown script format of {:?}, expected a .json, .yaml or .yml file",
+                file_path
+            )),
+        }
+    }
+}
+
+/// An entry of a YAML script, in the positional or the named form
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum YamlScriptEntry {
+    Positional(f64, String, serde_yaml::Value),
+    Named {
+        at: f64,
+        topic: String,
+        payload: serde_yaml::Value,
+    },
+}
+
+/// A task that reads a script file containing `[time, topic, message]` entries
 /// and publishes each message to the specified topic at the specified time.
 pub struct RunScriptTask {
     file_path: PathBuf,
@@ -24,57 +65,115 @@ pub fn get_example_script_json() -> String {
   [0.0, "auto/command", "{"height": 5.0}"],
   [5.0, "auto/position", "{x: 1.0, y: 0.0, z: 2.0}"],
   [10.0, "auto/position", "{x: 0.0, y: 1.0, z: 2.5}"],
-  [15.0, "auto/command", "{land: true}"]
+  [15.0, "auto/command", "{land: true}"],
+  [20.0, "auto/mission/waypoints", "[{"lat": 47.397742, "lon": 8.545594, "alt_m": 10.0, "loiter_s": 2.0}]"]
 ]"#
     .to_string()
 }
 
-impl RunScriptTask {
-    /// Creates a new RunScriptTask with the specified JSON file path.
-    pub fn new(file_path: PathBuf) -> Result<Self> {
-        let mut file = File::open(&file_path)
-            .with_context(|| format!("Failed to open script file: {:?}", file_path))?;
-
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)
-            .with_context(|| format!("Failed to read script file: {:?}", file_path))?;
-
-        let json_array: Vec<Value> =
-            serde_json::from_str(&contents).with_context(|| "Failed to parse JSON array")?;
-
-        let mut entries = Vec::new();
-        for entry in json_array {
-            if let Value::Array(arr) = entry {
-                if arr.len() >= 3 {
-                    let time = arr[0].as_f64().context("Time must be a number")?;
-                    let topic = arr[1]
-                        .as_str()
-                        .context("Topic must be a string")?
-                        .to_string();
-                    let message = arr[2]
-                        .as_str()
-                        .context("Message must be a string")?
-                        .to_string();
-                    entries.push((time, topic, message));
-                } else {
-                    return Err(anyhow::anyhow!(
-                        "Each entry must have at least 3 elements: [time, topic, message]"
-                    ));
-                }
+/// Reads the `[time, topic, message]` entries of a script file, sorted by time.
+/// The format is detected from the file extension, files without a known one are read as JSON.
+pub fn load_script_entries(file_path: &Path) -> Result<Vec<(f64, String, String)>> {
+    let format = ScriptFormat::from_path(file_path).unwrap_or(ScriptFormat::Json);
+    load_script_entries_with_format(file_path, format)
+}
+
+/// Reads the `[time, topic, message]` entries of a script file in `format`, sorted by time
+pub fn load_script_entries_with_format(
+    file_path: &Path,
+    format: ScriptFormat,
+) -> Result<Vec<(f64, String, String)>> {
+    let mut file = File::open(file_path)
+        .with_context(|| format!("Failed to open script file: {:?}", file_path))?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .with_context(|| format!("Failed to read script file: {:?}", file_path))?;
+
+    let mut entries = match format {
+        ScriptFormat::Json => parse_json_script(&contents)?,
+        ScriptFormat::Yaml => parse_yaml_script(&contents)?,
+    };
+
+    // Sort entries by time
+    entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(entries)
+}
+
+fn parse_json_script(contents: &str) -> Result<Vec<(f64, String, String)>> {
+    let json_array: Vec<Value> =
+        serde_json::from_str(contents).with_context(|| "Failed to parse JSON array")?;
+
+    let mut entries = Vec::new();
+    for entry in json_array {
+        if let Value::Array(arr) = entry {
+            if arr.len() >= 3 {
+                let time = arr[0].as_f64().context("Time must be a number")?;
+                let topic = arr[1]
+                    .as_str()
+                    .context("Topic must be a string")?
+                    .to_string();
+                let message = arr[2]
+                    .as_str()
+                    .context("Message must be a string")?
+                    .to_string();
+                entries.push((time, topic, message));
             } else {
-                return Err(anyhow::anyhow!("Each entry must be an array"));
+                return Err(anyhow::anyhow!(
+                    "Each entry must have at least 3 elements: [time, topic, message]"
+                ));
             }
+        } else {
+            return Err(anyhow::anyhow!("Each entry must be an array"));
         }
+    }
+    Ok(entries)
+}
+
+fn parse_yaml_script(contents: &str) -> Result<Vec<(f64, String, String)>> {
+    let yaml_entries: Vec<YamlScriptEntry> = serde_yaml::from_str(contents).with_context(|| {
+        "Failed to parse YAML script, expected a list of [time, topic, message] or {at, topic, payload}"
+    })?;
+
+    yaml_entries
+        .into_iter()
+        .map(|entry| {
+            let (time, topic, payload) = match entry {
+                YamlScriptEntry::Positional(time, topic, payload) => (time, topic, payload),
+                YamlScriptEntry::Named { at, topic, payload } => (at, topic, payload),
+            };
+            // Strings are passed on like the JSON messages, structured payloads become JSON
+            let message = match payload {
+                serde_yaml::Value::String(message) => message,
+                payload => serde_json::to_string(&payload)
+                    .with_context(|| format!("Invalid payload for topic {}", topic))?,
+            };
+            Ok((time, topic, message))
+        })
+        .collect()
+}
 
-        // Sort entries by time
-        entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+impl RunScriptTask {
+    /// Creates a new RunScriptTask with the specified JSON file path.
+    pub fn new(file_path: PathBuf) -> Result<Self> {
+        Self::new_with_format(file_path, ScriptFormat::Json)
+    }
+
+    /// Creates a new RunScriptTask, detecting the script format from the file extension
+    pub fn from_path(file_path: PathBuf) -> Result<Self> {
+        let format = ScriptFormat::from_path(&file_path)?;
+        Self::new_with_format(file_path, format)
+    }
+
+    pub fn new_with_format(file_path: PathBuf, format: ScriptFormat) -> Result<Self> {
+        let entries = load_script_entries_with_format(&file_path, format)?;
 
         Ok(Self {
             file_path,
             start_time: Instant::now(),
             entries,
             current_index: 0,
-            info: TaskInfo::new("RunScriptTask"),
+            info: task_info!(RunScriptTask),
         })
     }
 }
@@ -142,3 +241,59 @@ impl Task for RunScriptTask {
         &self.info
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_format_from_path() {
+        assert_eq!(
+            ScriptFormat::from_path(Path::new("scripts/script.json")).unwrap(),
+            ScriptFormat::Json
+        );
+        assert_eq!(
+            ScriptFormat::from_path(Path::new("scripts/mission.YML")).unwrap(),
+            ScriptFormat::Yaml
+        );
+        assert!(ScriptFormat::from_path(Path::new("scripts/script.txt")).is_err());
+    }
+
+    #[test]
+    fn test_parse_yaml_script() {
+        let yaml = r#"
+# Take off, then fly the mission
+- [0.0, auto/command, '{"height": 5.0}']
+- at: 10.0
+  topic: auto/mission/waypoints
+  payload:
+    - {lat: 47.397742, lon: 8.545594, alt_m: 10.0}
+- at: 5.0
+  topic: auto/command
+  payload: {height: 5.0}
+"#;
+        let entries = parse_yaml_script(yaml).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[0],
+            (
+                0.0,
+                "auto/command".to_string(),
+                r#"{"height": 5.0}"#.to_string()
+            )
+        );
+        assert_eq!(entries[1].1, "auto/mission/waypoints");
+        let waypoints: Value = serde_json::from_str(&entries[1].2).unwrap();
+        assert_eq!(waypoints[0]["alt_m"], 10.0);
+        assert_eq!(
+            entries[2],
+            (
+                5.0,
+                "auto/command".to_string(),
+                r#"{"height":5.0}"#.to_string()
+            )
+        );
+
+        assert!(parse_yaml_script("- at: 1.0\n  topic: auto/command\n").is_err());
+    }
+}
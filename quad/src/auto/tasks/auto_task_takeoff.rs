@@ -4,8 +4,8 @@
 use log::{debug, error, info};
 use mavlink::ardupilotmega::{MavCmd, MavMessage, COMMAND_LONG_DATA};
 use pubsub::{
-    publish, subscribe,
-    tasks::{info::TaskInfo, task::Task},
+    publish, subscribe, task_info,
+    tasks::{configurable::Configurable, info::TaskInfo, task::Task},
 };
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
@@ -16,6 +16,25 @@ pub struct TakeoffMessage {
     pub height: f32,
 }
 
+/// JSON config for `AutoTaskTakeoff`, missing fields use the defaults of `AutoTaskTakeoff::new`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoTaskTakeoffConfig {
+    pub takeoff_height: f32,
+    pub retry_interval_ms: u64,
+    pub max_attempts: u32,
+}
+
+impl Default for AutoTaskTakeoffConfig {
+    fn default() -> Self {
+        Self {
+            takeoff_height: 5.0,
+            retry_interval_ms: 2000,
+            max_attempts: 5,
+        }
+    }
+}
+
 /// Task that sends takeoff command to the drone
 pub struct AutoTaskTakeoff {
     info: TaskInfo,
@@ -30,7 +49,7 @@ pub struct AutoTaskTakeoff {
 impl AutoTaskTakeoff {
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("AutoTaskTakeoff"),
+            info: task_info!(AutoTaskTakeoff),
             command_sent: false,
             last_attempt_time: Instant::now(),
             retry_interval: Duration::from_secs(2), // Retry every 2 seconds
@@ -40,6 +59,11 @@ impl AutoTaskTakeoff {
         }
     }
 
+    /// Height in meters the takeoff command is sent with
+    pub fn takeoff_height(&self) -> f32 {
+        self.takeoff_height
+    }
+
     /// Build takeoff command message
     fn build_takeoff_message(&self) -> MavMessage {
         // Create takeoff command
@@ -61,6 +85,25 @@ impl AutoTaskTakeoff {
     }
 }
 
+impl Configurable for AutoTaskTakeoff {
+    type Config = AutoTaskTakeoffConfig;
+
+    fn new_from_config(config: Self::Config) -> Result<Self, anyhow::Error> {
+        if config.takeoff_height <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Takeoff height must be positive, got {}",
+                config.takeoff_height
+            ));
+        }
+
+        let mut task = Self::new();
+        task.takeoff_height = config.takeoff_height;
+        task.retry_interval = Duration::from_millis(config.retry_interval_ms);
+        task.max_attempts = config.max_attempts;
+        Ok(task)
+    }
+}
+
 impl Task for AutoTaskTakeoff {
     fn init(
         &mut self,
@@ -144,3 +187,28 @@ impl Task for AutoTaskTakeoff {
         &self.info
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubsub::tasks::configurable::task_from_json;
+    use pubsub::tasks::runner::Runner;
+
+    #[test]
+    fn test_takeoff_from_json_config() {
+        let task =
+            task_from_json::<AutoTaskTakeoff>(r#"{"takeoff_height": 12.5, "max_attempts": 3}"#)
+                .unwrap();
+        assert_eq!(task.takeoff_height(), 12.5);
+        assert_eq!(task.max_attempts, 3);
+        assert_eq!(task.retry_interval, Duration::from_secs(2));
+
+        assert!(task_from_json::<AutoTaskTakeoff>(r#"{"takeoff_height": -1.0}"#).is_err());
+
+        let mut runner = Runner::new();
+        runner
+            .add_task_with_config::<AutoTaskTakeoff>(r#"{"takeoff_height": 12.5}"#)
+            .unwrap();
+        assert_eq!(runner.task_infos(), vec![task_info!(AutoTaskTakeoff)]);
+    }
+}
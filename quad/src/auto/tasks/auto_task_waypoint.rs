@@ -0,0 +1,354 @@
+// Flies a sequence of GPS waypoints in guided mode
+// Publishes auto/mission_complete after the last one
+
+use std::path::Path;
+use std::time::Instant;
+
+use log::{debug, info, warn};
+use mavlink::ardupilotmega::{
+    MavFrame, MavMessage, PositionTargetTypemask, GLOBAL_POSITION_INT_DATA,
+    SET_POSITION_TARGET_GLOBAL_INT_DATA,
+};
+use pubsub::{
+    publish, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+
+use super::auto_task_runscript::load_script_entries;
+use crate::auto::message::{AutoMissionCompleteMessage, AutoWaypointMessage};
+
+/// Topic a mission is loaded from, e.g. by a `RunScriptTask` script entry
+pub const WAYPOINTS_TOPIC: &str = "auto/mission/waypoints";
+
+/// Distance in meters within which a waypoint counts as reached
+pub const DEFAULT_ACCEPTANCE_RADIUS_M: f32 = 2.0;
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// A GPS waypoint, altitude relative to home
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_m: f32,
+    /// Seconds to hold within the acceptance radius before moving on
+    #[serde(default)]
+    pub loiter_s: f32,
+}
+
+impl Waypoint {
+    /// Straight line distance in meters, using an equirectangular approximation
+    /// which is accurate over the few kilometers of a mission leg
+    pub fn distance_m(&self, lat: f64, lon: f64, alt_m: f32) -> f32 {
+        let d_lat = (lat - self.lat).to_radians();
+        let d_lon = (lon - self.lon).to_radians() * ((lat + self.lat) / 2.0).to_radians().cos();
+        let horizontal = EARTH_RADIUS_M * d_lat.hypot(d_lon);
+        horizontal.hypot((alt_m - self.alt_m) as f64) as f32
+    }
+}
+
+/// Task that commands each waypoint in turn and advances once the vehicle is within
+/// `acceptance_radius_m` of it for the waypoint's loiter time
+pub struct AutoTaskWaypoint {
+    info: TaskInfo,
+    waypoints: Vec<Waypoint>,
+    current_index: usize,
+    acceptance_radius_m: f32,
+    /// Index of the waypoint last sent to the autopilot
+    commanded_index: Option<usize>,
+    /// When the vehicle entered the acceptance radius of the current waypoint
+    loiter_started: Option<Instant>,
+}
+
+impl AutoTaskWaypoint {
+    pub fn new(waypoints: Vec<Waypoint>, acceptance_radius_m: f32) -> Self {
+        Self {
+            info: task_info!(AutoTaskWaypoint),
+            waypoints,
+            current_index: 0,
+            acceptance_radius_m,
+            commanded_index: None,
+            loiter_started: None,
+        }
+    }
+
+    /// Load the waypoints of every `auto/mission/waypoints` entry of a `RunScriptTask` script
+    pub fn from_script(file_path: &Path, acceptance_radius_m: f32) -> anyhow::Result<Self> {
+        let mut waypoints = Vec::new();
+        for (_, topic, message) in load_script_entries(file_path)? {
+            if topic == WAYPOINTS_TOPIC {
+                let mission: Vec<Waypoint> = serde_json::from_str(&message)?;
+                waypoints.extend(mission);
+            }
+        }
+        Ok(Self::new(waypoints, acceptance_radius_m))
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current_index
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current_index >= self.waypoints.len()
+    }
+
+    /// Replace the mission and start again from its first waypoint
+    fn load_waypoints(&mut self, waypoints: Vec<Waypoint>) {
+        info!("Loaded mission with {} waypoints", waypoints.len());
+        self.waypoints = waypoints;
+        self.current_index = 0;
+        self.commanded_index = None;
+        self.loiter_started = None;
+    }
+
+    /// Build a global position target for a waypoint
+    fn build_target_message(waypoint: &Waypoint) -> MavMessage {
+        // Only the position fields are used, ignore velocity / acceleration / yaw
+        let type_mask = PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+
+        MavMessage::SET_POSITION_TARGET_GLOBAL_INT(SET_POSITION_TARGET_GLOBAL_INT_DATA {
+            lat_int: (waypoint.lat * 1e7) as i32,
+            lon_int: (waypoint.lon * 1e7) as i32,
+            alt: waypoint.alt_m,
+            type_mask,
+            coordinate_frame: MavFrame::MAV_FRAME_GLOBAL_RELATIVE_ALT_INT,
+            target_system: 0,
+            target_component: 0,
+            ..Default::default()
+        })
+    }
+
+    /// Send the current waypoint if it was not commanded yet
+    fn command_current(&mut self, tx: &pubsub::tasks::task::TaskChannel) -> anyhow::Result<()> {
+        if self.is_complete() || self.commanded_index == Some(self.current_index) {
+            return Ok(());
+        }
+        let waypoint = &self.waypoints[self.current_index];
+        info!(
+            "Commanding waypoint {}/{}: {:.7}, {:.7} at {} m",
+            self.current_index + 1,
+            self.waypoints.len(),
+            waypoint.lat,
+            waypoint.lon,
+            waypoint.alt_m
+        );
+        tx.send(publish!(
+            "mavlink/send/set_position_target_global_int",
+            &Self::build_target_message(waypoint)
+        ))?;
+        self.commanded_index = Some(self.current_index);
+        Ok(())
+    }
+
+    /// Advance past the current waypoint once it has been held for its loiter time
+    fn check_progress(
+        &mut self,
+        position: &GLOBAL_POSITION_INT_DATA,
+        tx: &pubsub::tasks::task::TaskChannel,
+    ) -> anyhow::Result<()> {
+        if self.is_complete() {
+            return Ok(());
+        }
+        let waypoint = self.waypoints[self.current_index];
+        let distance = waypoint.distance_m(
+            position.lat as f64 / 1e7,
+            position.lon as f64 / 1e7,
+            position.relative_alt as f32 / 1000.0,
+        );
+        debug!(
+            "Distance to waypoint {}: {:.1} m",
+            self.current_index, distance
+        );
+
+        if distance > self.acceptance_radius_m {
+            self.loiter_started = None;
+            return Ok(());
+        }
+        let loiter_started = *self.loiter_started.get_or_insert_with(Instant::now);
+        if loiter_started.elapsed().as_secs_f32() < waypoint.loiter_s {
+            return Ok(());
+        }
+
+        info!("Waypoint {} reached", self.current_index);
+        tx.send(publish!(
+            "auto/waypoint_reached",
+            &AutoWaypointMessage::new(self.current_index)
+        ))?;
+        self.current_index += 1;
+        self.loiter_started = None;
+
+        if self.is_complete() {
+            info!("Mission complete after {} waypoints", self.waypoints.len());
+            tx.send(publish!(
+                "auto/mission_complete",
+                &AutoMissionCompleteMessage::new(self.waypoints.len())
+            ))?;
+        } else {
+            self.command_current(tx)?;
+        }
+        Ok(())
+    }
+}
+
+impl Task for AutoTaskWaypoint {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!(
+            "AutoTaskWaypoint initialized with {} waypoints",
+            self.waypoints.len()
+        );
+        self.commanded_index = None;
+        self.loiter_started = None;
+
+        tx.send(subscribe!("mavlink/global_position_int"))?;
+        tx.send(subscribe!(WAYPOINTS_TOPIC))?;
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        let mut position = None;
+        for record in &inputs {
+            match record.try_get_topic()?.as_str() {
+                WAYPOINTS_TOPIC => match record.to_serde::<Waypoint>() {
+                    Ok(waypoints) => self.load_waypoints(waypoints),
+                    Err(e) => warn!("Ignoring invalid mission: {}", e),
+                },
+                "mavlink/global_position_int" => {
+                    let positions: Vec<GLOBAL_POSITION_INT_DATA> =
+                        record.to_serde().unwrap_or_default();
+                    position = positions.last().cloned().or(position);
+                }
+                _ => {}
+            }
+        }
+
+        self.command_current(&tx)?;
+        if let Some(position) = position {
+            self.check_progress(&position, &tx)?;
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("AutoTaskWaypoint cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn position_record(waypoint: &Waypoint) -> pubsub::message::record::Record {
+        let position = GLOBAL_POSITION_INT_DATA {
+            lat: (waypoint.lat * 1e7) as i32,
+            lon: (waypoint.lon * 1e7) as i32,
+            relative_alt: (waypoint.alt_m * 1000.0) as i32,
+            ..Default::default()
+        };
+        publish!("mavlink/global_position_int", &position)
+    }
+
+    #[test]
+    fn test_mission_advances_and_completes() {
+        let waypoints = vec![
+            Waypoint {
+                lat: 47.397742,
+                lon: 8.545594,
+                alt_m: 10.0,
+                loiter_s: 0.0,
+            },
+            Waypoint {
+                lat: 47.398742,
+                lon: 8.545594,
+                alt_m: 10.0,
+                loiter_s: 0.0,
+            },
+        ];
+        // About 111 m north of the first waypoint
+        assert!((waypoints[0].distance_m(47.398742, 8.545594, 10.0) - 111.2).abs() < 0.5);
+
+        let mut task = AutoTaskWaypoint::new(waypoints.clone(), DEFAULT_ACCEPTANCE_RADIUS_M);
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        // Far from the first waypoint, only its command is sent
+        task.run(
+            vec![position_record(&waypoints[1])],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert_eq!(task.current_index(), 0);
+
+        for waypoint in &waypoints {
+            task.run(vec![position_record(waypoint)], tx.clone(), meta_tx.clone())
+                .unwrap();
+        }
+        assert!(task.is_complete());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        let topics: Vec<String> = sent.iter().map(|r| r.try_get_topic().unwrap()).collect();
+        assert_eq!(
+            topics,
+            vec![
+                "mavlink/send/set_position_target_global_int",
+                "auto/waypoint_reached",
+                "mavlink/send/set_position_target_global_int",
+                "auto/waypoint_reached",
+                "auto/mission_complete",
+            ]
+        );
+        let MavMessage::SET_POSITION_TARGET_GLOBAL_INT(target) =
+            &sent[2].to_serde::<MavMessage>().unwrap()[0]
+        else {
+            panic!("Expected a global position target");
+        };
+        assert_eq!(target.lat_int, 473_987_420);
+        assert_eq!(target.alt, 10.0);
+    }
+
+    #[test]
+    fn test_waypoints_from_script() {
+        let path =
+            std::env::temp_dir().join(format!("auto_waypoint_script_{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"[
+  [0.0, "auto/takeoff", "{\"height\": 10.0}"],
+  [1.0, "auto/mission/waypoints", "[{\"lat\": 47.39, \"lon\": 8.54, \"alt_m\": 10.0}]"]
+]"#,
+        )
+        .unwrap();
+
+        let task = AutoTaskWaypoint::from_script(&path, 3.0).unwrap();
+        assert_eq!(task.waypoints.len(), 1);
+        assert_eq!(task.waypoints[0].loiter_s, 0.0);
+        assert_eq!(task.acceptance_radius_m, 3.0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,2 +1,5 @@
+pub mod auto_task_obstacle_avoidance;
+pub mod auto_task_orbit;
 pub mod auto_task_runscript;
 pub mod auto_task_takeoff;
+pub mod auto_task_waypoint;
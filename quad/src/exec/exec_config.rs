@@ -1,10 +1,119 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use pubsub::task_info;
+use pubsub::tasks::info::TaskInfo;
 
 use super::stage::ExecStage;
+use super::tasks::{
+    exec_task_armwatchdog::ExecTaskArmWatchdog, exec_task_datawatchdog::ExecTaskDataWatchdog,
+    exec_task_errormonitor::ExecTaskErrorMonitor, exec_task_geofence::Geofence,
+    exec_task_healthwatchdog::ExecTaskHealthWatchdog, exec_task_lockwatchdog::ExecTaskLockWatchdog,
+    exec_task_watchdog::ExecTaskWatchdog,
+};
+
+/// A problem found while validating a stage config against the registered tasks
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Unknown task '{task_name}' in stage {stage}")]
+    UnknownTask { stage: String, task_name: String },
+
+    #[error("Stage {stage} is configured with no tasks")]
+    EmptyStageConfig { stage: String },
+
+    #[error("Default task '{task_name}' is listed more than once")]
+    DuplicateDefaultTask { task_name: String },
+
+    #[error("Conflicting script tasks '{first}' and '{second}'")]
+    ConflictingScriptTask { first: String, second: String },
+}
+
+/// A config issue that doesn't stop the stage runners but is likely a mistake
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ConfigWarning {
+    #[error("Task '{task_name}' of stage {stage} is also a default task")]
+    DefaultTaskInStage { stage: String, task_name: String },
+
+    #[error("Task '{task_name}' of stage {stage} publishes that same stage")]
+    SelfTransition { stage: String, task_name: String },
+}
+
+/// Join config errors into a single error listing all of them
+pub fn config_errors_to_anyhow(context: &str, errors: &[ConfigError]) -> anyhow::Error {
+    let details: Vec<String> = errors.iter().map(|e| format!("  - {}", e)).collect();
+    anyhow::anyhow!("{}:\n{}", context, details.join("\n"))
+}
+
+/// Append the names in `other` that are not already in `into`, keeping their order
+pub(crate) fn extend_unique(into: &mut Vec<String>, other: Vec<String>) {
+    for task_name in other {
+        if !into.contains(&task_name) {
+            into.push(task_name);
+        }
+    }
+}
+
+/// Union two stage maps, combining the task lists of stages configured in both
+pub(crate) fn merge_stage_task_names<S: Eq + Hash>(
+    into: &mut HashMap<S, Vec<String>>,
+    other: HashMap<S, Vec<String>>,
+) {
+    for (stage, task_names) in other {
+        extend_unique(into.entry(stage).or_default(), task_names);
+    }
+}
+
+/// Stages the exec task named `task_name` can publish on exec/stage when spawned for a stage.
+/// Empty for tasks that never change the stage and for tasks outside this crate.
+fn stage_transitions(task_name: &str) -> &'static [ExecStage] {
+    let known = [
+        (
+            task_info!(ExecTaskWatchdog),
+            ExecTaskWatchdog::STAGE_TRANSITIONS,
+        ),
+        (
+            task_info!(ExecTaskDataWatchdog),
+            ExecTaskDataWatchdog::STAGE_TRANSITIONS,
+        ),
+        (
+            task_info!(ExecTaskHealthWatchdog),
+            ExecTaskHealthWatchdog::STAGE_TRANSITIONS,
+        ),
+        (
+            task_info!(ExecTaskLockWatchdog),
+            ExecTaskLockWatchdog::STAGE_TRANSITIONS,
+        ),
+        (
+            task_info!(ExecTaskArmWatchdog),
+            ExecTaskArmWatchdog::STAGE_TRANSITIONS,
+        ),
+        (
+            task_info!(ExecTaskErrorMonitor),
+            ExecTaskErrorMonitor::STAGE_TRANSITIONS,
+        ),
+    ];
+    known
+        .into_iter()
+        .find(|(info, _)| info.name == task_name)
+        .map(|(_, stages)| stages)
+        .unwrap_or(&[])
+}
+
+/// Battery percentage below which `ExecTaskBatteryMonitor` reports a critical battery
+pub const DEFAULT_BATTERY_CRITICAL_PCT: i8 = 15;
+
+/// Seconds outside the geofence after which `ExecTaskGeofence` switches auto to `AutoLand`
+pub const DEFAULT_GEOFENCE_VIOLATION_GRACE_S: f32 = 3.0;
 
 pub struct ExecConfig {
     pub stage_task_names: HashMap<ExecStage, Vec<String>>,
     pub default_tasks: Vec<String>,
+    /// Battery percentage below which the battery is critical
+    pub battery_critical_pct: i8,
+    /// Boundary enforced by `ExecTaskGeofence`, see `with_geofence`
+    pub geofence: Option<Geofence>,
+    /// Seconds of sustained geofence violation before landing
+    pub geofence_violation_grace_s: f32,
 }
 
 impl ExecConfig {
@@ -12,6 +121,9 @@ impl ExecConfig {
         Self {
             stage_task_names: HashMap::new(),
             default_tasks: Vec::new(),
+            battery_critical_pct: DEFAULT_BATTERY_CRITICAL_PCT,
+            geofence: None,
+            geofence_violation_grace_s: DEFAULT_GEOFENCE_VIOLATION_GRACE_S,
         }
     }
 
@@ -38,6 +150,47 @@ impl ExecConfig {
         self
     }
 
+    pub fn with_battery_critical_pct(mut self, pct: i8) -> Self {
+        self.battery_critical_pct = pct;
+        self
+    }
+
+    /// Run `ExecTaskGpsMonitor` alongside `ExecTaskLockWatchdog` while armed so losing
+    /// the GPS 3D fix returns the vehicle to AwaitingLock. The monitor also runs in
+    /// AwaitingLock so the watchdog sees the fix come back.
+    pub fn with_gps_monitor(mut self) -> Self {
+        for stage in [
+            ExecStage::AwaitingLock,
+            ExecStage::HealthyArmed,
+            ExecStage::HealthyGuided,
+        ] {
+            let mut task_names = vec!["ExecTaskGpsMonitor".to_string()];
+            if stage != ExecStage::AwaitingLock {
+                task_names.push("ExecTaskLockWatchdog".to_string());
+            }
+            extend_unique(self.stage_task_names.entry(stage).or_default(), task_names);
+        }
+        self
+    }
+
+    /// Enforce `geofence` with `ExecTaskGeofence` while armed. Leaving it publishes
+    /// violations and, once sustained for the grace period, lands the vehicle.
+    pub fn with_geofence(mut self, geofence: Geofence) -> Self {
+        self.geofence = Some(geofence);
+        for stage in [ExecStage::HealthyArmed, ExecStage::HealthyGuided] {
+            extend_unique(
+                self.stage_task_names.entry(stage).or_default(),
+                vec!["ExecTaskGeofence".to_string()],
+            );
+        }
+        self
+    }
+
+    pub fn with_geofence_violation_grace_s(mut self, grace_s: f32) -> Self {
+        self.geofence_violation_grace_s = grace_s;
+        self
+    }
+
     pub fn add_default_task(&mut self, task_name: String) {
         self.default_tasks.push(task_name);
     }
@@ -56,4 +209,247 @@ impl ExecConfig {
     pub fn get_stage_tasks(&self, stage: ExecStage) -> Option<&Vec<String>> {
         self.stage_task_names.get(&stage)
     }
+
+    /// Combine two configs, e.g. separate safety and telemetry modules of a mission.
+    /// Stage task lists and default tasks are concatenated without duplicates,
+    /// thresholds are kept from `self` and the geofence from `other` is used if `self` has none.
+    pub fn merge(mut self, other: ExecConfig) -> ExecConfig {
+        merge_stage_task_names(&mut self.stage_task_names, other.stage_task_names);
+        extend_unique(&mut self.default_tasks, other.default_tasks);
+        self.geofence = self.geofence.or(other.geofence);
+        self
+    }
+
+    /// Check that every configured task is registered, that no stage is configured empty
+    /// and that default tasks are unique. All problems are returned, not just the first.
+    pub fn validate(&self, registered_tasks: &[TaskInfo]) -> Result<(), Vec<ConfigError>> {
+        let registered: HashSet<&str> = registered_tasks.iter().map(|t| t.name.as_str()).collect();
+        let mut errors = Vec::new();
+
+        let mut seen_defaults = HashSet::new();
+        for task_name in &self.default_tasks {
+            if !seen_defaults.insert(task_name) {
+                errors.push(ConfigError::DuplicateDefaultTask {
+                    task_name: task_name.clone(),
+                });
+            }
+            if !registered.contains(task_name.as_str()) {
+                errors.push(ConfigError::UnknownTask {
+                    stage: "default".to_string(),
+                    task_name: task_name.clone(),
+                });
+            }
+        }
+
+        // Sort stages so errors are reported in a stable order
+        let mut stages: Vec<(&ExecStage, &Vec<String>)> = self.stage_task_names.iter().collect();
+        stages.sort_by_key(|(stage, _)| stage.to_string());
+
+        for (stage, task_names) in stages {
+            if task_names.is_empty() {
+                errors.push(ConfigError::EmptyStageConfig {
+                    stage: stage.to_string(),
+                });
+            }
+            for task_name in task_names {
+                if !registered.contains(task_name.as_str()) {
+                    errors.push(ConfigError::UnknownTask {
+                        stage: stage.to_string(),
+                        task_name: task_name.clone(),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Soft issues of the config that `validate` lets through: stage tasks that are also
+    /// default tasks, and stage tasks that publish the stage they are spawned for
+    pub fn warnings(&self) -> Vec<ConfigWarning> {
+        let mut stages: Vec<(&ExecStage, &Vec<String>)> = self.stage_task_names.iter().collect();
+        stages.sort_by_key(|(stage, _)| stage.to_string());
+
+        let mut warnings = Vec::new();
+        for (stage, task_names) in stages {
+            for task_name in task_names {
+                if self.default_tasks.contains(task_name) {
+                    warnings.push(ConfigWarning::DefaultTaskInStage {
+                        stage: stage.to_string(),
+                        task_name: task_name.clone(),
+                    });
+                }
+                if stage_transitions(task_name).contains(stage) {
+                    warnings.push(ConfigWarning::SelfTransition {
+                        stage: stage.to_string(),
+                        task_name: task_name.clone(),
+                    });
+                }
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ardulink::task::MavlinkTask;
+
+    #[test]
+    fn test_validate_reports_all_errors() {
+        let registered = vec![task_info!(MavlinkTask), task_info!(ExecTaskWatchdog)];
+
+        let config = ExecConfig::new()
+            .with_default_task("MavlinkTask".to_string())
+            .with_default_task("MavlinkTask".to_string())
+            .with_stage_task(ExecStage::AwaitConnection, "ExecTaskWatchdog".to_string())
+            .with_stage_task(ExecStage::AwaitingData, "ExecTaskWatchdgo".to_string())
+            .with_stage_tasks(ExecStage::Fatal, vec![]);
+
+        let errors = config.validate(&registered).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors.contains(&ConfigError::DuplicateDefaultTask {
+            task_name: "MavlinkTask".to_string()
+        }));
+        assert!(errors.contains(&ConfigError::UnknownTask {
+            stage: "AwaitingData".to_string(),
+            task_name: "ExecTaskWatchdgo".to_string()
+        }));
+        assert!(errors.contains(&ConfigError::EmptyStageConfig {
+            stage: "Fatal".to_string()
+        }));
+    }
+
+    #[test]
+    fn test_merge_combines_stages() {
+        let safety = ExecConfig::new()
+            .with_default_task("MavlinkTask".to_string())
+            .with_stage_task(ExecStage::AwaitConnection, "ExecTaskWatchdog".to_string());
+        let telemetry = ExecConfig::new()
+            .with_default_task("TelemetryTask".to_string())
+            .with_stage_task(ExecStage::AwaitConnection, "ExecTaskHeartbeat".to_string())
+            .with_stage_task(ExecStage::AwaitingData, "ExecTaskRequestStream".to_string());
+
+        let merged = safety.merge(telemetry);
+        assert_eq!(merged.default_tasks, vec!["MavlinkTask", "TelemetryTask"]);
+        assert_eq!(
+            merged.get_stage_tasks(ExecStage::AwaitConnection).unwrap(),
+            &vec!["ExecTaskWatchdog", "ExecTaskHeartbeat"]
+        );
+        assert_eq!(
+            merged.get_stage_tasks(ExecStage::AwaitingData).unwrap(),
+            &vec!["ExecTaskRequestStream"]
+        );
+    }
+
+    #[test]
+    fn test_merge_deduplicates_default_tasks() {
+        let a = ExecConfig::new()
+            .with_default_tasks(vec!["MavlinkTask".to_string(), "LogTask".to_string()])
+            .with_stage_task(ExecStage::AwaitConnection, "ExecTaskWatchdog".to_string());
+        let b = ExecConfig::new()
+            .with_default_tasks(vec!["LogTask".to_string(), "MavlinkTask".to_string()])
+            .with_stage_task(ExecStage::AwaitConnection, "ExecTaskWatchdog".to_string());
+
+        let merged = a.merge(b);
+        assert_eq!(merged.default_tasks, vec!["MavlinkTask", "LogTask"]);
+        assert_eq!(
+            merged.get_stage_tasks(ExecStage::AwaitConnection).unwrap(),
+            &vec!["ExecTaskWatchdog"]
+        );
+    }
+
+    #[test]
+    fn test_with_gps_monitor() {
+        let config = ExecConfig::new()
+            .with_stage_task(ExecStage::AwaitingLock, "ExecTaskLockWatchdog".to_string())
+            .with_stage_task(ExecStage::HealthyArmed, "ExecTaskStartAuto".to_string())
+            .with_gps_monitor();
+
+        assert_eq!(
+            config.get_stage_tasks(ExecStage::AwaitingLock).unwrap(),
+            &vec!["ExecTaskLockWatchdog", "ExecTaskGpsMonitor"]
+        );
+        assert_eq!(
+            config.get_stage_tasks(ExecStage::HealthyArmed).unwrap(),
+            &vec![
+                "ExecTaskStartAuto",
+                "ExecTaskGpsMonitor",
+                "ExecTaskLockWatchdog"
+            ]
+        );
+        assert_eq!(
+            config.get_stage_tasks(ExecStage::HealthyGuided).unwrap(),
+            &vec!["ExecTaskGpsMonitor", "ExecTaskLockWatchdog"]
+        );
+    }
+
+    #[test]
+    fn test_with_geofence() {
+        let geofence = Geofence::new(47.397742, 8.545594, 100.0, 50.0);
+        let config = ExecConfig::new()
+            .with_stage_task(ExecStage::HealthyArmed, "ExecTaskStartAuto".to_string())
+            .with_geofence(geofence)
+            .with_geofence(geofence);
+
+        assert_eq!(config.geofence, Some(geofence));
+        assert_eq!(
+            config.get_stage_tasks(ExecStage::HealthyArmed).unwrap(),
+            &vec!["ExecTaskStartAuto", "ExecTaskGeofence"]
+        );
+        assert_eq!(
+            config.get_stage_tasks(ExecStage::HealthyGuided).unwrap(),
+            &vec!["ExecTaskGeofence"]
+        );
+
+        let merged = ExecConfig::new().merge(config);
+        assert_eq!(merged.geofence, Some(geofence));
+    }
+
+    #[test]
+    fn test_validate_valid_config() {
+        let registered = vec![task_info!(ExecTaskWatchdog)];
+        let config = ExecConfig::new()
+            .with_stage_task(ExecStage::AwaitConnection, "ExecTaskWatchdog".to_string());
+        assert!(config.validate(&registered).is_ok());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let config = ExecConfig::new()
+            .with_default_task("ExecTaskHeartbeat".to_string())
+            .with_stage_tasks(
+                ExecStage::AwaitConnection,
+                vec![
+                    "ExecTaskWatchdog".to_string(),
+                    "ExecTaskHeartbeat".to_string(),
+                ],
+            )
+            .with_stage_task(ExecStage::Fatal, "ExecTaskErrorMonitor".to_string());
+        assert_eq!(
+            config.warnings(),
+            vec![
+                ConfigWarning::DefaultTaskInStage {
+                    stage: "AwaitConnection".to_string(),
+                    task_name: "ExecTaskHeartbeat".to_string()
+                },
+                ConfigWarning::SelfTransition {
+                    stage: "Fatal".to_string(),
+                    task_name: "ExecTaskErrorMonitor".to_string()
+                },
+            ]
+        );
+
+        // Watchdogs that only promote their stage are not self-transitions
+        let config = ExecConfig::new()
+            .with_stage_task(ExecStage::AwaitingLock, "ExecTaskLockWatchdog".to_string())
+            .with_stage_task(ExecStage::HealthyUnarmed, "ExecTaskArmWatchdog".to_string())
+            .with_gps_monitor();
+        assert!(config.warnings().is_empty());
+    }
 }
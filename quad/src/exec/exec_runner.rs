@@ -1,8 +1,8 @@
 use core::task;
 
-use log::info;
+use log::{info, warn};
 use pubsub::{
-    subscribe,
+    subscribe, task_info,
     tasks::{
         info::TaskInfo,
         meta_control::{MetaCommand, MetaMessage},
@@ -10,12 +10,17 @@ use pubsub::{
     },
 };
 
-use super::{exec_config::ExecConfig, messages::ExecStageMessage, stage::ExecStage};
+use super::{
+    exec_config::{config_errors_to_anyhow, ExecConfig},
+    messages::ExecStageMessage,
+    stage::ExecStage,
+};
 
 pub struct ExecRunner {
     pub config: ExecConfig,
     pub stage: ExecStage,
     spawned_tasks: Vec<TaskInfo>,
+    registered_tasks: Option<Vec<TaskInfo>>,
     info: TaskInfo,
 }
 
@@ -25,9 +30,35 @@ impl ExecRunner {
             config,
             stage: ExecStage::AwaitConnection,
             spawned_tasks: vec![],
-            info: TaskInfo::new("ExecRunner").with_insta_spawn(),
+            registered_tasks: None,
+            info: task_info!(ExecRunner).with_insta_spawn(),
         }
     }
+
+    /// Tasks registered with the runner, used to validate the config on init and to
+    /// resolve task names to spawnable task infos. Without them no task can be spawned.
+    pub fn with_registered_tasks(mut self, registered_tasks: Vec<TaskInfo>) -> Self {
+        self.registered_tasks = Some(registered_tasks);
+        self
+    }
+
+    /// Find a registered task by name. Task ids are derived from the full type path, so
+    /// an unregistered name can't be turned into an info the runner would spawn.
+    fn resolve_task_info(&self, task_name: &str) -> Result<TaskInfo, anyhow::Error> {
+        let registered_tasks = self.registered_tasks.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot spawn task '{}': no tasks registered, see with_registered_tasks",
+                task_name
+            )
+        })?;
+        registered_tasks
+            .iter()
+            .find(|task| task.name == task_name)
+            .map(|task| task.clone().with_insta_spawn())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Task '{}' is not registered with the runner", task_name)
+            })
+    }
 }
 
 impl Task for ExecRunner {
@@ -36,10 +67,19 @@ impl Task for ExecRunner {
         tx: pubsub::tasks::task::TaskChannel,
         meta_tx: pubsub::tasks::task::MetaTaskChannel,
     ) -> Result<(), anyhow::Error> {
+        if let Some(registered_tasks) = &self.registered_tasks {
+            if let Err(errors) = self.config.validate(registered_tasks) {
+                return Err(config_errors_to_anyhow("Invalid exec config", &errors));
+            }
+        }
+        for warning in self.config.warnings() {
+            warn!("Exec config: {}", warning);
+        }
+
         // Spawn default tasks
         for task_name in self.config.default_tasks.iter() {
             info!("Spawning default task: {}", task_name);
-            let task_config = TaskInfo::new(task_name.clone()).with_insta_spawn();
+            let task_config = self.resolve_task_info(task_name)?;
             self.spawned_tasks.push(task_config.clone());
             let new_task_packet = MetaMessage::new(MetaCommand::SpawnTask, task_config);
             meta_tx.send(new_task_packet)?;
@@ -120,7 +160,7 @@ impl Task for ExecRunner {
         // Spawn new tasks
         for task_name in tasks_to_spawn {
             info!("Spawning task for stage {}: {}", self.stage, task_name);
-            let task_config = TaskInfo::new(task_name).with_insta_spawn();
+            let task_config = self.resolve_task_info(&task_name)?;
             let new_task_packet = MetaMessage::new(MetaCommand::SpawnTask, task_config.clone());
             meta_tx.send(new_task_packet)?;
             self.spawned_tasks.push(task_config);
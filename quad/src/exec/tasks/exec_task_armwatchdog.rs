@@ -1,7 +1,7 @@
 use log::{debug, info, warn};
 use mavlink::ardupilotmega::{MavMessage, MavModeFlag, HEARTBEAT_DATA};
 use pubsub::{
-    publish, subscribe,
+    publish, subscribe, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use serde::{Deserialize, Serialize};
@@ -17,9 +17,12 @@ pub struct ExecTaskArmWatchdog {
 }
 
 impl ExecTaskArmWatchdog {
+    /// Stages a freshly spawned ExecTaskArmWatchdog can publish on exec/stage
+    pub const STAGE_TRANSITIONS: &[ExecStage] = &[ExecStage::HealthyArmed];
+
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecArmWatchdog"),
+            info: task_info!(ExecTaskArmWatchdog),
             is_armed: false,
         }
     }
@@ -56,7 +59,9 @@ impl Task for ExecTaskArmWatchdog {
                 // Check for the reprocessed heartbeat armed status
                 if topic.contains("mavlink/reproc/heartbeat_armed") {
                     info!("Received heartbeat armed status: {:?}", record);
-                    let is_armed: Vec<HeartbeatFlag> = record.to_serde()?;
+                    // Only the flag is read, skip deserializing anything else
+                    let is_armed: Vec<HeartbeatFlag> =
+                        record.select_columns(&["value"])?.to_serde()?;
                     for armed in is_armed {
                         self.handle_armed_status(armed.value, &tx)?;
                     }
@@ -100,3 +105,73 @@ impl ExecTaskArmWatchdog {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubsub::message::record::Record;
+    use pubsub::tasks::runner::Runner;
+    use std::sync::{Arc, Mutex};
+
+    /// Row of `mavlink/reproc/heartbeat_armed` as written by the logger
+    #[derive(Serialize, Deserialize)]
+    struct RecordedArmed {
+        timestamp: u64,
+        value: bool,
+    }
+
+    /// A log with one heartbeat per 100ms, the vehicle arming on the third one
+    fn write_heartbeat_log(log_dir: &std::path::Path) {
+        let rows: Vec<RecordedArmed> = [(0, false), (100, false), (200, true), (300, true)]
+            .into_iter()
+            .map(|(timestamp, value)| RecordedArmed { timestamp, value })
+            .collect();
+        let record = Record::from_serde_batch(&rows).unwrap();
+        let batch = record.to_record_batch();
+
+        let path = log_dir.join("mavlink/reproc/heartbeat_armed.parquet");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut writer = parquet::arrow::ArrowWriter::try_new(
+            std::fs::File::create(&path).unwrap(),
+            batch.schema(),
+            None,
+        )
+        .unwrap();
+        writer.write(batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_replayed_heartbeat_arms() {
+        let log_dir =
+            std::env::temp_dir().join(format!("arm_watchdog_replay_{}", uuid::Uuid::new_v4()));
+        write_heartbeat_log(&log_dir);
+
+        // No MavlinkTask, the heartbeats come from the log
+        let watchdog = Arc::new(Mutex::new(ExecTaskArmWatchdog::new()));
+        let mut runner = Runner::new().with_tick_rate(0.0).disable_logging();
+        runner.add_task(watchdog.clone());
+        runner.init().unwrap();
+        runner.start_task(&task_info!(ExecTaskArmWatchdog)).unwrap();
+
+        runner
+            .replay_from_parquet(
+                log_dir.clone(),
+                vec!["mavlink/reproc/heartbeat_armed".to_string()],
+                "timestamp",
+                20.0,
+            )
+            .unwrap();
+        // Route the stage published on the last tick
+        runner.run().unwrap();
+        assert!(watchdog.lock().unwrap().is_armed);
+
+        let snapshot = runner.snapshot_to_record().unwrap();
+        let stages: Vec<serde_json::Value> = snapshot.to_serde().unwrap();
+        let stage: ExecStageMessage =
+            serde_json::from_str(stages[0]["exec/stage"].as_str().unwrap()).unwrap();
+        assert_eq!(stage.stage, ExecStage::HealthyArmed);
+
+        std::fs::remove_dir_all(&log_dir).unwrap();
+    }
+}
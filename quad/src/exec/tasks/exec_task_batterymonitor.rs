@@ -0,0 +1,220 @@
+use log::{debug, info, warn};
+use mavlink::ardupilotmega::SYS_STATUS_DATA;
+use pubsub::{
+    message::error_record::{ErrorRecord, ErrorSeverity},
+    publish, publish_error, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::exec::exec_config::{ExecConfig, DEFAULT_BATTERY_CRITICAL_PCT};
+
+/// Battery percentage below which the battery is low
+pub const BATTERY_LOW_PCT: i8 = 30;
+
+/// Health of the battery derived from its remaining charge
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryHealth {
+    Good,
+    Low,
+    Critical,
+    /// The autopilot does not report the remaining charge
+    Unknown,
+}
+
+/// Published on `vehicle/battery` for every `SYS_STATUS` message
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct BatteryStatus {
+    pub voltage_mv: u32,
+    /// Current drawn from the battery, -1 when not measured
+    pub current_ma: i32,
+    /// Remaining charge in percent, -1 when not reported
+    pub remaining_pct: i8,
+    pub health: BatteryHealth,
+}
+
+/// Task that turns `SYS_STATUS` battery fields into a `BatteryStatus` and reports a
+/// critical battery on `errors/battery_critical`
+pub struct ExecTaskBatteryMonitor {
+    info: TaskInfo,
+    critical_pct: i8,
+    last_health: BatteryHealth,
+}
+
+impl ExecTaskBatteryMonitor {
+    pub fn new() -> Self {
+        Self {
+            info: task_info!(ExecTaskBatteryMonitor),
+            critical_pct: DEFAULT_BATTERY_CRITICAL_PCT,
+            last_health: BatteryHealth::Unknown,
+        }
+    }
+
+    /// Use the battery thresholds of an exec config
+    pub fn from_config(config: &ExecConfig) -> Self {
+        Self {
+            critical_pct: config.battery_critical_pct,
+            ..Self::new()
+        }
+    }
+
+    fn health(&self, remaining_pct: i8) -> BatteryHealth {
+        if remaining_pct < 0 {
+            BatteryHealth::Unknown
+        } else if remaining_pct < self.critical_pct {
+            BatteryHealth::Critical
+        } else if remaining_pct < BATTERY_LOW_PCT {
+            BatteryHealth::Low
+        } else {
+            BatteryHealth::Good
+        }
+    }
+
+    fn battery_status(&self, sys_status: &SYS_STATUS_DATA) -> BatteryStatus {
+        // SYS_STATUS reports current in units of 10 mA, -1 if not measured
+        let current_ma = match sys_status.current_battery {
+            -1 => -1,
+            current => current as i32 * 10,
+        };
+        BatteryStatus {
+            voltage_mv: sys_status.voltage_battery as u32,
+            current_ma,
+            remaining_pct: sys_status.battery_remaining,
+            health: self.health(sys_status.battery_remaining),
+        }
+    }
+}
+
+impl Task for ExecTaskBatteryMonitor {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!(
+            "ExecTaskBatteryMonitor initialized (critical below {}%)",
+            self.critical_pct
+        );
+        self.last_health = BatteryHealth::Unknown;
+
+        tx.send(subscribe!("mavlink/sys_status"))?;
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            if record.try_get_topic().ok().as_deref() != Some("mavlink/sys_status") {
+                continue;
+            }
+
+            let sys_status: Vec<SYS_STATUS_DATA> = record.to_serde().unwrap_or_default();
+            for status in &sys_status {
+                let battery = self.battery_status(status);
+                debug!("Battery status: {:?}", battery);
+                tx.send(publish!("vehicle/battery", &battery))?;
+
+                // Report a critical battery once each time it becomes critical
+                if battery.health == BatteryHealth::Critical
+                    && self.last_health != BatteryHealth::Critical
+                {
+                    warn!("Battery critical: {}% remaining", battery.remaining_pct);
+                    let error = ErrorRecord::new(
+                        self.info.name.clone(),
+                        "battery_check",
+                        format!("Battery critical: {}% remaining", battery.remaining_pct),
+                        ErrorSeverity::Critical,
+                    );
+                    let mut error_packet = publish_error!(&error);
+                    error_packet.set_topic("errors/battery_critical".to_string())?;
+                    tx.send(error_packet)?;
+                }
+                self.last_health = battery.health;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskBatteryMonitor cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn sys_status_record(
+        voltage_battery: u16,
+        current_battery: i16,
+        battery_remaining: i8,
+    ) -> pubsub::message::record::Record {
+        let sys_status = SYS_STATUS_DATA {
+            voltage_battery,
+            current_battery,
+            battery_remaining,
+            ..Default::default()
+        };
+        publish!("mavlink/sys_status", &sys_status)
+    }
+
+    #[test]
+    fn test_battery_health_and_critical_error() {
+        let config = ExecConfig::new().with_battery_critical_pct(20);
+        let mut task = ExecTaskBatteryMonitor::from_config(&config);
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        for (current, remaining) in [(-1, -1), (1520, 80), (1480, 25), (1490, 18), (1500, 12)] {
+            task.run(
+                vec![sys_status_record(12_600, current, remaining)],
+                tx.clone(),
+                meta_tx.clone(),
+            )
+            .unwrap();
+        }
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        let statuses: Vec<BatteryStatus> = sent
+            .iter()
+            .filter(|r| r.try_get_topic().unwrap() == "vehicle/battery")
+            .flat_map(|r| r.to_serde::<BatteryStatus>().unwrap())
+            .collect();
+        let health: Vec<BatteryHealth> = statuses.iter().map(|s| s.health).collect();
+        assert_eq!(
+            health,
+            vec![
+                BatteryHealth::Unknown,
+                BatteryHealth::Good,
+                BatteryHealth::Low,
+                BatteryHealth::Critical,
+                BatteryHealth::Critical,
+            ]
+        );
+        assert_eq!(statuses[0].current_ma, -1);
+        assert_eq!(statuses[1].current_ma, 15_200);
+        assert_eq!(statuses[1].voltage_mv, 12_600);
+
+        // Only the transition into critical is reported
+        let errors: Vec<ErrorRecord> = sent
+            .iter()
+            .filter(|r| r.try_get_topic().unwrap() == "errors/battery_critical")
+            .flat_map(|r| r.to_serde::<ErrorRecord>().unwrap())
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].severity, ErrorSeverity::Critical);
+        assert_eq!(errors[0].task_name, "ExecTaskBatteryMonitor");
+    }
+}
@@ -1,6 +1,6 @@
 use log::{debug, info};
 use pubsub::{
-    publish, subscribe,
+    publish, subscribe, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use serde::{Deserialize, Serialize};
@@ -14,9 +14,12 @@ pub struct ExecTaskDataWatchdog {
 }
 
 impl ExecTaskDataWatchdog {
+    /// Stages a freshly spawned ExecTaskDataWatchdog can publish on exec/stage
+    pub const STAGE_TRANSITIONS: &[ExecStage] = &[ExecStage::AwaitingHealthy];
+
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecTaskDataWatchdog"),
+            info: task_info!(ExecTaskDataWatchdog),
             data_received: false,
         }
     }
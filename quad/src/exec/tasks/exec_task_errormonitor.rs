@@ -0,0 +1,139 @@
+use log::{debug, error, info, warn};
+use pubsub::{
+    message::error_record::{ErrorRecord, ErrorSeverity},
+    publish, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+
+use crate::exec::{messages::ExecStageMessage, stage::ExecStage};
+
+/// Task that watches the errors reported by other tasks and moves the exec stage to Fatal
+/// on the first critical one
+pub struct ExecTaskErrorMonitor {
+    info: TaskInfo,
+    fatal_sent: bool,
+}
+
+impl ExecTaskErrorMonitor {
+    /// Stages a freshly spawned ExecTaskErrorMonitor can publish on exec/stage
+    pub const STAGE_TRANSITIONS: &[ExecStage] = &[ExecStage::Fatal];
+
+    pub fn new() -> Self {
+        Self {
+            info: task_info!(ExecTaskErrorMonitor),
+            fatal_sent: false,
+        }
+    }
+}
+
+impl Task for ExecTaskErrorMonitor {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!("ExecTaskErrorMonitor initialized");
+        self.fatal_sent = false;
+
+        tx.send(subscribe!("errors/*"))?;
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            let errors: Vec<ErrorRecord> = match record.to_serde() {
+                Ok(errors) => errors,
+                Err(e) => {
+                    warn!("Failed to read error record: {}", e);
+                    continue;
+                }
+            };
+
+            for reported in errors {
+                if reported.severity != ErrorSeverity::Critical {
+                    debug!(
+                        "{:?} error from {}: {}",
+                        reported.severity, reported.task_name, reported.message
+                    );
+                    continue;
+                }
+
+                error!(
+                    "Critical error from {} during {}: {}",
+                    reported.task_name, reported.stage, reported.message
+                );
+                if !self.fatal_sent {
+                    self.fatal_sent = true;
+                    let pub_packet =
+                        publish!("exec/stage", &ExecStageMessage::new(ExecStage::Fatal));
+                    tx.send(pub_packet)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskErrorMonitor cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubsub::publish_error;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_critical_error_triggers_fatal() {
+        let mut task = ExecTaskErrorMonitor::new();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.run(
+            vec![publish_error!(
+                "ExecTaskHealthWatchdog",
+                "health_check",
+                "Battery low",
+                ErrorSeverity::Warning
+            )],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert_eq!(rx.try_iter().count(), 0);
+
+        // Only the first critical error changes the stage
+        for _ in 0..2 {
+            task.run(
+                vec![publish_error!(
+                    "ExecTaskHealthWatchdog",
+                    "health_check",
+                    "EKF unhealthy",
+                    ErrorSeverity::Critical
+                )],
+                tx.clone(),
+                meta_tx.clone(),
+            )
+            .unwrap();
+        }
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].try_get_topic().unwrap(), "exec/stage");
+        let stages: Vec<ExecStageMessage> = sent[0].to_serde().unwrap();
+        assert_eq!(stages[0].stage, ExecStage::Fatal);
+    }
+}
@@ -0,0 +1,345 @@
+use log::{debug, info, warn};
+use mavlink::ardupilotmega::GLOBAL_POSITION_INT_DATA;
+use pubsub::{
+    publish, subscribe, task_info,
+    tasks::{configurable::Configurable, info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::auto::auto_stage::AutoStage;
+use crate::auto::message::AutoStageMessage;
+use crate::auto::tasks::auto_task_waypoint::Waypoint;
+use crate::exec::exec_config::{ExecConfig, DEFAULT_GEOFENCE_VIOLATION_GRACE_S};
+
+/// Cylinder around a GPS point the vehicle has to stay in, altitude relative to home
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Geofence {
+    pub center_lat: f64,
+    pub center_lon: f64,
+    pub radius_m: f32,
+    pub max_alt_m: f32,
+}
+
+impl Geofence {
+    pub fn new(center_lat: f64, center_lon: f64, radius_m: f32, max_alt_m: f32) -> Self {
+        Self {
+            center_lat,
+            center_lon,
+            radius_m,
+            max_alt_m,
+        }
+    }
+
+    /// How far the vehicle is outside the fence, `None` while inside
+    pub fn violation(&self, lat: f64, lon: f64, alt_m: f32) -> Option<GeofenceViolation> {
+        let center = Waypoint {
+            lat: self.center_lat,
+            lon: self.center_lon,
+            alt_m: 0.0,
+            loiter_s: 0.0,
+        };
+        let violation = GeofenceViolation {
+            distance_outside_m: (center.distance_m(lat, lon, 0.0) - self.radius_m).max(0.0),
+            altitude_over_m: (alt_m - self.max_alt_m).max(0.0),
+        };
+        if violation.distance_outside_m > 0.0 || violation.altitude_over_m > 0.0 {
+            Some(violation)
+        } else {
+            None
+        }
+    }
+}
+
+/// Published on `vehicle/geofence_violation` for every position outside the geofence
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GeofenceViolation {
+    /// Horizontal distance beyond the fence radius, 0 if only the altitude is exceeded
+    pub distance_outside_m: f32,
+    /// Altitude above the fence ceiling, 0 if only the radius is exceeded
+    pub altitude_over_m: f32,
+}
+
+/// Task that checks `GLOBAL_POSITION_INT` against a geofence, publishes violations and
+/// switches auto to `AutoLand` once a violation lasted `violation_grace_s`.
+/// The violation time is taken from the position timestamps, not the wall clock.
+pub struct ExecTaskGeofence {
+    info: TaskInfo,
+    geofence: Geofence,
+    violation_grace_s: f32,
+    /// `time_boot_ms` of the first position of the current violation
+    violation_started_ms: Option<u32>,
+    land_requested: bool,
+}
+
+impl ExecTaskGeofence {
+    pub fn new(geofence: Geofence) -> Self {
+        Self {
+            info: task_info!(ExecTaskGeofence),
+            geofence,
+            violation_grace_s: DEFAULT_GEOFENCE_VIOLATION_GRACE_S,
+            violation_started_ms: None,
+            land_requested: false,
+        }
+    }
+
+    /// Use the geofence of an exec config, `None` if it has none
+    pub fn from_config(config: &ExecConfig) -> Option<Self> {
+        let geofence = config.geofence?;
+        Some(Self::new(geofence).with_violation_grace_s(config.geofence_violation_grace_s))
+    }
+
+    pub fn with_violation_grace_s(mut self, violation_grace_s: f32) -> Self {
+        self.violation_grace_s = violation_grace_s;
+        self
+    }
+
+    fn check_position(
+        &mut self,
+        position: &GLOBAL_POSITION_INT_DATA,
+        tx: &pubsub::tasks::task::TaskChannel,
+    ) -> anyhow::Result<()> {
+        let violation = self.geofence.violation(
+            position.lat as f64 / 1e7,
+            position.lon as f64 / 1e7,
+            position.relative_alt as f32 / 1000.0,
+        );
+        let Some(violation) = violation else {
+            if self.violation_started_ms.is_some() {
+                info!("Back inside the geofence");
+            }
+            self.violation_started_ms = None;
+            self.land_requested = false;
+            return Ok(());
+        };
+
+        debug!("Geofence violation: {:?}", violation);
+        tx.send(publish!("vehicle/geofence_violation", &violation))?;
+
+        let started_ms = *self
+            .violation_started_ms
+            .get_or_insert(position.time_boot_ms);
+        let violation_s = position.time_boot_ms.saturating_sub(started_ms) as f32 / 1000.0;
+        if violation_s >= self.violation_grace_s && !self.land_requested {
+            warn!(
+                "Outside the geofence for {:.1}s ({:.1} m out, {:.1} m over), landing",
+                violation_s, violation.distance_outside_m, violation.altitude_over_m
+            );
+            tx.send(publish!(
+                "auto/stage",
+                &AutoStageMessage::new(AutoStage::AutoLand)
+            ))?;
+            self.land_requested = true;
+        }
+        Ok(())
+    }
+}
+
+fn default_violation_grace_s() -> f32 {
+    DEFAULT_GEOFENCE_VIOLATION_GRACE_S
+}
+
+/// JSON config for `ExecTaskGeofence`: the geofence fields plus an optional grace period
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExecTaskGeofenceConfig {
+    #[serde(flatten)]
+    pub geofence: Geofence,
+    #[serde(default = "default_violation_grace_s")]
+    pub violation_grace_s: f32,
+}
+
+impl Configurable for ExecTaskGeofence {
+    type Config = ExecTaskGeofenceConfig;
+
+    fn new_from_config(config: Self::Config) -> Result<Self, anyhow::Error> {
+        let geofence = config.geofence;
+        if !(geofence.radius_m > 0.0 && geofence.max_alt_m > 0.0) {
+            return Err(anyhow::anyhow!(
+                "Geofence radius and ceiling must be positive, got {} and {}",
+                geofence.radius_m,
+                geofence.max_alt_m
+            ));
+        }
+        if !(-90.0..=90.0).contains(&geofence.center_lat)
+            || !(-180.0..=180.0).contains(&geofence.center_lon)
+        {
+            return Err(anyhow::anyhow!(
+                "Geofence center ({}, {}) is not a valid GPS position",
+                geofence.center_lat,
+                geofence.center_lon
+            ));
+        }
+        if !config.violation_grace_s.is_finite() || config.violation_grace_s < 0.0 {
+            return Err(anyhow::anyhow!(
+                "Geofence violation grace period must be a non-negative number, got {}",
+                config.violation_grace_s
+            ));
+        }
+
+        Ok(Self::new(geofence).with_violation_grace_s(config.violation_grace_s))
+    }
+}
+
+impl Task for ExecTaskGeofence {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!(
+            "ExecTaskGeofence initialized ({:.0} m radius, {:.0} m ceiling)",
+            self.geofence.radius_m, self.geofence.max_alt_m
+        );
+        self.violation_started_ms = None;
+        self.land_requested = false;
+
+        tx.send(subscribe!("mavlink/global_position_int"))?;
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            if record.try_get_topic().ok().as_deref() != Some("mavlink/global_position_int") {
+                continue;
+            }
+
+            let positions: Vec<GLOBAL_POSITION_INT_DATA> = record.to_serde().unwrap_or_default();
+            for position in &positions {
+                self.check_position(position, &tx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskGeofence cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    const CENTER_LAT: f64 = 47.397742;
+    const CENTER_LON: f64 = 8.545594;
+
+    /// Position `north_m` north of the fence center, as recorded by the autopilot
+    fn position_record(
+        time_boot_ms: u32,
+        north_m: f64,
+        alt_m: f32,
+    ) -> pubsub::message::record::Record {
+        let lat = CENTER_LAT + (north_m / 6_371_000.0).to_degrees();
+        let position = GLOBAL_POSITION_INT_DATA {
+            time_boot_ms,
+            lat: (lat * 1e7) as i32,
+            lon: (CENTER_LON * 1e7) as i32,
+            relative_alt: (alt_m * 1000.0) as i32,
+            ..Default::default()
+        };
+        publish!("mavlink/global_position_int", &position)
+    }
+
+    #[test]
+    fn test_geofence_violation_triggers_land() {
+        let config = ExecConfig::new()
+            .with_geofence(Geofence::new(CENTER_LAT, CENTER_LON, 100.0, 50.0))
+            .with_geofence_violation_grace_s(2.0);
+        let mut task = ExecTaskGeofence::from_config(&config).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        // Fly out through the fence, linger, come back, then climb through the ceiling
+        let flight = [
+            (0, 50.0, 20.0),
+            (1_000, 90.0, 20.0),
+            (2_000, 120.0, 20.0),
+            (3_000, 130.0, 20.0),
+            (4_000, 125.0, 20.0),
+            (5_000, 110.0, 20.0),
+            (6_000, 80.0, 20.0),
+            (7_000, 80.0, 60.0),
+            (9_500, 80.0, 55.0),
+        ];
+        let records = flight
+            .iter()
+            .map(|&(time_ms, north_m, alt_m)| position_record(time_ms, north_m, alt_m))
+            .collect();
+        task.run(records, tx.clone(), meta_tx.clone()).unwrap();
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        let topics: Vec<String> = sent.iter().map(|r| r.try_get_topic().unwrap()).collect();
+        assert_eq!(
+            topics,
+            vec![
+                "vehicle/geofence_violation",
+                "vehicle/geofence_violation",
+                "vehicle/geofence_violation",
+                // 2s after leaving the fence
+                "auto/stage",
+                "vehicle/geofence_violation",
+                "vehicle/geofence_violation",
+                "vehicle/geofence_violation",
+                "auto/stage",
+            ]
+        );
+
+        let violations: Vec<GeofenceViolation> = sent
+            .iter()
+            .filter(|r| r.try_get_topic().unwrap() == "vehicle/geofence_violation")
+            .flat_map(|r| r.to_serde::<GeofenceViolation>().unwrap())
+            .collect();
+        assert!((violations[0].distance_outside_m - 20.0).abs() < 0.5);
+        assert_eq!(violations[0].altitude_over_m, 0.0);
+        assert_eq!(violations[4].distance_outside_m, 0.0);
+        assert!((violations[4].altitude_over_m - 10.0).abs() < 0.01);
+
+        let stage: Vec<AutoStageMessage> = sent[3].to_serde().unwrap();
+        assert_eq!(stage[0].stage, AutoStage::AutoLand);
+    }
+
+    #[test]
+    fn test_geofence_from_json_config() {
+        let task = pubsub::tasks::configurable::task_from_json::<ExecTaskGeofence>(&format!(
+            r#"{{"center_lat": {}, "center_lon": {}, "radius_m": 100.0, "max_alt_m": 50.0}}"#,
+            CENTER_LAT, CENTER_LON
+        ))
+        .unwrap();
+        assert_eq!(
+            task.geofence,
+            Geofence::new(CENTER_LAT, CENTER_LON, 100.0, 50.0)
+        );
+        assert_eq!(task.violation_grace_s, DEFAULT_GEOFENCE_VIOLATION_GRACE_S);
+
+        let task = pubsub::tasks::configurable::task_from_json::<ExecTaskGeofence>(
+            r#"{"center_lat": 0.0, "center_lon": 0.0, "radius_m": 10.0, "max_alt_m": 20.0, "violation_grace_s": 0.5}"#,
+        )
+        .unwrap();
+        assert_eq!(task.violation_grace_s, 0.5);
+
+        assert!(
+            pubsub::tasks::configurable::task_from_json::<ExecTaskGeofence>(
+                r#"{"center_lat": 0.0, "center_lon": 0.0, "radius_m": 0.0, "max_alt_m": 20.0}"#,
+            )
+            .is_err()
+        );
+        assert!(
+            pubsub::tasks::configurable::task_from_json::<ExecTaskGeofence>(
+                r#"{"center_lat": 95.0, "center_lon": 0.0, "radius_m": 10.0, "max_alt_m": 20.0}"#,
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use log::{debug, info, warn};
+use mavlink::ardupilotmega::{GpsFixType, GPS_RAW_INT_DATA};
+use pubsub::{
+    message::error_record::{ErrorRecord, ErrorSeverity},
+    publish, publish_error, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+
+/// Published on `vehicle/gps_quality` for every `GPS_RAW_INT` message
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GpsQuality {
+    /// `GpsFixType` as its MAVLink value
+    pub fix_type: u8,
+    pub satellites_visible: u8,
+    /// Horizontal dilution of precision, None when unknown
+    pub hdop: Option<f32>,
+    /// Vertical dilution of precision, None when unknown
+    pub vdop: Option<f32>,
+    pub has_3d_fix: bool,
+}
+
+impl GpsQuality {
+    pub fn from_gps_raw(gps: &GPS_RAW_INT_DATA) -> Self {
+        // GPS_RAW_INT reports DOP scaled by 100, UINT16_MAX if unknown
+        let dop = |value: u16| (value != u16::MAX).then(|| value as f32 / 100.0);
+        let fix_type = gps.fix_type as u8;
+        Self {
+            fix_type,
+            satellites_visible: gps.satellites_visible,
+            hdop: dop(gps.eph),
+            vdop: dop(gps.epv),
+            has_3d_fix: fix_type >= GpsFixType::GPS_FIX_TYPE_3D_FIX as u8,
+        }
+    }
+}
+
+/// Task that publishes the GPS fix quality and reports when the 3D fix is lost
+pub struct ExecTaskGpsMonitor {
+    info: TaskInfo,
+    has_3d_fix: bool,
+}
+
+impl ExecTaskGpsMonitor {
+    pub fn new() -> Self {
+        Self {
+            info: task_info!(ExecTaskGpsMonitor),
+            has_3d_fix: false,
+        }
+    }
+}
+
+impl Task for ExecTaskGpsMonitor {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!("ExecTaskGpsMonitor initialized");
+        self.has_3d_fix = false;
+
+        tx.send(subscribe!("mavlink/gps_raw_int"))?;
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            if record.try_get_topic().ok().as_deref() != Some("mavlink/gps_raw_int") {
+                continue;
+            }
+
+            let gps_raw: Vec<GPS_RAW_INT_DATA> = record.to_serde().unwrap_or_default();
+            for gps in &gps_raw {
+                let quality = GpsQuality::from_gps_raw(gps);
+                debug!("GPS quality: {:?}", quality);
+                tx.send(publish!("vehicle/gps_quality", &quality))?;
+
+                // Only the drop from a 3D fix is reported, not a vehicle that never had one
+                if self.has_3d_fix && !quality.has_3d_fix {
+                    warn!(
+                        "GPS 3D fix lost (fix type {}, {} satellites)",
+                        quality.fix_type, quality.satellites_visible
+                    );
+                    let error = ErrorRecord::new(
+                        self.info.name.clone(),
+                        "gps_check",
+                        format!(
+                            "GPS 3D fix lost: fix type {}, {} satellites",
+                            quality.fix_type, quality.satellites_visible
+                        ),
+                        ErrorSeverity::Error,
+                    );
+                    let mut error_packet = publish_error!(&error);
+                    error_packet.set_topic("errors/gps_fix_lost".to_string())?;
+                    tx.send(error_packet)?;
+                }
+                self.has_3d_fix = quality.has_3d_fix;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskGpsMonitor cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn gps_record(fix_type: GpsFixType, satellites_visible: u8) -> pubsub::message::record::Record {
+        let gps = GPS_RAW_INT_DATA {
+            fix_type,
+            satellites_visible,
+            eph: 120,
+            epv: u16::MAX,
+            ..Default::default()
+        };
+        publish!("mavlink/gps_raw_int", &gps)
+    }
+
+    #[test]
+    fn test_fix_loss_reported_once() {
+        let mut task = ExecTaskGpsMonitor::new();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        for (fix_type, satellites) in [
+            (GpsFixType::GPS_FIX_TYPE_NO_FIX, 3),
+            (GpsFixType::GPS_FIX_TYPE_3D_FIX, 9),
+            (GpsFixType::GPS_FIX_TYPE_RTK_FIXED, 14),
+            (GpsFixType::GPS_FIX_TYPE_2D_FIX, 4),
+            (GpsFixType::GPS_FIX_TYPE_NO_FIX, 2),
+        ] {
+            task.run(
+                vec![gps_record(fix_type, satellites)],
+                tx.clone(),
+                meta_tx.clone(),
+            )
+            .unwrap();
+        }
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        let qualities: Vec<GpsQuality> = sent
+            .iter()
+            .filter(|r| r.try_get_topic().unwrap() == "vehicle/gps_quality")
+            .flat_map(|r| r.to_serde::<GpsQuality>().unwrap())
+            .collect();
+        let fixes: Vec<bool> = qualities.iter().map(|q| q.has_3d_fix).collect();
+        assert_eq!(fixes, vec![false, true, true, false, false]);
+        assert_eq!(qualities[1].fix_type, 3);
+        assert_eq!(qualities[1].hdop, Some(1.2));
+        assert_eq!(qualities[1].vdop, None);
+
+        let errors: Vec<ErrorRecord> = sent
+            .iter()
+            .filter(|r| r.try_get_topic().unwrap() == "errors/gps_fix_lost")
+            .flat_map(|r| r.to_serde::<ErrorRecord>().unwrap())
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].severity, ErrorSeverity::Error);
+    }
+}
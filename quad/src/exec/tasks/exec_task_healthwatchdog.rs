@@ -1,7 +1,8 @@
 use log::{debug, info, warn};
 use mavlink::ardupilotmega::{EkfStatusFlags, MavMessage, EKF_STATUS_REPORT_DATA, SYS_STATUS_DATA};
 use pubsub::{
-    publish, subscribe,
+    message::error_record::ErrorSeverity,
+    publish, publish_error, subscribe, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use serde::{Deserialize, Serialize};
@@ -24,9 +25,12 @@ pub struct ExecTaskHealthWatchdog {
 }
 
 impl ExecTaskHealthWatchdog {
+    /// Stages a freshly spawned ExecTaskHealthWatchdog can publish on exec/stage
+    pub const STAGE_TRANSITIONS: &[ExecStage] = &[ExecStage::AwaitingLock];
+
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecTaskHealthWatchdog"),
+            info: task_info!(ExecTaskHealthWatchdog),
             is_healthy: false,
             last_check_time: Instant::now(),
             check_interval: Duration::from_millis(500), // Check health every 500ms
@@ -160,6 +164,19 @@ impl Task for ExecTaskHealthWatchdog {
                 // If was healthy but now unhealthy, update status but don't demote
                 warn!("Health check failed, system no longer fully healthy");
                 self.is_healthy = false;
+
+                // Losing the EKF leaves nothing to navigate with, report it as critical
+                let (severity, message) = if self.ekf_healthy {
+                    (ErrorSeverity::Error, "System status no longer healthy")
+                } else {
+                    (ErrorSeverity::Critical, "EKF no longer healthy")
+                };
+                tx.send(publish_error!(
+                    self.info.name.clone(),
+                    "health_check",
+                    message,
+                    severity
+                ))?;
             }
         }
 
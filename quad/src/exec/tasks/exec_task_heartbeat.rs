@@ -1,12 +1,37 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use mavlink::ardupilotmega::{MavMessage, MavType, HEARTBEAT_DATA};
 use pubsub::{
-    publish, publish_json, subscribe,
-    tasks::{info::TaskInfo, task::Task},
+    message::error_record::ErrorSeverity,
+    publish, publish_error, subscribe, task_info,
+    tasks::{configurable::Configurable, info::TaskInfo, task::Task},
 };
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+/// Slowest heartbeat rate accepted by `ExecTaskHeartbeat`
+pub const MIN_HEARTBEAT_HZ: f64 = 0.5;
+/// Fastest heartbeat rate accepted by `ExecTaskHeartbeat`
+pub const MAX_HEARTBEAT_HZ: f64 = 50.0;
+
+/// Published on `exec/config/heartbeat_hz` to change the heartbeat rate at runtime
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HeartbeatConfig {
+    pub rate_hz: f64,
+}
+
+/// Interval between heartbeats at `rate_hz`, which must be within the allowed bounds
+fn heartbeat_interval(rate_hz: f64) -> Result<Duration, anyhow::Error> {
+    if !(MIN_HEARTBEAT_HZ..=MAX_HEARTBEAT_HZ).contains(&rate_hz) {
+        return Err(anyhow::anyhow!(
+            "Heartbeat rate {} Hz is outside {} to {} Hz",
+            rate_hz,
+            MIN_HEARTBEAT_HZ,
+            MAX_HEARTBEAT_HZ
+        ));
+    }
+    Ok(Duration::from_secs_f64(1.0 / rate_hz))
+}
+
 /// Task that sends regular MAVLink heartbeat messages
 pub struct ExecTaskHeartbeat {
     info: TaskInfo,
@@ -17,11 +42,86 @@ pub struct ExecTaskHeartbeat {
 impl ExecTaskHeartbeat {
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecHeartbeatTask"),
+            // Run first each tick so a busy tick never delays the heartbeat
+            info: task_info!(ExecTaskHeartbeat).with_priority(255),
             last_heartbeat_time: std::time::Instant::now(),
             heartbeat_interval: Duration::from_millis(1000), // 1Hz heartbeat rate
         }
     }
+
+    /// Heartbeat task sending at `rate_hz`, clamped to `MIN_HEARTBEAT_HZ`..=`MAX_HEARTBEAT_HZ`.
+    /// A NaN rate falls back to the default 1 Hz.
+    pub fn with_rate(rate_hz: f64) -> Self {
+        let clamped = if rate_hz.is_nan() {
+            1.0
+        } else {
+            rate_hz.clamp(MIN_HEARTBEAT_HZ, MAX_HEARTBEAT_HZ)
+        };
+        if clamped != rate_hz {
+            warn!(
+                "Heartbeat rate {} Hz out of range, using {} Hz",
+                rate_hz, clamped
+            );
+        }
+
+        let mut task = Self::new();
+        task.heartbeat_interval = Duration::from_secs_f64(1.0 / clamped);
+        task
+    }
+
+    /// Apply the `HeartbeatConfig`s of an `exec/config/heartbeat_hz` record.
+    /// An out of range rate keeps the current interval and publishes an error.
+    fn apply_config(
+        &mut self,
+        record: &pubsub::message::record::Record,
+        tx: &pubsub::tasks::task::TaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for config in record.to_serde::<HeartbeatConfig>()? {
+            match heartbeat_interval(config.rate_hz) {
+                Ok(interval) => {
+                    info!("Heartbeat rate set to {} Hz", config.rate_hz);
+                    self.heartbeat_interval = interval;
+                }
+                Err(e) => {
+                    warn!("Ignoring heartbeat config: {}", e);
+                    tx.send(publish_error!(
+                        self.info.name.clone(),
+                        "heartbeat_config",
+                        e.to_string(),
+                        ErrorSeverity::Warning
+                    ))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// JSON config for `ExecTaskHeartbeat`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ExecTaskHeartbeatConfig {
+    pub interval_ms: u64,
+}
+
+impl Default for ExecTaskHeartbeatConfig {
+    fn default() -> Self {
+        Self { interval_ms: 1000 }
+    }
+}
+
+impl Configurable for ExecTaskHeartbeat {
+    type Config = ExecTaskHeartbeatConfig;
+
+    fn new_from_config(config: Self::Config) -> Result<Self, anyhow::Error> {
+        if config.interval_ms == 0 {
+            return Err(anyhow::anyhow!("Heartbeat interval must be non-zero"));
+        }
+
+        let mut task = Self::new();
+        task.heartbeat_interval = Duration::from_millis(config.interval_ms);
+        Ok(task)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -42,6 +142,9 @@ impl Task for ExecTaskHeartbeat {
     ) -> Result<(), anyhow::Error> {
         info!("ExecTaskHeartbeat initialized");
 
+        // Config records are queued and applied with the next heartbeat
+        tx.send(subscribe!("exec/config/heartbeat_hz"))?;
+
         // Reset the timer
         self.last_heartbeat_time = std::time::Instant::now();
 
@@ -55,10 +158,16 @@ impl Task for ExecTaskHeartbeat {
 
     fn run(
         &mut self,
-        _inputs: Vec<pubsub::message::record::Record>,
+        inputs: Vec<pubsub::message::record::Record>,
         tx: pubsub::tasks::task::TaskChannel,
         _meta_tx: pubsub::tasks::task::MetaTaskChannel,
     ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            if record.try_get_topic().ok().as_deref() == Some("exec/config/heartbeat_hz") {
+                self.apply_config(record, &tx)?;
+            }
+        }
+
         debug!("ExecTaskHeartbeat sending heartbeat");
 
         // Create heartbeat message
@@ -94,3 +203,83 @@ impl Task for ExecTaskHeartbeat {
         &self.info
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pubsub::message::record::RecordFlag;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_task_info_from_type_name() {
+        let info = TaskInfo::from_type_name::<ExecTaskHeartbeat>();
+        assert_eq!(info.name, "ExecTaskHeartbeat");
+        assert_eq!(ExecTaskHeartbeat::new().get_task_info(), &info);
+    }
+
+    #[test]
+    fn test_heartbeat_from_json_config() {
+        let task = pubsub::tasks::configurable::task_from_json::<ExecTaskHeartbeat>(
+            r#"{"interval_ms": 250}"#,
+        )
+        .unwrap();
+        assert_eq!(task.heartbeat_interval, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_heartbeat_with_rate() {
+        assert_eq!(
+            ExecTaskHeartbeat::with_rate(4.0).heartbeat_interval,
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            ExecTaskHeartbeat::with_rate(1000.0).heartbeat_interval,
+            Duration::from_millis(20)
+        );
+        assert_eq!(
+            ExecTaskHeartbeat::with_rate(0.0).heartbeat_interval,
+            Duration::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_rate_config_message() {
+        let mut task = ExecTaskHeartbeat::new();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.init(tx.clone(), meta_tx.clone()).unwrap();
+        let subscription = rx.try_recv().unwrap();
+        assert_eq!(
+            subscription.try_get_topic().unwrap(),
+            "exec/config/heartbeat_hz"
+        );
+
+        let config = HeartbeatConfig { rate_hz: 10.0 };
+        task.run(
+            vec![publish!("exec/config/heartbeat_hz", &config)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert_eq!(task.heartbeat_interval, Duration::from_millis(100));
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].try_get_topic().unwrap(), "mavlink/send/heartbeat");
+
+        // Out of range, the rate is unchanged and an error is published
+        let config = HeartbeatConfig { rate_hz: 100.0 };
+        task.run(
+            vec![publish!("exec/config/heartbeat_hz", &config)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert_eq!(task.heartbeat_interval, Duration::from_millis(100));
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].get_flag().unwrap(), RecordFlag::ErrorPacket);
+        assert_eq!(sent[0].try_get_topic().unwrap(), "errors/ExecTaskHeartbeat");
+        assert_eq!(sent[1].try_get_topic().unwrap(), "mavlink/send/heartbeat");
+    }
+}
@@ -1,14 +1,18 @@
 use log::{debug, info, warn};
 use mavlink::ardupilotmega::{EkfStatusFlags, MavMessage, EKF_STATUS_REPORT_DATA};
 use pubsub::{
-    publish, subscribe,
+    publish, subscribe, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use std::time::{Duration, Instant};
 
-use crate::exec::{messages::ExecStageMessage, stage::ExecStage};
+use crate::exec::{
+    messages::ExecStageMessage, stage::ExecStage, tasks::exec_task_gpsmonitor::GpsQuality,
+};
 
-/// Task that monitors EKF lock status and updates exec stage to HealthyUnarmed when lock is achieved
+/// Task that monitors EKF lock status and updates exec stage to HealthyUnarmed when lock is achieved.
+/// Once `ExecTaskGpsMonitor` reports GPS quality, a 3D fix is also required for lock, and
+/// losing it returns the exec stage to AwaitingLock.
 pub struct ExecTaskLockWatchdog {
     info: TaskInfo,
     has_lock: bool,
@@ -16,17 +20,62 @@ pub struct ExecTaskLockWatchdog {
     check_interval: Duration,
     // Tracking subscribed data
     has_ekf_data: bool,
+    ekf_lock: bool,
+    /// Last reported GPS 3D fix, None until `vehicle/gps_quality` is received
+    gps_fix: Option<bool>,
 }
 
 impl ExecTaskLockWatchdog {
+    /// Stages a freshly spawned ExecTaskLockWatchdog can publish on exec/stage
+    pub const STAGE_TRANSITIONS: &[ExecStage] = &[
+        ExecStage::HealthyUnarmed,
+        // Losing the GPS fix falls back to AwaitingLock, but only after the lock was
+        // reported with HealthyUnarmed
+    ];
+
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecTaskLockWatchdog"),
+            info: task_info!(ExecTaskLockWatchdog),
             has_lock: false,
             last_check_time: Instant::now(),
             check_interval: Duration::from_millis(500), // Check lock every 500ms
             has_ekf_data: false,
+            ekf_lock: false,
+            gps_fix: None,
+        }
+    }
+
+    fn publish_stage(
+        &self,
+        stage: ExecStage,
+        tx: &pubsub::tasks::task::TaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        tx.send(publish!("exec/stage", &ExecStageMessage::new(stage)))?;
+        Ok(())
+    }
+
+    /// Promote or demote the lock after the EKF or GPS state changed
+    fn update_lock(&mut self, tx: &pubsub::tasks::task::TaskChannel) -> Result<(), anyhow::Error> {
+        if !self.has_ekf_data {
+            return Ok(());
+        }
+        let gps_fix_lost = self.gps_fix == Some(false);
+        let has_lock = self.ekf_lock && !gps_fix_lost;
+
+        if has_lock && !self.has_lock {
+            info!("EKF lock achieved, updating exec stage to HealthyUnarmed");
+            self.has_lock = true;
+            self.publish_stage(ExecStage::HealthyUnarmed, tx)?;
+        } else if !has_lock && self.has_lock {
+            self.has_lock = false;
+            if gps_fix_lost {
+                warn!("GPS 3D fix lost, returning exec stage to AwaitingLock");
+                self.publish_stage(ExecStage::AwaitingLock, tx)?;
+            } else {
+                warn!("EKF lock lost");
+            }
         }
+        Ok(())
     }
 
     /// Check if EKF has sufficient position lock
@@ -56,8 +105,9 @@ impl Task for ExecTaskLockWatchdog {
     ) -> Result<(), anyhow::Error> {
         info!("ExecTaskLockWatchdog initialized");
 
-        // Subscribe to the EKF status topics
+        // Subscribe to the EKF status and GPS quality topics
         tx.send(subscribe!("mavlink/ekf_status_report"))?;
+        tx.send(subscribe!("vehicle/gps_quality"))?;
 
         Ok(())
     }
@@ -85,22 +135,14 @@ impl Task for ExecTaskLockWatchdog {
                         record.to_serde().unwrap_or_default();
                     for status in ekf_status {
                         self.has_ekf_data = true;
-                        let has_lock = self.check_ekf_lock(&status);
-
-                        if has_lock && !self.has_lock {
-                            info!("EKF lock achieved, updating exec stage to HealthyUnarmed");
-                            self.has_lock = true;
-
-                            // Publish stage update to exec/stage
-                            let pub_packet = publish!(
-                                "exec/stage",
-                                &ExecStageMessage::new(ExecStage::HealthyUnarmed)
-                            );
-                            tx.send(pub_packet)?;
-                        } else if !has_lock && self.has_lock {
-                            warn!("EKF lock lost");
-                            self.has_lock = false;
-                        }
+                        self.ekf_lock = self.check_ekf_lock(&status);
+                        self.update_lock(&tx)?;
+                    }
+                } else if topic == "vehicle/gps_quality" {
+                    let gps_quality: Vec<GpsQuality> = record.to_serde().unwrap_or_default();
+                    for quality in gps_quality {
+                        self.gps_fix = Some(quality.has_3d_fix);
+                        self.update_lock(&tx)?;
                     }
                 }
             }
@@ -118,3 +160,59 @@ impl Task for ExecTaskLockWatchdog {
         &self.info
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn gps_quality_record(has_3d_fix: bool) -> pubsub::message::record::Record {
+        let quality = GpsQuality {
+            fix_type: if has_3d_fix { 3 } else { 1 },
+            satellites_visible: 8,
+            hdop: Some(1.0),
+            vdop: Some(1.5),
+            has_3d_fix,
+        };
+        publish!("vehicle/gps_quality", &quality)
+    }
+
+    #[test]
+    fn test_gps_fix_loss_returns_to_awaiting_lock() {
+        let mut task = ExecTaskLockWatchdog::new();
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        let ekf_status = EKF_STATUS_REPORT_DATA {
+            flags: EkfStatusFlags::EKF_ATTITUDE
+                | EkfStatusFlags::EKF_VELOCITY_HORIZ
+                | EkfStatusFlags::EKF_POS_HORIZ_ABS,
+            ..Default::default()
+        };
+        let ekf_record = publish!("mavlink/ekf_status_report", &ekf_status);
+
+        for inputs in [
+            vec![gps_quality_record(true), ekf_record.clone()],
+            vec![gps_quality_record(false)],
+            vec![ekf_record.clone()],
+            vec![gps_quality_record(true)],
+        ] {
+            task.run(inputs, tx.clone(), meta_tx.clone()).unwrap();
+        }
+
+        let stages: Vec<ExecStage> = rx
+            .try_iter()
+            .filter(|r| r.try_get_topic().unwrap() == "exec/stage")
+            .flat_map(|r| r.to_serde::<ExecStageMessage>().unwrap())
+            .map(|m| m.stage)
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                ExecStage::HealthyUnarmed,
+                ExecStage::AwaitingLock,
+                ExecStage::HealthyUnarmed,
+            ]
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use log::{debug, info, warn};
+use mavlink::ardupilotmega::{MavMessage, PARAM_REQUEST_READ_DATA, PARAM_VALUE_DATA};
+use pubsub::{
+    message::{error_record::ErrorSeverity, record::Record},
+    publish, publish_error, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How long the param tasks wait for the `PARAM_VALUE` answer before giving up
+pub const PARAM_RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Length of the null padded `param_id` of the MAVLink PARAM_* messages
+const PARAM_ID_LEN: usize = 16;
+
+/// Published on `vehicle/param/{id}` once the autopilot reported a parameter
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ParamValue {
+    pub id: String,
+    pub value: f32,
+    /// `MavParamType` of the parameter as reported by the autopilot
+    #[serde(rename = "type")]
+    pub param_type: u8,
+    pub count: u16,
+    pub index: u16,
+}
+
+impl From<&PARAM_VALUE_DATA> for ParamValue {
+    fn from(data: &PARAM_VALUE_DATA) -> Self {
+        Self {
+            id: decode_param_id(&data.param_id),
+            value: data.param_value,
+            param_type: data.param_type as u8,
+            count: data.param_count,
+            index: data.param_index,
+        }
+    }
+}
+
+/// Topic a parameter is published on after reading or writing it
+pub fn param_topic(param_id: &str) -> String {
+    format!("vehicle/param/{}", param_id)
+}
+
+/// Encode a parameter name like `WPNAV_SPEED` into a MAVLink `param_id`
+pub fn encode_param_id(param_id: &str) -> Result<[u8; PARAM_ID_LEN], anyhow::Error> {
+    if param_id.is_empty() || param_id.len() > PARAM_ID_LEN || !param_id.is_ascii() {
+        return Err(anyhow::anyhow!(
+            "Invalid parameter id '{}', expected 1 to {} ASCII characters",
+            param_id,
+            PARAM_ID_LEN
+        ));
+    }
+    let mut encoded = [0; PARAM_ID_LEN];
+    encoded[..param_id.len()].copy_from_slice(param_id.as_bytes());
+    Ok(encoded)
+}
+
+/// Decode a MAVLink `param_id`, stopping at the first null character
+pub fn decode_param_id(param_id: &[u8]) -> String {
+    param_id
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as char)
+        .collect()
+}
+
+/// The `PARAM_VALUE` for `param_id` in `mavlink/param_value` records, if any
+pub(crate) fn find_param_value(inputs: &[Record], param_id: &str) -> Option<ParamValue> {
+    inputs
+        .iter()
+        .filter(|r| r.try_get_topic().ok().as_deref() == Some("mavlink/param_value"))
+        .flat_map(|r| r.to_serde::<PARAM_VALUE_DATA>().unwrap_or_default())
+        .map(|data| ParamValue::from(&data))
+        .find(|value| value.id == param_id)
+}
+
+/// Task that requests a single autopilot parameter and publishes it on `vehicle/param/{id}`.
+/// Publishes an error if the autopilot did not answer within the timeout.
+pub struct ExecTaskParamRead {
+    info: TaskInfo,
+    param_id: String,
+    timeout: Duration,
+    requested_at: Instant,
+    done: bool,
+}
+
+impl ExecTaskParamRead {
+    pub fn new(param_id: &str) -> Self {
+        Self {
+            info: task_info!(ExecTaskParamRead),
+            param_id: param_id.to_string(),
+            timeout: PARAM_RESPONSE_TIMEOUT,
+            requested_at: Instant::now(),
+            done: false,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn build_request(&self) -> Result<MavMessage, anyhow::Error> {
+        Ok(MavMessage::PARAM_REQUEST_READ(PARAM_REQUEST_READ_DATA {
+            // -1 to look the parameter up by `param_id`
+            param_index: -1,
+            target_system: 0,
+            target_component: 0,
+            param_id: encode_param_id(&self.param_id)?,
+        }))
+    }
+}
+
+impl Task for ExecTaskParamRead {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!("ExecTaskParamRead initialized, reading {}", self.param_id);
+        let request = self.build_request()?;
+
+        // Subscribe first so the answer can't arrive before the subscription
+        tx.send(subscribe!("mavlink/param_value"))?;
+        tx.send(publish!("mavlink/send/param_request_read", &request))?;
+        self.requested_at = Instant::now();
+        self.done = false;
+
+        Ok(())
+    }
+
+    fn should_run(&self) -> Result<bool, anyhow::Error> {
+        Ok(!self.done)
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(value) = find_param_value(&inputs, &self.param_id) {
+            info!("Parameter {} = {}", value.id, value.value);
+            tx.send(publish!(param_topic(&self.param_id), &value))?;
+            self.done = true;
+        } else if self.requested_at.elapsed() >= self.timeout {
+            warn!("No answer reading parameter {}", self.param_id);
+            tx.send(publish_error!(
+                self.info.name.clone(),
+                "param_read",
+                format!(
+                    "No PARAM_VALUE for {} after {:.1}s",
+                    self.param_id,
+                    self.timeout.as_secs_f32()
+                ),
+                ErrorSeverity::Error
+            ))?;
+            self.done = true;
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskParamRead cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mavlink::ardupilotmega::MavParamType;
+    use pubsub::message::record::RecordFlag;
+    use std::sync::mpsc;
+
+    fn param_value_record(param_id: &str, value: f32) -> Record {
+        let data = PARAM_VALUE_DATA {
+            param_value: value,
+            param_count: 1200,
+            param_index: 42,
+            param_id: encode_param_id(param_id).unwrap(),
+            param_type: MavParamType::MAV_PARAM_TYPE_REAL32,
+        };
+        publish!("mavlink/param_value", &data)
+    }
+
+    #[test]
+    fn test_param_id_encoding() {
+        let encoded = encode_param_id("WPNAV_SPEED").unwrap();
+        assert_eq!(&encoded[..11], b"WPNAV_SPEED");
+        assert_eq!(encoded[11], 0);
+        assert_eq!(decode_param_id(&encoded), "WPNAV_SPEED");
+
+        assert!(encode_param_id("").is_err());
+        assert!(encode_param_id("A_PARAMETER_NAME_TOO_LONG").is_err());
+    }
+
+    #[test]
+    fn test_param_read() {
+        let mut task = ExecTaskParamRead::new("WPNAV_SPEED");
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.init(tx.clone(), meta_tx.clone()).unwrap();
+        let request = rx
+            .try_iter()
+            .find(|r| r.try_get_topic().unwrap() == "mavlink/send/param_request_read")
+            .unwrap();
+        let request: Vec<MavMessage> = request.to_serde().unwrap();
+        let MavMessage::PARAM_REQUEST_READ(request) = &request[0] else {
+            panic!("Expected PARAM_REQUEST_READ, got {:?}", request[0]);
+        };
+        assert_eq!(decode_param_id(&request.param_id), "WPNAV_SPEED");
+        assert_eq!(request.param_index, -1);
+
+        // Other parameters are ignored
+        task.run(
+            vec![param_value_record("WPNAV_ACCEL", 250.0)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert!(task.should_run().unwrap());
+        assert_eq!(rx.try_iter().count(), 0);
+
+        task.run(
+            vec![param_value_record("WPNAV_SPEED", 500.0)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert!(!task.should_run().unwrap());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].try_get_topic().unwrap(),
+            "vehicle/param/WPNAV_SPEED"
+        );
+        let values: Vec<ParamValue> = sent[0].to_serde().unwrap();
+        assert_eq!(
+            values[0],
+            ParamValue {
+                id: "WPNAV_SPEED".to_string(),
+                value: 500.0,
+                param_type: MavParamType::MAV_PARAM_TYPE_REAL32 as u8,
+                count: 1200,
+                index: 42,
+            }
+        );
+    }
+
+    #[test]
+    fn test_param_read_timeout() {
+        let mut task = ExecTaskParamRead::new("WPNAV_SPEED").with_timeout(Duration::ZERO);
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.init(tx.clone(), meta_tx.clone()).unwrap();
+        assert_eq!(rx.try_iter().count(), 2);
+
+        task.run(Vec::new(), tx.clone(), meta_tx.clone()).unwrap();
+        assert!(!task.should_run().unwrap());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].get_flag().unwrap(), RecordFlag::ErrorPacket);
+        assert_eq!(sent[0].try_get_topic().unwrap(), "errors/ExecTaskParamRead");
+    }
+}
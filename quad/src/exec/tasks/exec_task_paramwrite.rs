@@ -0,0 +1,230 @@
+use log::{debug, info, warn};
+use mavlink::ardupilotmega::{MavMessage, MavParamType, PARAM_SET_DATA};
+use pubsub::{
+    message::error_record::ErrorSeverity,
+    publish, publish_error, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use std::time::{Duration, Instant};
+
+use super::exec_task_paramread::{
+    encode_param_id, find_param_value, param_topic, PARAM_RESPONSE_TIMEOUT,
+};
+
+/// Task that sets a single autopilot parameter and publishes the value the autopilot
+/// acknowledged on `vehicle/param/{id}`.
+/// Publishes an error if the autopilot did not answer within the timeout or kept another value.
+pub struct ExecTaskParamWrite {
+    info: TaskInfo,
+    param_id: String,
+    value: f32,
+    param_type: MavParamType,
+    timeout: Duration,
+    requested_at: Instant,
+    done: bool,
+}
+
+impl ExecTaskParamWrite {
+    pub fn new(param_id: &str, value: f32) -> Self {
+        Self {
+            info: task_info!(ExecTaskParamWrite),
+            param_id: param_id.to_string(),
+            value,
+            param_type: MavParamType::MAV_PARAM_TYPE_REAL32,
+            timeout: PARAM_RESPONSE_TIMEOUT,
+            requested_at: Instant::now(),
+            done: false,
+        }
+    }
+
+    /// Type sent with the value, ArduPilot ignores it and keeps the type of the parameter
+    pub fn with_param_type(mut self, param_type: MavParamType) -> Self {
+        self.param_type = param_type;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn build_request(&self) -> Result<MavMessage, anyhow::Error> {
+        Ok(MavMessage::PARAM_SET(PARAM_SET_DATA {
+            param_value: self.value,
+            target_system: 0,
+            target_component: 0,
+            param_id: encode_param_id(&self.param_id)?,
+            param_type: self.param_type,
+        }))
+    }
+}
+
+impl Task for ExecTaskParamWrite {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!(
+            "ExecTaskParamWrite initialized, setting {} to {}",
+            self.param_id, self.value
+        );
+        let request = self.build_request()?;
+
+        // The autopilot answers a PARAM_SET with the PARAM_VALUE it stored
+        tx.send(subscribe!("mavlink/param_value"))?;
+        tx.send(publish!("mavlink/send/param_set", &request))?;
+        self.requested_at = Instant::now();
+        self.done = false;
+
+        Ok(())
+    }
+
+    fn should_run(&self) -> Result<bool, anyhow::Error> {
+        Ok(!self.done)
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(value) = find_param_value(&inputs, &self.param_id) {
+            tx.send(publish!(param_topic(&self.param_id), &value))?;
+            if value.value == self.value {
+                info!("Parameter {} set to {}", value.id, value.value);
+            } else {
+                // E.g. rounded for an integer parameter or clamped to its range
+                warn!(
+                    "Parameter {} is {} after setting it to {}",
+                    value.id, value.value, self.value
+                );
+                tx.send(publish_error!(
+                    self.info.name.clone(),
+                    "param_write",
+                    format!(
+                        "Parameter {} is {} after setting it to {}",
+                        value.id, value.value, self.value
+                    ),
+                    ErrorSeverity::Warning
+                ))?;
+            }
+            self.done = true;
+        } else if self.requested_at.elapsed() >= self.timeout {
+            warn!("No answer setting parameter {}", self.param_id);
+            tx.send(publish_error!(
+                self.info.name.clone(),
+                "param_write",
+                format!(
+                    "No PARAM_VALUE for {} after {:.1}s",
+                    self.param_id,
+                    self.timeout.as_secs_f32()
+                ),
+                ErrorSeverity::Error
+            ))?;
+            self.done = true;
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskParamWrite cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::tasks::exec_task_paramread::ParamValue;
+    use mavlink::ardupilotmega::PARAM_VALUE_DATA;
+    use pubsub::message::record::{Record, RecordFlag};
+    use std::sync::mpsc;
+
+    fn param_value_record(param_id: &str, value: f32) -> Record {
+        let data = PARAM_VALUE_DATA {
+            param_value: value,
+            param_count: 1200,
+            param_index: 42,
+            param_id: encode_param_id(param_id).unwrap(),
+            param_type: MavParamType::MAV_PARAM_TYPE_REAL32,
+        };
+        publish!("mavlink/param_value", &data)
+    }
+
+    #[test]
+    fn test_param_write() {
+        let mut task = ExecTaskParamWrite::new("WPNAV_SPEED", 750.0);
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.init(tx.clone(), meta_tx.clone()).unwrap();
+        let request = rx
+            .try_iter()
+            .find(|r| r.try_get_topic().unwrap() == "mavlink/send/param_set")
+            .unwrap();
+        let request: Vec<MavMessage> = request.to_serde().unwrap();
+        let MavMessage::PARAM_SET(request) = &request[0] else {
+            panic!("Expected PARAM_SET, got {:?}", request[0]);
+        };
+        assert_eq!(request.param_value, 750.0);
+        assert_eq!(&request.param_id[..11], b"WPNAV_SPEED");
+
+        task.run(
+            vec![param_value_record("WPNAV_SPEED", 750.0)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        assert!(!task.should_run().unwrap());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].try_get_topic().unwrap(),
+            "vehicle/param/WPNAV_SPEED"
+        );
+        let values: Vec<ParamValue> = sent[0].to_serde().unwrap();
+        assert_eq!(values[0].value, 750.0);
+    }
+
+    #[test]
+    fn test_param_write_rejected_and_timeout() {
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        // The autopilot clamped the value
+        let mut task = ExecTaskParamWrite::new("WPNAV_SPEED", 5000.0);
+        task.init(tx.clone(), meta_tx.clone()).unwrap();
+        assert_eq!(rx.try_iter().count(), 2);
+        task.run(
+            vec![param_value_record("WPNAV_SPEED", 2000.0)],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].get_flag().unwrap(), RecordFlag::ErrorPacket);
+
+        let mut task = ExecTaskParamWrite::new("WPNAV_SPEED", 750.0).with_timeout(Duration::ZERO);
+        task.init(tx.clone(), meta_tx.clone()).unwrap();
+        assert_eq!(rx.try_iter().count(), 2);
+        task.run(Vec::new(), tx.clone(), meta_tx.clone()).unwrap();
+        assert!(!task.should_run().unwrap());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].get_flag().unwrap(), RecordFlag::ErrorPacket);
+        assert_eq!(
+            sent[0].try_get_topic().unwrap(),
+            "errors/ExecTaskParamWrite"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use log::{debug, error, info};
+use mavlink::ardupilotmega::{
+    MavFrame, MavMessage, PositionTargetTypemask, LOCAL_POSITION_NED_DATA,
+    SET_POSITION_TARGET_LOCAL_NED_DATA,
+};
+use pubsub::{
+    publish, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Position (NED, meters) that the task is holding
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct HoldPoint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Task that captures the current local position and keeps commanding the vehicle back to it
+pub struct ExecTaskPositionHold {
+    info: TaskInfo,
+    hold_point: Option<HoldPoint>,
+    fixed_altitude: Option<f32>,
+    released: bool,
+    last_send_time: Instant,
+    send_interval: Duration,
+}
+
+impl ExecTaskPositionHold {
+    pub fn new() -> Self {
+        Self {
+            info: task_info!(ExecTaskPositionHold),
+            hold_point: None,
+            fixed_altitude: None,
+            released: false,
+            last_send_time: Instant::now(),
+            send_interval: Duration::from_millis(500), // Re-send hold target every 500ms
+        }
+    }
+
+    /// Create a hold task that keeps the captured x/y but holds a fixed altitude (meters, positive up)
+    pub fn new_with_altitude(alt: f32) -> Self {
+        let mut task = Self::new();
+        task.fixed_altitude = Some(alt);
+        task
+    }
+
+    /// Get the currently held position, if one has been captured
+    pub fn hold_point(&self) -> Option<HoldPoint> {
+        self.hold_point
+    }
+
+    /// Capture the hold point from a local position report (only the first one is used)
+    fn capture_hold_point(&mut self, position: &LOCAL_POSITION_NED_DATA) {
+        if self.hold_point.is_some() {
+            return;
+        }
+
+        // NED frame: altitude up is negative z
+        let z = match self.fixed_altitude {
+            Some(alt) => -alt,
+            None => position.z,
+        };
+
+        let hold_point = HoldPoint {
+            x: position.x,
+            y: position.y,
+            z,
+        };
+        info!("Position hold captured at {:?}", hold_point);
+        self.hold_point = Some(hold_point);
+    }
+
+    /// Build the position target message for the held point
+    fn build_hold_message(&self, hold_point: &HoldPoint) -> MavMessage {
+        // Only the position fields are used, ignore velocity / acceleration / yaw
+        let type_mask = PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+
+        MavMessage::SET_POSITION_TARGET_LOCAL_NED(SET_POSITION_TARGET_LOCAL_NED_DATA {
+            x: hold_point.x,
+            y: hold_point.y,
+            z: hold_point.z,
+            type_mask,
+            coordinate_frame: MavFrame::MAV_FRAME_LOCAL_NED,
+            target_system: 0,
+            target_component: 0,
+            ..Default::default()
+        })
+    }
+}
+
+impl Task for ExecTaskPositionHold {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!("ExecTaskPositionHold initialized");
+        self.hold_point = None;
+        self.released = false;
+        self.last_send_time = Instant::now();
+
+        tx.send(subscribe!("mavlink/local_position_ned"))?;
+        tx.send(subscribe!("exec/position_hold_release"))?;
+
+        Ok(())
+    }
+
+    fn should_run(&self) -> Result<bool, anyhow::Error> {
+        // Run every tick until a hold point is captured, then at the send interval
+        Ok(!self.released
+            && (self.hold_point.is_none() || self.last_send_time.elapsed() >= self.send_interval))
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            if let Ok(topic) = record.try_get_topic() {
+                if topic == "exec/position_hold_release" {
+                    info!("Position hold released");
+                    self.released = true;
+                    return Ok(());
+                }
+
+                if topic == "mavlink/local_position_ned" {
+                    let positions: Vec<LOCAL_POSITION_NED_DATA> =
+                        record.to_serde().unwrap_or_default();
+                    if let Some(position) = positions.first() {
+                        self.capture_hold_point(position);
+                    }
+                }
+            }
+        }
+
+        let hold_point = match self.hold_point {
+            Some(hold_point) => hold_point,
+            None => return Ok(()),
+        };
+
+        if self.last_send_time.elapsed() < self.send_interval {
+            return Ok(());
+        }
+
+        debug!("Sending position hold target {:?}", hold_point);
+        let hold_msg = self.build_hold_message(&hold_point);
+        let pub_packet = publish!("mavlink/send/set_position_target_local_ned", &hold_msg);
+        if let Err(e) = tx.send(pub_packet) {
+            error!("Failed to send position hold target: {}", e);
+            return Err(anyhow::anyhow!(
+                "Failed to send position hold target: {}",
+                e
+            ));
+        }
+        self.last_send_time = Instant::now();
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskPositionHold cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn position_record(x: f32, y: f32, z: f32) -> pubsub::message::record::Record {
+        let position = LOCAL_POSITION_NED_DATA {
+            x,
+            y,
+            z,
+            ..Default::default()
+        };
+        publish!("mavlink/local_position_ned", &position)
+    }
+
+    #[test]
+    fn test_hold_point_matches_first_position() {
+        let mut task = ExecTaskPositionHold::new();
+        let (tx, _rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.run(
+            vec![
+                position_record(1.0, 2.0, -3.0),
+                position_record(5.0, 5.0, -5.0),
+            ],
+            tx.clone(),
+            meta_tx.clone(),
+        )
+        .unwrap();
+        task.run(vec![position_record(9.0, 9.0, -9.0)], tx, meta_tx)
+            .unwrap();
+
+        assert_eq!(
+            task.hold_point(),
+            Some(HoldPoint {
+                x: 1.0,
+                y: 2.0,
+                z: -3.0
+            })
+        );
+    }
+
+    #[test]
+    fn test_hold_point_fixed_altitude() {
+        let mut task = ExecTaskPositionHold::new_with_altitude(10.0);
+        let (tx, _rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.run(vec![position_record(1.0, 2.0, -3.0)], tx, meta_tx)
+            .unwrap();
+
+        assert_eq!(task.hold_point().unwrap().z, -10.0);
+    }
+}
@@ -1,7 +1,7 @@
 use log::{debug, error, info};
 use mavlink::ardupilotmega::{MavMessage, REQUEST_DATA_STREAM_DATA};
 use pubsub::{
-    publish, publish_json, subscribe,
+    publish, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use std::time::Duration;
@@ -15,7 +15,7 @@ pub struct ExecTaskRequestStream {
 impl ExecTaskRequestStream {
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecRequestStreamTask"),
+            info: task_info!(ExecTaskRequestStream),
             has_run: false,
         }
     }
@@ -1,7 +1,7 @@
 use log::{debug, error, info};
 use mavlink::ardupilotmega::{MavCmd, MavMessage, COMMAND_LONG_DATA};
 use pubsub::{
-    publish, subscribe,
+    publish, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use std::time::{Duration, Instant};
@@ -21,7 +21,7 @@ pub struct ExecTaskSendArm {
 impl ExecTaskSendArm {
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecTaskSendArm"),
+            info: task_info!(ExecTaskSendArm),
             command_sent: false,
             last_attempt_time: Instant::now(),
             retry_interval: Duration::from_secs(2), // Retry every 2 seconds
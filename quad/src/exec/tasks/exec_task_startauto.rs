@@ -1,6 +1,6 @@
 use log::{debug, info};
 use pubsub::{
-    publish, subscribe,
+    publish, subscribe, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use serde::{Deserialize, Serialize};
@@ -19,7 +19,7 @@ pub struct ExecTaskStartAuto {
 impl ExecTaskStartAuto {
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecTaskStartAuto"),
+            info: task_info!(ExecTaskStartAuto),
             auto_stage: AutoStage::AutoShadow,
         }
     }
@@ -0,0 +1,245 @@
+use log::{debug, error, info, warn};
+use mavlink::ardupilotmega::{
+    MavFrame, MavMessage, PositionTargetTypemask, LOCAL_POSITION_NED_DATA,
+    SET_POSITION_TARGET_LOCAL_NED_DATA,
+};
+use pubsub::{
+    publish, subscribe, task_info,
+    tasks::{info::TaskInfo, task::Task},
+};
+use serde::{Deserialize, Serialize};
+
+/// Speeds (m/s) are released once they drop below this fraction of the limits
+const RESET_FRACTION: f32 = 0.9;
+
+/// Published on `exec/velocity_limit_exceeded` when the vehicle goes over a speed limit
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct VelocityLimitEvent {
+    pub ground_speed: f32,
+    pub vertical_speed: f32,
+}
+
+/// Task that watches the local velocity and commands a capped velocity while over the limits
+pub struct ExecTaskVelocityLimiter {
+    info: TaskInfo,
+    max_horizontal_ms: f32,
+    max_vertical_ms: f32,
+    limiting: bool,
+}
+
+impl ExecTaskVelocityLimiter {
+    pub fn new(max_horizontal_ms: f32, max_vertical_ms: f32) -> Self {
+        Self {
+            info: task_info!(ExecTaskVelocityLimiter),
+            max_horizontal_ms,
+            max_vertical_ms,
+            limiting: false,
+        }
+    }
+
+    /// Whether a velocity override is currently being sent
+    pub fn is_limiting(&self) -> bool {
+        self.limiting
+    }
+
+    /// Update the limit state from a position report, returns true while the override is needed
+    fn update_limiting(&mut self, ground_speed: f32, vertical_speed: f32) -> bool {
+        if ground_speed > self.max_horizontal_ms || vertical_speed > self.max_vertical_ms {
+            self.limiting = true;
+        } else if ground_speed < self.max_horizontal_ms * RESET_FRACTION
+            && vertical_speed < self.max_vertical_ms * RESET_FRACTION
+        {
+            if self.limiting {
+                info!("Velocity back within limits");
+            }
+            self.limiting = false;
+        }
+        self.limiting
+    }
+
+    /// Build a velocity target with the current velocity scaled down to the limits
+    fn build_override_message(&self, position: &LOCAL_POSITION_NED_DATA) -> MavMessage {
+        let ground_speed = position.vx.hypot(position.vy);
+        let scale = if ground_speed > self.max_horizontal_ms {
+            self.max_horizontal_ms / ground_speed
+        } else {
+            1.0
+        };
+
+        // Only the velocity fields are used, ignore position / acceleration / yaw
+        let type_mask = PositionTargetTypemask::POSITION_TARGET_TYPEMASK_X_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_Y_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_Z_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+
+        MavMessage::SET_POSITION_TARGET_LOCAL_NED(SET_POSITION_TARGET_LOCAL_NED_DATA {
+            vx: position.vx * scale,
+            vy: position.vy * scale,
+            vz: position
+                .vz
+                .clamp(-self.max_vertical_ms, self.max_vertical_ms),
+            type_mask,
+            coordinate_frame: MavFrame::MAV_FRAME_LOCAL_NED,
+            target_system: 0,
+            target_component: 0,
+            ..Default::default()
+        })
+    }
+}
+
+impl Task for ExecTaskVelocityLimiter {
+    fn init(
+        &mut self,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        info!(
+            "ExecTaskVelocityLimiter initialized (horizontal: {} m/s, vertical: {} m/s)",
+            self.max_horizontal_ms, self.max_vertical_ms
+        );
+        self.limiting = false;
+
+        tx.send(subscribe!("mavlink/local_position_ned"))?;
+
+        Ok(())
+    }
+
+    fn run(
+        &mut self,
+        inputs: Vec<pubsub::message::record::Record>,
+        tx: pubsub::tasks::task::TaskChannel,
+        _meta_tx: pubsub::tasks::task::MetaTaskChannel,
+    ) -> Result<(), anyhow::Error> {
+        for record in &inputs {
+            if record.try_get_topic().ok().as_deref() != Some("mavlink/local_position_ned") {
+                continue;
+            }
+
+            let positions: Vec<LOCAL_POSITION_NED_DATA> = record.to_serde().unwrap_or_default();
+            for position in &positions {
+                let ground_speed = position.vx.hypot(position.vy);
+                let vertical_speed = position.vz.abs();
+
+                let was_limiting = self.limiting;
+                if !self.update_limiting(ground_speed, vertical_speed) {
+                    continue;
+                }
+
+                if !was_limiting {
+                    let event = VelocityLimitEvent {
+                        ground_speed,
+                        vertical_speed,
+                    };
+                    warn!("Velocity limit exceeded: {:?}", event);
+                    tx.send(publish!("exec/velocity_limit_exceeded", &event))?;
+                }
+
+                debug!(
+                    "Capping velocity (ground: {:.2} m/s, vertical: {:.2} m/s)",
+                    ground_speed, vertical_speed
+                );
+                let override_msg = self.build_override_message(position);
+                let pub_packet =
+                    publish!("mavlink/send/set_position_target_local_ned", &override_msg);
+                if let Err(e) = tx.send(pub_packet) {
+                    error!("Failed to send velocity override: {}", e);
+                    return Err(anyhow::anyhow!("Failed to send velocity override: {}", e));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&mut self) -> Result<(), anyhow::Error> {
+        debug!("ExecTaskVelocityLimiter cleaning up");
+        Ok(())
+    }
+
+    fn get_task_info(&self) -> &pubsub::tasks::info::TaskInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn velocity_record(vx: f32, vy: f32, vz: f32) -> pubsub::message::record::Record {
+        let position = LOCAL_POSITION_NED_DATA {
+            vx,
+            vy,
+            vz,
+            ..Default::default()
+        };
+        publish!("mavlink/local_position_ned", &position)
+    }
+
+    #[test]
+    fn test_override_only_while_limiting() {
+        let mut task = ExecTaskVelocityLimiter::new(5.0, 2.0);
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        // Within limits, over the horizontal limit, between 90% and the limit,
+        // below 90% (reset) and between 90% and the limit again
+        for (vx, vy, vz) in [
+            (3.0, 0.0, 0.0),
+            (6.0, 8.0, 0.0),
+            (4.8, 0.0, 0.0),
+            (3.0, 0.0, -1.0),
+            (4.8, 0.0, 0.0),
+        ] {
+            task.run(
+                vec![velocity_record(vx, vy, vz)],
+                tx.clone(),
+                meta_tx.clone(),
+            )
+            .unwrap();
+        }
+        assert!(!task.is_limiting());
+
+        let sent: Vec<_> = rx.try_iter().collect();
+        let events: Vec<VelocityLimitEvent> = sent
+            .iter()
+            .filter(|r| r.try_get_topic().unwrap() == "exec/velocity_limit_exceeded")
+            .flat_map(|r| r.to_serde::<VelocityLimitEvent>().unwrap())
+            .collect();
+        assert_eq!(
+            events,
+            vec![VelocityLimitEvent {
+                ground_speed: 10.0,
+                vertical_speed: 0.0
+            }]
+        );
+
+        let overrides: Vec<MavMessage> = sent
+            .iter()
+            .filter(|r| r.try_get_topic().unwrap() == "mavlink/send/set_position_target_local_ned")
+            .flat_map(|r| r.to_serde::<MavMessage>().unwrap())
+            .collect();
+        assert_eq!(overrides.len(), 2);
+        let MavMessage::SET_POSITION_TARGET_LOCAL_NED(first) = &overrides[0] else {
+            panic!("Expected a position target");
+        };
+        assert!((first.vx - 3.0).abs() < 1e-5);
+        assert!((first.vy - 4.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_vertical_limit() {
+        let mut task = ExecTaskVelocityLimiter::new(5.0, 2.0);
+        let (tx, rx) = mpsc::channel();
+        let (meta_tx, _meta_rx) = mpsc::channel();
+
+        task.run(vec![velocity_record(0.0, 0.0, -3.0)], tx, meta_tx)
+            .unwrap();
+        assert!(task.is_limiting());
+        assert_eq!(rx.try_iter().count(), 2);
+    }
+}
@@ -1,6 +1,6 @@
 use log::{debug, info};
 use pubsub::{
-    publish, publish_json, subscribe,
+    publish, subscribe, task_info,
     tasks::{info::TaskInfo, task::Task},
 };
 use serde::{Deserialize, Serialize};
@@ -19,9 +19,16 @@ pub struct ConnectionStatus {
 }
 
 impl ExecTaskWatchdog {
+    /// Stages a freshly spawned ExecTaskWatchdog can publish on exec/stage
+    pub const STAGE_TRANSITIONS: &[ExecStage] = &[
+        ExecStage::AwaitingData,
+        // Dropping the connection again falls back to AwaitConnection, but only after the
+        // stage moved on to AwaitingData
+    ];
+
     pub fn new() -> Self {
         Self {
-            info: TaskInfo::new("ExecTaskWatchdog"),
+            info: task_info!(ExecTaskWatchdog),
             connection_detected: false,
         }
     }
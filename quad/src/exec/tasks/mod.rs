@@ -1,9 +1,17 @@
 pub mod exec_task_armwatchdog;
+pub mod exec_task_batterymonitor;
 pub mod exec_task_datawatchdog;
+pub mod exec_task_errormonitor;
+pub mod exec_task_geofence;
+pub mod exec_task_gpsmonitor;
 pub mod exec_task_healthwatchdog;
 pub mod exec_task_heartbeat;
 pub mod exec_task_lockwatchdog;
+pub mod exec_task_paramread;
+pub mod exec_task_paramwrite;
+pub mod exec_task_positionhold;
 pub mod exec_task_requeststream;
 pub mod exec_task_sendarm;
 pub mod exec_task_startauto;
+pub mod exec_task_velocitylimiter;
 pub mod exec_task_watchdog;
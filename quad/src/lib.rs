@@ -1,4 +1,3 @@
 pub mod ardulink;
 pub mod auto;
 pub mod exec;
-pub mod system;
@@ -4,16 +4,22 @@ use log::{error, info};
 use quad::auto::auto_config::AutoConfig;
 use quad::auto::auto_runner::AutoRunner;
 use quad::auto::auto_stage::AutoStage;
+use quad::auto::tasks::auto_task_obstacle_avoidance::AutoTaskObstacleAvoidance;
 use quad::auto::tasks::auto_task_runscript::RunScriptTask;
 use quad::auto::tasks::auto_task_takeoff::AutoTaskTakeoff;
+use quad::auto::tasks::auto_task_waypoint::{AutoTaskWaypoint, DEFAULT_ACCEPTANCE_RADIUS_M};
 use quad::exec::exec_config::ExecConfig;
 use quad::exec::exec_runner::ExecRunner;
 use quad::exec::stage::ExecStage;
 use quad::exec::tasks::exec_task_armwatchdog::ExecTaskArmWatchdog;
+use quad::exec::tasks::exec_task_batterymonitor::ExecTaskBatteryMonitor;
 use quad::exec::tasks::exec_task_datawatchdog::ExecTaskDataWatchdog;
+use quad::exec::tasks::exec_task_errormonitor::ExecTaskErrorMonitor;
+use quad::exec::tasks::exec_task_gpsmonitor::ExecTaskGpsMonitor;
 use quad::exec::tasks::exec_task_healthwatchdog::ExecTaskHealthWatchdog;
 use quad::exec::tasks::exec_task_heartbeat::ExecTaskHeartbeat;
 use quad::exec::tasks::exec_task_lockwatchdog::ExecTaskLockWatchdog;
+use quad::exec::tasks::exec_task_positionhold::ExecTaskPositionHold;
 use quad::exec::tasks::exec_task_requeststream::ExecTaskRequestStream;
 use quad::exec::tasks::exec_task_sendarm::ExecTaskSendArm;
 use quad::exec::tasks::exec_task_startauto::ExecTaskStartAuto;
@@ -23,7 +29,9 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use pubsub::tasks::graph::generate_task_graph_dot;
 use pubsub::tasks::runner::Runner;
+use pubsub::tasks::state::RunnerState;
 use quad::ardulink::config::ArdulinkConnectionType;
 use quad::ardulink::task::MavlinkTask;
 
@@ -62,77 +70,109 @@ struct Args {
     /// Directory for docker compose logs
     #[arg(long, default_value = "logs/docker")]
     log_dir: PathBuf,
+
+    /// Write the task graph as a Graphviz DOT file after initialization
+    #[arg(long)]
+    dump_graph: Option<PathBuf>,
+
+    /// Replay a directory of parquet logs instead of connecting to a simulator
+    #[arg(long)]
+    replay_dir: Option<PathBuf>,
+
+    /// Playback speed multiplier used with --replay-dir
+    #[arg(long, default_value = "1.0")]
+    replay_speed: f64,
+
+    /// Persist the auto mission stage to this file and resume from it on startup
+    #[arg(long)]
+    auto_state_file: Option<PathBuf>,
+
+    /// Restore the runner state from this snapshot directory if it exists,
+    /// and snapshot to it when shutting down
+    #[arg(long)]
+    snapshot_dir: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
     pretty_env_logger::init();
     let args = Args::parse();
 
-    // Start docker compose for simulation
-    info!(
-        "Starting ArduPilot simulator with {} copters",
-        args.num_copters
-    );
-
-    // Set environment variables for docker compose
-    std::env::set_var("NUMCOPTERS", args.num_copters.to_string());
-
-    // Create and start docker compose
-    let docker_compose = DockerComposeCmd::new(
-        args.service_file.to_str().unwrap(),
-        args.log_dir.to_str().unwrap(),
-    );
-
-    docker_compose.up();
-    info!("Docker Compose started");
-
-    // Wait a bit for the simulator to start up
-    std::thread::sleep(Duration::from_secs(3));
-    info!("Simulator started, connecting to MAVLink");
-
-    // Create connection configuration
-    let connection_type = match args.connection.as_str() {
-        "udp" => ArdulinkConnectionType::Udp(args.address.clone(), args.port),
-        "tcp" => ArdulinkConnectionType::Tcp(args.address.clone(), args.port),
-        "serial" => {
-            let device = args.device.ok_or_else(|| {
-                anyhow::anyhow!("Serial device path required for serial connections")
-            })?;
-            ArdulinkConnectionType::Serial(device, args.port)
-        }
-        _ => return Err(anyhow::anyhow!("Unsupported connection type")),
-    };
+    let mut runner = Runner::new();
+    let mut docker_compose = None;
 
-    // Create MAVLink task
-    info!(
-        "Creating MAVLink task with connection: {:?}",
-        connection_type
-    );
-    let mavlink_task = Arc::new(Mutex::new(MavlinkTask::new(connection_type)));
+    // Replays feed logged data straight into the runner, no simulator or MAVLink link needed
+    if args.replay_dir.is_none() {
+        // Start docker compose for simulation
+        info!(
+            "Starting ArduPilot simulator with {} copters",
+            args.num_copters
+        );
 
-    // Create and set up runner
-    let mut runner = Runner::new();
-    runner.add_task(mavlink_task);
+        // Set environment variables for docker compose
+        std::env::set_var("NUMCOPTERS", args.num_copters.to_string());
+
+        // Create and start docker compose
+        let compose = DockerComposeCmd::new(
+            args.service_file.to_str().unwrap(),
+            args.log_dir.to_str().unwrap(),
+        );
+
+        compose.up();
+        info!("Docker Compose started");
 
-    let exec_config = ExecConfig::new()
-        .with_default_task("MavlinkTask".to_string())
+        // Wait a bit for the simulator to start up
+        std::thread::sleep(Duration::from_secs(3));
+        info!("Simulator started, connecting to MAVLink");
+
+        // Create connection configuration
+        let connection_type = match args.connection.as_str() {
+            "udp" => ArdulinkConnectionType::Udp(args.address.clone(), args.port),
+            "tcp" => ArdulinkConnectionType::Tcp(args.address.clone(), args.port),
+            "serial" => {
+                let device = args.device.ok_or_else(|| {
+                    anyhow::anyhow!("Serial device path required for serial connections")
+                })?;
+                ArdulinkConnectionType::Serial(device, args.port)
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported connection type")),
+        };
+
+        // Create MAVLink task
+        info!(
+            "Creating MAVLink task with connection: {:?}",
+            connection_type
+        );
+        let mavlink_task = Arc::new(Mutex::new(MavlinkTask::new(connection_type)));
+
+        runner.add_task(mavlink_task);
+        docker_compose = Some(compose);
+    }
+
+    let mut exec_config = ExecConfig::new();
+    if docker_compose.is_some() {
+        exec_config = exec_config.with_default_task("MavlinkTask".to_string());
+    }
+    let exec_config = exec_config
+        .with_default_task("ExecTaskErrorMonitor".to_string())
+        .with_default_task("ExecTaskBatteryMonitor".to_string())
         .with_stage_task(ExecStage::AwaitConnection, "ExecTaskWatchdog".to_string())
-        .with_stage_task(ExecStage::AwaitingData, "ExecHeartbeatTask".to_string())
-        .with_stage_task(ExecStage::AwaitingData, "ExecRequestStreamTask".to_string())
+        .with_stage_task(ExecStage::AwaitingData, "ExecTaskHeartbeat".to_string())
+        .with_stage_task(ExecStage::AwaitingData, "ExecTaskRequestStream".to_string())
         .with_stage_task(ExecStage::AwaitingData, "ExecTaskDataWatchdog".to_string())
-        .with_stage_task(ExecStage::AwaitingHealthy, "ExecHeartbeatTask".to_string())
+        .with_stage_task(ExecStage::AwaitingHealthy, "ExecTaskHeartbeat".to_string())
         .with_stage_task(
             ExecStage::AwaitingHealthy,
             "ExecTaskHealthWatchdog".to_string(),
         )
         .with_stage_task(ExecStage::AwaitingLock, "ExecTaskLockWatchdog".to_string())
-        .with_stage_task(ExecStage::AwaitingLock, "ExecHeartbeatTask".to_string())
+        .with_stage_task(ExecStage::AwaitingLock, "ExecTaskHeartbeat".to_string())
         .with_stage_task(ExecStage::HealthyUnarmed, "ExecTaskSendArm".to_string())
-        .with_stage_task(ExecStage::HealthyUnarmed, "ExecArmWatchdog".to_string())
-        .with_stage_task(ExecStage::HealthyUnarmed, "ExecHeartbeatTask".to_string())
-        .with_stage_task(ExecStage::HealthyArmed, "ExecTaskStartAuto".to_string());
+        .with_stage_task(ExecStage::HealthyUnarmed, "ExecTaskArmWatchdog".to_string())
+        .with_stage_task(ExecStage::HealthyUnarmed, "ExecTaskHeartbeat".to_string())
+        .with_stage_task(ExecStage::HealthyArmed, "ExecTaskStartAuto".to_string())
+        .with_stage_task(ExecStage::HealthyGuided, "ExecTaskPositionHold".to_string())
+        .with_gps_monitor();
 
-    let exec_runner = ExecRunner::new(exec_config);
     let exec_task_watchdog = ExecTaskWatchdog::new();
     let exec_task_heartbeat = ExecTaskHeartbeat::new();
     let exec_task_requeststream = ExecTaskRequestStream::new();
@@ -142,8 +182,11 @@ fn main() -> Result<()> {
     let exec_task_sendarm = ExecTaskSendArm::new();
     let exec_task_armwatchdog = ExecTaskArmWatchdog::new();
     let exec_task_startauto = ExecTaskStartAuto::new();
+    let exec_task_positionhold = ExecTaskPositionHold::new();
+    let exec_task_errormonitor = ExecTaskErrorMonitor::new();
+    let exec_task_batterymonitor = ExecTaskBatteryMonitor::from_config(&exec_config);
+    let exec_task_gpsmonitor = ExecTaskGpsMonitor::new();
 
-    runner.add_task(Arc::new(Mutex::new(exec_runner)));
     runner.add_task(Arc::new(Mutex::new(exec_task_watchdog)));
     runner.add_task(Arc::new(Mutex::new(exec_task_heartbeat)));
     runner.add_task(Arc::new(Mutex::new(exec_task_requeststream)));
@@ -153,24 +196,72 @@ fn main() -> Result<()> {
     runner.add_task(Arc::new(Mutex::new(exec_task_sendarm)));
     runner.add_task(Arc::new(Mutex::new(exec_task_armwatchdog)));
     runner.add_task(Arc::new(Mutex::new(exec_task_startauto)));
+    runner.add_task(Arc::new(Mutex::new(exec_task_positionhold)));
+    runner.add_task(Arc::new(Mutex::new(exec_task_errormonitor)));
+    runner.add_task(Arc::new(Mutex::new(exec_task_batterymonitor)));
+    runner.add_task(Arc::new(Mutex::new(exec_task_gpsmonitor)));
 
     let auto_config = AutoConfig::new()
         .with_script_task("RunScriptTask".to_string())
-        .with_stage_task(AutoStage::AutoTakeoff, "AutoTaskTakeoff".to_string());
-
-    let auto_runner = AutoRunner::new(auto_config);
-    runner.add_task(Arc::new(Mutex::new(auto_runner)));
+        .with_stage_task(AutoStage::AutoTakeoff, "AutoTaskTakeoff".to_string())
+        .with_stage_task(
+            AutoStage::AutoGuided,
+            "AutoTaskObstacleAvoidance".to_string(),
+        )
+        .with_stage_task(AutoStage::AutoWaypoint, "AutoTaskWaypoint".to_string());
 
     let auto_task_takeoff = AutoTaskTakeoff::new();
     runner.add_task(Arc::new(Mutex::new(auto_task_takeoff)));
 
-    let auto_task_runscript = RunScriptTask::new(PathBuf::from("scripts/script.json"))?;
+    let auto_task_obstacle_avoidance = AutoTaskObstacleAvoidance::new();
+    runner.add_task(Arc::new(Mutex::new(auto_task_obstacle_avoidance)));
+
+    let script_path = PathBuf::from("scripts/script.json");
+    let auto_task_waypoint =
+        AutoTaskWaypoint::from_script(&script_path, DEFAULT_ACCEPTANCE_RADIUS_M)?;
+    runner.add_task(Arc::new(Mutex::new(auto_task_waypoint)));
+
+    let auto_task_runscript = RunScriptTask::from_path(script_path)?;
     runner.add_task(Arc::new(Mutex::new(auto_task_runscript)));
 
+    // Stage runners are added last so their configs can be validated against all tasks
+    let exec_runner = ExecRunner::new(exec_config).with_registered_tasks(runner.task_infos());
+    runner.add_task(Arc::new(Mutex::new(exec_runner)));
+
+    let mut auto_runner = AutoRunner::new(auto_config).with_registered_tasks(runner.task_infos());
+    if let Some(state_file) = &args.auto_state_file {
+        auto_runner = auto_runner.with_state_file(state_file.clone());
+    }
+    runner.add_task(Arc::new(Mutex::new(auto_runner)));
+
+    // Stage-aware tasks resume from the last stage of a restored snapshot
+    runner.add_stage_topic("exec/stage");
+    runner.add_stage_topic("auto/stage");
+    if let Some(snapshot_dir) = &args.snapshot_dir {
+        if snapshot_dir.exists() {
+            info!("Restoring runner state from {:?}", snapshot_dir);
+            runner.load_state(Runner::restore(snapshot_dir)?);
+        }
+    }
+
     // Initialize tasks
     info!("Initializing tasks");
     runner.init()?;
 
+    if let Some(graph_path) = &args.dump_graph {
+        std::fs::write(graph_path, generate_task_graph_dot(&runner))?;
+        info!("Wrote task graph to {:?}", graph_path);
+    }
+
+    if let Some(replay_dir) = &args.replay_dir {
+        info!("Replaying logs from {:?}", replay_dir);
+        let replay_state = RunnerState::from_log_dir(replay_dir)?;
+        runner.replay_from_state(replay_state, args.replay_speed)?;
+        info!("Replay finished, shutting down");
+        runner.cleanup()?;
+        return Ok(());
+    }
+
     // Run for specified duration
     let start_time = std::time::Instant::now();
     let max_duration = Duration::from_secs(args.timeout);
@@ -193,10 +284,17 @@ fn main() -> Result<()> {
 
     // Clean up
     info!("Shutting down");
+    if let Some(snapshot_dir) = &args.snapshot_dir {
+        if let Err(err) = runner.snapshot(snapshot_dir) {
+            error!("Failed to snapshot runner state: {}", err);
+        }
+    }
     runner.cleanup()?;
     // Stop containers
-    docker_compose.down();
-    info!("Docker Compose stopped");
+    if let Some(docker_compose) = docker_compose {
+        docker_compose.down();
+        info!("Docker Compose stopped");
+    }
 
     Ok(())
 }